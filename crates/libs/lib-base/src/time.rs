@@ -1,31 +1,99 @@
-use time::{Duration, OffsetDateTime};
+//! The time utilities, where the times are `OffsetDateTime`, stored and
+//! exchanged in UTC as Rfc3339 strings, and only formatted in a user timezone
+//! (IANA name, e.g., `Europe/Paris`) for display (see `format_time_in_tz`).
+
+use time::{Duration, OffsetDateTime, UtcOffset};
+use time_tz::{timezones, OffsetDateTimeExt, Tz};
 
 pub use time::format_description::well_known::Rfc3339;
 
+/// The default user timezone.
+pub const TZ_UTC: &str = "UTC";
+
 pub fn now_utc() -> OffsetDateTime {
 	OffsetDateTime::now_utc()
 }
 
+// region:    --- Format & Parse
+
+/// The Rfc3339 UTC string of the time.
+///
+/// NOTE: Panics for the years outside 0 to 9999 (see `try_format_time`).
 pub fn format_time(time: OffsetDateTime) -> String {
-	time.format(&Rfc3339).unwrap() // TODO: need to check if safe.
+	try_format_time(time).expect("time year within Rfc3339 range")
 }
 
-pub fn now_utc_plus_sec_str(sec: f64) -> String {
-	let new_time = now_utc() + Duration::seconds_f64(sec);
-	format_time(new_time)
+pub fn try_format_time(time: OffsetDateTime) -> Result<String> {
+	time.to_offset(UtcOffset::UTC)
+		.format(&Rfc3339)
+		.map_err(|ex| Error::FailToDateFormat(ex.to_string()))
+}
+
+/// The Rfc3339 string of the time in the timezone (e.g., `Europe/Paris`),
+/// i.e., with its local offset at that time.
+pub fn format_time_in_tz(time: OffsetDateTime, tz_name: &str) -> Result<String> {
+	time.to_timezone(parse_tz(tz_name)?)
+		.format(&Rfc3339)
+		.map_err(|ex| Error::FailToDateFormat(ex.to_string()))
 }
 
+/// Parses an Rfc3339 string (with any offset), as UTC.
 pub fn parse_utc(moment: &str) -> Result<OffsetDateTime> {
 	OffsetDateTime::parse(moment, &Rfc3339)
-		.map_err(|_| Error::FailToDateParse(moment.to_string()))
+		.map(|time| time.to_offset(UtcOffset::UTC))
+		.map_err(|ex| Error::FailToDateParse {
+			moment: moment.to_string(),
+			cause: ex.to_string(),
+		})
+}
+
+/// The timezone of an IANA name (e.g., `Europe/Paris`, `UTC`).
+pub fn parse_tz(tz_name: &str) -> Result<&'static Tz> {
+	timezones::get_by_name(tz_name)
+		.ok_or_else(|| Error::TimezoneUnknown(tz_name.to_string()))
+}
+
+// endregion: --- Format & Parse
+
+// region:    --- Durations
+
+/// The duration of the seconds (saturated, and zero for NaN).
+pub fn duration_sec(sec: f64) -> Duration {
+	Duration::saturating_seconds_f64(sec)
+}
+
+pub fn now_utc_plus(duration: Duration) -> OffsetDateTime {
+	now_utc().saturating_add(duration)
+}
+
+pub fn now_utc_plus_sec_str(sec: f64) -> String {
+	format_time(now_utc_plus(duration_sec(sec)))
+}
+
+/// The time left until `time` (negative when passed).
+pub fn remaining(time: OffsetDateTime) -> Duration {
+	time - now_utc()
 }
 
+pub fn is_expired(exp: OffsetDateTime) -> bool {
+	exp < now_utc()
+}
+
+/// The time without its sub-second part (e.g., as an Rfc3339 token `exp`).
+pub fn truncate_to_sec(time: OffsetDateTime) -> OffsetDateTime {
+	time.replace_nanosecond(0).unwrap_or(time)
+}
+
+// endregion: --- Durations
+
 // region:    --- Error
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
-	FailToDateParse(String),
+	FailToDateParse { moment: String, cause: String },
+	FailToDateFormat(String),
+	TimezoneUnknown(String),
 }
 
 // region:    --- Error Boilerplate
@@ -41,3 +109,63 @@ impl core::fmt::Display for Error {
 impl std::error::Error for Error {}
 // endregion: --- Error Boilerplate
 // endregion: --- Error
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_utc_ok_and_err() -> Result<()> {
+		// -- Exec
+		let time = parse_utc("2024-03-10T12:30:00+02:00")?;
+
+		// -- Check
+		assert_eq!(format_time(time), "2024-03-10T10:30:00Z");
+		assert!(matches!(
+			parse_utc("2024-03-10 12:30"),
+			Err(Error::FailToDateParse { .. })
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_format_time_in_tz_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_winter = parse_utc("2024-01-15T12:00:00Z")?;
+		let fx_summer = parse_utc("2024-07-15T12:00:00Z")?;
+
+		// -- Exec & Check
+		assert_eq!(
+			format_time_in_tz(fx_winter, "Europe/Paris")?,
+			"2024-01-15T13:00:00+01:00"
+		);
+		assert_eq!(
+			format_time_in_tz(fx_summer, "Europe/Paris")?,
+			"2024-07-15T14:00:00+02:00"
+		);
+		assert_eq!(
+			format_time_in_tz(fx_summer, TZ_UTC)?,
+			"2024-07-15T12:00:00Z"
+		);
+		assert!(matches!(
+			format_time_in_tz(fx_summer, "Mars/Olympus"),
+			Err(Error::TimezoneUnknown(_))
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_durations_ok() {
+		let fx_exp = now_utc_plus(duration_sec(60.));
+
+		assert!(!is_expired(fx_exp));
+		assert!(remaining(fx_exp) > Duration::seconds(50));
+		assert!(is_expired(now_utc_plus(duration_sec(-1.))));
+		assert_eq!(truncate_to_sec(fx_exp).nanosecond(), 0);
+		assert_eq!(duration_sec(f64::NAN), Duration::ZERO);
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,27 @@
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+	/// The dev db recreation only runs against a local postgres.
+	NonLocalDbHost {
+		name: &'static str,
+		host: String,
+	},
+	InvalidDbUrl {
+		name: &'static str,
+		cause: String,
+	},
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
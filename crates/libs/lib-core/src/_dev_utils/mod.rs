@@ -1,6 +1,7 @@
 // region:    --- Modules
 
 use crate::{
+	config::{config, AppEnv},
 	ctx::Ctx,
 	model::{
 		self,
@@ -13,14 +14,27 @@ use tokio::sync::OnceCell;
 use tracing::info;
 
 mod dev_db;
+mod error;
+
+pub use self::error::{Error, Result};
 
 // endregion: --- Modules
 
 /// Initialize environment for local development.
 /// (for early development, will be called from main()).
+///
+/// IMPORTANT: Recreates the db, so panics if the `SERVICE_ENV` is not `dev`, or
+///            if the db urls are not on a local host. The root db url and the sql
+///            dir are from the config (`SERVICE_DEV_DB_ROOT_URL`, `SERVICE_DEV_SQL_DIR`).
 pub async fn init_dev() {
 	static INIT: OnceCell<()> = OnceCell::const_new();
 
+	let env = config().ENV;
+	assert!(
+		env == AppEnv::Dev,
+		"init_dev refused, it recreates the db, and SERVICE_ENV is {env:?}"
+	);
+
 	INIT.get_or_init(|| async {
 		info!("{:<12} - init_dev_all()", "FOR-DEV-ONLY");
 
@@ -30,17 +44,14 @@ pub async fn init_dev() {
 }
 
 /// Initialize test environment
+///
+/// NOTE: The dev db is initialized only once, but each call returns a new
+///       `ModelManager`, as each `#[tokio::test]` runs on its own runtime, and
+///       a db pool cannot be shared across runtimes.
 pub async fn init_test() -> ModelManager {
-	static INIT: OnceCell<ModelManager> = OnceCell::const_new();
-
-	let mm = INIT
-		.get_or_init(|| async {
-			init_dev().await;
-			ModelManager::new().await.unwrap()
-		})
-		.await;
+	init_dev().await;
 
-	mm.clone()
+	ModelManager::new().await.unwrap()
 }
 
 pub async fn seed_project(
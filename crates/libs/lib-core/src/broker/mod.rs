@@ -0,0 +1,110 @@
+//! The optional message broker publisher of the domain events
+//! (e.g., for the analytics and search indexers).
+//!
+//! When `SERVICE_BROKER_URL` is set (`broker-nats` feature), each `DomainEvent`
+//! is published, as json, to the `{SERVICE_BROKER_TOPIC}.{event name}` NATS
+//! subject (e.g., `app.events.task.created`).
+//!
+//! NOTE: At most once, as the events come from the in-process bus (see `event`),
+//!       so the events missed by a lagging publisher, or when the broker is
+//!       unreachable for longer than the client buffering, are logged and dropped.
+
+// region:    --- Modules
+
+mod error;
+
+pub use self::error::{Error, Result};
+
+use crate::model::ModelManager;
+use tokio::task::JoinHandle;
+
+// endregion: --- Modules
+
+/// Connects to the `SERVICE_BROKER_URL`, and spawns the publisher
+/// (None when no broker is configured).
+pub async fn spawn_broker_publisher(
+	mm: &ModelManager,
+) -> Result<Option<JoinHandle<()>>> {
+	#[cfg(feature = "broker-nats")]
+	if let Some(url) = &crate::config().BROKER_URL {
+		let publisher =
+			nats::NatsPublisher::connect(url, &crate::config().BROKER_TOPIC).await?;
+		return Ok(Some(crate::model::event::spawn_subscriber(mm, publisher)));
+	}
+
+	// Note: Without the feature, the config rejects a `SERVICE_BROKER_URL`.
+	let _ = mm;
+	Ok(None)
+}
+
+/// The subject of an event name, e.g., `app.events.task.created`.
+pub fn event_subject(topic: &str, event_name: &str) -> String {
+	format!("{topic}.{event_name}")
+}
+
+// region:    --- Nats
+
+#[cfg(feature = "broker-nats")]
+mod nats {
+	use super::{event_subject, Error, Result};
+	use crate::model::event::{DomainEvent, EventSubscriber};
+	use async_trait::async_trait;
+	use tracing::warn;
+
+	pub struct NatsPublisher {
+		client: async_nats::Client,
+		topic: String,
+	}
+
+	impl NatsPublisher {
+		pub async fn connect(url: &str, topic: &str) -> Result<Self> {
+			let client = async_nats::connect(url)
+				.await
+				// Note: Not the url, which may have credentials.
+				.map_err(|ex| Error::Connect(ex.to_string()))?;
+
+			Ok(Self {
+				client,
+				topic: topic.to_string(),
+			})
+		}
+	}
+
+	#[async_trait]
+	impl EventSubscriber for NatsPublisher {
+		fn name(&self) -> &'static str {
+			"broker"
+		}
+
+		async fn handle(&self, event: &DomainEvent) {
+			let payload = match serde_json::to_vec(event) {
+				Ok(payload) => payload,
+				Err(ex) => {
+					warn!("broker - {} not serialized - {ex}", event.name());
+					return;
+				}
+			};
+			let subject = event_subject(&self.topic, event.name());
+			if let Err(ex) = self.client.publish(subject, payload.into()).await {
+				warn!("broker - {} not published - {ex}", event.name());
+			}
+		}
+	}
+}
+
+// endregion: --- Nats
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_event_subject_ok() {
+		assert_eq!(
+			event_subject("app.events", "task.created"),
+			"app.events.task.created"
+		);
+	}
+}
+// endregion: --- Tests
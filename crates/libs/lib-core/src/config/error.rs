@@ -3,7 +3,35 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
 	MissingEnv(&'static str),
-	WrongFormat(&'static str),
+	WrongFormat {
+		name: &'static str,
+		expected: &'static str,
+	},
+	Invalid {
+		name: &'static str,
+		reason: String,
+	},
+	SecretFileRead {
+		name: &'static str,
+		path: String,
+		cause: String,
+	},
+	SecretProvider {
+		provider: &'static str,
+		cause: String,
+	},
+	/// All the entries errors (see `Config::load_from_ev`).
+	Multiple(Vec<Error>),
+
+	// -- Config file
+	ConfigFileRead {
+		path: String,
+		cause: String,
+	},
+	ConfigFileParse {
+		path: String,
+		cause: String,
+	},
 }
 
 // region:    --- Error Boilerplate
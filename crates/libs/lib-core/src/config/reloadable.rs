@@ -0,0 +1,189 @@
+//! The config subset which can be changed without a restart, by re-reading the
+//! config sources (e.g., after editing the `service.toml` file) on SIGHUP,
+//! or with the admin `POST /config/reload` endpoint.
+//!
+//! The components holding a `ConfigHandle` read the `current()` values, or
+//! `subscribe()` to apply the changes (e.g., tracing filter, rate limiter).
+
+use super::sources::ConfigSources;
+use super::{ConfigErrors, Error, Result};
+use serde::Serialize;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+
+// region:    --- ReloadableConfig
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadableConfig {
+	// -- log
+	/// The tracing `EnvFilter` directives (default to the `RUST_LOG` env variable).
+	pub LOG_FILTER: String,
+	/// The percentage (0 to 100) of the requests also logged with the
+	/// `LOG_SAMPLE_FILTER` (e.g., the debug logs of a few prod requests), default 0.
+	pub LOG_SAMPLE_PERCENT: f64,
+	/// The `target=level` directives of the sampled requests (default `debug`).
+	pub LOG_SAMPLE_FILTER: String,
+
+	// -- rate limit (per client ip, and per user, on /api)
+	pub RATE_LIMIT_ENABLED: bool,
+	/// Sustained rate, in requests per second.
+	pub RATE_LIMIT_PER_SEC: u32,
+	pub RATE_LIMIT_BURST: u32,
+
+	// -- token
+	pub TOKEN_DURATION_SEC: f64,
+	/// The auth-token cookie is re-issued only when the token expires within
+	/// this window (default half the `TOKEN_DURATION_SEC`).
+	pub TOKEN_REFRESH_WINDOW_SEC: f64,
+
+	// -- feature flags
+	/// The names of the enabled feature flags.
+	pub FEATURE_FLAGS: Vec<String>,
+
+	// -- read-only mode
+	/// Only the reads are served, the mutations fail with the web-server
+	/// `READ_ONLY_MODE` (e.g., during a failover, or a data migration).
+	pub READ_ONLY: bool,
+}
+
+impl ReloadableConfig {
+	pub fn is_feature_enabled(&self, flag: &str) -> bool {
+		self.FEATURE_FLAGS.iter().any(|enabled| enabled == flag)
+	}
+
+	pub(super) fn load_from_sources(
+		src: &ConfigSources,
+		errs: &mut ConfigErrors,
+	) -> ReloadableConfig {
+		// -- token
+		let token_duration_sec =
+			errs.check(src.get_env_parse("SERVICE_TOKEN_DURATION_SEC").and_then(
+				|val| super::validate_positive("SERVICE_TOKEN_DURATION_SEC", val),
+			));
+		let token_refresh_window_sec = errs.check(
+			src.get_env_parse_opt("SERVICE_TOKEN_REFRESH_WINDOW_SEC")
+				.and_then(|val| match val {
+					Some(val) => super::validate_positive(
+						"SERVICE_TOKEN_REFRESH_WINDOW_SEC",
+						val,
+					),
+					None => Ok(token_duration_sec / 2.),
+				}),
+		);
+
+		ReloadableConfig {
+			// -- log
+			LOG_FILTER: errs.check(validate_log_filter(
+				"SERVICE_LOG_FILTER",
+				src.get_env_opt("SERVICE_LOG_FILTER")
+					.or_else(|| env::var("RUST_LOG").ok())
+					.unwrap_or_default(),
+			)),
+			LOG_SAMPLE_PERCENT: errs.check(
+				src.get_env_parse_or("SERVICE_LOG_SAMPLE_PERCENT", 0.)
+					.and_then(|val| {
+						validate_percent("SERVICE_LOG_SAMPLE_PERCENT", val)
+					}),
+			),
+			LOG_SAMPLE_FILTER: errs.check(validate_log_sample_filter(
+				"SERVICE_LOG_SAMPLE_FILTER",
+				src.get_env_opt("SERVICE_LOG_SAMPLE_FILTER")
+					.unwrap_or_else(|| "debug".to_string()),
+			)),
+			// -- rate limit
+			RATE_LIMIT_ENABLED: errs
+				.check(src.get_env_parse_or("SERVICE_RATE_LIMIT_ENABLED", true)),
+			RATE_LIMIT_PER_SEC: errs.check(
+				src.get_env_parse_or_non_zero("SERVICE_RATE_LIMIT_PER_SEC", 20),
+			),
+			RATE_LIMIT_BURST: errs.check(
+				src.get_env_parse_or_non_zero("SERVICE_RATE_LIMIT_BURST", 50),
+			),
+			// -- token
+			TOKEN_DURATION_SEC: token_duration_sec,
+			TOKEN_REFRESH_WINDOW_SEC: token_refresh_window_sec,
+			// -- feature flags
+			FEATURE_FLAGS: src.get_env_list_or("SERVICE_FEATURE_FLAGS", &[]),
+			// -- read-only mode
+			READ_ONLY: errs.check(src.get_env_parse_or("SERVICE_READ_ONLY", false)),
+		}
+	}
+}
+
+fn validate_log_filter(name: &'static str, val: String) -> Result<String> {
+	EnvFilter::try_new(&val).map_err(|ex| Error::Invalid {
+		name,
+		reason: format!("not a valid tracing filter ({ex})"),
+	})?;
+
+	Ok(val)
+}
+
+/// The sample directives are scoped to the sampled request span by the
+/// web-server, so they cannot have their own span filters.
+fn validate_log_sample_filter(name: &'static str, val: String) -> Result<String> {
+	if val.contains('[') {
+		return Err(Error::Invalid {
+			name,
+			reason: "only target=level directives (no span filters)".to_string(),
+		});
+	}
+
+	validate_log_filter(name, val)
+}
+
+fn validate_percent(name: &'static str, val: f64) -> Result<f64> {
+	if !(0. ..=100.).contains(&val) {
+		return Err(Error::Invalid {
+			name,
+			reason: format!("must be between 0 and 100, was {val}"),
+		});
+	}
+
+	Ok(val)
+}
+
+// endregion: --- ReloadableConfig
+
+// region:    --- ConfigHandle
+
+/// Cheap to clone handle on the current `ReloadableConfig`.
+#[derive(Clone)]
+pub struct ConfigHandle {
+	tx: Arc<watch::Sender<Arc<ReloadableConfig>>>,
+}
+
+impl ConfigHandle {
+	pub(super) fn new(reloadable: ReloadableConfig) -> Self {
+		let (tx, _) = watch::channel(Arc::new(reloadable));
+		Self { tx: Arc::new(tx) }
+	}
+
+	pub fn current(&self) -> Arc<ReloadableConfig> {
+		self.tx.borrow().clone()
+	}
+
+	/// Notified on each successful reload.
+	pub fn subscribe(&self) -> watch::Receiver<Arc<ReloadableConfig>> {
+		self.tx.subscribe()
+	}
+
+	/// Re-reads the config sources, and publishes the new values.
+	/// On error (all the entries errors), the current values are kept.
+	pub fn reload(&self) -> Result<Arc<ReloadableConfig>> {
+		let src = ConfigSources::load()?;
+		let mut errs = ConfigErrors::default();
+		let reloadable = ReloadableConfig::load_from_sources(&src, &mut errs);
+		errs.into_result()?;
+
+		let reloadable = Arc::new(reloadable);
+		self.tx.send_replace(reloadable.clone());
+
+		Ok(reloadable)
+	}
+}
+
+// endregion: --- ConfigHandle
@@ -0,0 +1,66 @@
+//! AWS Secrets Manager provider (`secrets-aws` feature).
+//!
+//! - `SERVICE_AWS_SECRET_ID` - the secret name or arn, with a json object
+//!   `SecretString` (e.g., `{"pwd_key": "...", "token_key": "...", "db_url": "..."}`).
+//!
+//! The aws credentials and region are from the standard aws environment/profile.
+
+use super::SecretProvider;
+use crate::config::sources::ConfigSources;
+use crate::config::{Error, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::OnceCell;
+
+pub(super) struct AwsProvider {
+	secret_id: String,
+	client: OnceCell<aws_sdk_secretsmanager::Client>,
+}
+
+impl AwsProvider {
+	pub(super) fn from_sources(src: &ConfigSources) -> Result<Self> {
+		Ok(Self {
+			secret_id: src.get_env("SERVICE_AWS_SECRET_ID")?,
+			client: OnceCell::new(),
+		})
+	}
+
+	async fn client(&self) -> &aws_sdk_secretsmanager::Client {
+		self.client
+			.get_or_init(|| async {
+				let sdk_config =
+					aws_config::load_defaults(aws_config::BehaviorVersion::latest())
+						.await;
+				aws_sdk_secretsmanager::Client::new(&sdk_config)
+			})
+			.await
+	}
+}
+
+#[async_trait]
+impl SecretProvider for AwsProvider {
+	fn name(&self) -> &'static str {
+		"aws"
+	}
+
+	async fn fetch_all(&self) -> Result<HashMap<String, String>> {
+		let to_error = |cause: String| Error::SecretProvider {
+			provider: self.name(),
+			cause,
+		};
+
+		let output = self
+			.client()
+			.await
+			.get_secret_value()
+			.secret_id(&self.secret_id)
+			.send()
+			.await
+			.map_err(|ex| to_error(ex.to_string()))?;
+		let secret_string = output
+			.secret_string()
+			.ok_or_else(|| to_error("no SecretString".to_string()))?;
+
+		serde_json::from_str(secret_string).map_err(|ex| to_error(ex.to_string()))
+	}
+}
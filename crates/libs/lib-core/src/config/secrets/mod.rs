@@ -0,0 +1,212 @@
+//! Optional external secret managers for the `PWD_KEY`, `TOKEN_KEY`, and
+//! `DB_URL` secrets, behind the `SecretProvider` trait.
+//!
+//! - `SERVICE_SECRET_PROVIDER` - `vault` (`secrets-vault` feature) or
+//!   `aws` (`secrets-aws` feature), none by default.
+//! - `SERVICE_SECRET_REFRESH_SEC` - the rotation check interval (default 300).
+//!
+//! The secrets are fetched once by `init_secrets` (before the config load),
+//! and cached in memory, where `get_env_secret` looks first. The rotation task
+//! refreshes the cache, and notifies the changed names (e.g., the web-server
+//! applies a rotated `DB_URL` to the db pool).
+//!
+//! Note: The provider documents hold the secrets by key name (e.g., `pwd_key`).
+
+// region:    --- Modules
+
+#[cfg(feature = "secrets-aws")]
+mod aws;
+#[cfg(feature = "secrets-vault")]
+mod vault;
+
+use super::sources::{to_env_name, ConfigSources};
+use super::{Error, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+// endregion: --- Modules
+
+// region:    --- SecretProvider
+
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+	fn name(&self) -> &'static str;
+
+	/// Fetches all the secrets of the provider, by key name (e.g., `pwd_key`).
+	async fn fetch_all(&self) -> Result<HashMap<String, String>>;
+}
+
+// endregion: --- SecretProvider
+
+// region:    --- SecretCache
+
+/// The in-memory cache of the provider secrets, by env name (e.g., `SERVICE_PWD_KEY`).
+pub struct SecretCache {
+	provider: Arc<dyn SecretProvider>,
+	values: RwLock<HashMap<String, String>>,
+	/// The names changed by the last rotation.
+	rotated_tx: watch::Sender<Vec<String>>,
+}
+
+impl SecretCache {
+	pub async fn new(provider: Arc<dyn SecretProvider>) -> Result<Self> {
+		let (rotated_tx, _) = watch::channel(Vec::new());
+		let cache = Self {
+			provider,
+			values: RwLock::new(HashMap::new()),
+			rotated_tx,
+		};
+		cache.refresh().await?;
+
+		Ok(cache)
+	}
+
+	pub fn get(&self, name: &str) -> Option<String> {
+		let values = self.values.read().unwrap_or_else(|e| e.into_inner());
+		values.get(name).cloned()
+	}
+
+	/// Notified with the changed names on each rotation.
+	pub fn subscribe_rotations(&self) -> watch::Receiver<Vec<String>> {
+		self.rotated_tx.subscribe()
+	}
+
+	/// Re-fetches the secrets, and returns the names which changed.
+	pub async fn refresh(&self) -> Result<Vec<String>> {
+		let fetched: HashMap<String, String> = self
+			.provider
+			.fetch_all()
+			.await?
+			.into_iter()
+			.map(|(key, val)| (to_env_name(&key), val))
+			.collect();
+
+		let mut values = self.values.write().unwrap_or_else(|e| e.into_inner());
+		let mut changed: Vec<String> = fetched
+			.iter()
+			.filter(|(name, val)| values.get(*name) != Some(*val))
+			.map(|(name, _)| name.clone())
+			.collect();
+		changed.sort();
+		*values = fetched;
+
+		Ok(changed)
+	}
+
+	/// Refreshes the cache every `interval`, and notifies the rotated names.
+	/// (On fetch error, the cached values are kept)
+	pub fn spawn_rotation(&'static self, interval: Duration) {
+		tokio::spawn(async move {
+			let mut ticker = tokio::time::interval(interval);
+			ticker.tick().await; // The first tick is immediate.
+			loop {
+				ticker.tick().await;
+				match self.refresh().await {
+					Ok(changed) if changed.is_empty() => (),
+					Ok(changed) => {
+						info!(
+							"{:<12} - rotated secrets {changed:?} ({})",
+							"SECRETS",
+							self.provider.name()
+						);
+						self.rotated_tx.send_replace(changed);
+					}
+					Err(ex) => warn!("secrets refresh failed, kept cached - {ex:?}"),
+				}
+			}
+		});
+	}
+}
+
+// endregion: --- SecretCache
+
+// region:    --- Init
+
+static SECRET_CACHE: OnceLock<SecretCache> = OnceLock::new();
+
+/// The secrets cache, when a provider was initialized by `init_secrets`.
+pub fn secrets() -> Option<&'static SecretCache> {
+	SECRET_CACHE.get()
+}
+
+/// Fetches the secrets from the configured `SERVICE_SECRET_PROVIDER` (if any),
+/// and starts their rotation. Must be called before the first `config()`.
+pub async fn init_secrets() -> Result<Option<&'static SecretCache>> {
+	let src = ConfigSources::load()?;
+
+	let Some(provider) = new_provider(&src)? else {
+		return Ok(None);
+	};
+	let refresh_sec =
+		src.get_env_parse_or_non_zero("SERVICE_SECRET_REFRESH_SEC", 300)?;
+
+	let cache = SecretCache::new(provider).await?;
+	let cache = SECRET_CACHE.get_or_init(|| cache);
+	cache.spawn_rotation(Duration::from_secs(refresh_sec.into()));
+
+	Ok(Some(cache))
+}
+
+fn new_provider(src: &ConfigSources) -> Result<Option<Arc<dyn SecretProvider>>> {
+	let name = "SERVICE_SECRET_PROVIDER";
+	match src.get_env_opt(name).as_deref() {
+		None => Ok(None),
+		#[cfg(feature = "secrets-vault")]
+		Some("vault") => Ok(Some(Arc::new(vault::VaultProvider::from_sources(src)?))),
+		#[cfg(feature = "secrets-aws")]
+		Some("aws") => Ok(Some(Arc::new(aws::AwsProvider::from_sources(src)?))),
+		Some(_) => Err(Error::WrongFormat {
+			name,
+			expected: "a provider of the enabled features (vault, aws)",
+		}),
+	}
+}
+
+// endregion: --- Init
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	/// Returns `fx-db-url-{n}`, where n is the fetch count.
+	struct FxProvider(AtomicUsize);
+
+	#[async_trait]
+	impl SecretProvider for FxProvider {
+		fn name(&self) -> &'static str {
+			"fx"
+		}
+
+		async fn fetch_all(&self) -> Result<HashMap<String, String>> {
+			let n = self.0.fetch_add(1, Ordering::Relaxed);
+			Ok(HashMap::from([
+				("pwd_key".to_string(), "fx-pwd-key".to_string()),
+				("db_url".to_string(), format!("fx-db-url-{n}")),
+			]))
+		}
+	}
+
+	#[tokio::test]
+	async fn test_secret_cache_refresh_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let cache =
+			SecretCache::new(Arc::new(FxProvider(AtomicUsize::new(0)))).await?;
+
+		// -- Exec
+		let changed = cache.refresh().await?;
+
+		// -- Check
+		assert_eq!(cache.get("SERVICE_PWD_KEY").as_deref(), Some("fx-pwd-key"));
+		assert_eq!(cache.get("SERVICE_DB_URL").as_deref(), Some("fx-db-url-1"));
+		assert_eq!(changed, ["SERVICE_DB_URL"]);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,72 @@
+//! HashiCorp Vault KV v2 provider (`secrets-vault` feature).
+//!
+//! - `SERVICE_VAULT_ADDR` - e.g., `https://vault.internal:8200`
+//! - `SERVICE_VAULT_TOKEN` (or `SERVICE_VAULT_TOKEN_FILE`)
+//! - `SERVICE_VAULT_SECRET_PATH` - the kv v2 data path, e.g., `secret/data/web-server`
+
+use super::SecretProvider;
+use crate::config::sources::ConfigSources;
+use crate::config::{Error, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub(super) struct VaultProvider {
+	client: reqwest::Client,
+	url: String,
+	token: String,
+}
+
+impl VaultProvider {
+	pub(super) fn from_sources(src: &ConfigSources) -> Result<Self> {
+		let addr = src.get_env("SERVICE_VAULT_ADDR")?;
+		let path = src.get_env("SERVICE_VAULT_SECRET_PATH")?;
+
+		Ok(Self {
+			client: reqwest::Client::new(),
+			url: format!(
+				"{}/v1/{}",
+				addr.trim_end_matches('/'),
+				path.trim_start_matches('/')
+			),
+			token: src.get_env_secret("SERVICE_VAULT_TOKEN")?,
+		})
+	}
+}
+
+#[derive(Deserialize)]
+struct KvResponse {
+	data: KvData,
+}
+
+#[derive(Deserialize)]
+struct KvData {
+	data: HashMap<String, String>,
+}
+
+#[async_trait]
+impl SecretProvider for VaultProvider {
+	fn name(&self) -> &'static str {
+		"vault"
+	}
+
+	async fn fetch_all(&self) -> Result<HashMap<String, String>> {
+		let to_error = |cause: String| Error::SecretProvider {
+			provider: self.name(),
+			cause,
+		};
+
+		let res = self
+			.client
+			.get(&self.url)
+			.header("X-Vault-Token", &self.token)
+			.send()
+			.await
+			.and_then(|res| res.error_for_status())
+			.map_err(|ex| to_error(ex.to_string()))?;
+		let kv: KvResponse =
+			res.json().await.map_err(|ex| to_error(ex.to_string()))?;
+
+		Ok(kv.data.data)
+	}
+}
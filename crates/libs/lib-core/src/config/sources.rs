@@ -0,0 +1,205 @@
+//! The layered config sources, where a `SERVICE_*` key is resolved from
+//! (highest precedence first):
+//!
+//! 1. The command line, as `--key-name=value` (e.g., `--db-url=...` for `SERVICE_DB_URL`).
+//! 2. The environment, as `SERVICE_KEY_NAME`.
+//! 3. The toml config file, as `key_name = value`, where the tables are
+//!    flattened with `_` (e.g., `[rate_limit] per_sec = 20` for `SERVICE_RATE_LIMIT_PER_SEC`)
+//!    and the arrays are comma joined.
+//!
+//! The config file is `--config=path`, or `SERVICE_CONFIG_FILE`,
+//! or `service.toml` (only when present) in the current dir.
+
+use super::{Error, Result};
+use std::collections::HashMap;
+use std::{env, fs};
+
+const KEY_PREFIX: &str = "SERVICE_";
+const CONFIG_FILE_DEFAULT: &str = "service.toml";
+
+#[derive(Debug, Default)]
+pub(super) struct ConfigSources {
+	/// By env name (e.g., `SERVICE_DB_URL`).
+	cli: HashMap<String, String>,
+	/// By env name.
+	file: HashMap<String, String>,
+}
+
+impl ConfigSources {
+	pub(super) fn load() -> Result<Self> {
+		let cli = parse_cli_args(env::args().skip(1));
+
+		let file = match cli
+			.get("SERVICE_CONFIG")
+			.cloned()
+			.or_else(|| env::var("SERVICE_CONFIG_FILE").ok())
+		{
+			Some(path) => read_config_file(&path)?,
+			None if fs::metadata(CONFIG_FILE_DEFAULT).is_ok() => {
+				read_config_file(CONFIG_FILE_DEFAULT)?
+			}
+			None => HashMap::new(),
+		};
+
+		Ok(Self { cli, file })
+	}
+
+	/// For tests, the sources with some command line entries.
+	#[cfg(test)]
+	pub(super) fn with_cli_overrides<const N: usize>(
+		entries: [(&str, &str); N],
+	) -> Self {
+		Self {
+			cli: entries
+				.into_iter()
+				.map(|(name, val)| (name.to_string(), val.to_string()))
+				.collect(),
+			file: HashMap::new(),
+		}
+	}
+
+	/// Returns the raw value of the first source having the key.
+	pub(super) fn get_raw(&self, name: &str) -> Option<String> {
+		self.cli
+			.get(name)
+			.cloned()
+			.or_else(|| env::var(name).ok())
+			.or_else(|| self.file.get(name).cloned())
+	}
+}
+
+/// `--db-url=val` to (`SERVICE_DB_URL`, `val`). The other args are ignored.
+fn parse_cli_args(args: impl Iterator<Item = String>) -> HashMap<String, String> {
+	args.filter_map(|arg| {
+		let (key, val) = arg.strip_prefix("--")?.split_once('=')?;
+		Some((to_env_name(key), val.to_string()))
+	})
+	.collect()
+}
+
+fn read_config_file(path: &str) -> Result<HashMap<String, String>> {
+	let content = fs::read_to_string(path).map_err(|ex| Error::ConfigFileRead {
+		path: path.to_string(),
+		cause: ex.to_string(),
+	})?;
+
+	parse_config_toml(&content).map_err(|cause| Error::ConfigFileParse {
+		path: path.to_string(),
+		cause,
+	})
+}
+
+fn parse_config_toml(
+	content: &str,
+) -> core::result::Result<HashMap<String, String>, String> {
+	let table: toml::Table = content
+		.parse()
+		.map_err(|ex: toml::de::Error| ex.to_string())?;
+
+	let mut entries = HashMap::new();
+	flatten_table("", &table, &mut entries)?;
+
+	Ok(entries)
+}
+
+fn flatten_table(
+	prefix: &str,
+	table: &toml::Table,
+	entries: &mut HashMap<String, String>,
+) -> core::result::Result<(), String> {
+	for (key, val) in table {
+		let key = format!("{prefix}{key}");
+		match val {
+			toml::Value::Table(sub_table) => {
+				flatten_table(&format!("{key}_"), sub_table, entries)?
+			}
+			toml::Value::Array(items) => {
+				let items = items
+					.iter()
+					.map(|item| value_to_string(&key, item))
+					.collect::<core::result::Result<Vec<_>, _>>()?;
+				entries.insert(to_env_name(&key), items.join(","));
+			}
+			val => {
+				entries.insert(to_env_name(&key), value_to_string(&key, val)?);
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn value_to_string(
+	key: &str,
+	val: &toml::Value,
+) -> core::result::Result<String, String> {
+	match val {
+		toml::Value::String(val) => Ok(val.clone()),
+		toml::Value::Integer(val) => Ok(val.to_string()),
+		toml::Value::Float(val) => Ok(val.to_string()),
+		toml::Value::Boolean(val) => Ok(val.to_string()),
+		_ => Err(format!("unsupported value type for '{key}'")),
+	}
+}
+
+/// `db-url` or `db_url` to `SERVICE_DB_URL`.
+pub(super) fn to_env_name(key: &str) -> String {
+	format!("{KEY_PREFIX}{}", key.replace('-', "_").to_uppercase())
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn test_parse_config_toml_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_toml = r#"
+			web_folder = "web-folder/"
+			ip_allow_list = ["10.0.0.0/8", "127.0.0.1"]
+
+			[rate_limit]
+			enabled = true
+			per_sec = 20
+		"#;
+
+		// -- Exec
+		let entries = parse_config_toml(fx_toml).map_err(anyhow::Error::msg)?;
+
+		// -- Check
+		assert_eq!(entries["SERVICE_WEB_FOLDER"], "web-folder/");
+		assert_eq!(entries["SERVICE_IP_ALLOW_LIST"], "10.0.0.0/8,127.0.0.1");
+		assert_eq!(entries["SERVICE_RATE_LIMIT_ENABLED"], "true");
+		assert_eq!(entries["SERVICE_RATE_LIMIT_PER_SEC"], "20");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_config_sources_precedence_ok() {
+		// -- Setup & Fixtures
+		let fx_key = "SERVICE_TEST_SOURCES_PRECEDENCE";
+		let cli = parse_cli_args(
+			["--nocapture", "--test-sources-precedence=from-cli"]
+				.into_iter()
+				.map(String::from),
+		);
+		let file = HashMap::from([(fx_key.to_string(), "from-file".to_string())]);
+
+		// -- Exec & Check
+		let sources = ConfigSources {
+			cli,
+			file: file.clone(),
+		};
+		assert_eq!(sources.get_raw(fx_key).as_deref(), Some("from-cli"));
+
+		let sources = ConfigSources {
+			cli: HashMap::new(),
+			file,
+		};
+		assert_eq!(sources.get_raw(fx_key).as_deref(), Some("from-file"));
+	}
+}
+// endregion: --- Tests
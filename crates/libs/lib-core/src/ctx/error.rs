@@ -5,6 +5,8 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug, Serialize)]
 pub enum Error {
 	CtxCannotNewRootCtx,
+	CtxCannotNewServiceCtx(i64),
+	CtxServiceUnknown(&'static str),
 }
 
 // region:    --- Error Boilerplate
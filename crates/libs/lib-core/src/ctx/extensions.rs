@@ -0,0 +1,49 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A typed map of request-scoped data attached to a `Ctx`.
+///
+/// Values are keyed by their type (one value per type), so middlewares can
+/// attach data (e.g., tenant, feature flags) that BMCs and RPC
+/// handlers can later get back by type, without changing the `Ctx` struct.
+///
+/// NOTE: Values are stored behind an `Arc` so that cloning a `Ctx` stays cheap.
+#[derive(Clone, Default)]
+pub struct Extensions {
+	map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+	/// Insert a value, returning `true` if a value of the same type was replaced.
+	pub fn insert<T: Send + Sync + 'static>(&mut self, val: T) -> bool {
+		self.map.insert(TypeId::of::<T>(), Arc::new(val)).is_some()
+	}
+
+	pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+		self.map
+			.get(&TypeId::of::<T>())
+			.and_then(|val| val.downcast_ref::<T>())
+	}
+
+	/// Remove the value of this type, returning `true` if there was one.
+	pub fn remove<T: Send + Sync + 'static>(&mut self) -> bool {
+		self.map.remove(&TypeId::of::<T>()).is_some()
+	}
+
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.map.is_empty()
+	}
+}
+
+impl core::fmt::Debug for Extensions {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Extensions")
+			.field("len", &self.map.len())
+			.finish()
+	}
+}
@@ -0,0 +1,132 @@
+//! The request locale, among the supported `LOCALES` (BCP 47 tags, e.g., `en`,
+//! `fr-CA`), for the i18n of the error messages, mails, and dates formatting.
+//!
+//! The locale is, in order (see `resolve_locale`):
+//! - The user preference (the `user.locale` column, see `UserBmc::update_locale`).
+//! - The best supported `Accept-Language` one (by q-value, then order).
+//! - The default locale, i.e., the first of `LOCALES`.
+//!
+//! NOTE: A tag matches a supported locale exactly (case insensitive), or else
+//!       by its primary language (e.g., `fr-CH` is the supported `fr`).
+
+use crate::config::config;
+
+/// When the `LOCALES` config is empty.
+const LOCALE_FALLBACK: &str = "en";
+
+/// The first of `LOCALES`, for the contexts without request (e.g., services).
+pub fn default_locale() -> &'static str {
+	config()
+		.LOCALES
+		.first()
+		.map_or(LOCALE_FALLBACK, |locale| locale.as_str())
+}
+
+/// The request locale (see module doc).
+pub fn resolve_locale(
+	user_locale: Option<&str>,
+	accept_language: Option<&str>,
+) -> &'static str {
+	let supported = &config().LOCALES;
+
+	user_locale
+		.and_then(|locale| supported_locale(locale, supported))
+		.or_else(|| {
+			accept_language.and_then(|header| negotiate_locale(header, supported))
+		})
+		.unwrap_or_else(default_locale)
+}
+
+/// The supported locale of the tag, if any.
+pub fn supported_locale<'a>(tag: &str, supported: &'a [String]) -> Option<&'a str> {
+	let tag = tag.trim();
+	if tag.is_empty() || tag == "*" {
+		return None;
+	}
+
+	supported
+		.iter()
+		.find(|locale| locale.eq_ignore_ascii_case(tag))
+		.or_else(|| {
+			let primary = primary_language(tag);
+			supported.iter().find(|locale| {
+				primary_language(locale).eq_ignore_ascii_case(primary)
+			})
+		})
+		.map(|locale| locale.as_str())
+}
+
+/// The best supported locale of an `Accept-Language` header
+/// (e.g., `fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5`), if any.
+pub fn negotiate_locale<'a>(
+	accept_language: &str,
+	supported: &'a [String],
+) -> Option<&'a str> {
+	let mut tags: Vec<(&str, f32)> = accept_language
+		.split(',')
+		.filter_map(|item| {
+			let mut parts = item.split(';');
+			let tag = parts.next()?.trim();
+			let q = parts
+				.find_map(|param| param.trim().strip_prefix("q="))
+				.map_or(Some(1.), |q| q.trim().parse::<f32>().ok())?;
+			(q > 0.).then_some((tag, q))
+		})
+		.collect();
+	// Note: Stable, so the equal q-values keep the header order.
+	tags.sort_by(|(_, q_a), (_, q_b)| q_b.total_cmp(q_a));
+
+	tags.into_iter()
+		.find_map(|(tag, _)| supported_locale(tag, supported))
+}
+
+fn primary_language(tag: &str) -> &str {
+	tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fx_supported() -> Vec<String> {
+		vec!["en".to_string(), "fr".to_string(), "pt-BR".to_string()]
+	}
+
+	#[test]
+	fn test_negotiate_locale_ok() {
+		// -- Setup & Fixtures
+		let fx_supported = fx_supported();
+		let fx_cases: &[(&str, Option<&str>)] = &[
+			("fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5", Some("fr")),
+			("de, en;q=0.5, fr;q=0.7", Some("fr")),
+			("en;q=0.5, fr;q=0.5", Some("en")),
+			("pt-br", Some("pt-BR")),
+			("pt-PT", Some("pt-BR")),
+			("fr;q=0, en;q=0.1", Some("en")),
+			("de, *", None),
+			("fr;q=abc", None),
+			("", None),
+		];
+
+		// -- Exec & Check
+		for (header, expected) in fx_cases {
+			assert_eq!(
+				negotiate_locale(header, &fx_supported),
+				*expected,
+				"header: '{header}'"
+			);
+		}
+	}
+
+	#[test]
+	fn test_resolve_locale_ok() {
+		// -- Exec & Check
+		// Note: The `.cargo/config.toml` locales are `en, fr`.
+		assert_eq!(resolve_locale(Some("fr"), Some("en")), "fr");
+		assert_eq!(resolve_locale(Some("xx"), Some("fr-FR")), "fr");
+		assert_eq!(resolve_locale(None, Some("de")), default_locale());
+		assert_eq!(resolve_locale(None, None), "en");
+	}
+}
+// endregion: --- Tests
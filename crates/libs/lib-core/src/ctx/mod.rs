@@ -1,25 +1,93 @@
 mod error;
+mod extensions;
+pub mod locale;
 
 pub use self::error::{Error, Result};
+pub use self::extensions::Extensions;
+
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
 
 // endregion: --- Modules
 
+/// The user_id range reserved for the system/service contexts.
+/// (root is `0`, and the db user ids start at `1000`)
+pub const SERVICE_ID_RANGE: RangeInclusive<i64> = 1..=999;
+
+/// The known system services, with their reserved (and stable) user_id.
+///
+/// NOTE: Those ids are stamped into the `cid`/`mid` columns, so they must never
+///       be changed or reused once a service has written data.
+const SERVICES: &[(&str, i64)] = &[
+	("scheduler", 1),
+	("dev_seed", 2),
+	("maintenance", 3),
+	("admin_cli", 4),
+	("webhooks", 5),
+	("request_log", 6),
+	("hooks", 7),
+];
+
 #[derive(Clone, Debug)]
 pub struct Ctx {
 	user_id: i64,
+
+	/// The service name when this is a system/service context.
+	service: Option<&'static str>,
+
+	/// When the request must be done, applied to the db statements
+	/// (see `remaining`).
+	deadline: Option<Instant>,
+
+	/// The request locale (see `locale::resolve_locale`).
+	locale: Option<&'static str>,
+
+	/// Request-scoped data attached by the middlewares (by type).
+	extensions: Extensions,
 }
 
 // Constructors.
 impl Ctx {
 	pub fn root_ctx() -> Self {
-		Ctx { user_id: 0 }
+		Ctx {
+			user_id: 0,
+			service: None,
+			deadline: None,
+			locale: None,
+			extensions: Extensions::default(),
+		}
+	}
+
+	/// Named system context for background jobs, so their writes are
+	/// distinguishable from the root user ones in the data (cid/mid).
+	pub fn service(name: &'static str) -> Result<Self> {
+		let (name, user_id) = SERVICES
+			.iter()
+			.find(|(service_name, _)| *service_name == name)
+			.ok_or(Error::CtxServiceUnknown(name))?;
+
+		Ok(Ctx {
+			user_id: *user_id,
+			service: Some(name),
+			deadline: None,
+			locale: None,
+			extensions: Extensions::default(),
+		})
 	}
 
 	pub fn new(user_id: i64) -> Result<Self> {
 		if user_id == 0 {
 			Err(Error::CtxCannotNewRootCtx)
+		} else if SERVICE_ID_RANGE.contains(&user_id) {
+			Err(Error::CtxCannotNewServiceCtx(user_id))
 		} else {
-			Ok(Self { user_id })
+			Ok(Self {
+				user_id,
+				service: None,
+				deadline: None,
+				locale: None,
+				extensions: Extensions::default(),
+			})
 		}
 	}
 }
@@ -29,4 +97,124 @@ impl Ctx {
 	pub fn user_id(&self) -> i64 {
 		self.user_id
 	}
+
+	/// The service name, if this is a system/service context.
+	pub fn service_name(&self) -> Option<&'static str> {
+		self.service
+	}
+
+	pub fn is_root(&self) -> bool {
+		self.user_id == 0
+	}
+
+	pub fn is_service(&self) -> bool {
+		self.service.is_some()
+	}
+
+	pub fn deadline(&self) -> Option<Instant> {
+		self.deadline
+	}
+
+	pub fn set_deadline(&mut self, deadline: Instant) {
+		self.deadline = Some(deadline);
+	}
+
+	/// The time left before the deadline (zero when passed), if any deadline.
+	pub fn remaining(&self) -> Option<Duration> {
+		self.deadline
+			.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+	}
+
+	/// The request locale, or the default one (e.g., for the services).
+	pub fn locale(&self) -> &'static str {
+		self.locale.unwrap_or_else(locale::default_locale)
+	}
+
+	pub fn set_locale(&mut self, locale: &'static str) {
+		self.locale = Some(locale);
+	}
+
+	pub fn extensions(&self) -> &Extensions {
+		&self.extensions
+	}
+
+	pub fn extensions_mut(&mut self) -> &mut Extensions {
+		&mut self.extensions
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[derive(Debug, PartialEq)]
+	struct FxLocale(&'static str);
+
+	#[test]
+	fn test_ctx_extensions_insert_get_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mut ctx = Ctx::new(1000)?;
+
+		// -- Exec
+		ctx.extensions_mut().insert(FxLocale("fr"));
+		let replaced = ctx.extensions_mut().insert(FxLocale("en"));
+		let ctx_cloned = ctx.clone();
+
+		// -- Check
+		assert!(replaced, "should have replaced the first FxLocale");
+		assert_eq!(
+			ctx_cloned.extensions().get::<FxLocale>(),
+			Some(&FxLocale("en"))
+		);
+		assert_eq!(ctx.extensions().get::<String>(), None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_ctx_locale_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mut ctx = Ctx::new(1000)?;
+
+		// -- Exec & Check
+		assert_eq!(ctx.locale(), locale::default_locale());
+		ctx.set_locale("fr");
+		assert_eq!(ctx.clone().locale(), "fr");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_ctx_service_ok() -> Result<()> {
+		// -- Exec
+		let ctx = Ctx::service("scheduler")?;
+
+		// -- Check
+		assert!(SERVICE_ID_RANGE.contains(&ctx.user_id()));
+		assert_eq!(ctx.service_name(), Some("scheduler"));
+		assert!(!ctx.is_root());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_ctx_service_err() -> Result<()> {
+		// -- Exec & Check
+		let res = Ctx::service("not-a-service");
+		assert!(
+			matches!(res, Err(Error::CtxServiceUnknown("not-a-service"))),
+			"Should have matched `Err(Error::CtxServiceUnknown)` but was `{res:?}`"
+		);
+
+		let res = Ctx::new(1);
+		assert!(
+			matches!(res, Err(Error::CtxCannotNewServiceCtx(1))),
+			"Should have matched `Err(Error::CtxCannotNewServiceCtx)` but was `{res:?}`"
+		);
+
+		Ok(())
+	}
 }
+// endregion: --- Tests
@@ -1,10 +1,13 @@
+pub mod broker;
 pub mod config;
 pub mod ctx;
+pub mod mailer;
 pub mod model;
 pub mod pwd;
 pub mod token;
 
-// #[cfg(test)] // Commented during early development.
+// Recreates the db, so only with the `dev-utils` feature (and the tests).
+#[cfg(any(test, feature = "dev-utils"))]
 pub mod _dev_utils;
 
 pub use config::config;
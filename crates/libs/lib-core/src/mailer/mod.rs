@@ -0,0 +1,106 @@
+//! The mail sending, with the `SERVICE_MAILER` implementation:
+//!
+//! - `log` (default) - only logs the mails (the body only in dev), for dev and tests.
+//! - `smtp` (`mailer-smtp` feature) - sent to the `SERVICE_SMTP_URL` server.
+//!
+//! The messages are built from the `MailTemplate`s (e.g., the account
+//! verification, pwd reset, and task reminder mails).
+
+// region:    --- Modules
+
+mod error;
+#[cfg(feature = "mailer-smtp")]
+mod smtp;
+mod templates;
+
+pub use self::error::{Error, Result};
+pub use self::templates::MailTemplate;
+
+use crate::config::{config, AppEnv, MailerKind};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::info;
+
+// endregion: --- Modules
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mail {
+	pub to: String,
+	pub subject: String,
+	/// The plain text body.
+	pub text: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+	async fn send(&self, mail: &Mail) -> Result<()>;
+}
+
+/// Returns the configured `SERVICE_MAILER`.
+pub fn new_mailer() -> Result<Arc<dyn Mailer>> {
+	let config = config();
+
+	match config.MAILER {
+		MailerKind::Log => Ok(Arc::new(LogMailer {
+			log_body: config.ENV == AppEnv::Dev,
+		})),
+		#[cfg(feature = "mailer-smtp")]
+		MailerKind::Smtp => {
+			// Note: Required by the config for `smtp`.
+			let url = config.SMTP_URL.as_deref().unwrap_or_default();
+			Ok(Arc::new(smtp::SmtpMailer::new(url, &config.MAIL_FROM)?))
+		}
+		#[cfg(not(feature = "mailer-smtp"))]
+		MailerKind::Smtp => Err(Error::SmtpInit(
+			"needs the `mailer-smtp` feature".to_string(),
+		)),
+	}
+}
+
+// region:    --- LogMailer
+
+/// Only logs the mails.
+///
+/// NOTE: The body only in dev, as it can have secrets (e.g., pwd reset links).
+pub struct LogMailer {
+	log_body: bool,
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+	async fn send(&self, mail: &Mail) -> Result<()> {
+		let Mail { to, subject, text } = mail;
+		if self.log_body {
+			info!("{:<12} - to: {to} - {subject}\n{text}\n", "MAIL");
+		} else {
+			info!("{:<12} - to: {to} - {subject}", "MAIL");
+		}
+
+		Ok(())
+	}
+}
+
+// endregion: --- LogMailer
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[tokio::test]
+	async fn test_log_mailer_send_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_mail = MailTemplate::Verification {
+			username: "demo1",
+			link: "https://app.example.com/verify?token=fx-token",
+		}
+		.to_mail("demo1@example.com");
+
+		// -- Exec & Check
+		LogMailer { log_body: false }.send(&fx_mail).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
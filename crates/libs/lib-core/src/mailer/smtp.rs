@@ -0,0 +1,52 @@
+//! The SMTP mailer (lettre), pooling its connections.
+
+use crate::mailer::{Error, Mail, Mailer, Result};
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+pub struct SmtpMailer {
+	transport: AsyncSmtpTransport<Tokio1Executor>,
+	from: Mailbox,
+}
+
+impl SmtpMailer {
+	/// The `url` is `smtp(s)://[user:pwd@]host[:port]`
+	/// (`smtp://` upgrades with STARTTLS when `?tls=required`).
+	pub fn new(url: &str, from: &str) -> Result<Self> {
+		let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+			// Note: Not the url, which may have the smtp password.
+			.map_err(|ex| Error::SmtpInit(format!("invalid SMTP_URL - {ex}")))?
+			.build();
+		let from = parse_mailbox(from)?;
+
+		Ok(Self { transport, from })
+	}
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+	async fn send(&self, mail: &Mail) -> Result<()> {
+		let message = Message::builder()
+			.from(self.from.clone())
+			.to(parse_mailbox(&mail.to)?)
+			.subject(&mail.subject)
+			.header(ContentType::TEXT_PLAIN)
+			.body(mail.text.clone())
+			.map_err(|ex| Error::MailBuild(ex.to_string()))?;
+
+		self.transport
+			.send(message)
+			.await
+			.map_err(|ex| Error::SmtpSend(ex.to_string()))?;
+
+		Ok(())
+	}
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox> {
+	address.parse().map_err(|_| Error::InvalidAddress {
+		address: address.to_string(),
+	})
+}
@@ -0,0 +1,99 @@
+//! The mail messages, as plain text templates.
+
+use crate::mailer::Mail;
+
+pub enum MailTemplate<'a> {
+	/// The account email verification.
+	Verification { username: &'a str, link: &'a str },
+	/// The pwd reset request, with its link validity.
+	PwdReset {
+		username: &'a str,
+		link: &'a str,
+		valid_min: u32,
+	},
+	/// A task due reminder.
+	TaskReminder {
+		username: &'a str,
+		task_title: &'a str,
+		project_name: &'a str,
+		link: &'a str,
+	},
+}
+
+impl MailTemplate<'_> {
+	pub fn to_mail(&self, to: &str) -> Mail {
+		let (subject, text) = match self {
+			Self::Verification { username, link } => (
+				"Verify your email".to_string(),
+				format!(
+					"Hi {username},\n\n\
+					 Please verify your email by opening this link:\n\
+					 {link}\n\n\
+					 If you did not create an account, you can ignore this email.\n"
+				),
+			),
+			Self::PwdReset {
+				username,
+				link,
+				valid_min,
+			} => (
+				"Reset your password".to_string(),
+				format!(
+					"Hi {username},\n\n\
+					 A password reset was requested for your account.\n\
+					 Reset it with this link (valid for {valid_min} minutes):\n\
+					 {link}\n\n\
+					 If you did not request it, you can ignore this email.\n"
+				),
+			),
+			Self::TaskReminder {
+				username,
+				task_title,
+				project_name,
+				link,
+			} => (
+				format!("Reminder: {task_title}"),
+				format!(
+					"Hi {username},\n\n\
+					 This is a reminder for the task \"{task_title}\" \
+					 of the project \"{project_name}\":\n\
+					 {link}\n"
+				),
+			),
+		};
+
+		Mail {
+			to: to.to_string(),
+			subject,
+			text,
+		}
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_mail_ok() {
+		// -- Setup & Fixtures
+		let fx_template = MailTemplate::PwdReset {
+			username: "demo1",
+			link: "https://app.example.com/reset?token=fx-token",
+			valid_min: 30,
+		};
+
+		// -- Exec
+		let mail = fx_template.to_mail("demo1@example.com");
+
+		// -- Check
+		assert_eq!(mail.to, "demo1@example.com");
+		assert_eq!(mail.subject, "Reset your password");
+		assert!(mail.text.starts_with("Hi demo1,\n\n"));
+		assert!(mail
+			.text
+			.contains("(valid for 30 minutes):\nhttps://app.example.com/reset?token=fx-token\n"));
+	}
+}
+// endregion: --- Tests
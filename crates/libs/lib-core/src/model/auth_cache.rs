@@ -0,0 +1,108 @@
+//! The short-lived cache of the `UserForAuth`, in front of the per-request
+//! auth lookup (see `UserBmc::auth_by_username`).
+//!
+//! The entries are invalidated by the `UserBmc` updates of this process
+//! (e.g., pwd, active), and expire after `USER_AUTH_CACHE_TTL` otherwise.
+//!
+//! NOTE: A lookup which read the db before an invalidation does not cache its
+//!       (maybe previous) row, as the invalidations bump the cache generation
+//!       (see `generation` and `insert`).
+//!
+//! NOTE: So, the changes from another process (e.g., the `admin` tool, or another
+//!       instance) are seen after at most the TTL.
+
+use crate::model::user::UserForAuth;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const USER_AUTH_CACHE_TTL: Duration = Duration::from_secs(30);
+/// Over it, the expired entries are dropped (and all, if still full).
+const USER_AUTH_CACHE_MAX_ENTRIES: usize = 10_000;
+
+#[derive(Default)]
+pub(in crate::model) struct UserAuthCache {
+	entries: Mutex<HashMap<String, (Instant, UserForAuth)>>,
+	/// Bumped by each invalidation.
+	generation: AtomicU64,
+}
+
+impl UserAuthCache {
+	pub fn get(&self, username: &str) -> Option<UserForAuth> {
+		let entries = self.entries.lock().ok()?;
+		entries
+			.get(username)
+			.filter(|(time, _)| time.elapsed() < USER_AUTH_CACHE_TTL)
+			.map(|(_, user)| user.clone())
+	}
+
+	/// The generation to take before the db lookup, for its `insert`.
+	pub fn generation(&self) -> u64 {
+		self.generation.load(Ordering::Acquire)
+	}
+
+	/// Caches the user looked up at `generation`, unless invalidated since.
+	pub fn insert(&self, user: UserForAuth, generation: u64) {
+		let Ok(mut entries) = self.entries.lock() else {
+			return;
+		};
+		// Note: Checked under the lock, as bumped under it (see `invalidate`).
+		if self.generation.load(Ordering::Acquire) != generation {
+			return;
+		}
+		if entries.len() >= USER_AUTH_CACHE_MAX_ENTRIES {
+			entries.retain(|_, (time, _)| time.elapsed() < USER_AUTH_CACHE_TTL);
+			if entries.len() >= USER_AUTH_CACHE_MAX_ENTRIES {
+				entries.clear();
+			}
+		}
+		entries.insert(user.username.clone(), (Instant::now(), user));
+	}
+
+	pub fn invalidate(&self, user_id: i64) {
+		if let Ok(mut entries) = self.entries.lock() {
+			self.generation.fetch_add(1, Ordering::AcqRel);
+			entries.retain(|_, (_, user)| user.id != user_id);
+		}
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::user::UserRole;
+	use uuid::Uuid;
+
+	fn fx_user(username: &str) -> UserForAuth {
+		UserForAuth {
+			id: 1000,
+			username: username.to_string(),
+			role: UserRole::User,
+			token_salt: Uuid::new_v4().into(),
+			active: true,
+			must_change_pwd: false,
+			locale: None,
+		}
+	}
+
+	#[test]
+	fn test_insert_invalidated_since_not_cached() {
+		// -- Setup & Fixtures
+		let cache = UserAuthCache::default();
+		let fx_username = "test_insert_invalidated_since_not_cached";
+
+		// -- Exec
+		// A lookup reading the db, while the user is updated (and invalidated).
+		let generation = cache.generation();
+		cache.invalidate(1000);
+		cache.insert(fx_user(fx_username), generation);
+
+		// -- Check
+		assert!(cache.get(fx_username).is_none());
+		cache.insert(fx_user(fx_username), cache.generation());
+		assert!(cache.get(fx_username).is_some());
+	}
+}
+// endregion: --- Tests
@@ -1,26 +1,43 @@
+use futures::stream::{self, Stream, TryStreamExt};
 use lib_base::time::now_utc;
 use modql::field::{Field, Fields, HasFields};
-use modql::filter::{FilterGroups, ListOptions};
+use modql::filter::{FilterGroups, ListOptions, OrderBy};
 use modql::SIden;
 use sea_query::{
-	Condition, Expr, Iden, IntoIden, PostgresQueryBuilder, Query, TableRef,
+	Asterisk, Condition, DynIden, Expr, Func, Iden, IntoIden, Keyword, Order,
+	PostgresQueryBuilder, Query, SimpleExpr, TableRef, Values,
 };
-use sea_query_binder::SqlxBinder;
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use serde::Serialize;
 use sqlx::postgres::PgRow;
-use sqlx::FromRow;
+use sqlx::{FromRow, Postgres, Row, Transaction};
+use std::any::type_name;
+use std::borrow::Cow;
+use std::iter::once;
+use std::sync::Arc;
+use time::OffsetDateTime;
 
+use crate::config::config;
 use crate::ctx::Ctx;
+use crate::model::event::{ModelEvent, ModelEventKind};
+use crate::model::outbox;
+use crate::model::sql_cache::{self, plain_values, SqlOp};
+use crate::model::sync;
 use crate::model::ModelManager;
 use crate::model::{Error, Result};
-
-const LIST_LIMIT_DEFAULT: i64 = 1000;
-const LIST_LIMIT_MAX: i64 = 5000;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
 
 #[derive(Iden)]
 pub enum CommonIden {
 	Id,
 }
 
+#[derive(Iden)]
+pub enum TrashIden {
+	DeletedAt,
+}
+
 #[derive(Iden)]
 pub enum TimestampIden {
 	Cid,
@@ -29,41 +46,174 @@ pub enum TimestampIden {
 	Mtime,
 }
 
+/// A list page, with its paging metadata.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ListPage<E> {
+	pub items: Vec<E>,
+	/// The number of entities matching the filter (all pages).
+	pub total: i64,
+	pub offset: i64,
+	pub limit: i64,
+	pub has_more: bool,
+}
+
 pub trait DbBmc {
 	const TABLE: &'static str;
 
+	/// The column holding the owning project id, when the entity
+	/// changes should be published as `ModelEvent`s.
+	const EVENT_PROJECT_ID_COLUMN: Option<&'static str> = None;
+
+	/// When the deletes are recorded in the `tombstone` table
+	/// (for the delta sync, see `model::sync`).
+	const TOMBSTONES: bool = false;
+
+	/// When the deletes only set the `deleted_at` (i.e., move to the trash),
+	/// so they can be restored (or purged, see `model::trash`). The trashed
+	/// rows are then ignored by the other operations.
+	const SOFT_DELETE: bool = false;
+
+	/// The tables referencing the entity, and what its (hard) delete does to
+	/// their rows (see `OnDelete`).
+	const DEPENDENTS: &'static [Dependent] = &[];
+
+	/// The unique constraints names, to their field, for the `UniqueViolation`
+	/// of `create` / `update` (e.g., `("user_username_key", "username")`).
+	///
+	/// NOTE: When not listed, the field is the constraint key columns.
+	const UNIQUE_FIELDS: &'static [(&'static str, &'static str)] = &[];
+
+	/// The list limit when none is given.
+	const DEFAULT_LIMIT: i64 = 1000;
+	/// The max list limit (over it, the list fails with `ListLimitOverMax`, or
+	/// is lowered to it by `clamp_list_options`).
+	const MAX_LIMIT: i64 = 5000;
+	/// The list `order_bys` when none is given (for a stable paging).
+	const DEFAULT_ORDER_BYS: &'static str = "id";
+
 	fn table_ref() -> TableRef {
 		TableRef::Table(SIden(Self::TABLE).into_iden())
 	}
 }
 
-pub fn compute_list_options(
+/// What the delete of an entity does to the rows referencing it
+/// (see `DbBmc::DEPENDENTS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnDelete {
+	/// Deletes them first, in the delete transaction (with their tombstones, and
+	/// `Deleted` events).
+	Cascade,
+	/// Fails the delete with `EntityInUse` when there are any.
+	Restrict,
+	/// Sets their reference to null (so the column must be nullable).
+	Nullify,
+}
+
+/// The rows of a table referencing the entity by `column`.
+#[derive(Debug, Clone, Copy)]
+pub struct Dependent {
+	table: &'static str,
+	column: &'static str,
+	on_delete: OnDelete,
+	tombstones: bool,
+	event_project_id_column: Option<&'static str>,
+}
+
+impl Dependent {
+	/// The `MC` rows referencing the entity by `column`.
+	pub const fn of<MC: DbBmc>(column: &'static str, on_delete: OnDelete) -> Self {
+		Self {
+			table: MC::TABLE,
+			column,
+			on_delete,
+			tombstones: MC::TOMBSTONES,
+			event_project_id_column: MC::EVENT_PROJECT_ID_COLUMN,
+		}
+	}
+}
+
+/// Applies the `MC` list defaults (limit and order_bys), and validates the limit.
+pub fn compute_list_options<MC: DbBmc>(
 	list_options: Option<ListOptions>,
 ) -> Result<ListOptions> {
-	if let Some(mut list_options) = list_options {
-		// Validate the limit.
-		if let Some(limit) = list_options.limit {
-			if limit > LIST_LIMIT_MAX {
-				return Err(Error::ListLimitOverMax {
-					max: LIST_LIMIT_MAX,
-					actual: limit,
-				});
-			}
+	let mut list_options = list_options.unwrap_or_default();
+
+	// Validate the limit.
+	if let Some(limit) = list_options.limit.filter(|limit| *limit > MC::MAX_LIMIT) {
+		return Err(Error::ListLimitOverMax {
+			max: MC::MAX_LIMIT,
+			actual: limit,
+		});
+	}
+	apply_list_defaults::<MC>(&mut list_options);
+
+	Ok(list_options)
+}
+
+/// A list limit over the `MAX_LIMIT`, lowered to it (see `clamp_list_options`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct LimitClamp {
+	pub requested: i64,
+	pub max: i64,
+}
+
+/// Applies the `MC` list defaults (as `compute_list_options`), but lowers a
+/// limit over `MC::MAX_LIMIT` to it, rather than failing, and returns the clamp.
+///
+/// NOTE: For the api callers (e.g., the json-rpc list methods), which report
+///       the clamp to the client.
+pub fn clamp_list_options<MC: DbBmc>(
+	list_options: Option<ListOptions>,
+) -> (ListOptions, Option<LimitClamp>) {
+	let mut list_options = list_options.unwrap_or_default();
+
+	let clamp = match list_options.limit {
+		Some(limit) if limit > MC::MAX_LIMIT => {
+			list_options.limit = Some(MC::MAX_LIMIT);
+			Some(LimitClamp {
+				requested: limit,
+				max: MC::MAX_LIMIT,
+			})
 		}
-		// Set the default limit if no limit
-		else {
-			list_options.limit = Some(LIST_LIMIT_DEFAULT);
+		_ => None,
+	};
+	apply_list_defaults::<MC>(&mut list_options);
+
+	(list_options, clamp)
+}
+
+/// Sets the `MC` default limit and order_bys, when none.
+fn apply_list_defaults<MC: DbBmc>(list_options: &mut ListOptions) {
+	if list_options.limit.is_none() {
+		list_options.limit = Some(MC::DEFAULT_LIMIT);
+	}
+	if list_options.order_bys.is_none() {
+		list_options.order_bys = Some(MC::DEFAULT_ORDER_BYS.into());
+	}
+}
+
+/// Checks that the `order_bys` are columns of `E`, so an unknown one fails
+/// with `ListOrderByUnknown` rather than a database error.
+///
+/// Note: The filter fields are checked when deserialized
+///       (i.e., the filters are `#[serde(deny_unknown_fields)]`).
+pub fn validate_order_bys<E: HasFields>(list_options: &ListOptions) -> Result<()> {
+	let Some(order_bys) = &list_options.order_bys else {
+		return Ok(());
+	};
+
+	for order_by in order_bys {
+		let (OrderBy::Asc(column) | OrderBy::Desc(column)) = order_by;
+		if !E::field_names().contains(&column.as_str()) {
+			return Err(Error::ListOrderByUnknown {
+				column: column.to_string(),
+			});
 		}
-		Ok(list_options)
-	}
-	// When None, return default
-	else {
-		Ok(ListOptions {
-			limit: Some(LIST_LIMIT_DEFAULT),
-			offset: None,
-			order_bys: Some("id".into()),
-		})
 	}
+
+	Ok(())
 }
 
 pub async fn create<MC, E>(ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
@@ -71,59 +221,145 @@ where
 	MC: DbBmc,
 	E: HasFields,
 {
-	let db = mm.db();
+	let mut tx = begin(ctx, mm).await?;
+	let (id, has_event) = insert::<MC, E>(ctx, &mut tx, data).await?;
+	commit(mm, tx, has_event).await?;
+
+	Ok(id)
+}
+
+/// Same as `create`, for all the `data` in one transaction (all or none
+/// created), returning the ids in the `data` order.
+pub async fn create_many<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	data: Vec<E>,
+) -> Result<Vec<i64>>
+where
+	MC: DbBmc,
+	E: HasFields,
+{
+	let mut tx = begin(ctx, mm).await?;
+	let mut ids = Vec::with_capacity(data.len());
+	let mut has_event = false;
+	for data in data {
+		let (id, row_has_event) = insert::<MC, E>(ctx, &mut tx, data).await?;
+		ids.push(id);
+		has_event |= row_has_event;
+	}
+	commit(mm, tx, has_event).await?;
+
+	Ok(ids)
+}
 
+/// Inserts a `create` row (with its outbox event), and returns its id and
+/// if an event was written.
+pub(in crate::model) async fn insert<MC, E>(
+	ctx: &Ctx,
+	tx: &mut Transaction<'_, Postgres>,
+	data: E,
+) -> Result<(i64, bool)>
+where
+	MC: DbBmc,
+	E: HasFields,
+{
 	// -- Extract fields (name / sea-query value expression)
 	let mut fields = data.not_none_fields();
 	add_timestamps_for_create(&mut fields, ctx.user_id());
 	let (columns, sea_values) = fields.for_sea_insert();
 
-	// -- Build query
-	let mut query = Query::insert();
-	query
-		.into_table(MC::table_ref())
-		.columns(columns)
-		.values(sea_values)?
-		.returning(Query::returning().columns([CommonIden::Id]));
-	// -- Exec query
-	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-	let (id,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
-		.fetch_one(db)
-		.await?;
+	// -- Build query (or get it from the cache)
+	let (sql, values) = cached_sql::<MC>(
+		SqlOp::Create,
+		column_names(&columns).into(),
+		&sea_values,
+		|| {
+			let mut query = Query::insert();
+			query
+				.into_table(MC::table_ref())
+				.columns(columns.clone())
+				.values(sea_values.clone())?
+				.returning(Query::returning().columns(returning_columns::<MC>()));
+			Ok(query.build_sqlx(PostgresQueryBuilder))
+		},
+	)?;
 
-	Ok(id)
+	// -- Exec query (with its outbox event)
+	let row = sqlx::query_with(&sql, values)
+		.fetch_one(&mut **tx)
+		.await
+		.map_err(write_error::<MC>)?;
+	let id: i64 = row.try_get(0)?;
+	let has_event =
+		write_event::<MC>(ctx, tx, &row, ModelEventKind::Created).await?;
+
+	Ok((id, has_event))
 }
 
-pub async fn get<MC, E>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
+/// The insert / update error, where a unique violation is of the `MC` entity,
+/// and its field resolved by the constraint (see `DbBmc::UNIQUE_FIELDS`).
+fn write_error<MC: DbBmc>(err: sqlx::Error) -> Error {
+	let unique_field = err
+		.as_database_error()
+		.filter(|db_err| db_err.is_unique_violation())
+		.and_then(|db_err| db_err.constraint())
+		.and_then(|constraint| {
+			MC::UNIQUE_FIELDS
+				.iter()
+				.find(|(name, _)| *name == constraint)
+		})
+		.map(|(_, field)| field.to_string());
+
+	match Error::from(err) {
+		Error::UniqueViolation { field, .. } => Error::UniqueViolation {
+			entity: MC::TABLE.to_string(),
+			field: unique_field.or(field),
+		},
+		err => err,
+	}
+}
+
+pub async fn get<MC, E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
 where
 	MC: DbBmc,
 	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
 	E: HasFields,
 {
-	let db = mm.db();
+	let db = mm.db()?;
 
-	// -- Build query
-	let mut query = Query::select();
-	query
-		.from(MC::table_ref())
-		.columns(E::field_column_refs())
-		.and_where(Expr::col(CommonIden::Id).eq(id));
+	// -- Build query (or get it from the cache)
+	let id_value = SimpleExpr::Value(id.into());
+	let (sql, values) =
+		cached_sql::<MC>(SqlOp::Get, type_name::<E>().into(), [&id_value], || {
+			let mut query = Query::select();
+			query
+				.from(MC::table_ref())
+				.columns(E::field_column_refs())
+				.and_where(Expr::col(CommonIden::Id).eq(id))
+				.cond_where(trash_condition::<MC>(false));
+			Ok(query.build_sqlx(PostgresQueryBuilder))
+		})?;
 
 	// -- Exec query
-	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-	let entity = sqlx::query_as_with::<_, E, _>(&sql, values)
-		.fetch_optional(db)
-		.await?
-		.ok_or(Error::EntityNotFound {
-			entity: MC::TABLE,
-			id,
-		})?;
+	let query = sqlx::query_as_with::<_, E, _>(&sql, values);
+	let entity = if needs_tx(ctx) {
+		let mut tx = begin(ctx, mm).await?;
+		let entity = query.fetch_optional(&mut *tx).await?;
+		tx.commit().await?;
+		entity
+	} else {
+		query.fetch_optional(db).await?
+	}
+	.ok_or(Error::EntityNotFound {
+		entity: MC::TABLE,
+		id,
+	})?;
 
 	Ok(entity)
 }
 
 pub async fn list<MC, E, F>(
-	_ctx: &Ctx,
+	ctx: &Ctx,
 	mm: &ModelManager,
 	filter: Option<F>,
 	list_options: Option<ListOptions>,
@@ -134,31 +370,218 @@ where
 	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
 	E: HasFields,
 {
-	let db = mm.db();
+	list_in::<MC, E, F>(ctx, mm, filter, list_options, false).await
+}
+
+/// Same as `list`, for the trashed entities (see `DbBmc::SOFT_DELETE`).
+pub async fn list_trashed<MC, E, F>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+	list_options: Option<ListOptions>,
+) -> Result<Vec<E>>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasFields,
+{
+	list_in::<MC, E, F>(ctx, mm, filter, list_options, true).await
+}
+
+async fn list_in<MC, E, F>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+	list_options: Option<ListOptions>,
+	trashed: bool,
+) -> Result<Vec<E>>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasFields,
+{
+	let db = mm.db()?;
 
 	// -- Build the query
 	let mut query = Query::select();
-	query.from(MC::table_ref()).columns(E::field_column_refs());
+	query
+		.from(MC::table_ref())
+		.columns(E::field_column_refs())
+		.cond_where(trash_condition::<MC>(trashed));
 
 	// condition from filter
-	if let Some(filter) = filter {
-		let filters: FilterGroups = filter.into();
-		let cond: Condition = filters.try_into()?;
+	if let Some(cond) = filter_condition(filter)? {
 		query.cond_where(cond);
 	}
 
 	// list options
-	let list_options = compute_list_options(list_options)?;
+	let list_options = compute_list_options::<MC>(list_options)?;
+	validate_order_bys::<E>(&list_options)?;
 	list_options.apply_to_sea_query(&mut query);
 
 	// -- Execute the query
 	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-	let entities = sqlx::query_as_with::<_, E, _>(&sql, values)
-		.fetch_all(db)
-		.await?;
+	let query = sqlx::query_as_with::<_, E, _>(&sql, values);
+	let entities = if needs_tx(ctx) {
+		let mut tx = begin(ctx, mm).await?;
+		let entities = query.fetch_all(&mut *tx).await?;
+		tx.commit().await?;
+		entities
+	} else {
+		query.fetch_all(db).await?
+	};
+
 	Ok(entities)
 }
 
+/// The rows per query of `list_stream`.
+const LIST_STREAM_BATCH: u64 = 500;
+
+/// Streams all the entities matching the filter (and the `extra_cond`), in
+/// `id` order, with no limit (e.g., for the exports).
+///
+/// Note: Fetched by batches (keyset paged on `id`), so neither all the rows,
+///       nor a db connection, are held for the whole stream.
+pub fn list_stream<MC, E, F>(
+	mm: &ModelManager,
+	filter: Option<F>,
+	extra_cond: Option<Condition>,
+) -> Result<impl Stream<Item = Result<E>> + Send + 'static>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send + 'static,
+	E: HasFields,
+{
+	let mut cond = trash_condition::<MC>(false);
+	if let Some(filter_cond) = filter_condition(filter)? {
+		cond = cond.add(filter_cond);
+	}
+	if let Some(extra_cond) = extra_cond {
+		cond = cond.add(extra_cond);
+	}
+
+	let batches = stream::try_unfold(
+		(mm.clone(), cond, Some(i64::MIN)),
+		|(mm, cond, after_id)| async move {
+			let Some(after_id) = after_id else {
+				return Ok::<_, Error>(None);
+			};
+			let (entities, last_id) =
+				list_stream_batch::<MC, E>(&mm, cond.clone(), after_id).await?;
+
+			Ok(Some((entities, (mm, cond, last_id))))
+		},
+	);
+
+	Ok(batches
+		.map_ok(|entities| stream::iter(entities.into_iter().map(Ok)))
+		.try_flatten())
+}
+
+/// The `list_stream` entities after `after_id`, and the id to continue
+/// after (None when it is the last batch).
+async fn list_stream_batch<MC, E>(
+	mm: &ModelManager,
+	cond: Condition,
+	after_id: i64,
+) -> Result<(Vec<E>, Option<i64>)>
+where
+	MC: DbBmc,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasFields,
+{
+	let mut query = Query::select();
+	query
+		.from(MC::table_ref())
+		.columns(E::field_column_refs())
+		.cond_where(cond)
+		.and_where(Expr::col(CommonIden::Id).gt(after_id))
+		.order_by(CommonIden::Id, Order::Asc)
+		.limit(LIST_STREAM_BATCH);
+	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+	let rows = sqlx::query_with(&sql, values).fetch_all(mm.db()?).await?;
+
+	// Note: A partial batch is the last one.
+	let last_id = match rows.last() {
+		Some(row) if rows.len() as u64 == LIST_STREAM_BATCH => {
+			Some(row.try_get::<i64, _>("id")?)
+		}
+		_ => None,
+	};
+	let entities = rows
+		.iter()
+		.map(E::from_row)
+		.collect::<core::result::Result<Vec<E>, _>>()?;
+
+	Ok((entities, last_id))
+}
+
+/// Same as `list`, with the paging metadata (`total` from a `COUNT(*)` with the
+/// same filter).
+pub async fn list_with_meta<MC, E, F>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+	list_options: Option<ListOptions>,
+) -> Result<ListPage<E>>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasFields,
+{
+	let cond = filter_condition(filter)?;
+	let list_options = compute_list_options::<MC>(list_options)?;
+	validate_order_bys::<E>(&list_options)?;
+	let offset = list_options.offset.unwrap_or(0);
+	let limit = list_options.limit.unwrap_or(MC::DEFAULT_LIMIT);
+
+	// -- Build the queries
+	let mut query = Query::select();
+	query
+		.from(MC::table_ref())
+		.columns(E::field_column_refs())
+		.cond_where(trash_condition::<MC>(false));
+	let mut count_query = Query::select();
+	count_query
+		.from(MC::table_ref())
+		.expr(Func::count(Expr::col(Asterisk)))
+		.cond_where(trash_condition::<MC>(false));
+	if let Some(cond) = cond {
+		query.cond_where(cond.clone());
+		count_query.cond_where(cond);
+	}
+	list_options.apply_to_sea_query(&mut query);
+
+	// -- Execute the queries
+	// Note: In a transaction, for the deadline, which is not a snapshot though
+	//       (read committed), so `total` might be off by the concurrent writes,
+	//       which is fine for paging.
+	let mut tx = begin(ctx, mm).await?;
+	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+	let items = sqlx::query_as_with::<_, E, _>(&sql, values)
+		.fetch_all(&mut *tx)
+		.await?;
+	let (sql, values) = count_query.build_sqlx(PostgresQueryBuilder);
+	let (total,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
+		.fetch_one(&mut *tx)
+		.await?;
+	tx.commit().await?;
+
+	let has_more = offset + (items.len() as i64) < total;
+
+	Ok(ListPage {
+		items,
+		total,
+		offset,
+		limit,
+		has_more,
+	})
+}
+
 pub async fn update<MC, E>(
 	ctx: &Ctx,
 	mm: &ModelManager,
@@ -169,68 +592,573 @@ where
 	MC: DbBmc,
 	E: HasFields,
 {
-	let db = mm.db();
+	update_in::<MC, E>(ctx, mm, id, None, data).await
+}
 
+/// Updates the entity only if its `mtime` is still `mtime` (i.e., not modified
+/// since read), in the same statement, so the concurrent writes are not lost.
+///
+/// Fails with `EntityModified` otherwise (or if it is unknown).
+pub async fn update_if_unmodified<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	mtime: OffsetDateTime,
+	data: E,
+) -> Result<()>
+where
+	MC: DbBmc,
+	E: HasFields,
+{
+	update_in::<MC, E>(ctx, mm, id, Some(mtime), data).await
+}
+
+async fn update_in<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	mtime: Option<OffsetDateTime>,
+	data: E,
+) -> Result<()>
+where
+	MC: DbBmc,
+	E: HasFields,
+{
 	let mut fields = data.not_none_fields();
 	add_timestamps_for_update(&mut fields, ctx.user_id());
-	let fields = fields.for_sea_update();
+	let fields: Vec<_> = fields.for_sea_update().collect();
+
+	// -- Build query (or get it from the cache)
+	let columns: Vec<DynIden> = fields.iter().map(|(c, _)| c.clone()).collect();
+	let id_value = SimpleExpr::Value(id.into());
+	let mtime_value = mtime.map(|mtime| SimpleExpr::Value(mtime.into()));
+	let mut variant = column_names(&columns);
+	if mtime.is_some() {
+		variant.push_str(";if_mtime");
+	}
+	let (sql, values) = cached_sql::<MC>(
+		SqlOp::Update,
+		variant.into(),
+		fields
+			.iter()
+			.map(|(_, v)| v)
+			.chain(once(&id_value))
+			.chain(mtime_value.as_ref()),
+		|| {
+			let mut query = Query::update();
+			query
+				.table(MC::table_ref())
+				.values(fields.clone())
+				.and_where(Expr::col(CommonIden::Id).eq(id))
+				.cond_where(mtime_condition(mtime))
+				.cond_where(trash_condition::<MC>(false))
+				.returning(Query::returning().columns(returning_columns::<MC>()));
+			Ok(query.build_sqlx(PostgresQueryBuilder))
+		},
+	)?;
+
+	// -- Execute query (with its outbox event)
+	let mut tx = begin(ctx, mm).await?;
+	let row = sqlx::query_with(&sql, values)
+		.fetch_optional(&mut *tx)
+		.await
+		.map_err(write_error::<MC>)?;
+
+	// -- Check result
+	let row = row.ok_or_else(|| not_written_error::<MC>(id, mtime))?;
+	let has_event =
+		write_event::<MC>(ctx, &mut tx, &row, ModelEventKind::Updated).await?;
+	commit(mm, tx, has_event).await?;
+
+	Ok(())
+}
+
+pub async fn delete<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+where
+	MC: DbBmc,
+{
+	delete_in::<MC>(ctx, mm, id, None).await
+}
+
+/// Deletes the entity only if its `mtime` is still `mtime` (see
+/// `update_if_unmodified`).
+///
+/// Fails with `EntityModified` otherwise (or if it is unknown).
+pub async fn delete_if_unmodified<MC>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	mtime: OffsetDateTime,
+) -> Result<()>
+where
+	MC: DbBmc,
+{
+	delete_in::<MC>(ctx, mm, id, Some(mtime)).await
+}
+
+async fn delete_in<MC>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	mtime: Option<OffsetDateTime>,
+) -> Result<()>
+where
+	MC: DbBmc,
+{
+	// -- Build query (or get it from the cache)
+	let id_value = SimpleExpr::Value(id.into());
+	let mtime_value = mtime.map(|mtime| SimpleExpr::Value(mtime.into()));
+	let variant = if mtime.is_some() { "if_mtime" } else { "" };
+	let (sql, values) = cached_sql::<MC>(
+		SqlOp::Delete,
+		Cow::Borrowed(variant),
+		once(&id_value).chain(mtime_value.as_ref()),
+		|| {
+			let returning = Query::returning().columns(returning_columns::<MC>());
+			// Note: The soft delete keeps the `mid` / `mtime`, so the restore
+			//       is the only trace of a trashed row in the delta sync.
+			if MC::SOFT_DELETE {
+				let mut query = Query::update();
+				query
+					.table(MC::table_ref())
+					.value(TrashIden::DeletedAt, Expr::current_timestamp())
+					.and_where(Expr::col(CommonIden::Id).eq(id))
+					.cond_where(mtime_condition(mtime))
+					.cond_where(trash_condition::<MC>(false))
+					.returning(returning);
+				Ok(query.build_sqlx(PostgresQueryBuilder))
+			} else {
+				let mut query = Query::delete();
+				query
+					.from_table(MC::table_ref())
+					.and_where(Expr::col(CommonIden::Id).eq(id))
+					.cond_where(mtime_condition(mtime))
+					.returning(returning);
+				Ok(query.build_sqlx(PostgresQueryBuilder))
+			}
+		},
+	)?;
+
+	// -- Execute query (with its dependents, and outbox events)
+	let mut tx = begin(ctx, mm).await?;
+	// Note: The trashed rows keep their dependents, to be restored with them.
+	let dependents_have_event = if MC::SOFT_DELETE {
+		false
+	} else {
+		delete_dependents(ctx, &mut tx, MC::TABLE, id, MC::DEPENDENTS).await?
+	};
+	// Note: Before the delete, as the project might be the deleted one.
+	let tombstone_owner_id = if MC::TOMBSTONES {
+		tombstone_owner_id::<MC>(&mut tx, id).await?
+	} else {
+		None
+	};
+	let row = sqlx::query_with(&sql, values)
+		.fetch_optional(&mut *tx)
+		.await?;
+
+	// -- Check result
+	// Note: Dropping the tx rolls back the dependents deletes.
+	let row = row.ok_or_else(|| not_written_error::<MC>(id, mtime))?;
+	if MC::TOMBSTONES {
+		sync::insert_tombstone(&mut tx, MC::TABLE, id, tombstone_owner_id).await?;
+	}
+	let has_event =
+		write_event::<MC>(ctx, &mut tx, &row, ModelEventKind::Deleted).await?;
+	commit(mm, tx, has_event || dependents_have_event).await?;
+
+	Ok(())
+}
 
-	// -- Build query
+/// Restores a trashed entity (see `DbBmc::SOFT_DELETE`), as a `Created`
+/// event, and without its tombstones (for the delta sync).
+pub async fn restore<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+where
+	MC: DbBmc,
+{
 	let mut query = Query::update();
 	query
 		.table(MC::table_ref())
-		.values(fields)
-		.and_where(Expr::col(CommonIden::Id).eq(id));
-
-	// -- Execute query
+		.value(TrashIden::DeletedAt, SimpleExpr::Keyword(Keyword::Null))
+		.value(TimestampIden::Mid, ctx.user_id())
+		.value(TimestampIden::Mtime, now_utc())
+		.and_where(Expr::col(CommonIden::Id).eq(id))
+		.cond_where(trash_condition::<MC>(true))
+		.returning(Query::returning().columns(returning_columns::<MC>()));
 	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-	let count = sqlx::query_with(&sql, values)
-		.execute(db)
-		.await?
-		.rows_affected();
 
-	// -- Check result
-	if count == 0 {
-		Err(Error::EntityNotFound {
+	let mut tx = begin(ctx, mm).await?;
+	let row = sqlx::query_with(&sql, values)
+		.fetch_optional(&mut *tx)
+		.await?
+		.ok_or(Error::EntityNotFound {
 			entity: MC::TABLE,
 			id,
-		})
-	} else {
-		Ok(())
+		})?;
+	if MC::TOMBSTONES {
+		sync::delete_tombstones(&mut tx, MC::TABLE, id).await?;
 	}
+	let has_event =
+		write_event::<MC>(ctx, &mut tx, &row, ModelEventKind::Created).await?;
+	commit(mm, tx, has_event).await?;
+
+	Ok(())
 }
 
-pub async fn delete<MC>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+/// Deletes a trashed entity for good (no event, it was sent by the delete).
+pub async fn purge<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
 where
 	MC: DbBmc,
 {
-	let db = mm.db();
-
-	// -- Build query
 	let mut query = Query::delete();
 	query
 		.from_table(MC::table_ref())
-		.and_where(Expr::col(CommonIden::Id).eq(id));
-
-	// -- Execute query
+		.and_where(Expr::col(CommonIden::Id).eq(id))
+		.cond_where(trash_condition::<MC>(true));
 	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+	let mut tx = begin(ctx, mm).await?;
 	let count = sqlx::query_with(&sql, values)
-		.execute(db)
+		.execute(&mut *tx)
 		.await?
 		.rows_affected();
-
-	// -- Check result
 	if count == 0 {
-		Err(Error::EntityNotFound {
+		return Err(Error::EntityNotFound {
 			entity: MC::TABLE,
 			id,
-		})
-	} else {
-		Ok(())
+		});
 	}
+	tx.commit().await?;
+
+	Ok(())
+}
+
+/// Applies the `OnDelete` of the `dependents` of the entity `id`, before its
+/// delete (in its transaction, so all or none), and returns if events were
+/// written (for the cascade deleted rows, see `write_event`).
+pub(in crate::model) async fn delete_dependents(
+	ctx: &Ctx,
+	tx: &mut Transaction<'_, Postgres>,
+	entity: &'static str,
+	id: i64,
+	dependents: &[Dependent],
+) -> Result<bool> {
+	// Note: All the restrict checks first, so no useless cascade.
+	let restricts = dependents
+		.iter()
+		.filter(|dependent| dependent.on_delete == OnDelete::Restrict);
+	for dependent in restricts {
+		let mut query = Query::select();
+		query
+			.expr(Expr::val(1))
+			.from(SIden(dependent.table))
+			.and_where(Expr::col(SIden(dependent.column)).eq(id))
+			.limit(1);
+		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+		if sqlx::query_with(&sql, values)
+			.fetch_optional(&mut **tx)
+			.await?
+			.is_some()
+		{
+			return Err(Error::EntityInUse {
+				entity,
+				id,
+				dependent: dependent.table,
+			});
+		}
+	}
+
+	let mut has_event = false;
+	for dependent in dependents {
+		match dependent.on_delete {
+			OnDelete::Restrict => (),
+			OnDelete::Cascade => {
+				// Note: The same returning columns as `returning_columns`.
+				let mut returning = vec![CommonIden::Id.into_iden()];
+				if let Some(project_id_column) = dependent.event_project_id_column {
+					returning.push(SIden(project_id_column).into_iden());
+				}
+				let mut query = Query::delete();
+				query
+					.from_table(SIden(dependent.table))
+					.and_where(Expr::col(SIden(dependent.column)).eq(id))
+					.returning(Query::returning().columns(returning));
+				let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+				let rows =
+					sqlx::query_with(&sql, values).fetch_all(&mut **tx).await?;
+				for row in rows {
+					if dependent.tombstones {
+						let dependent_id: i64 = row.try_get(0)?;
+						// Note: The project of the row is not deleted yet.
+						let owner_id = match dependent.event_project_id_column {
+							Some(_) => {
+								sync::project_owner_id(tx, row.try_get(1)?).await?
+							}
+							None => None,
+						};
+						sync::insert_tombstone(
+							tx,
+							dependent.table,
+							dependent_id,
+							owner_id,
+						)
+						.await?;
+					}
+					if dependent.event_project_id_column.is_some() {
+						write_row_event(
+							ctx,
+							tx,
+							dependent.table,
+							&row,
+							ModelEventKind::Deleted,
+						)
+						.await?;
+						has_event = true;
+					}
+				}
+			}
+			OnDelete::Nullify => {
+				let mut query = Query::update();
+				query
+					.table(SIden(dependent.table))
+					.value(SIden(dependent.column), Expr::val(None::<i64>))
+					.and_where(Expr::col(SIden(dependent.column)).eq(id));
+				let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+				sqlx::query_with(&sql, values).execute(&mut **tx).await?;
+			}
+		}
+	}
+
+	Ok(has_event)
 }
 
 // region:    --- Utils
+/// Begins a transaction, with the remaining time of the ctx deadline, if any,
+/// as the `statement_timeout` of its queries (so a query does not outlive a
+/// timed out request), and the ctx for the RLS policies (see `set_rls_ctx`).
+pub(in crate::model) async fn begin<'a>(
+	ctx: &Ctx,
+	mm: &'a ModelManager,
+) -> Result<Transaction<'a, Postgres>> {
+	let mut tx = mm.db()?.begin().await?;
+
+	if config().DB_RLS {
+		set_rls_ctx(ctx, &mut tx).await?;
+	}
+
+	if let Some(remaining) = ctx.remaining() {
+		if remaining.is_zero() {
+			return Err(Error::DeadlineExceeded);
+		}
+		let timeout_ms = remaining.as_millis().max(1).to_string();
+		sqlx::query("SELECT set_config('statement_timeout', $1, true)")
+			.bind(timeout_ms)
+			.execute(&mut *tx)
+			.await?;
+	}
+
+	Ok(tx)
+}
+
+/// The reads go through `begin` when its settings apply (otherwise on the
+/// pool, without a transaction).
+fn needs_tx(ctx: &Ctx) -> bool {
+	ctx.deadline().is_some() || config().DB_RLS
+}
+
+/// Sets the ctx as the transaction local `app.user_id` and `app.service`
+/// (empty for the users), and switches to the `DB_RLS_ROLE`, if any, so the
+/// RLS policies can check them, e.g.,
+/// `USING (cid = current_setting('app.user_id')::bigint)`.
+///
+/// NOTE: Only the BMC transactions (see `begin`), the other queries (e.g.,
+///       `list_stream`, the jobs) are on the pool role.
+async fn set_rls_ctx(ctx: &Ctx, tx: &mut Transaction<'_, Postgres>) -> Result<()> {
+	sqlx::query(
+		"SELECT set_config('app.user_id', $1, true), \
+		 set_config('app.service', $2, true)",
+	)
+	.bind(ctx.user_id().to_string())
+	.bind(ctx.service_name().unwrap_or_default())
+	.execute(&mut **tx)
+	.await?;
+
+	if let Some(role) = &config().DB_RLS_ROLE {
+		sqlx::query("SELECT set_config('role', $1, true)")
+			.bind(role)
+			.execute(&mut **tx)
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// The sql and values of a base operation, with the sql from the cache
+/// (see `model::sql_cache`), or from `build` (then cached).
+///
+/// The `exprs` are the values in the sql placeholders order. When one is not
+/// a plain value, the statement is built (and not cached).
+fn cached_sql<'a, MC: DbBmc>(
+	op: SqlOp,
+	variant: Cow<'static, str>,
+	exprs: impl IntoIterator<Item = &'a SimpleExpr>,
+	build: impl FnOnce() -> Result<(String, SqlxValues)>,
+) -> Result<(Arc<str>, SqlxValues)> {
+	let Some(values) = plain_values(exprs) else {
+		let (sql, values) = build()?;
+		return Ok((sql.into(), values));
+	};
+	let sql = sql_cache::get_or_build::<MC>(op, variant, || Ok(build()?.0))?;
+
+	Ok((sql, SqlxValues(Values(values))))
+}
+
+/// The comma separated column names (i.e., the cache variant of the writes).
+fn column_names(columns: &[DynIden]) -> String {
+	columns
+		.iter()
+		.map(|column| column.to_string())
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+/// The `mtime` condition of the `_if_unmodified` writes (always true without).
+fn mtime_condition(mtime: Option<OffsetDateTime>) -> Condition {
+	let cond = Condition::all();
+	match mtime {
+		Some(mtime) => cond.add(Expr::col(TimestampIden::Mtime).eq(mtime)),
+		None => cond,
+	}
+}
+
+/// The error of a write without row, `EntityModified` for the `_if_unmodified`
+/// ones (as the row might be there, but modified).
+fn not_written_error<MC: DbBmc>(id: i64, mtime: Option<OffsetDateTime>) -> Error {
+	match mtime {
+		Some(_) => Error::EntityModified {
+			entity: MC::TABLE,
+			id,
+		},
+		None => Error::EntityNotFound {
+			entity: MC::TABLE,
+			id,
+		},
+	}
+}
+
+/// The live (or `trashed`) rows condition, always true when not `SOFT_DELETE`.
+fn trash_condition<MC: DbBmc>(trashed: bool) -> Condition {
+	let cond = Condition::all();
+	if !MC::SOFT_DELETE {
+		return cond;
+	}
+
+	let deleted_at = Expr::col(TrashIden::DeletedAt);
+	if trashed {
+		cond.add(deleted_at.is_not_null())
+	} else {
+		cond.add(deleted_at.is_null())
+	}
+}
+
+fn filter_condition<F>(filter: Option<F>) -> Result<Option<Condition>>
+where
+	F: Into<FilterGroups>,
+{
+	let Some(filter) = filter else {
+		return Ok(None);
+	};
+	let filters: FilterGroups = filter.into();
+	let cond: Condition = filters.try_into()?;
+
+	Ok(Some(cond))
+}
+
+/// The columns returned by the writes, `id` and, if any, the event project id
+/// (so that `write_event` does not need another query).
+fn returning_columns<MC: DbBmc>() -> Vec<DynIden> {
+	let mut columns = vec![CommonIden::Id.into_iden()];
+	if let Some(project_id_column) = MC::EVENT_PROJECT_ID_COLUMN {
+		columns.push(SIden(project_id_column).into_iden());
+	}
+	columns
+}
+
+/// Writes the `ModelEvent` of a `returning_columns` row to the outbox, and
+/// returns if written (no-op for the entities without `EVENT_PROJECT_ID_COLUMN`).
+pub(in crate::model) async fn write_event<MC: DbBmc>(
+	ctx: &Ctx,
+	tx: &mut Transaction<'_, Postgres>,
+	row: &PgRow,
+	kind: ModelEventKind,
+) -> Result<bool> {
+	if MC::EVENT_PROJECT_ID_COLUMN.is_none() {
+		return Ok(false);
+	}
+	write_row_event(ctx, tx, MC::TABLE, row, kind).await?;
+
+	Ok(true)
+}
+
+/// The project owner of the `MC` entity `id` (see `EVENT_PROJECT_ID_COLUMN`),
+/// for its tombstone (see `sync::project_owner_id`).
+async fn tombstone_owner_id<MC: DbBmc>(
+	tx: &mut Transaction<'_, Postgres>,
+	id: i64,
+) -> Result<Option<i64>> {
+	let Some(project_id_column) = MC::EVENT_PROJECT_ID_COLUMN else {
+		return Ok(None);
+	};
+	// Note: The `TABLE` and column are consts, so not an injection.
+	let sql = format!(
+		"SELECT {project_id_column} FROM {} WHERE id = $1",
+		MC::TABLE
+	);
+	let project_id: Option<i64> = sqlx::query_scalar(&sql)
+		.bind(id)
+		.fetch_optional(&mut **tx)
+		.await?;
+
+	match project_id {
+		Some(project_id) => sync::project_owner_id(tx, project_id).await,
+		None => Ok(None),
+	}
+}
+
+/// Writes the `ModelEvent` of an `(id, project_id)` row of the `entity` table.
+async fn write_row_event(
+	ctx: &Ctx,
+	tx: &mut Transaction<'_, Postgres>,
+	entity: &'static str,
+	row: &PgRow,
+	kind: ModelEventKind,
+) -> Result<()> {
+	let event = ModelEvent {
+		entity: entity.to_string(),
+		id: row.try_get(0)?,
+		project_id: row.try_get(1)?,
+		kind,
+		user_id: ctx.user_id(),
+	};
+	outbox::insert_event(tx, &event).await?;
+
+	Ok(())
+}
+
+/// Commits the mutation, and wakes up the outbox relay when it has an event.
+pub(in crate::model) async fn commit(
+	mm: &ModelManager,
+	tx: Transaction<'_, Postgres>,
+	has_event: bool,
+) -> Result<()> {
+	tx.commit().await?;
+	if has_event {
+		mm.notify_outbox();
+	}
+
+	Ok(())
+}
+
 /// Update the timestamps info for create
 /// (e.g., cid, ctime, and mid, mtime will be updated with the same values)
 pub fn add_timestamps_for_create(fields: &mut Fields, user_id: i64) {
@@ -0,0 +1,161 @@
+//! The db circuit breaker, so that during a db outage the model calls fail at
+//! once with `Error::ServiceUnavailable`, rather than each waiting out the pool
+//! connect timeout (see `ModelManager::db`).
+//!
+//! - Closed - the db connection failures (see `is_connection_error`) are
+//!   counted, and any connection success (see `store::new_db_pool`) resets
+//!   the count.
+//! - Open - after `DB_BREAKER_THRESHOLD` consecutive failures, the db access
+//!   short-circuits for `DB_BREAKER_OPEN_SEC`.
+//! - Then, one db access per `DB_BREAKER_OPEN_SEC` is let through, as the
+//!   recovery probe, whose success closes the breaker.
+//!
+//! NOTE: The breaker is per process, as the db pool.
+
+use crate::config::config;
+use crate::model::{Error, Result};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+pub(in crate::model) fn db_breaker() -> &'static DbBreaker {
+	static INSTANCE: OnceLock<DbBreaker> = OnceLock::new();
+	INSTANCE.get_or_init(|| {
+		let config = config();
+		DbBreaker::new(
+			config.DB_BREAKER_THRESHOLD,
+			Duration::from_secs(config.DB_BREAKER_OPEN_SEC.into()),
+		)
+	})
+}
+
+pub(in crate::model) struct DbBreaker {
+	threshold: u32,
+	open_duration: Duration,
+	state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BreakerState {
+	Closed { failures: u32 },
+	Open { probe_at: Instant },
+}
+
+impl DbBreaker {
+	fn new(threshold: u32, open_duration: Duration) -> Self {
+		Self {
+			threshold,
+			open_duration,
+			state: Mutex::new(BreakerState::Closed { failures: 0 }),
+		}
+	}
+
+	/// Fails with `ServiceUnavailable` when open, except for the probe.
+	pub fn check(&self) -> Result<()> {
+		let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+		let BreakerState::Open { probe_at } = *state else {
+			return Ok(());
+		};
+
+		let now = Instant::now();
+		if now < probe_at {
+			return Err(Error::ServiceUnavailable {
+				retry_after_sec: (probe_at - now).as_secs().max(1),
+			});
+		}
+		// Note: This access is the probe, and the next one is after the open
+		//       duration (so also when this one is dropped before its outcome).
+		*state = BreakerState::Open {
+			probe_at: now + self.open_duration,
+		};
+
+		Ok(())
+	}
+
+	pub fn record_success(&self) {
+		let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+		if matches!(*state, BreakerState::Open { .. }) {
+			info!("{:<12} - db breaker closed (db reachable)", "DB_BREAKER");
+		}
+		*state = BreakerState::Closed { failures: 0 };
+	}
+
+	/// Counts the db connection failures (the other errors are ignored).
+	pub fn record_error(&self, err: &sqlx::Error) {
+		if !is_connection_error(err) {
+			return;
+		}
+
+		let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+		// Note: When open, this is the probe failure (already rescheduled).
+		let BreakerState::Closed { failures } = *state else {
+			return;
+		};
+
+		let failures = failures + 1;
+		if failures < self.threshold {
+			*state = BreakerState::Closed { failures };
+			return;
+		}
+
+		warn!(
+			"{:<12} - db breaker open ({failures} connection failures) - {err}",
+			"DB_BREAKER"
+		);
+		*state = BreakerState::Open {
+			probe_at: Instant::now() + self.open_duration,
+		};
+	}
+}
+
+/// The db is not reachable (rather than a query error).
+fn is_connection_error(err: &sqlx::Error) -> bool {
+	matches!(
+		err,
+		sqlx::Error::PoolTimedOut | sqlx::Error::Io(_) | sqlx::Error::Tls(_)
+	)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_db_breaker_open_and_probe() {
+		// -- Setup & Fixtures
+		let fx_breaker = DbBreaker::new(2, Duration::from_millis(50));
+
+		// -- Exec & Check - trips after the threshold
+		fx_breaker.record_error(&sqlx::Error::PoolTimedOut);
+		fx_breaker.record_error(&sqlx::Error::RowNotFound);
+		assert!(fx_breaker.check().is_ok(), "should ignore the query errors");
+		fx_breaker.record_error(&sqlx::Error::PoolTimedOut);
+		assert!(matches!(
+			fx_breaker.check(),
+			Err(Error::ServiceUnavailable { retry_after_sec: 1 })
+		));
+
+		// -- Exec & Check - one probe, then closed on success
+		std::thread::sleep(Duration::from_millis(60));
+		assert!(fx_breaker.check().is_ok(), "should let the probe through");
+		assert!(fx_breaker.check().is_err(), "should let only one probe");
+		fx_breaker.record_success();
+		assert!(fx_breaker.check().is_ok());
+	}
+
+	#[test]
+	fn test_db_breaker_success_resets_failures() {
+		// -- Setup & Fixtures
+		let fx_breaker = DbBreaker::new(2, Duration::from_secs(10));
+
+		// -- Exec
+		fx_breaker.record_error(&sqlx::Error::PoolTimedOut);
+		fx_breaker.record_success();
+		fx_breaker.record_error(&sqlx::Error::PoolTimedOut);
+
+		// -- Check
+		assert!(fx_breaker.check().is_ok(), "should not be consecutive");
+	}
+}
+// endregion: --- Tests
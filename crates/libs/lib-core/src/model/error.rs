@@ -1,8 +1,11 @@
+use crate::model::db_breaker::db_breaker;
 use crate::model::store;
 use crate::pwd;
 use derive_more::From;
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
+use sqlx::error::ErrorKind;
+use sqlx::postgres::PgDatabaseError;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -13,10 +16,76 @@ pub enum Error {
 		entity: &'static str,
 		id: i64,
 	},
+	/// The entity was modified since the expected `mtime` (or is unknown), see
+	/// `base::update_if_unmodified`.
+	EntityModified {
+		entity: &'static str,
+		id: i64,
+	},
+	/// The entity is referenced by `dependent` rows, so cannot be deleted
+	/// (see `base::OnDelete::Restrict`).
+	EntityInUse {
+		entity: &'static str,
+		id: i64,
+		dependent: &'static str,
+	},
+	/// A db unique constraint violation, where `entity` is the table, and
+	/// `field` the key columns (e.g., `username`), when known.
+	UniqueViolation {
+		entity: String,
+		field: Option<String>,
+	},
+	/// A db foreign key violation, i.e., a reference to a missing entity, or
+	/// the delete of a still referenced one (`entity` and `field` as above).
+	ForeignKeyViolation {
+		entity: String,
+		field: Option<String>,
+	},
+	/// The ctx deadline passed (see `Ctx::deadline`).
+	DeadlineExceeded,
+	/// The db is not reachable (see `db_breaker`).
+	ServiceUnavailable {
+		retry_after_sec: u64,
+	},
 	ListLimitOverMax {
 		max: i64,
 		actual: i64,
 	},
+	ListOrderByUnknown {
+		column: String,
+	},
+	LocaleUnsupported {
+		locale: String,
+	},
+	/// The create would go over the `quota` (see `model::quota`).
+	QuotaExceeded {
+		quota: &'static str,
+		max: u32,
+	},
+	/// The seed `set` is not allowed on the `env` (e.g., the demo data in prod).
+	SeedRefused {
+		set: &'static str,
+		env: String,
+	},
+	ShareLinkDurationInvalid {
+		max_days: u32,
+	},
+	/// The share link token is invalid, expired, or revoked.
+	ShareLinkInvalid,
+	SyncCursorExpired {
+		max_age_days: u64,
+	},
+	TimezoneUnknown {
+		timezone: String,
+	},
+	UserAlreadyExists {
+		username: String,
+	},
+	WebhookInvalid {
+		field: &'static str,
+		reason: String,
+	},
+	WebhookPayload(String),
 
 	// -- Modules
 	#[from]
@@ -27,12 +96,46 @@ pub enum Error {
 	// -- Externals
 	#[from]
 	SeaQuery(#[serde_as(as = "DisplayFromStr")] sea_query::error::Error),
-	#[from]
 	Sqlx(#[serde_as(as = "DisplayFromStr")] sqlx::Error),
 	#[from]
 	ModqlIntoSea(#[serde_as(as = "DisplayFromStr")] modql::filter::IntoSeaError),
 }
 
+/// Note: Not derived, as the db connection failures trip the db breaker, and
+///       the constraint violations are their own variants.
+impl From<sqlx::Error> for Error {
+	fn from(val: sqlx::Error) -> Self {
+		db_breaker().record_error(&val);
+		constraint_violation(&val).unwrap_or(Self::Sqlx(val))
+	}
+}
+
+/// The `UniqueViolation` or `ForeignKeyViolation` of a db error, if any.
+fn constraint_violation(err: &sqlx::Error) -> Option<Error> {
+	let db_err = err.as_database_error()?;
+	let entity = db_err.table().unwrap_or_default().to_string();
+	let field = db_err
+		.try_downcast_ref::<PgDatabaseError>()
+		.and_then(|pg_err| pg_err.detail())
+		.and_then(key_columns);
+
+	match db_err.kind() {
+		ErrorKind::UniqueViolation => Some(Error::UniqueViolation { entity, field }),
+		ErrorKind::ForeignKeyViolation => {
+			Some(Error::ForeignKeyViolation { entity, field })
+		}
+		_ => None,
+	}
+}
+
+/// The key columns of a postgres constraint violation detail, e.g.,
+/// `username` for `Key (username)=(demo1) already exists.`
+fn key_columns(detail: &str) -> Option<String> {
+	let (columns, _) = detail.strip_prefix("Key (")?.split_once(")=")?;
+
+	Some(columns.to_string())
+}
+
 // region:    --- Error Boilerplate
 impl core::fmt::Display for Error {
 	fn fmt(
@@ -0,0 +1,257 @@
+//! Model change events
+//!
+//! The base create/update/delete write a `ModelEvent` for the project scoped
+//! entities (see `DbBmc::EVENT_PROJECT_ID_COLUMN`) to the outbox, and the relay
+//! publishes them (see `outbox`), which can be received with
+//! `ModelManager::subscribe_events` (e.g., to push live updates to the UIs).
+//!
+//! The subsystems reacting to the changes (e.g., metrics, webhooks) implement an
+//! `EventSubscriber` of the typed `DomainEvent`s (e.g., `TaskCreated`), and are
+//! run with `spawn_subscriber`, so the side effects stay out of the handlers.
+//!
+//! NOTE: The bus is in-process. Events published without subscribers are
+//!       dropped, and slow subscribers may lag (missing events).
+
+use crate::model::ModelManager;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::str::FromStr;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Max events buffered per subscriber before it lags.
+pub(in crate::model) const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelEvent {
+	/// The entity table name (e.g., `task`, `project`).
+	pub entity: String,
+	pub id: i64,
+	pub project_id: i64,
+	pub kind: ModelEventKind,
+	/// The user_id of the ctx which made the change.
+	pub user_id: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelEventKind {
+	Created,
+	Updated,
+	Deleted,
+}
+
+impl ModelEventKind {
+	/// The outbox `kind` value.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Created => "created",
+			Self::Updated => "updated",
+			Self::Deleted => "deleted",
+		}
+	}
+}
+
+impl FromStr for ModelEventKind {
+	type Err = ();
+
+	fn from_str(kind: &str) -> core::result::Result<Self, ()> {
+		match kind {
+			"created" => Ok(Self::Created),
+			"updated" => Ok(Self::Updated),
+			"deleted" => Ok(Self::Deleted),
+			_ => Err(()),
+		}
+	}
+}
+
+pub type ModelEventReceiver = broadcast::Receiver<ModelEvent>;
+
+// region:    --- Domain Events
+
+/// The typed domain events, from the `ModelEvent`s of the known entities.
+/// (serialized with its `name` as the `event` field, e.g., for the webhooks)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event")]
+pub enum DomainEvent {
+	#[serde(rename = "task.created")]
+	TaskCreated(EntityChange),
+	#[serde(rename = "task.updated")]
+	TaskUpdated(EntityChange),
+	#[serde(rename = "task.deleted")]
+	TaskDeleted(EntityChange),
+	#[serde(rename = "project.created")]
+	ProjectCreated(EntityChange),
+	#[serde(rename = "project.updated")]
+	ProjectUpdated(EntityChange),
+	#[serde(rename = "project.deleted")]
+	ProjectDeleted(EntityChange),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntityChange {
+	pub id: i64,
+	pub project_id: i64,
+	/// The user_id of the ctx which made the change.
+	pub user_id: i64,
+}
+
+impl DomainEvent {
+	/// All the event `name`s.
+	pub const NAMES: &'static [&'static str] = &[
+		"task.created",
+		"task.updated",
+		"task.deleted",
+		"project.created",
+		"project.updated",
+		"project.deleted",
+	];
+
+	/// Returns `None` for the entities without domain events.
+	pub fn from_model_event(event: &ModelEvent) -> Option<Self> {
+		let change = EntityChange {
+			id: event.id,
+			project_id: event.project_id,
+			user_id: event.user_id,
+		};
+		let event = match (event.entity.as_str(), event.kind) {
+			("task", ModelEventKind::Created) => Self::TaskCreated(change),
+			("task", ModelEventKind::Updated) => Self::TaskUpdated(change),
+			("task", ModelEventKind::Deleted) => Self::TaskDeleted(change),
+			("project", ModelEventKind::Created) => Self::ProjectCreated(change),
+			("project", ModelEventKind::Updated) => Self::ProjectUpdated(change),
+			("project", ModelEventKind::Deleted) => Self::ProjectDeleted(change),
+			_ => return None,
+		};
+
+		Some(event)
+	}
+
+	/// The event name, e.g., `task.created`
+	/// (e.g., for the metrics labels and the subscription filters).
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::TaskCreated(_) => "task.created",
+			Self::TaskUpdated(_) => "task.updated",
+			Self::TaskDeleted(_) => "task.deleted",
+			Self::ProjectCreated(_) => "project.created",
+			Self::ProjectUpdated(_) => "project.updated",
+			Self::ProjectDeleted(_) => "project.deleted",
+		}
+	}
+
+	pub fn change(&self) -> &EntityChange {
+		match self {
+			Self::TaskCreated(change)
+			| Self::TaskUpdated(change)
+			| Self::TaskDeleted(change)
+			| Self::ProjectCreated(change)
+			| Self::ProjectUpdated(change)
+			| Self::ProjectDeleted(change) => change,
+		}
+	}
+}
+
+// endregion: --- Domain Events
+
+// region:    --- Subscribers
+
+/// A subsystem reacting to the domain events.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync + 'static {
+	/// For the logs (e.g., `webhooks`).
+	fn name(&self) -> &'static str;
+
+	/// Called for each event, in order. A slow `handle` delays the next events
+	/// (and may lag), so long work should be queued.
+	async fn handle(&self, event: &DomainEvent);
+}
+
+/// Runs the `subscriber` on the domain events published from now on.
+pub fn spawn_subscriber(
+	mm: &ModelManager,
+	subscriber: impl EventSubscriber,
+) -> JoinHandle<()> {
+	let mut events = mm.subscribe_events();
+	tokio::spawn(async move {
+		loop {
+			match events.recv().await {
+				Ok(event) => {
+					if let Some(event) = DomainEvent::from_model_event(&event) {
+						subscriber.handle(&event).await;
+					}
+				}
+				Err(RecvError::Lagged(missed)) => {
+					warn!(
+						"event subscriber {} lagged by {missed}",
+						subscriber.name()
+					);
+				}
+				Err(RecvError::Closed) => break,
+			}
+		}
+	})
+}
+
+// endregion: --- Subscribers
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn test_domain_event_from_model_event_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_model_event = |entity: &str, kind| ModelEvent {
+			entity: entity.to_string(),
+			id: 1001,
+			project_id: 1000,
+			kind,
+			user_id: 1,
+		};
+
+		// -- Exec
+		let task_created = DomainEvent::from_model_event(&fx_model_event(
+			"task",
+			ModelEventKind::Created,
+		));
+		let project_deleted = DomainEvent::from_model_event(&fx_model_event(
+			"project",
+			ModelEventKind::Deleted,
+		));
+		let unknown = DomainEvent::from_model_event(&fx_model_event(
+			"note",
+			ModelEventKind::Created,
+		));
+
+		// -- Check
+		let fx_change = EntityChange {
+			id: 1001,
+			project_id: 1000,
+			user_id: 1,
+		};
+		assert_eq!(
+			task_created,
+			Some(DomainEvent::TaskCreated(fx_change.clone()))
+		);
+		assert_eq!(
+			project_deleted.as_ref().map(DomainEvent::name),
+			Some("project.deleted")
+		);
+		assert_eq!(
+			project_deleted.as_ref().map(DomainEvent::change),
+			Some(&fx_change)
+		);
+		assert!(unknown.is_none());
+		let task_created = serde_json::to_value(task_created)?;
+		assert_eq!(task_created["event"], "task.created");
+		assert_eq!(task_created["id"], 1001);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,151 @@
+//! The db schema migrations (`sql/migrations`), embedded in the binaries.
+//!
+//! The applied ones are recorded (with their checksum) by sqlx, in the
+//! `_sqlx_migrations` table. At boot, the services check the applied ones match
+//! the embedded ones (see `check_migrations`), so an old binary does not write
+//! to a newer schema (or the opposite).
+
+use crate::model::{ModelManager, Result};
+use sqlx::migrate::Migrator;
+
+/// The embedded migrations.
+/// (Applied by the dev db init, and the deployment tooling)
+pub static MIGRATOR: Migrator = sqlx::migrate!("../../../sql/migrations");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationStatus {
+	UpToDate,
+	/// Embedded migrations not applied yet (the binary is newer than the db).
+	Behind {
+		pending: Vec<i64>,
+	},
+	/// Applied migrations with another checksum, failed, or not embedded
+	/// (e.g., the db is newer than the binary).
+	Diverged {
+		versions: Vec<i64>,
+	},
+}
+
+impl MigrationStatus {
+	pub fn is_up_to_date(&self) -> bool {
+		matches!(self, Self::UpToDate)
+	}
+}
+
+/// Compares the applied migrations to the embedded ones.
+pub async fn check_migrations(mm: &ModelManager) -> Result<MigrationStatus> {
+	// Note: No migrations table means none applied.
+	let (has_table,): (bool,) =
+		sqlx::query_as("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+			.fetch_one(mm.db()?)
+			.await?;
+	let applied: Vec<AppliedMigration> = if has_table {
+		sqlx::query_as(
+			"SELECT version, checksum, success FROM _sqlx_migrations ORDER BY version",
+		)
+		.fetch_all(mm.db()?)
+		.await?
+	} else {
+		Vec::new()
+	};
+
+	let embedded: Vec<(i64, &[u8])> = MIGRATOR
+		.iter()
+		.filter(|m| !m.migration_type.is_down_migration())
+		.map(|m| (m.version, m.checksum.as_ref()))
+		.collect();
+
+	Ok(migration_status(&embedded, &applied))
+}
+
+type AppliedMigration = (i64, Vec<u8>, bool);
+
+fn migration_status(
+	embedded: &[(i64, &[u8])],
+	applied: &[AppliedMigration],
+) -> MigrationStatus {
+	let diverged: Vec<i64> = applied
+		.iter()
+		.filter(|(version, checksum, success)| {
+			!success
+				|| !embedded
+					.iter()
+					.any(|(v, c)| v == version && *c == checksum.as_slice())
+		})
+		.map(|(version, ..)| *version)
+		.collect();
+	if !diverged.is_empty() {
+		return MigrationStatus::Diverged { versions: diverged };
+	}
+
+	let pending: Vec<i64> = embedded
+		.iter()
+		.map(|(version, _)| *version)
+		.filter(|version| !applied.iter().any(|(v, ..)| v == version))
+		.collect();
+	if !pending.is_empty() {
+		return MigrationStatus::Behind { pending };
+	}
+
+	MigrationStatus::UpToDate
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[test]
+	fn test_migration_status() {
+		let fx_embedded: &[(i64, &[u8])] = &[(1, b"c1"), (2, b"c2")];
+		let applied = |rows: &[(i64, &[u8], bool)]| -> Vec<AppliedMigration> {
+			rows.iter().map(|(v, c, s)| (*v, c.to_vec(), *s)).collect()
+		};
+
+		let fx_cases = [
+			(
+				applied(&[(1, b"c1", true), (2, b"c2", true)]),
+				MigrationStatus::UpToDate,
+			),
+			(
+				applied(&[(1, b"c1", true)]),
+				MigrationStatus::Behind { pending: vec![2] },
+			),
+			(
+				applied(&[(1, b"c1", true), (2, b"other", true)]),
+				MigrationStatus::Diverged { versions: vec![2] },
+			),
+			(
+				applied(&[(1, b"c1", true), (2, b"c2", false)]),
+				MigrationStatus::Diverged { versions: vec![2] },
+			),
+			(
+				applied(&[(1, b"c1", true), (2, b"c2", true), (3, b"c3", true)]),
+				MigrationStatus::Diverged { versions: vec![3] },
+			),
+		];
+
+		for (fx_applied, expected) in fx_cases {
+			assert_eq!(migration_status(fx_embedded, &fx_applied), expected);
+		}
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_check_migrations_up_to_date() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+
+		// -- Exec
+		let status = check_migrations(&mm).await?;
+
+		// -- Check
+		assert_eq!(status, MigrationStatus::UpToDate);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
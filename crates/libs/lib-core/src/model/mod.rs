@@ -18,35 +18,123 @@
 //!
 
 // region:    --- Modules
+mod auth_cache;
 mod base;
+mod db_breaker;
 mod error;
+pub mod event;
+pub mod migration;
 pub mod modql_utils;
+pub mod outbox;
+pub mod presence;
 pub mod project;
+pub mod project_share;
+pub mod quota;
+pub mod request_log;
+pub mod search;
+pub mod secret;
+pub mod seed;
+mod sql_cache;
 mod store;
+pub mod sync;
 pub mod task;
+pub mod trash;
 pub mod user;
+pub mod webhook;
 
+use self::auth_cache::UserAuthCache;
+pub use self::base::{clamp_list_options, DbBmc, LimitClamp, ListPage};
+use self::db_breaker::db_breaker;
 pub use self::error::{Error, Result};
-use self::store::{new_db_pool, Db};
+use self::event::{ModelEvent, ModelEventReceiver, EVENT_CHANNEL_CAPACITY};
+use self::presence::PresenceRegistry;
+pub use self::store::DbPoolStats;
+use self::store::{db_ping, db_pool_stats, new_db_pool, set_db_url, Db};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Notify};
 
 // endregion: --- Modules
 
 #[derive(Clone)]
 pub struct ModelManager {
 	db: Db,
+	events: broadcast::Sender<ModelEvent>,
+	outbox_notify: Arc<Notify>,
+	user_auth_cache: Arc<UserAuthCache>,
+	presence: Arc<PresenceRegistry>,
 }
 
 impl ModelManager {
 	/// Constructor
 	pub async fn new() -> Result<Self> {
 		let db = new_db_pool().await?;
+		let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 		// FIXME - TBC
-		Ok(ModelManager { db })
+		Ok(ModelManager {
+			db,
+			events,
+			outbox_notify: Arc::default(),
+			user_auth_cache: Arc::default(),
+			presence: Arc::default(),
+		})
 	}
 
-	/// Returns the sqlx db pool reference.
+	/// Subscribes to the model change events (from now on).
+	pub fn subscribe_events(&self) -> ModelEventReceiver {
+		self.events.subscribe()
+	}
+
+	/// Checks the db is reachable (`SELECT 1`) within the `timeout`, and
+	/// returns the pool connection acquire wait (e.g., the pool saturation).
+	pub async fn db_ping(&self, timeout: Duration) -> Result<Duration> {
+		let acquire_wait = db_ping(&self.db, timeout).await?;
+		Ok(acquire_wait)
+	}
+
+	/// The db pool connections (size, idle, max).
+	pub fn db_pool_stats(&self) -> DbPoolStats {
+		db_pool_stats(&self.db)
+	}
+
+	/// Applies a rotated `DB_URL` to the new db connections
+	/// (see `config::SecretCache`).
+	pub fn set_db_url(&self, db_url: &str) -> Result<()> {
+		set_db_url(&self.db, db_url)?;
+		Ok(())
+	}
+
+	/// Returns the sqlx db pool reference, or `ServiceUnavailable` at once
+	/// when the db breaker is open (see `db_breaker`).
 	/// (Only for the model layer)
-	pub(in crate::model) fn db(&self) -> &Db {
-		&self.db
+	pub(in crate::model) fn db(&self) -> Result<&Db> {
+		db_breaker().check()?;
+		Ok(&self.db)
+	}
+
+	/// The `UserForAuth` cache (see `UserBmc::auth_by_username`).
+	pub(in crate::model) fn user_auth_cache(&self) -> &UserAuthCache {
+		&self.user_auth_cache
+	}
+
+	/// The live connections presence (see `PresenceBmc`).
+	pub(in crate::model) fn presence(&self) -> &PresenceRegistry {
+		&self.presence
+	}
+
+	/// Publishes a model change event.
+	/// (Only for the outbox relay, and fine without subscribers)
+	pub(in crate::model) fn publish_event(&self, event: ModelEvent) {
+		let _ = self.events.send(event);
+	}
+
+	/// Wakes up the outbox relay, after a commit with outbox events.
+	pub(in crate::model) fn notify_outbox(&self) {
+		self.outbox_notify.notify_one();
+	}
+
+	/// Waits for a `notify_outbox`.
+	pub(in crate::model) async fn outbox_notified(&self) {
+		self.outbox_notify.notified().await;
 	}
 }
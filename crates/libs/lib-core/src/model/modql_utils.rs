@@ -1,7 +1,111 @@
+use serde::{Deserialize, Deserializer};
 use time::serde::rfc3339;
+use time::OffsetDateTime;
 
 pub fn time_to_sea_value(
 	json_value: serde_json::Value,
 ) -> modql::filter::SeaResult<sea_query::Value> {
 	Ok(rfc3339::deserialize(json_value)?.into())
 }
+
+// region:    --- Nullable Fields
+
+/// The value of a nullable column in the `ForUpdate` types, as an
+/// `Option<Nullable<T>>` with the `nullable` deserializer (JSON Merge Patch):
+/// - absent - `None`, the column is unchanged (not in the `not_none_fields`).
+/// - `null` - `Some(Nullable(None))`, the column is set to NULL.
+/// - a value - `Some(Nullable(Some(val)))`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nullable<T>(pub Option<T>);
+
+impl<T> From<Nullable<T>> for sea_query::Value
+where
+	T: Into<sea_query::Value> + sea_query::Nullable,
+{
+	fn from(val: Nullable<T>) -> Self {
+		val.0.into()
+	}
+}
+
+impl<T: sea_query::Nullable> sea_query::Nullable for Nullable<T> {
+	fn null() -> sea_query::Value {
+		T::null()
+	}
+}
+
+/// The `deserialize_with` of the `Option<Nullable<T>>` fields, with
+/// `#[serde(default)]` (so that absent is `None`).
+pub fn nullable<'de, D, T>(
+	deserializer: D,
+) -> core::result::Result<Option<Nullable<T>>, D::Error>
+where
+	D: Deserializer<'de>,
+	T: Deserialize<'de>,
+{
+	Option::<T>::deserialize(deserializer).map(|val| Some(Nullable(val)))
+}
+
+/// The `nullable` of the Rfc3339 times.
+pub fn nullable_time<'de, D>(
+	deserializer: D,
+) -> core::result::Result<Option<Nullable<OffsetDateTime>>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	rfc3339::option::deserialize(deserializer).map(|val| Some(Nullable(val)))
+}
+
+// endregion: --- Nullable Fields
+
+// region:    --- TypeScript Types
+
+/// The TypeScript types of the modql filter values and list options (not ts-rs
+/// types), for the filters and params derives of the `ts` feature, e.g.,
+/// `#[ts(as = "Option<ts::OpValsInt64>")]`.
+#[cfg(feature = "ts")]
+pub mod ts {
+	use ts_rs::TS;
+
+	#[derive(TS)]
+	#[ts(
+		export,
+		type = "number | { $eq?: number, $not?: number, $in?: Array<number>, \
+		        $notIn?: Array<number>, $lt?: number, $lte?: number, \
+		        $gt?: number, $gte?: number }"
+	)]
+	pub struct OpValsInt64;
+
+	#[derive(TS)]
+	#[ts(
+		export,
+		type = "string | { $eq?: string, $not?: string, $in?: Array<string>, \
+		        $notIn?: Array<string>, $contains?: string, \
+		        $notContains?: string, $startsWith?: string, \
+		        $endsWith?: string, $empty?: boolean, $null?: boolean }"
+	)]
+	pub struct OpValsString;
+
+	#[derive(TS)]
+	#[ts(export, type = "boolean | { $eq?: boolean, $not?: boolean }")]
+	pub struct OpValsBool;
+
+	/// The Rfc3339 times (e.g., `{"$gte": "2023-11-01T10:00:00Z"}`).
+	#[derive(TS)]
+	#[ts(
+		export,
+		type = "string | { $eq?: string, $not?: string, $lt?: string, \
+		        $lte?: string, $gt?: string, $gte?: string, $null?: boolean }"
+	)]
+	pub struct OpValsTime;
+
+	/// The `order_bys` are column names, `!` prefixed for descending.
+	#[derive(TS)]
+	#[ts(
+		export,
+		type = "{ limit?: number, offset?: number, \
+		        order_bys?: string | Array<string> }"
+	)]
+	pub struct ListOptions;
+}
+
+// endregion: --- TypeScript Types
@@ -0,0 +1,148 @@
+//! Transactional outbox of the model change events
+//!
+//! The base create/update/delete insert the `ModelEvent` in the `event_outbox`
+//! table, in the same transaction as the mutation, and the relay publishes the
+//! pending rows to the event bus (see `ModelManager::subscribe_events`).
+//! So, an event is never lost for a committed change, even if the bus (or the
+//! process) is down at write time, it is published once it is back.
+//!
+//! NOTE: The relay claims the rows with `FOR UPDATE SKIP LOCKED`, so with many
+//!       instances, each event is published by one of them (the writing one
+//!       most of the time, as it is notified on commit).
+
+use crate::model::event::{ModelEvent, ModelEventKind};
+use crate::model::{ModelManager, Result};
+use sqlx::{Postgres, Transaction};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Max events published per relay batch.
+const RELAY_BATCH_SIZE: i64 = 100;
+/// The relay poll interval, for the rows written by other instances
+/// (or left by a failed relay).
+const RELAY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// The published rows are kept for a day (e.g., for troubleshooting).
+const PUBLISHED_RETENTION_SEC: f64 = 24. * 3600.;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Inserts the event in the outbox, as part of the mutation transaction.
+pub(in crate::model) async fn insert_event(
+	tx: &mut Transaction<'_, Postgres>,
+	event: &ModelEvent,
+) -> Result<()> {
+	sqlx::query(
+		"INSERT INTO event_outbox (entity, entity_id, project_id, kind, user_id, ctime)
+		 VALUES ($1, $2, $3, $4, $5, now())",
+	)
+	.bind(&event.entity)
+	.bind(event.id)
+	.bind(event.project_id)
+	.bind(event.kind.as_str())
+	.bind(event.user_id)
+	.execute(&mut **tx)
+	.await?;
+
+	Ok(())
+}
+
+/// Publishes the pending outbox events (in order, up to a batch),
+/// and returns the number of published events.
+pub async fn relay_pending(mm: &ModelManager) -> Result<usize> {
+	let mut tx = mm.db()?.begin().await?;
+
+	let rows: Vec<(i64, String, i64, i64, String, i64)> = sqlx::query_as(
+		"SELECT id, entity, entity_id, project_id, kind, user_id FROM event_outbox
+		 WHERE published_time IS NULL
+		 ORDER BY id
+		 LIMIT $1
+		 FOR UPDATE SKIP LOCKED",
+	)
+	.bind(RELAY_BATCH_SIZE)
+	.fetch_all(&mut *tx)
+	.await?;
+	if rows.is_empty() {
+		return Ok(0);
+	}
+
+	let mut ids = Vec::with_capacity(rows.len());
+	let mut events = Vec::with_capacity(rows.len());
+	for (outbox_id, entity, id, project_id, kind, user_id) in rows {
+		ids.push(outbox_id);
+		// Note: Should not happen, as written by `insert_event`, but a bad row
+		//       must not block the relay.
+		let Ok(kind) = kind.parse::<ModelEventKind>() else {
+			warn!("event outbox - row {outbox_id} has an unknown kind '{kind}'");
+			continue;
+		};
+		events.push(ModelEvent {
+			entity,
+			id,
+			project_id,
+			kind,
+			user_id,
+		});
+	}
+
+	sqlx::query("UPDATE event_outbox SET published_time = now() WHERE id = ANY($1)")
+		.bind(&ids)
+		.execute(&mut *tx)
+		.await?;
+	tx.commit().await?;
+
+	// Note: Published after the commit, so a failed commit does not publish
+	//       events which will be published again.
+	let count = events.len();
+	for event in events {
+		mm.publish_event(event);
+	}
+
+	Ok(count)
+}
+
+/// Deletes the published rows older than the retention.
+async fn prune_published(mm: &ModelManager) -> Result<u64> {
+	let res = sqlx::query(
+		"DELETE FROM event_outbox
+		 WHERE published_time < now() - make_interval(secs => $1)",
+	)
+	.bind(PUBLISHED_RETENTION_SEC)
+	.execute(mm.db()?)
+	.await?;
+
+	Ok(res.rows_affected())
+}
+
+/// Spawns the relay, which publishes the pending events on each local commit
+/// (see `ModelManager::notify_outbox`), and on each poll interval.
+pub fn spawn_relay(mm: ModelManager) -> JoinHandle<()> {
+	tokio::spawn(async move {
+		let mut poll = tokio::time::interval(RELAY_POLL_INTERVAL);
+		let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+		loop {
+			tokio::select! {
+				_ = mm.outbox_notified() => {}
+				_ = poll.tick() => {}
+				_ = prune.tick() => {
+					match prune_published(&mm).await {
+						Ok(count) => debug!("event outbox - {count} published rows pruned"),
+						Err(ex) => warn!("event outbox - prune failed - {ex:?}"),
+					}
+					continue;
+				}
+			}
+
+			// Relay until no more pending (e.g., after a bus or db outage).
+			loop {
+				match relay_pending(&mm).await {
+					Ok(count) if count > 0 => continue,
+					Ok(_) => break,
+					Err(ex) => {
+						warn!("event outbox - relay failed, retried on next poll - {ex:?}");
+						break;
+					}
+				}
+			}
+		}
+	})
+}
@@ -0,0 +1,203 @@
+//! The project presence, i.e., who is currently viewing a project (for the
+//! UI collaborator avatars).
+//!
+//! A viewer is a live connection (e.g., a `/ws/events` one) subscribed to the
+//! project. The connection sets its viewed projects on (un)subscribe, refreshes
+//! them while alive, and leaves on close. The entries not refreshed within
+//! `PRESENCE_TTL` (e.g., a stuck connection) are not listed anymore.
+//!
+//! NOTE: The presence is in memory, so only the connections of this process
+//!       are listed (the same as the `/ws/events` model events).
+
+use crate::ctx::Ctx;
+use crate::model::project::ProjectBmc;
+use crate::model::ModelManager;
+use crate::model::Result;
+use lib_base::time::{now_utc, Rfc3339};
+use serde::Serialize;
+use serde_with::serde_as;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use time::{Duration, OffsetDateTime};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// The connections must refresh their presence within it (e.g., every half).
+pub const PRESENCE_TTL: Duration = Duration::seconds(60);
+
+// region:    --- Presence Types
+
+/// A user viewing a project (once, whatever its number of connections).
+#[serde_as]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ProjectPresence {
+	pub user_id: i64,
+	/// The last refresh of its most recent connection.
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub last_seen: OffsetDateTime,
+}
+
+/// The id of a live connection (see `PresenceBmc::new_conn_id`).
+pub type PresenceConnId = u64;
+
+struct PresenceConn {
+	user_id: i64,
+	project_ids: Vec<i64>,
+	last_seen: OffsetDateTime,
+}
+
+#[derive(Default)]
+pub(in crate::model) struct PresenceRegistry {
+	next_conn_id: AtomicU64,
+	conns: Mutex<HashMap<PresenceConnId, PresenceConn>>,
+}
+
+// endregion: --- Presence Types
+
+// region:    --- PresenceBmc
+
+pub struct PresenceBmc;
+
+impl PresenceBmc {
+	/// A new connection id, unique in this process.
+	pub fn new_conn_id(mm: &ModelManager) -> PresenceConnId {
+		mm.presence().next_conn_id.fetch_add(1, Ordering::Relaxed)
+	}
+
+	/// Sets (or replaces) the projects viewed by the connection, and refreshes
+	/// its `last_seen` (so, also the periodic refresh).
+	///
+	/// Note: The projects owner is checked on subscribe (see `/ws/events`).
+	pub fn set_viewing(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		conn_id: PresenceConnId,
+		project_ids: impl IntoIterator<Item = i64>,
+	) {
+		let Ok(mut conns) = mm.presence().conns.lock() else {
+			return;
+		};
+
+		let now = now_utc();
+		// Note: The dropped connections leave, so this is only for the stuck ones.
+		conns.retain(|_, conn| now - conn.last_seen < PRESENCE_TTL);
+		conns.insert(
+			conn_id,
+			PresenceConn {
+				user_id: ctx.user_id(),
+				project_ids: project_ids.into_iter().collect(),
+				last_seen: now,
+			},
+		);
+	}
+
+	/// Removes the connection presence (e.g., on close).
+	pub fn leave(mm: &ModelManager, conn_id: PresenceConnId) {
+		if let Ok(mut conns) = mm.presence().conns.lock() {
+			conns.remove(&conn_id);
+		}
+	}
+
+	/// The users viewing the project (ordered by `user_id`).
+	///
+	/// Fails with `EntityNotFound` if the project is unknown, or not owned by
+	/// the ctx user (see `ProjectBmc::check_owner`).
+	pub async fn list_for_project(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		project_id: i64,
+	) -> Result<Vec<ProjectPresence>> {
+		ProjectBmc::check_owner(ctx, mm, project_id).await?;
+
+		let Ok(conns) = mm.presence().conns.lock() else {
+			return Ok(Vec::new());
+		};
+
+		let now = now_utc();
+		let mut last_seen_by_user: BTreeMap<i64, OffsetDateTime> = BTreeMap::new();
+		for conn in conns.values() {
+			if now - conn.last_seen >= PRESENCE_TTL
+				|| !conn.project_ids.contains(&project_id)
+			{
+				continue;
+			}
+			let last_seen = last_seen_by_user
+				.entry(conn.user_id)
+				.or_insert(conn.last_seen);
+			*last_seen = (*last_seen).max(conn.last_seen);
+		}
+
+		let presences = last_seen_by_user
+			.into_iter()
+			.map(|(user_id, last_seen)| ProjectPresence { user_id, last_seen })
+			.collect();
+
+		Ok(presences)
+	}
+}
+
+// endregion: --- PresenceBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::Error;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_presence_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_presence_ok project").await?;
+		let fx_ctx_demo = Ctx::new(1000)?;
+
+		// -- Exec
+		let conn_1 = PresenceBmc::new_conn_id(&mm);
+		let conn_2 = PresenceBmc::new_conn_id(&mm);
+		let conn_3 = PresenceBmc::new_conn_id(&mm);
+		PresenceBmc::set_viewing(&fx_ctx_demo, &mm, conn_1, [fx_project_id]);
+		PresenceBmc::set_viewing(&fx_ctx_demo, &mm, conn_2, [fx_project_id]);
+		PresenceBmc::set_viewing(&ctx, &mm, conn_3, [fx_project_id + 1]);
+
+		// -- Check
+		let presences =
+			PresenceBmc::list_for_project(&ctx, &mm, fx_project_id).await?;
+		let user_ids: Vec<_> = presences.iter().map(|p| p.user_id).collect();
+		assert_eq!(user_ids, [1000]);
+
+		// -- Check - not owned.
+		let res =
+			PresenceBmc::list_for_project(&fx_ctx_demo, &mm, fx_project_id).await;
+		assert!(matches!(res, Err(Error::EntityNotFound { .. })));
+
+		// -- Check - one connection left, still present.
+		PresenceBmc::leave(&mm, conn_1);
+		let presences =
+			PresenceBmc::list_for_project(&ctx, &mm, fx_project_id).await?;
+		assert_eq!(presences.len(), 1);
+
+		// -- Check - unsubscribed.
+		PresenceBmc::set_viewing(&fx_ctx_demo, &mm, conn_2, []);
+		let presences =
+			PresenceBmc::list_for_project(&ctx, &mm, fx_project_id).await?;
+		assert!(presences.is_empty());
+
+		// -- Clean
+		PresenceBmc::leave(&mm, conn_2);
+		PresenceBmc::leave(&mm, conn_3);
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
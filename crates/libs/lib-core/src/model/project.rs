@@ -1,8 +1,11 @@
 use crate::ctx::Ctx;
-use crate::model::base::{self, DbBmc};
+use crate::model::base::{self, DbBmc, Dependent, OnDelete};
 use crate::model::modql_utils::*;
-use crate::model::ModelManager;
-use crate::model::Result;
+use crate::model::project_share::ProjectShareBmc;
+use crate::model::quota::QuotaBmc;
+use crate::model::task::TaskBmc;
+use crate::model::{Error, Result};
+use crate::model::{ListPage, ModelManager};
 use lib_base::time::Rfc3339;
 use modql::field::Fields;
 use modql::filter::{FilterNodes, OpValsString, OpValsValue};
@@ -11,10 +14,14 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use sqlx::types::time::OffsetDateTime;
 use sqlx::FromRow;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 // region:    --- Project Types
 #[serde_as]
-#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct Project {
 	pub id: i64,
 	pub name: String,
@@ -23,20 +30,28 @@ pub struct Project {
 	//    (creator and last modified user_id/time)
 	pub cid: i64,
 	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
 	pub ctime: OffsetDateTime,
 	pub mid: i64,
 	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
 	pub mtime: OffsetDateTime,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct ProjectForCreate {
 	pub name: String,
 }
 
-#[derive(Fields, Deserialize)]
+/// NOTE: Its columns are not nullable, so a `null` is as absent
+///       (see `modql_utils::Nullable` for the nullable ones).
+#[derive(Fields, Deserialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct ProjectForUpdate {
+	#[cfg_attr(feature = "ts", ts(optional))]
 	pub name: Option<String>,
+	#[cfg_attr(feature = "ts", ts(optional))]
 	pub owner_id: Option<i64>,
 }
 
@@ -54,16 +69,34 @@ struct ProjectForCreateInner {
 	pub owner_id: i64,
 }
 
+/// The task copied by `ProjectBmc::duplicate`.
+#[derive(Fields, FromRow)]
+struct TaskForCopy {
+	project_id: i64,
+	title: String,
+	done: bool,
+}
+
+/// Note: The `ctime` / `mtime` filters take Rfc3339 times
+///       (e.g., `{"mtime": {"$gte": "2023-11-01T10:00:00Z"}}`).
 #[derive(FilterNodes, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct ProjectFilter {
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	id: Option<OpValsInt64>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsString>", optional))]
 	name: Option<OpValsString>,
 
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	cid: Option<OpValsInt64>,
 	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsTime>", optional))]
 	ctime: Option<OpValsValue>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	mid: Option<OpValsInt64>,
 	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsTime>", optional))]
 	mtime: Option<OpValsValue>,
 }
 // endregion: --- Project Types
@@ -73,6 +106,12 @@ pub struct ProjectBmc;
 
 impl DbBmc for ProjectBmc {
 	const TABLE: &'static str = "project";
+	const EVENT_PROJECT_ID_COLUMN: Option<&'static str> = Some("id");
+	const TOMBSTONES: bool = true;
+	const DEPENDENTS: &'static [Dependent] = &[
+		Dependent::of::<TaskBmc>("project_id", OnDelete::Cascade),
+		Dependent::of::<ProjectShareBmc>("project_id", OnDelete::Cascade),
+	];
 }
 
 impl ProjectBmc {
@@ -81,6 +120,7 @@ impl ProjectBmc {
 		mm: &ModelManager,
 		project_c: ProjectForCreate,
 	) -> Result<i64> {
+		QuotaBmc::check_projects(ctx, mm, 1).await?;
 		let project_c = ProjectForCreateInner {
 			name: project_c.name,
 			owner_id: ctx.user_id(),
@@ -92,6 +132,92 @@ impl ProjectBmc {
 		base::get::<Self, _>(ctx, mm, id).await
 	}
 
+	/// The `ids` of the projects owned by the ctx user (e.g., to check the
+	/// imported tasks projects).
+	pub async fn owned_ids(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		ids: &[i64],
+	) -> Result<Vec<i64>> {
+		let owned_ids: Vec<(i64,)> = sqlx::query_as(
+			"SELECT id FROM project WHERE owner_id = $1 AND id = ANY($2)",
+		)
+		.bind(ctx.user_id())
+		.bind(ids)
+		.fetch_all(mm.db()?)
+		.await?;
+
+		Ok(owned_ids.into_iter().map(|(id,)| id).collect())
+	}
+
+	/// Fails with `EntityNotFound` if the project is unknown, or not owned by
+	/// the ctx user (root owns all).
+	pub async fn check_owner(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		project_id: i64,
+	) -> Result<()> {
+		if ctx.is_root()
+			|| !Self::owned_ids(ctx, mm, &[project_id]).await?.is_empty()
+		{
+			Ok(())
+		} else {
+			Err(Error::EntityNotFound {
+				entity: Self::TABLE,
+				id: project_id,
+			})
+		}
+	}
+
+	/// Copies the project and its tasks (not the trashed ones) in one
+	/// transaction, as a new project of the ctx user, and returns its id.
+	///
+	/// The copy is named `name`, or else `"{name} (copy)"`, and its tasks are
+	/// not done when `reset_done`.
+	pub async fn duplicate(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		name: Option<String>,
+		reset_done: bool,
+	) -> Result<i64> {
+		Self::check_owner(ctx, mm, id).await?;
+		let project: Project = Self::get(ctx, mm, id).await?;
+		QuotaBmc::check_projects(ctx, mm, 1).await?;
+
+		let mut tx = base::begin(ctx, mm).await?;
+		let project_c = ProjectForCreateInner {
+			name: name.unwrap_or_else(|| format!("{} (copy)", project.name)),
+			owner_id: ctx.user_id(),
+		};
+		let (copy_id, mut has_event) =
+			base::insert::<Self, _>(ctx, &mut tx, project_c).await?;
+
+		// Note: In the tx, so the copy is of the tasks as of the project one.
+		let tasks: Vec<TaskForCopy> = sqlx::query_as(
+			"SELECT project_id, title, done FROM task \
+			 WHERE project_id = $1 AND deleted_at IS NULL ORDER BY id",
+		)
+		.bind(id)
+		.fetch_all(&mut *tx)
+		.await?;
+		QuotaBmc::check_tasks(ctx, mm, copy_id, tasks.len() as i64).await?;
+		for task in tasks {
+			let task_c = TaskForCopy {
+				project_id: copy_id,
+				title: task.title,
+				done: task.done && !reset_done,
+			};
+			let (_, task_has_event) =
+				base::insert::<TaskBmc, _>(ctx, &mut tx, task_c).await?;
+			has_event |= task_has_event;
+		}
+
+		base::commit(mm, tx, has_event).await?;
+
+		Ok(copy_id)
+	}
+
 	pub async fn list(
 		ctx: &Ctx,
 		mm: &ModelManager,
@@ -101,6 +227,15 @@ impl ProjectBmc {
 		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
 	}
 
+	pub async fn list_paged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ProjectFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<ListPage<Project>> {
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
 	pub async fn update(
 		ctx: &Ctx,
 		mm: &ModelManager,
@@ -110,8 +245,217 @@ impl ProjectBmc {
 		base::update::<Self, _>(ctx, mm, id, project_u).await
 	}
 
+	/// Updates the project only if not modified since `mtime` (e.g., REST
+	/// `If-Match`).
+	pub async fn update_if_unmodified(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		mtime: OffsetDateTime,
+		project_u: ProjectForUpdate,
+	) -> Result<()> {
+		base::update_if_unmodified::<Self, _>(ctx, mm, id, mtime, project_u).await
+	}
+
+	/// Deletes the project, with its tasks (the trashed ones too) and share
+	/// links (see `DEPENDENTS`).
 	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
 		base::delete::<Self>(ctx, mm, id).await
 	}
+
+	/// Deletes the project only if not modified since `mtime` (see `delete`).
+	pub async fn delete_if_unmodified(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		mtime: OffsetDateTime,
+	) -> Result<()> {
+		base::delete_if_unmodified::<Self>(ctx, mm, id, mtime).await
+	}
 }
 // endregion: --- ProjectBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::event::ModelEventKind;
+	use crate::model::outbox;
+	use crate::model::task::TaskForUpdate;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_duplicate_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_duplicate_ok project").await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&[
+				"test_duplicate_ok 01",
+				"test_duplicate_ok 02",
+				"test_duplicate_ok 03",
+			],
+		)
+		.await?;
+		let fx_task_u = TaskForUpdate {
+			done: Some(true),
+			..Default::default()
+		};
+		TaskBmc::update(&ctx, &mm, fx_tasks[0].id, fx_task_u).await?;
+		TaskBmc::delete(&ctx, &mm, fx_tasks[2].id).await?;
+		let db = mm.db()?;
+		let copy_tasks = |project_id: i64| {
+			sqlx::query_as::<_, (String, bool)>(
+				"SELECT title, done FROM task WHERE project_id = $1 ORDER BY id",
+			)
+			.bind(project_id)
+			.fetch_all(db)
+		};
+
+		// -- Exec
+		let copy_id =
+			ProjectBmc::duplicate(&ctx, &mm, fx_project_id, None, false).await?;
+		let reset_id = ProjectBmc::duplicate(
+			&ctx,
+			&mm,
+			fx_project_id,
+			Some("test_duplicate_ok reset".to_string()),
+			true,
+		)
+		.await?;
+
+		// -- Check
+		let copy = ProjectBmc::get(&ctx, &mm, copy_id).await?;
+		assert_eq!(copy.name, "test_duplicate_ok project (copy)");
+		assert_eq!(
+			copy_tasks(copy_id).await?,
+			[
+				("test_duplicate_ok 01".to_string(), true),
+				("test_duplicate_ok 02".to_string(), false),
+			]
+		);
+		let reset = ProjectBmc::get(&ctx, &mm, reset_id).await?;
+		assert_eq!(reset.name, "test_duplicate_ok reset");
+		assert!(copy_tasks(reset_id).await?.iter().all(|(_, done)| !done));
+		assert!(matches!(
+			ProjectBmc::duplicate(&ctx, &mm, -1, None, false).await,
+			Err(Error::EntityNotFound { .. })
+		));
+		let other_ctx = Ctx::new(1000)?;
+		assert!(matches!(
+			ProjectBmc::duplicate(&other_ctx, &mm, fx_project_id, None, false).await,
+			Err(Error::EntityNotFound { .. })
+		));
+
+		// -- Clean
+		for id in [fx_project_id, copy_id, reset_id] {
+			ProjectBmc::delete(&ctx, &mm, id).await?;
+		}
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_delete_cascade_tasks_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_delete_cascade_tasks_ok project",
+		)
+		.await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&[
+				"test_delete_cascade_tasks_ok 01",
+				"test_delete_cascade_tasks_ok 02",
+			],
+		)
+		.await?;
+		TaskBmc::delete(&ctx, &mm, fx_tasks[1].id).await?;
+		let fx_task_ids: Vec<i64> = fx_tasks.iter().map(|task| task.id).collect();
+		let db = mm.db()?;
+
+		// -- Exec & Check - restrict
+		let fx_restrict =
+			[Dependent::of::<TaskBmc>("project_id", OnDelete::Restrict)];
+		let mut tx = base::begin(&ctx, &mm).await?;
+		let res = base::delete_dependents(
+			&ctx,
+			&mut tx,
+			"project",
+			fx_project_id,
+			&fx_restrict,
+		)
+		.await;
+		drop(tx);
+		assert!(
+			matches!(
+				res,
+				Err(Error::EntityInUse {
+					entity: "project",
+					dependent: "task",
+					..
+				})
+			),
+			"{res:?}"
+		);
+
+		// Note: No relay task in the tests (see the task `test_events_ok`).
+		while outbox::relay_pending(&mm).await? > 0 {}
+		let mut events = mm.subscribe_events();
+
+		// -- Exec
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+		outbox::relay_pending(&mm).await?;
+
+		// -- Check - cascade (the trashed task too), with the tombstones
+		let (task_count,): (i64,) =
+			sqlx::query_as("SELECT count(*) FROM task WHERE id = ANY($1)")
+				.bind(&fx_task_ids)
+				.fetch_one(db)
+				.await?;
+		assert_eq!(task_count, 0);
+		let (tombstone_count,): (i64,) = sqlx::query_as(
+			"SELECT count(DISTINCT entity_id) FROM tombstone \
+			 WHERE entity = 'task' AND entity_id = ANY($1)",
+		)
+		.bind(&fx_task_ids)
+		.fetch_one(db)
+		.await?;
+		assert_eq!(tombstone_count, 2);
+
+		// -- Check - the events, of the cascade deleted tasks and the project
+		let mut event_ids = Vec::new();
+		while let Ok(event) = events.try_recv() {
+			assert_eq!(event.kind, ModelEventKind::Deleted);
+			assert_eq!(event.project_id, fx_project_id);
+			event_ids.push((event.entity, event.id));
+		}
+		event_ids.sort();
+		assert_eq!(
+			event_ids,
+			[
+				("project".to_string(), fx_project_id),
+				("task".to_string(), fx_task_ids[0]),
+				("task".to_string(), fx_task_ids[1]),
+			]
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
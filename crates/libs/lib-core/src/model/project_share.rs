@@ -0,0 +1,262 @@
+//! The public share links of the projects, i.e., a signed and expiring token
+//! (see `token::generate_share_token`) giving a read-only access to one
+//! project and its tasks, without login.
+//!
+//! The token is signed with the share `token_salt`, so a link is revoked by
+//! deleting its share.
+//!
+//! NOTE: The shares are only visible to the project owner (root sees all).
+
+use crate::config::config;
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::project::ProjectBmc;
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use crate::token::{generate_share_token, validate_share_token, Token};
+use lib_base::time::{now_utc_plus, truncate_to_sec, Rfc3339};
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+use time::Duration;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+const SHARE_LINK_DAYS_DEFAULT: u32 = 7;
+
+// region:    --- ProjectShare Types
+
+/// Note: The `token_salt` is never returned (the token is, on create).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ProjectShare {
+	pub id: i64,
+	pub project_id: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub expires_at: OffsetDateTime,
+	// -- Timestamps
+	//    (creator and last modified user_id/time)
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ProjectShareForCreate {
+	pub project_id: i64,
+	/// The days the link is valid (7 by default, max `SHARE_LINK_MAX_DAYS`).
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub duration_days: Option<u32>,
+}
+
+/// The validated `ProjectShareForCreate` (see `ProjectForCreateInner`).
+#[derive(Fields)]
+struct ProjectShareForCreateInner {
+	project_id: i64,
+	expires_at: OffsetDateTime,
+}
+
+#[derive(FilterNodes, Default)]
+struct ProjectShareFilter {
+	project_id: Option<OpValsInt64>,
+}
+
+// endregion: --- ProjectShare Types
+
+// region:    --- ProjectShareBmc
+
+pub struct ProjectShareBmc;
+
+impl DbBmc for ProjectShareBmc {
+	const TABLE: &'static str = "project_share";
+	// Note: A project has a few links.
+	const DEFAULT_LIMIT: i64 = 100;
+	const MAX_LIMIT: i64 = 100;
+}
+
+impl ProjectShareBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		share_c: ProjectShareForCreate,
+	) -> Result<i64> {
+		ProjectBmc::check_owner(ctx, mm, share_c.project_id).await?;
+
+		let max_days = config().SHARE_LINK_MAX_DAYS;
+		let days = share_c
+			.duration_days
+			.unwrap_or(SHARE_LINK_DAYS_DEFAULT.min(max_days));
+		if days == 0 || days > max_days {
+			return Err(Error::ShareLinkDurationInvalid { max_days });
+		}
+		// Note: To the second, as the token `exp`.
+		let expires_at = truncate_to_sec(now_utc_plus(Duration::days(days.into())));
+
+		let share_c = ProjectShareForCreateInner {
+			project_id: share_c.project_id,
+			expires_at,
+		};
+		base::create::<Self, _>(ctx, mm, share_c).await
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<ProjectShare> {
+		let share: ProjectShare = base::get::<Self, _>(ctx, mm, id).await?;
+		ProjectBmc::check_owner(ctx, mm, share.project_id)
+			.await
+			.map_err(|_| Error::EntityNotFound {
+				entity: Self::TABLE,
+				id,
+			})?;
+
+		Ok(share)
+	}
+
+	/// The link token of the share (the same for the share lifetime).
+	pub async fn token(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<String> {
+		let share = Self::get(ctx, mm, id).await?;
+		let (salt,): (Uuid,) =
+			sqlx::query_as("SELECT token_salt FROM project_share WHERE id = $1")
+				.bind(id)
+				.fetch_one(mm.db()?)
+				.await?;
+		let token = generate_share_token(id, share.expires_at, salt)
+			.map_err(|_| Error::ShareLinkInvalid)?;
+
+		Ok(token.to_string())
+	}
+
+	/// The shares of a project (expired included).
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		project_id: i64,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<ProjectShare>> {
+		ProjectBmc::check_owner(ctx, mm, project_id).await?;
+
+		let filter = ProjectShareFilter {
+			project_id: Some(project_id.into()),
+		};
+		base::list::<Self, _, _>(ctx, mm, Some(filter), list_options).await
+	}
+
+	/// Revokes the share link.
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		// Note: Checks the ownership.
+		Self::get(ctx, mm, id).await?;
+		base::delete::<Self>(ctx, mm, id).await
+	}
+
+	/// The project id of a valid link token, otherwise `ShareLinkInvalid`.
+	pub async fn resolve_token(mm: &ModelManager, token: &str) -> Result<i64> {
+		let token: Token = token.parse().map_err(|_| Error::ShareLinkInvalid)?;
+		let id: i64 = token.ident.parse().map_err(|_| Error::ShareLinkInvalid)?;
+
+		let share: Option<(i64, Uuid)> = sqlx::query_as(
+			"SELECT project_id, token_salt FROM project_share WHERE id = $1",
+		)
+		.bind(id)
+		.fetch_optional(mm.db()?)
+		.await?;
+		let (project_id, salt) = share.ok_or(Error::ShareLinkInvalid)?;
+		validate_share_token(&token, salt).map_err(|_| Error::ShareLinkInvalid)?;
+
+		Ok(project_id)
+	}
+}
+
+// endregion: --- ProjectShareBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_share_link_ok_and_revoke() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_share_link_ok_and_revoke project",
+		)
+		.await?;
+		let fx_share_c = ProjectShareForCreate {
+			project_id: fx_project_id,
+			duration_days: None,
+		};
+
+		// -- Exec
+		let share_id = ProjectShareBmc::create(&ctx, &mm, fx_share_c).await?;
+		let token = ProjectShareBmc::token(&ctx, &mm, share_id).await?;
+
+		// -- Check
+		assert_eq!(token, ProjectShareBmc::token(&ctx, &mm, share_id).await?);
+		assert_eq!(
+			ProjectShareBmc::resolve_token(&mm, &token).await?,
+			fx_project_id
+		);
+		let tampered = format!("{token}x");
+		assert!(matches!(
+			ProjectShareBmc::resolve_token(&mm, &tampered).await,
+			Err(Error::ShareLinkInvalid)
+		));
+		let shares = ProjectShareBmc::list(&ctx, &mm, fx_project_id, None).await?;
+		assert_eq!(shares.len(), 1);
+
+		// -- Exec & Check - revoke
+		ProjectShareBmc::delete(&ctx, &mm, share_id).await?;
+		assert!(matches!(
+			ProjectShareBmc::resolve_token(&mm, &token).await,
+			Err(Error::ShareLinkInvalid)
+		));
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_share_link_create_err_duration() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_share_c = ProjectShareForCreate {
+			project_id: 1000,
+			duration_days: Some(config().SHARE_LINK_MAX_DAYS + 1),
+		};
+
+		// -- Exec
+		let res = ProjectShareBmc::create(&ctx, &mm, fx_share_c).await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::ShareLinkDurationInvalid { .. })),
+			"should be ShareLinkDurationInvalid"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
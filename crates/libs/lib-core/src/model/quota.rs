@@ -0,0 +1,189 @@
+//! The soft quotas of the users (see the `QUOTA_*` config), checked by the
+//! create paths (e.g., `ProjectBmc::create`, `TaskBmc::create_many`,
+//! `ProjectBmc::duplicate`), and the current usage (e.g., for the UI account
+//! page).
+//!
+//! - `projects` - the projects owned by the user.
+//! - `tasks_per_project` - the (not trashed) tasks of a project.
+//!
+//! NOTE: No max attachment bytes quota, as there are no attachments (yet).
+//!
+//! NOTE: Soft, as the count and the insert are not atomic (so concurrent
+//!       creates might go a bit over), and a lowered quota does not remove the
+//!       existing entities (nor block the trash restores). Root has no quota.
+
+use crate::config::config;
+use crate::ctx::Ctx;
+use crate::model::{Error, ModelManager, Result};
+use serde::Serialize;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+// region:    --- Quota Types
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct QuotaUsage {
+	pub used: i64,
+	/// None when unlimited.
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub max: Option<u32>,
+}
+
+/// The usage of the ctx user.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct Usage {
+	pub projects: QuotaUsage,
+	/// Of its project with the most tasks (the quota is per project).
+	pub tasks_per_project: QuotaUsage,
+}
+
+// endregion: --- Quota Types
+
+// region:    --- QuotaBmc
+
+pub struct QuotaBmc;
+
+impl QuotaBmc {
+	/// Fails with `QuotaExceeded` if the ctx user cannot own `adding` more
+	/// projects.
+	pub async fn check_projects(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		adding: i64,
+	) -> Result<()> {
+		let max = config().QUOTA_MAX_PROJECTS;
+		if max.is_none() || ctx.is_root() {
+			return Ok(());
+		}
+
+		let used = count_projects(ctx, mm).await?;
+		check_quota("projects", max, used, adding)
+	}
+
+	/// Fails with `QuotaExceeded` if the project cannot have `adding` more
+	/// tasks.
+	pub async fn check_tasks(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		project_id: i64,
+		adding: i64,
+	) -> Result<()> {
+		let max = config().QUOTA_MAX_TASKS_PER_PROJECT;
+		if max.is_none() || ctx.is_root() {
+			return Ok(());
+		}
+
+		let (used,): (i64,) = sqlx::query_as(
+			"SELECT count(*) FROM task WHERE project_id = $1 AND deleted_at IS NULL",
+		)
+		.bind(project_id)
+		.fetch_one(mm.db()?)
+		.await?;
+		check_quota("tasks_per_project", max, used, adding)
+	}
+
+	/// The current usage of the ctx user, with its quotas.
+	pub async fn usage(ctx: &Ctx, mm: &ModelManager) -> Result<Usage> {
+		let projects = count_projects(ctx, mm).await?;
+		let (max_project_tasks,): (i64,) = sqlx::query_as(
+			"SELECT coalesce(max(tasks), 0) FROM ( \
+			 SELECT count(*) AS tasks FROM task t JOIN project p ON p.id = t.project_id \
+			 WHERE p.owner_id = $1 AND t.deleted_at IS NULL GROUP BY t.project_id \
+			 ) project_tasks",
+		)
+		.bind(ctx.user_id())
+		.fetch_one(mm.db()?)
+		.await?;
+
+		Ok(Usage {
+			projects: QuotaUsage {
+				used: projects,
+				max: config().QUOTA_MAX_PROJECTS,
+			},
+			tasks_per_project: QuotaUsage {
+				used: max_project_tasks,
+				max: config().QUOTA_MAX_TASKS_PER_PROJECT,
+			},
+		})
+	}
+}
+
+// endregion: --- QuotaBmc
+
+async fn count_projects(ctx: &Ctx, mm: &ModelManager) -> Result<i64> {
+	let (count,): (i64,) =
+		sqlx::query_as("SELECT count(*) FROM project WHERE owner_id = $1")
+			.bind(ctx.user_id())
+			.fetch_one(mm.db()?)
+			.await?;
+
+	Ok(count)
+}
+
+fn check_quota(
+	quota: &'static str,
+	max: Option<u32>,
+	used: i64,
+	adding: i64,
+) -> Result<()> {
+	match max {
+		Some(max) if used + adding > i64::from(max) => {
+			Err(Error::QuotaExceeded { quota, max })
+		}
+		_ => Ok(()),
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::project::ProjectBmc;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[test]
+	fn test_check_quota() {
+		assert!(check_quota("projects", None, 1_000, 1).is_ok());
+		assert!(check_quota("projects", Some(3), 2, 1).is_ok());
+		assert!(matches!(
+			check_quota("projects", Some(3), 3, 1),
+			Err(Error::QuotaExceeded {
+				quota: "projects",
+				max: 3
+			})
+		));
+		assert!(check_quota("tasks_per_project", Some(3), 1, 3).is_err());
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_usage_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::new(1000)?;
+		let before = QuotaBmc::usage(&ctx, &mm).await?;
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_usage_ok project").await?;
+		let fx_tasks =
+			vec!["test_usage_ok task"; before.tasks_per_project.used as usize + 1];
+		_dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &fx_tasks).await?;
+
+		// -- Exec
+		let usage = QuotaBmc::usage(&ctx, &mm).await?;
+
+		// -- Check
+		assert_eq!(usage.projects.used, before.projects.used + 1);
+		assert_eq!(usage.tasks_per_project.used, fx_tasks.len() as i64);
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
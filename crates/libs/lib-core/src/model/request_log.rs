@@ -0,0 +1,320 @@
+//! The request log, i.e., one row per served request.
+//!
+//! The rows are written in batches by the web-server request log writer,
+//! never in the request path (see web-server `log`), and listed for the
+//! audits (see web-server `request_log_rpc`, admin only).
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+#[cfg(feature = "ts")]
+use crate::model::modql_utils::ts;
+use crate::model::{ModelManager, Result};
+use lib_base::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, ListOptions, OpValsInt64, OpValsString, OpValsValue,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+use std::time::Duration;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+// region:    --- RequestLog Types
+
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct RequestLog {
+	pub id: i64,
+	pub req_id: String,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub time_in: OffsetDateTime,
+	pub duration_ms: f64,
+	pub user_id: Option<i64>,
+	pub client_ip: Option<String>,
+
+	pub http_method: String,
+	pub http_path: String,
+	pub http_status: i32,
+
+	pub rpc_id: Option<String>,
+	pub rpc_method: Option<String>,
+
+	pub client_error_type: Option<String>,
+	pub error_type: Option<String>,
+	/// The redacted error data json.
+	pub error_data: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestLogForCreate {
+	/// The `x-request-id`.
+	pub req_id: String,
+	pub time_in: OffsetDateTime,
+	pub duration_ms: f64,
+	pub user_id: Option<i64>,
+	pub client_ip: Option<String>,
+
+	pub http_method: String,
+	pub http_path: String,
+	pub http_status: u16,
+
+	pub rpc_id: Option<String>,
+	pub rpc_method: Option<String>,
+
+	pub client_error_type: Option<String>,
+	pub error_type: Option<String>,
+	/// The redacted error data json.
+	pub error_data: Option<String>,
+}
+
+/// Note: The `time_in` filters take Rfc3339 times
+///       (e.g., `{"time_in": {"$gte": "2023-11-01T10:00:00Z"}}`).
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct RequestLogFilter {
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
+	user_id: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsTime>", optional))]
+	time_in: Option<OpValsValue>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
+	http_status: Option<OpValsInt64>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsString>", optional))]
+	rpc_method: Option<OpValsString>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsString>", optional))]
+	client_ip: Option<OpValsString>,
+}
+
+// endregion: --- RequestLog Types
+
+// region:    --- RequestLogBmc
+
+pub struct RequestLogBmc;
+
+impl DbBmc for RequestLogBmc {
+	const TABLE: &'static str = "request_log";
+	// Note: The newest first, as for an audit.
+	const DEFAULT_LIMIT: i64 = 100;
+	const DEFAULT_ORDER_BYS: &'static str = "!id";
+}
+
+impl RequestLogBmc {
+	/// Inserts the request logs in one statement, and returns the inserted count.
+	pub async fn create_batch(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		logs: &[RequestLogForCreate],
+	) -> Result<u64> {
+		if logs.is_empty() {
+			return Ok(0);
+		}
+
+		// Note: One array per column, so the statement is the same for any batch size.
+		let res = sqlx::query(
+			"INSERT INTO request_log (req_id, time_in, duration_ms, user_id,
+			   client_ip, http_method, http_path, http_status, rpc_id, rpc_method,
+			   client_error_type, error_type, error_data)
+			 SELECT * FROM UNNEST($1::varchar[], $2::timestamptz[], $3::float8[],
+			   $4::int8[], $5::varchar[], $6::varchar[], $7::text[], $8::int4[],
+			   $9::text[], $10::varchar[], $11::varchar[], $12::varchar[], $13::text[])",
+		)
+		.bind(column(logs, |l| l.req_id.clone()))
+		.bind(column(logs, |l| l.time_in))
+		.bind(column(logs, |l| l.duration_ms))
+		.bind(column(logs, |l| l.user_id))
+		.bind(column(logs, |l| l.client_ip.clone()))
+		.bind(column(logs, |l| l.http_method.clone()))
+		.bind(column(logs, |l| l.http_path.clone()))
+		.bind(column(logs, |l| i32::from(l.http_status)))
+		.bind(column(logs, |l| l.rpc_id.clone()))
+		.bind(column(logs, |l| l.rpc_method.clone()))
+		.bind(column(logs, |l| l.client_error_type.clone()))
+		.bind(column(logs, |l| l.error_type.clone()))
+		.bind(column(logs, |l| l.error_data.clone()))
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(res.rows_affected())
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<RequestLogFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<RequestLog>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// Deletes the request logs older than the `age`.
+	pub async fn prune(_ctx: &Ctx, mm: &ModelManager, age: Duration) -> Result<u64> {
+		let res = sqlx::query(
+			"DELETE FROM request_log WHERE time_in < now() - make_interval(secs => $1)",
+		)
+		.bind(age.as_secs_f64())
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(res.rows_affected())
+	}
+}
+
+fn column<T>(
+	logs: &[RequestLogForCreate],
+	f: impl Fn(&RequestLogForCreate) -> T,
+) -> Vec<T> {
+	logs.iter().map(f).collect()
+}
+
+// endregion: --- RequestLogBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use anyhow::Result;
+	use lib_base::time::now_utc;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_batch_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_log = RequestLogForCreate {
+			req_id: "test_create_batch_ok-01".to_string(),
+			time_in: now_utc(),
+			duration_ms: 1.5,
+			user_id: Some(1000),
+			client_ip: Some("203.0.113.7".to_string()),
+			http_method: "POST".to_string(),
+			http_path: "/api/rpc".to_string(),
+			http_status: 200,
+			rpc_id: Some("1".to_string()),
+			rpc_method: Some("list_tasks".to_string()),
+			client_error_type: None,
+			error_type: None,
+			error_data: None,
+		};
+		let fx_logs = [
+			fx_log.clone(),
+			RequestLogForCreate {
+				req_id: "test_create_batch_ok-02".to_string(),
+				user_id: None,
+				client_ip: None,
+				http_status: 401,
+				rpc_id: None,
+				rpc_method: None,
+				client_error_type: Some("NO_AUTH".to_string()),
+				error_type: Some("CtxExt".to_string()),
+				error_data: Some(r#"{"reason":"TokenNotInCookie"}"#.to_string()),
+				..fx_log
+			},
+		];
+
+		// -- Exec
+		let count = RequestLogBmc::create_batch(&ctx, &mm, &fx_logs).await?;
+
+		// -- Check
+		assert_eq!(count, 2);
+		let rows: Vec<(String, i32, Option<i64>, Option<String>)> = sqlx::query_as(
+			"SELECT req_id, http_status, user_id, client_ip FROM request_log
+			 WHERE req_id LIKE 'test_create_batch_ok-%' ORDER BY req_id",
+		)
+		.fetch_all(mm.db()?)
+		.await?;
+		assert_eq!(
+			rows,
+			[
+				(
+					"test_create_batch_ok-01".to_string(),
+					200,
+					Some(1000),
+					Some("203.0.113.7".to_string())
+				),
+				("test_create_batch_ok-02".to_string(), 401, None, None),
+			]
+		);
+
+		// -- Cleanup
+		sqlx::query(
+			"DELETE FROM request_log WHERE req_id LIKE 'test_create_batch_ok-%'",
+		)
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_filter_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_log = RequestLogForCreate {
+			req_id: "test_list_filter_ok-01".to_string(),
+			time_in: now_utc(),
+			duration_ms: 1.5,
+			user_id: Some(1000),
+			client_ip: None,
+			http_method: "POST".to_string(),
+			http_path: "/api/rpc".to_string(),
+			http_status: 200,
+			rpc_id: Some("1".to_string()),
+			rpc_method: Some("test_list_filter_ok_method".to_string()),
+			client_error_type: None,
+			error_type: None,
+			error_data: None,
+		};
+		let fx_logs = [
+			fx_log.clone(),
+			RequestLogForCreate {
+				req_id: "test_list_filter_ok-02".to_string(),
+				http_status: 403,
+				client_error_type: Some("ROLE_REQUIRED".to_string()),
+				..fx_log.clone()
+			},
+			RequestLogForCreate {
+				req_id: "test_list_filter_ok-03".to_string(),
+				user_id: Some(1001),
+				http_status: 403,
+				..fx_log
+			},
+		];
+		RequestLogBmc::create_batch(&ctx, &mm, &fx_logs).await?;
+
+		// -- Exec
+		let filter: RequestLogFilter = serde_json::from_value(serde_json::json!({
+			"user_id": 1000,
+			"http_status": {"$gte": 400},
+			"rpc_method": "test_list_filter_ok_method",
+			"time_in": {"$gte": "2020-01-01T00:00:00Z"},
+		}))?;
+		let logs = RequestLogBmc::list(&ctx, &mm, Some(vec![filter]), None).await?;
+
+		// -- Check
+		let req_ids: Vec<&str> = logs.iter().map(|l| l.req_id.as_str()).collect();
+		assert_eq!(req_ids, ["test_list_filter_ok-02"]);
+		assert_eq!(logs[0].client_error_type.as_deref(), Some("ROLE_REQUIRED"));
+
+		// -- Cleanup
+		sqlx::query(
+			"DELETE FROM request_log WHERE req_id LIKE 'test_list_filter_ok-%'",
+		)
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,228 @@
+//! The global search of the tasks (title) and projects (name) together, e.g.,
+//! for the UI search box.
+//!
+//! - The full-text match (`websearch_to_tsquery`, so quoted phrases and `-word`
+//!   work) is ranked by `ts_rank`.
+//! - The substring match (e.g., a partial word) is a fallback, ranked `0`.
+//!
+//! NOTE: Only the projects owned by the ctx user (and their tasks) are searched
+//!       (root searches all), and never the trashed tasks.
+
+use crate::ctx::Ctx;
+use crate::model::base;
+use crate::model::{ListPage, ModelManager, Result};
+use lib_base::time::Rfc3339;
+use serde::Serialize;
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+pub const SEARCH_DEFAULT_LIMIT: i64 = 20;
+/// Over it, the limit is lowered to it.
+pub const SEARCH_MAX_LIMIT: i64 = 100;
+
+/// The hits of both entities, with the params `$1` the tsquery text, `$2` the
+/// ILIKE pattern, `$3` if root, and `$4` the ctx user id.
+const SEARCH_HITS_SQL: &str = "\
+	SELECT 'task'::text AS entity, t.id, t.project_id, t.title, t.done, t.mtime, \
+	ts_rank(to_tsvector('simple', t.title), websearch_to_tsquery('simple', $1)) AS rank \
+	FROM task t JOIN project p ON p.id = t.project_id \
+	WHERE t.deleted_at IS NULL AND ($3 OR p.owner_id = $4) \
+	AND (to_tsvector('simple', t.title) @@ websearch_to_tsquery('simple', $1) \
+	OR t.title ILIKE $2) \
+	UNION ALL \
+	SELECT 'project'::text, p.id, p.id, p.name, NULL, p.mtime, \
+	ts_rank(to_tsvector('simple', p.name), websearch_to_tsquery('simple', $1)) \
+	FROM project p \
+	WHERE ($3 OR p.owner_id = $4) \
+	AND (to_tsvector('simple', p.name) @@ websearch_to_tsquery('simple', $1) \
+	OR p.name ILIKE $2)";
+
+// region:    --- Search Types
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum SearchEntity {
+	Task,
+	Project,
+}
+
+/// A task or project matching the search.
+#[serde_as]
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct SearchHit {
+	pub entity: SearchEntity,
+	pub id: i64,
+	/// The task project, or the project itself.
+	pub project_id: i64,
+	/// The task title, or the project name.
+	pub title: String,
+	/// Only for the tasks.
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub done: Option<bool>,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
+	/// The higher, the more relevant (`0` for the substring only matches).
+	pub rank: f32,
+}
+
+// endregion: --- Search Types
+
+// region:    --- SearchBmc
+
+pub struct SearchBmc;
+
+impl SearchBmc {
+	/// The hits of the `query`, by relevance (then the most recently modified).
+	///
+	/// The `limit` defaults to `SEARCH_DEFAULT_LIMIT`, and is lowered to
+	/// `SEARCH_MAX_LIMIT`. An empty query has no hits.
+	pub async fn search(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		query: &str,
+		offset: Option<i64>,
+		limit: Option<i64>,
+	) -> Result<ListPage<SearchHit>> {
+		let offset = offset.unwrap_or(0).max(0);
+		let limit = limit
+			.unwrap_or(SEARCH_DEFAULT_LIMIT)
+			.clamp(0, SEARCH_MAX_LIMIT);
+
+		let query = query.trim();
+		if query.is_empty() {
+			return Ok(ListPage {
+				items: Vec::new(),
+				total: 0,
+				offset,
+				limit,
+				has_more: false,
+			});
+		}
+		let pattern = format!("%{}%", escape_like(query));
+
+		// -- Execute the queries
+		// Note: In a transaction, for the deadline (see `base::list_with_meta`).
+		let mut tx = base::begin(ctx, mm).await?;
+		let items: Vec<SearchHit> = sqlx::query_as(&format!(
+			"SELECT * FROM ({SEARCH_HITS_SQL}) hits \
+			 ORDER BY rank DESC, mtime DESC, entity, id DESC \
+			 LIMIT $5 OFFSET $6"
+		))
+		.bind(query)
+		.bind(&pattern)
+		.bind(ctx.is_root())
+		.bind(ctx.user_id())
+		.bind(limit)
+		.bind(offset)
+		.fetch_all(&mut *tx)
+		.await?;
+		let (total,): (i64,) = sqlx::query_as(&format!(
+			"SELECT count(*) FROM ({SEARCH_HITS_SQL}) hits"
+		))
+		.bind(query)
+		.bind(&pattern)
+		.bind(ctx.is_root())
+		.bind(ctx.user_id())
+		.fetch_one(&mut *tx)
+		.await?;
+		tx.commit().await?;
+
+		let has_more = offset + (items.len() as i64) < total;
+
+		Ok(ListPage {
+			items,
+			total,
+			offset,
+			limit,
+			has_more,
+		})
+	}
+}
+
+// endregion: --- SearchBmc
+
+/// Escapes the ILIKE wildcards (`%`, `_`), so they match literally.
+fn escape_like(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		if matches!(c, '\\' | '%' | '_') {
+			escaped.push('\\');
+		}
+		escaped.push(c);
+	}
+	escaped
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::project::ProjectBmc;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[test]
+	fn test_escape_like() {
+		assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+		assert_eq!(escape_like("plain"), "plain");
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_search_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_search_ok zorblax project")
+				.await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&["buy zorblax", "zorblaxing later", "unrelated"],
+		)
+		.await?;
+
+		// -- Exec
+		let page = SearchBmc::search(&ctx, &mm, "zorblax", None, None).await?;
+
+		// -- Check - full-text hits first, then the substring one.
+		assert_eq!(page.total, 3);
+		assert!(!page.has_more);
+		let hits: Vec<_> = page
+			.items
+			.iter()
+			.map(|hit| (hit.entity, hit.id, hit.rank > 0.))
+			.collect();
+		assert_eq!(hits.len(), 3);
+		assert!(hits[..2].contains(&(SearchEntity::Task, fx_tasks[0].id, true)));
+		assert!(hits[..2].contains(&(SearchEntity::Project, fx_project_id, true)));
+		assert_eq!(hits[2], (SearchEntity::Task, fx_tasks[1].id, false));
+
+		// -- Check - paging.
+		let page = SearchBmc::search(&ctx, &mm, "zorblax", Some(2), Some(1)).await?;
+		assert_eq!(page.items.len(), 1);
+		assert_eq!(page.total, 3);
+
+		// -- Check - not owned.
+		let other_ctx = Ctx::new(1000)?;
+		let page = SearchBmc::search(&other_ctx, &mm, "zorblax", None, None).await?;
+		assert_eq!(page.total, 0);
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,120 @@
+//! The `Secret` wrapper of the sensitive fields (e.g., the user pwd hash and
+//! salts), which fails to serialize and is redacted in the debug output.
+//!
+//! So, an accidental `Serialize` of the wrong type (e.g., a `UserForLogin`
+//! returned by a rpc) fails the response rather than leaking its secrets.
+//!
+//! The value is read (and bound) as its inner type, and only accessible with
+//! `expose`, which makes the usages explicit.
+
+use sea_query::{Nullable, Value};
+use serde::ser::Error as _;
+use serde::{Serialize, Serializer};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres, Type};
+use std::fmt;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+	pub fn new(val: T) -> Self {
+		Self(val)
+	}
+
+	/// The secret value (never to be sent to the clients).
+	pub fn expose(&self) -> &T {
+		&self.0
+	}
+
+	pub fn into_exposed(self) -> T {
+		self.0
+	}
+}
+
+impl<T> From<T> for Secret<T> {
+	fn from(val: T) -> Self {
+		Self(val)
+	}
+}
+
+impl<T> fmt::Debug for Secret<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("Secret(REDACTED)")
+	}
+}
+
+impl<T> Serialize for Secret<T> {
+	fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+		Err(S::Error::custom("Secret field cannot be serialized"))
+	}
+}
+
+// region:    --- Sqlx & SeaQuery
+
+impl<T: Type<Postgres>> Type<Postgres> for Secret<T> {
+	fn type_info() -> PgTypeInfo {
+		T::type_info()
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		T::compatible(ty)
+	}
+}
+
+impl<'r, T: Decode<'r, Postgres>> Decode<'r, Postgres> for Secret<T> {
+	fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+		T::decode(value).map(Self)
+	}
+}
+
+impl<'q, T: Encode<'q, Postgres>> Encode<'q, Postgres> for Secret<T> {
+	fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+		self.0.encode_by_ref(buf)
+	}
+}
+
+/// For the modql `Fields` (i.e., the inserts and updates).
+impl<T: Into<Value>> From<Secret<T>> for Value {
+	fn from(secret: Secret<T>) -> Self {
+		secret.0.into()
+	}
+}
+
+impl<T: Nullable> Nullable for Secret<T> {
+	fn null() -> Value {
+		T::null()
+	}
+}
+
+// endregion: --- Sqlx & SeaQuery
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Serialize)]
+	struct FxUser {
+		username: String,
+		pwd: Option<Secret<String>>,
+	}
+
+	#[test]
+	fn test_secret_no_leak() {
+		let fx_user = FxUser {
+			username: "demo1".to_string(),
+			pwd: Some(Secret::new("#02#some-pwd-hash".to_string())),
+		};
+
+		assert!(serde_json::to_value(&fx_user).is_err());
+		assert!(!format!("{fx_user:?}").contains("some-pwd-hash"));
+		assert_eq!(
+			fx_user.pwd.as_ref().map(|pwd| pwd.expose().as_str()),
+			Some("#02#some-pwd-hash")
+		);
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,274 @@
+//! The idempotent seed of the baseline and demo data (see admin `seed`).
+//!
+//! Unlike the dev db recreate (see `_dev_utils`), nothing is dropped, and the
+//! existing rows are left as-is (insert on conflict do nothing), so it can be
+//! re-run on any env (e.g., at each staging deploy).
+//!
+//! - `Baseline` - the root user, and an admin user.
+//! - `Demo` - the baseline, and the demo user with its project and tasks
+//!   (refused in prod).
+//!
+//! NOTE: The created users get a generated pwd (returned once, see `SeedEntry`),
+//!       which they must change at the first login.
+
+use crate::config::AppEnv;
+use crate::ctx::Ctx;
+use crate::model::user::{UserBmc, UserRole};
+use crate::model::{Error, ModelManager, Result};
+use core::str::FromStr;
+use uuid::Uuid;
+
+pub const DEMO_USERNAME: &str = "demo1";
+const DEMO_PROJECT_NAME: &str = "demo1 project";
+const DEMO_TASK_TITLES: &[&str] =
+	&["Read the docs", "Try the rpc api", "Invite the team"];
+
+// region:    --- Seed Types
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedSet {
+	Baseline,
+	Demo,
+}
+
+impl FromStr for SeedSet {
+	type Err = ();
+
+	fn from_str(val: &str) -> core::result::Result<Self, Self::Err> {
+		match val {
+			"baseline" => Ok(Self::Baseline),
+			"demo" => Ok(Self::Demo),
+			_ => Err(()),
+		}
+	}
+}
+
+/// A seeded row, and whether it was created (false when already there).
+#[derive(Debug)]
+pub struct SeedEntry {
+	/// e.g., `user 'admin'`
+	pub name: String,
+	pub created: bool,
+	/// The generated pwd of a created user (to be shown once).
+	pub pwd: Option<String>,
+}
+
+// endregion: --- Seed Types
+
+/// Seeds the `set` data, refusing the demo data in prod.
+pub async fn seed(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	env: AppEnv,
+	set: SeedSet,
+	admin_username: &str,
+) -> Result<Vec<SeedEntry>> {
+	if set == SeedSet::Demo && env == AppEnv::Prod {
+		return Err(Error::SeedRefused {
+			set: "demo",
+			env: format!("{env:?}"),
+		});
+	}
+
+	// -- Baseline
+	let mut entries = vec![upsert_root_user(mm).await?];
+	let (_, admin) = upsert_user(ctx, mm, admin_username, UserRole::Admin).await?;
+	entries.push(admin);
+
+	// -- Demo
+	if set == SeedSet::Demo {
+		let (demo_id, demo) =
+			upsert_user(ctx, mm, DEMO_USERNAME, UserRole::User).await?;
+		entries.push(demo);
+		entries.extend(upsert_demo_project(ctx, mm, demo_id).await?);
+	}
+
+	Ok(entries)
+}
+
+/// The root user, at id 0 (as the dev seed).
+async fn upsert_root_user(mm: &ModelManager) -> Result<SeedEntry> {
+	let res = sqlx::query(
+		r#"INSERT INTO "user" (id, username, role, cid, ctime, mid, mtime)
+		   VALUES (0, 'root', 'admin', 0, now(), 0, now())
+		   ON CONFLICT (id) DO NOTHING"#,
+	)
+	.execute(mm.db()?)
+	.await?;
+
+	Ok(SeedEntry {
+		name: "user 'root'".to_string(),
+		created: res.rows_affected() > 0,
+		pwd: None,
+	})
+}
+
+/// Returns the user id, and its entry (with the generated pwd when created).
+async fn upsert_user(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	username: &str,
+	role: UserRole,
+) -> Result<(i64, SeedEntry)> {
+	let created_id: Option<(i64,)> = sqlx::query_as(
+		r#"INSERT INTO "user" (username, role, must_change_pwd, cid, ctime, mid, mtime)
+		   VALUES ($1, $2, true, $3, now(), $3, now())
+		   ON CONFLICT (username) DO NOTHING
+		   RETURNING id"#,
+	)
+	.bind(username)
+	.bind(role.as_str())
+	.bind(ctx.user_id())
+	.fetch_optional(mm.db()?)
+	.await?;
+
+	let (id, pwd) = match created_id {
+		Some((id,)) => {
+			let pwd_clear = Uuid::new_v4().simple().to_string();
+			UserBmc::update_pwd(ctx, mm, id, &pwd_clear).await?;
+			(id, Some(pwd_clear))
+		}
+		None => {
+			let (id,): (i64,) =
+				sqlx::query_as(r#"SELECT id FROM "user" WHERE username = $1"#)
+					.bind(username)
+					.fetch_one(mm.db()?)
+					.await?;
+			(id, None)
+		}
+	};
+
+	let entry = SeedEntry {
+		name: format!("user '{username}'"),
+		created: pwd.is_some(),
+		pwd,
+	};
+
+	Ok((id, entry))
+}
+
+/// The demo project (by owner and name, which have no unique constraint),
+/// and its tasks when created.
+async fn upsert_demo_project(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	owner_id: i64,
+) -> Result<Vec<SeedEntry>> {
+	let mut tx = mm.db()?.begin().await?;
+
+	// Note: The lock serializes the concurrent seeds, as the `NOT EXISTS`
+	//       alone would not.
+	sqlx::query("SELECT pg_advisory_xact_lock(hashtext('seed_demo_project'))")
+		.execute(&mut *tx)
+		.await?;
+	let created_id: Option<(i64,)> = sqlx::query_as(
+		"INSERT INTO project (owner_id, name, cid, ctime, mid, mtime)
+		 SELECT $1, $2, $3, now(), $3, now()
+		 WHERE NOT EXISTS (SELECT 1 FROM project WHERE owner_id = $1 AND name = $2)
+		 RETURNING id",
+	)
+	.bind(owner_id)
+	.bind(DEMO_PROJECT_NAME)
+	.bind(ctx.user_id())
+	.fetch_optional(&mut *tx)
+	.await?;
+
+	let mut entries = vec![SeedEntry {
+		name: format!("project '{DEMO_PROJECT_NAME}'"),
+		created: created_id.is_some(),
+		pwd: None,
+	}];
+
+	// Note: The tasks of an existing project are left as-is (may be edited).
+	if let Some((project_id,)) = created_id {
+		for title in DEMO_TASK_TITLES {
+			sqlx::query(
+				"INSERT INTO task (project_id, title, cid, ctime, mid, mtime)
+				 VALUES ($1, $2, $3, now(), $3, now())",
+			)
+			.bind(project_id)
+			.bind(title)
+			.bind(ctx.user_id())
+			.execute(&mut *tx)
+			.await?;
+			entries.push(SeedEntry {
+				name: format!("task '{title}'"),
+				created: true,
+				pwd: None,
+			});
+		}
+	}
+
+	tx.commit().await?;
+
+	Ok(entries)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::user::UserForLogin;
+	use anyhow::{anyhow, Result};
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_seed_baseline_idempotent_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_admin = "test_seed_baseline_idempotent_ok-admin";
+
+		// -- Exec
+		let first =
+			seed(&ctx, &mm, AppEnv::Staging, SeedSet::Baseline, fx_admin).await?;
+		let second =
+			seed(&ctx, &mm, AppEnv::Staging, SeedSet::Baseline, fx_admin).await?;
+
+		// -- Check
+		// The root user is from the dev seed.
+		let created = |entries: &[SeedEntry]| {
+			entries.iter().map(|e| e.created).collect::<Vec<_>>()
+		};
+		assert_eq!(created(&first), [false, true]);
+		assert_eq!(created(&second), [false, false]);
+		assert!(first[1].pwd.is_some());
+		assert!(second[1].pwd.is_none());
+
+		let admin: UserForLogin = UserBmc::first_by_username(&ctx, &mm, fx_admin)
+			.await?
+			.ok_or_else(|| anyhow!("admin not seeded"))?;
+		assert!(admin.must_change_pwd);
+		assert!(admin.pwd.is_some());
+
+		// -- Cleanup
+		sqlx::query(r#"DELETE FROM "user" WHERE username = $1"#)
+			.bind(fx_admin)
+			.execute(mm.db()?)
+			.await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_seed_demo_err_prod() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+
+		// -- Exec
+		let res = seed(&ctx, &mm, AppEnv::Prod, SeedSet::Demo, "admin").await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::SeedRefused { set: "demo", .. })),
+			"should be SeedRefused, was {res:?}"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,125 @@
+//! The cache of the base operations sql (see `base`), by entity, operation,
+//! and columns, so the hot paths do not build the same statement on each call.
+//!
+//! Only the sql is cached, the values are still bound per call, so a statement
+//! is cached only when all its values are plain values (see `plain_values`).
+
+use crate::model::base::DbBmc;
+use crate::model::Result;
+use sea_query::{SimpleExpr, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Over it, the new statements are not cached (e.g., the many column sets
+/// of the partial updates of a wide entity).
+const SQL_CACHE_MAX_ENTRIES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(in crate::model) enum SqlOp {
+	Create,
+	Get,
+	Update,
+	Delete,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct SqlKey {
+	table: &'static str,
+	op: SqlOp,
+	/// What else the sql depends on (e.g., the written columns).
+	variant: Cow<'static, str>,
+}
+
+fn sql_cache() -> &'static RwLock<HashMap<SqlKey, Arc<str>>> {
+	static SQL_CACHE: OnceLock<RwLock<HashMap<SqlKey, Arc<str>>>> = OnceLock::new();
+	SQL_CACHE.get_or_init(RwLock::default)
+}
+
+/// The cached sql of the `MC` operation `variant`, or the one from `build`
+/// (then cached).
+pub(in crate::model) fn get_or_build<MC: DbBmc>(
+	op: SqlOp,
+	variant: Cow<'static, str>,
+	build: impl FnOnce() -> Result<String>,
+) -> Result<Arc<str>> {
+	let key = SqlKey {
+		table: MC::TABLE,
+		op,
+		variant,
+	};
+
+	if let Some(sql) = sql_cache().read().ok().and_then(|c| c.get(&key).cloned()) {
+		return Ok(sql);
+	}
+
+	let sql: Arc<str> = build()?.into();
+	if let Ok(mut cache) = sql_cache().write() {
+		if cache.len() < SQL_CACHE_MAX_ENTRIES {
+			cache.insert(key, sql.clone());
+		}
+	}
+
+	Ok(sql)
+}
+
+/// The values of the `exprs` (in the sql placeholders order), or None when
+/// one is not a plain value (e.g., a function call), so not bindable as is.
+pub(in crate::model) fn plain_values<'a>(
+	exprs: impl IntoIterator<Item = &'a SimpleExpr>,
+) -> Option<Vec<Value>> {
+	exprs
+		.into_iter()
+		.map(|expr| match expr {
+			SimpleExpr::Value(value) => Some(value.clone()),
+			_ => None,
+		})
+		.collect()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sea_query::Func;
+	use std::cell::Cell;
+
+	struct TestSqlCacheBmc;
+
+	impl DbBmc for TestSqlCacheBmc {
+		const TABLE: &'static str = "test_sql_cache";
+	}
+
+	#[test]
+	fn test_get_or_build_once_ok() -> anyhow::Result<()> {
+		// -- Setup & Fixtures
+		let builds = Cell::new(0);
+		let build = || {
+			builds.set(builds.get() + 1);
+			Ok("SELECT 1".to_string())
+		};
+
+		// -- Exec
+		let sql_1 = get_or_build::<TestSqlCacheBmc>(SqlOp::Get, "e".into(), build)?;
+		let sql_2 = get_or_build::<TestSqlCacheBmc>(SqlOp::Get, "e".into(), build)?;
+		let sql_3 =
+			get_or_build::<TestSqlCacheBmc>(SqlOp::Delete, "e".into(), build)?;
+
+		// -- Check
+		assert!(Arc::ptr_eq(&sql_1, &sql_2));
+		assert_eq!(&*sql_3, "SELECT 1");
+		assert_eq!(builds.get(), 2);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_plain_values_ok() {
+		let fx_value = SimpleExpr::Value(1i64.into());
+		let fx_func = SimpleExpr::from(Func::random());
+
+		assert_eq!(plain_values([&fx_value]), Some(vec![Value::from(1i64)]));
+		assert_eq!(plain_values([&fx_value, &fx_func]), None);
+	}
+}
+// endregion: --- Tests
@@ -5,6 +5,9 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug, Serialize)]
 pub enum Error {
     FailToCreatePool(String),
+    InvalidDbUrl(String),
+    FailToPing(String),
+    PingTimeout,
 }
 
 // region:    --- Error Boilerplate
@@ -4,17 +4,137 @@ mod error;
 pub use self::error::{Error, Result};
 
 use crate::config::config;
-use sqlx::postgres::PgPoolOptions;
+use crate::model::db_breaker::db_breaker;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use serde::Serialize;
 use sqlx::{Pool, Postgres};
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 // endregion: --- Modules
 
 pub type Db = Pool<Postgres>;
 
+/// Over it, the connect retries wait this delay.
+const CONNECT_RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Connects the pool, retrying `DB_CONNECT_RETRIES` times with an exponential
+/// backoff (e.g., the db container starting after the service).
 pub async fn new_db_pool() -> Result<Db> {
+    let config = config();
+    let base_delay = Duration::from_millis(config.DB_CONNECT_RETRY_BASE_MS.into());
+
+    let mut retries = 0;
+    loop {
+        let ex = match pool_options().connect(&config.DB_URL).await {
+            Ok(db) => return Ok(db),
+            // Note: Not the url, which may have the db password.
+            Err(sqlx::Error::Configuration(_)) => {
+                return Err(Error::FailToCreatePool("invalid db url".to_string()))
+            }
+            Err(ex) => ex,
+        };
+        if retries >= config.DB_CONNECT_RETRIES {
+            return Err(Error::FailToCreatePool(ex.to_string()));
+        }
+
+        retries += 1;
+        let delay = connect_retry_delay(base_delay, retries);
+        warn!(
+            "{:<12} - db connect failed, retry {retries}/{} in {delay:?} - {ex}",
+            "DB_CONNECT", config.DB_CONNECT_RETRIES
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Note: The connections handed out (new or idle) are the db breaker successes
+///       (see `model::db_breaker`).
+fn pool_options() -> PgPoolOptions {
     PgPoolOptions::new()
         .max_connections(5)
-        .connect(&config().DB_URL)
+        .after_connect(|_, _| {
+            Box::pin(async {
+                db_breaker().record_success();
+                Ok(())
+            })
+        })
+        .before_acquire(|_, _| {
+            Box::pin(async {
+                db_breaker().record_success();
+                Ok(true)
+            })
+        })
+}
+
+/// The delay before the `retry`-th connect retry (base, 2 x base, 4 x base, ...).
+fn connect_retry_delay(base_delay: Duration, retry: u32) -> Duration {
+    let exp = retry.saturating_sub(1).min(16);
+    base_delay
+        .saturating_mul(2u32.pow(exp))
+        .min(CONNECT_RETRY_MAX_DELAY)
+}
+
+/// Points the new pool connections to `db_url` (e.g., rotated credentials).
+/// The open connections are kept until they are recycled.
+pub fn set_db_url(db: &Db, db_url: &str) -> Result<()> {
+    let connect_options: PgConnectOptions = db_url
+        .parse()
+        .map_err(|ex: sqlx::Error| Error::InvalidDbUrl(ex.to_string()))?;
+    db.set_connect_options(connect_options);
+
+    Ok(())
+}
+
+/// The pool connections, at a point in time.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DbPoolStats {
+    /// The open connections (in use and idle).
+    pub size: u32,
+    pub idle: u32,
+    /// The `max_connections` of the pool.
+    pub max: u32,
+}
+
+pub fn db_pool_stats(db: &Db) -> DbPoolStats {
+    DbPoolStats {
+        size: db.size(),
+        idle: db.num_idle() as u32,
+        max: db.options().get_max_connections(),
+    }
+}
+
+/// Executes a `SELECT 1` on a pool connection, failing if it takes longer than
+/// `timeout`, and returns the wait to acquire the connection.
+pub async fn db_ping(db: &Db, timeout: Duration) -> Result<Duration> {
+    let ping = async {
+        let acquire_start = Instant::now();
+        let mut conn = db.acquire().await?;
+        let acquire_wait = acquire_start.elapsed();
+        sqlx::query("SELECT 1").execute(&mut *conn).await?;
+        Ok::<_, sqlx::Error>(acquire_wait)
+    };
+
+    let acquire_wait = tokio::time::timeout(timeout, ping)
         .await
-        .map_err(|ex| Error::FailToCreatePool(ex.to_string()))
+        .map_err(|_| Error::PingTimeout)?
+        .map_err(|ex| Error::FailToPing(ex.to_string()))?;
+
+    Ok(acquire_wait)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_retry_delay_ok() {
+        let fx_base_delay = Duration::from_millis(500);
+
+        assert_eq!(connect_retry_delay(fx_base_delay, 1), fx_base_delay);
+        assert_eq!(connect_retry_delay(fx_base_delay, 3), Duration::from_secs(2));
+        assert_eq!(connect_retry_delay(fx_base_delay, 12), CONNECT_RETRY_MAX_DELAY);
+    }
 }
+// endregion: --- Tests
@@ -0,0 +1,388 @@
+//! Delta sync of the tasks and projects
+//!
+//! `SyncBmc::changes` returns the ids of the entities created, updated, and
+//! deleted since a cursor (the `cursor` of the previous sync), so the offline
+//! capable clients do not need to list everything again.
+//!
+//! - The created and updated ones are from the entity `ctime` / `mtime`.
+//! - The deleted ones are from the `tombstone` table, written by the base
+//!   delete (see `DbBmc::TOMBSTONES`), and kept for `TOMBSTONE_RETENTION`.
+//!   So, a cursor older than that fails with `SyncCursorExpired`, and the client
+//!   must do a full sync (i.e., list all).
+//! - Both are of the projects owned by the ctx user (all of them for root),
+//!   the tombstones having the project owner (see `project_owner_id`).
+//!
+//! NOTE: The project delete cascades to its tasks, each with its tombstone
+//!       (and `Deleted` event), so they are in the deleted ids too.
+
+use crate::ctx::Ctx;
+use crate::model::base::DbBmc;
+use crate::model::project::ProjectBmc;
+use crate::model::task::TaskBmc;
+use crate::model::{Error, ModelManager, Result};
+use lib_base::time::{now_utc, Rfc3339};
+use serde::Serialize;
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{Postgres, Transaction};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// How long the tombstones are kept, i.e., the max age of a sync cursor.
+pub const TOMBSTONE_RETENTION: Duration = Duration::from_secs(30 * 24 * 3600);
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// The changes are from the cursor minus this overlap, so a change written
+/// just before the cursor, but committed after the sync, is not missed
+/// (the clients get some changes twice, which is fine as they are ids).
+const CURSOR_OVERLAP: Duration = Duration::from_secs(5);
+
+// region:    --- Sync Types
+
+/// The changes of the entities since a cursor.
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct SyncChanges {
+	/// The `since` of the next sync.
+	#[serde_as(as = "Rfc3339")]
+	pub cursor: OffsetDateTime,
+	pub tasks: EntityChanges,
+	pub projects: EntityChanges,
+}
+
+/// The changed ids of one entity.
+#[derive(Debug, Default, Serialize)]
+pub struct EntityChanges {
+	pub created: Vec<i64>,
+	/// Updated (and not created) since the cursor.
+	pub updated: Vec<i64>,
+	pub deleted: Vec<i64>,
+}
+
+// endregion: --- Sync Types
+
+// region:    --- SyncBmc
+
+pub struct SyncBmc;
+
+impl SyncBmc {
+	/// The changes since the `since` cursor (`None` for the first sync,
+	/// which returns only the `cursor`).
+	pub async fn changes(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		since: Option<OffsetDateTime>,
+	) -> Result<SyncChanges> {
+		// Note: Taken before the queries, so the next sync gets what changes
+		//       during this one.
+		let cursor = now_utc();
+
+		let Some(since) = since else {
+			return Ok(SyncChanges {
+				cursor,
+				tasks: EntityChanges::default(),
+				projects: EntityChanges::default(),
+			});
+		};
+		if since < cursor - TOMBSTONE_RETENTION {
+			return Err(Error::SyncCursorExpired {
+				max_age_days: TOMBSTONE_RETENTION.as_secs() / (24 * 3600),
+			});
+		}
+		let since = since - CURSOR_OVERLAP;
+
+		Ok(SyncChanges {
+			cursor,
+			tasks: entity_changes::<TaskBmc>(ctx, mm, since).await?,
+			projects: entity_changes::<ProjectBmc>(ctx, mm, since).await?,
+		})
+	}
+}
+
+// endregion: --- SyncBmc
+
+// region:    --- Tombstones
+
+/// Inserts the tombstone of a deleted entity, as part of the delete transaction,
+/// with its project owner (see `project_owner_id`).
+pub(in crate::model) async fn insert_tombstone(
+	tx: &mut Transaction<'_, Postgres>,
+	entity: &str,
+	entity_id: i64,
+	owner_id: Option<i64>,
+) -> Result<()> {
+	sqlx::query(
+		"INSERT INTO tombstone (entity, entity_id, dtime, owner_id)
+		 VALUES ($1, $2, $3, $4)",
+	)
+	.bind(entity)
+	.bind(entity_id)
+	.bind(now_utc())
+	.bind(owner_id)
+	.execute(&mut **tx)
+	.await?;
+
+	Ok(())
+}
+
+/// The owner of a project, for the tombstones of its entities (so, to be
+/// called before the project delete).
+pub(in crate::model) async fn project_owner_id(
+	tx: &mut Transaction<'_, Postgres>,
+	project_id: i64,
+) -> Result<Option<i64>> {
+	let owner_id = sqlx::query_scalar("SELECT owner_id FROM project WHERE id = $1")
+		.bind(project_id)
+		.fetch_optional(&mut **tx)
+		.await?;
+
+	Ok(owner_id)
+}
+
+/// Deletes the tombstones of a restored entity (see `base::restore`).
+pub(in crate::model) async fn delete_tombstones(
+	tx: &mut Transaction<'_, Postgres>,
+	entity: &str,
+	entity_id: i64,
+) -> Result<()> {
+	sqlx::query("DELETE FROM tombstone WHERE entity = $1 AND entity_id = $2")
+		.bind(entity)
+		.bind(entity_id)
+		.execute(&mut **tx)
+		.await?;
+
+	Ok(())
+}
+
+/// Deletes the tombstones older than the retention.
+async fn prune_tombstones(mm: &ModelManager) -> Result<u64> {
+	let res = sqlx::query("DELETE FROM tombstone WHERE dtime < $1")
+		.bind(now_utc() - TOMBSTONE_RETENTION)
+		.execute(mm.db()?)
+		.await?;
+
+	Ok(res.rows_affected())
+}
+
+/// Spawns the pruning of the tombstones older than the `TOMBSTONE_RETENTION`.
+pub fn spawn_tombstone_pruner(mm: ModelManager) -> JoinHandle<()> {
+	tokio::spawn(async move {
+		let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+		loop {
+			prune.tick().await;
+			match prune_tombstones(&mm).await {
+				Ok(count) => debug!("sync - {count} tombstones pruned"),
+				Err(ex) => warn!("sync - tombstones prune failed - {ex:?}"),
+			}
+		}
+	})
+}
+
+// endregion: --- Tombstones
+
+async fn entity_changes<MC: DbBmc>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	since: OffsetDateTime,
+) -> Result<EntityChanges> {
+	let db = mm.db()?;
+	let mut changes = EntityChanges::default();
+
+	// Note: The `TABLE` and columns are consts, so not an injection.
+	let live = if MC::SOFT_DELETE {
+		" AND deleted_at IS NULL"
+	} else {
+		""
+	};
+	// Note: The entities without project are only synced to root.
+	let owned = match MC::EVENT_PROJECT_ID_COLUMN {
+		Some(project_id_column) => format!(
+			" AND ($2 OR {project_id_column} IN \
+			 (SELECT id FROM project WHERE owner_id = $3))"
+		),
+		None => " AND $2".to_string(),
+	};
+	let sql = format!(
+		"SELECT id, ctime >= $1 FROM {} WHERE mtime >= $1{live}{owned} ORDER BY id",
+		MC::TABLE
+	);
+	let rows: Vec<(i64, bool)> = sqlx::query_as(&sql)
+		.bind(since)
+		.bind(ctx.is_root())
+		.bind(ctx.user_id())
+		.fetch_all(db)
+		.await?;
+	for (id, created) in rows {
+		if created {
+			changes.created.push(id);
+		} else {
+			changes.updated.push(id);
+		}
+	}
+
+	changes.deleted = sqlx::query_scalar(
+		"SELECT DISTINCT entity_id FROM tombstone
+		 WHERE entity = $1 AND dtime >= $2 AND ($3 OR owner_id = $4)
+		 ORDER BY entity_id",
+	)
+	.bind(MC::TABLE)
+	.bind(since)
+	.bind(ctx.is_root())
+	.bind(ctx.user_id())
+	.fetch_all(db)
+	.await?;
+
+	Ok(changes)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::task::TaskForUpdate;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_changes_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_changes_ok project").await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&["test_changes_ok 01", "test_changes_ok 02"],
+		)
+		.await?;
+		// As if seeded an hour ago (i.e., before the cursor overlap).
+		sqlx::query(
+			"UPDATE task SET ctime = ctime - interval '1 hour',
+			                 mtime = mtime - interval '1 hour'
+			 WHERE project_id = $1",
+		)
+		.bind(fx_project_id)
+		.execute(mm.db()?)
+		.await?;
+		let fx_since = now_utc();
+
+		// -- Exec
+		let fx_task_u = TaskForUpdate {
+			done: Some(true),
+			..Default::default()
+		};
+		TaskBmc::update(&ctx, &mm, fx_tasks[0].id, fx_task_u).await?;
+		TaskBmc::delete(&ctx, &mm, fx_tasks[1].id).await?;
+		let fx_created = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&["test_changes_ok 03"],
+		)
+		.await?;
+		let changes = SyncBmc::changes(&ctx, &mm, Some(fx_since)).await?;
+
+		// -- Check
+		// Note: Might have the changes of the previous tests (in the overlap).
+		let tasks = &changes.tasks;
+		assert!(tasks.created.contains(&fx_created[0].id));
+		assert!(!tasks.created.contains(&fx_tasks[0].id));
+		assert!(tasks.updated.contains(&fx_tasks[0].id));
+		assert!(!tasks.updated.contains(&fx_created[0].id));
+		assert!(tasks.deleted.contains(&fx_tasks[1].id));
+
+		// -- Cleanup
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_changes_other_user_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::new(1000)?;
+		let fx_since = now_utc();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_changes_other_user_ok project",
+		)
+		.await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&[
+				"test_changes_other_user_ok 01",
+				"test_changes_other_user_ok 02",
+			],
+		)
+		.await?;
+		TaskBmc::delete(&ctx, &mm, fx_tasks[1].id).await?;
+		let fx_deleted_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_changes_other_user_ok deleted project",
+		)
+		.await?;
+		ProjectBmc::delete(&ctx, &mm, fx_deleted_project_id).await?;
+
+		// -- Exec
+		let changes = SyncBmc::changes(&ctx, &mm, Some(fx_since)).await?;
+		let other_ctx = Ctx::new(1001)?;
+		let other_changes =
+			SyncBmc::changes(&other_ctx, &mm, Some(fx_since)).await?;
+
+		// -- Check - the owner sees the changes.
+		assert!(changes.projects.created.contains(&fx_project_id));
+		assert!(changes.projects.deleted.contains(&fx_deleted_project_id));
+		assert!(changes.tasks.created.contains(&fx_tasks[0].id));
+		assert!(changes.tasks.deleted.contains(&fx_tasks[1].id));
+
+		// -- Check - a second user sees nothing of them.
+		let is_synced = |changes: &EntityChanges, id: i64| {
+			changes.created.contains(&id)
+				|| changes.updated.contains(&id)
+				|| changes.deleted.contains(&id)
+		};
+		for fx_project_id in [fx_project_id, fx_deleted_project_id] {
+			assert!(!is_synced(&other_changes.projects, fx_project_id));
+		}
+		for fx_task in &fx_tasks {
+			assert!(!is_synced(&other_changes.tasks, fx_task.id));
+		}
+
+		// -- Cleanup
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_changes_err_cursor_expired() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_since = now_utc() - TOMBSTONE_RETENTION - Duration::from_secs(60);
+
+		// -- Exec
+		let res = SyncBmc::changes(&ctx, &mm, Some(fx_since)).await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(Error::SyncCursorExpired { max_age_days: 30 })),
+			"should be SyncCursorExpired"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -1,50 +1,126 @@
 use crate::ctx::Ctx;
 use crate::model::base::{self, DbBmc};
-use crate::model::modql_utils::time_to_sea_value;
-use crate::model::ModelManager;
+use crate::model::event::ModelEventKind;
+#[cfg(feature = "ts")]
+use crate::model::modql_utils::ts;
+use crate::model::modql_utils::{nullable_time, time_to_sea_value, Nullable};
+use crate::model::project::ProjectBmc;
+use crate::model::quota::QuotaBmc;
 use crate::model::Result;
+use crate::model::{ListPage, ModelManager};
+use futures::Stream;
+use lib_base::time::{now_utc, Rfc3339};
 use modql::field::Fields;
 use modql::filter::{
 	FilterNodes, ListOptions, OpValsBool, OpValsInt64, OpValsString, OpValsValue,
 };
+use modql::SIden;
+use sea_query::{Condition, Expr, Query};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{FromRow, Row};
+use std::collections::BTreeMap;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 // region:    --- Task Types
 
-#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct Task {
 	pub id: i64,
 	pub project_id: i64,
 
 	pub title: String,
 	pub done: bool,
+	#[serde_as(as = "Option<Rfc3339>")]
+	#[cfg_attr(feature = "ts", ts(type = "string | null"))]
+	pub due_date: Option<OffsetDateTime>,
+
+	// -- Timestamps
+	//    (creator and last modified user_id/time)
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
+}
+
+/// A trashed task (see `model::trash`).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct TrashedTask {
+	pub id: i64,
+	pub project_id: i64,
+
+	pub title: String,
+	pub done: bool,
+
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub deleted_at: OffsetDateTime,
+	// -- Timestamps
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
 }
 
-#[derive(Deserialize, Fields)]
+#[derive(Deserialize, Fields, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct TaskForCreate {
 	pub title: String,
 	pub project_id: i64,
 }
 
-#[derive(Deserialize, Fields, Default)]
+#[derive(Deserialize, Fields, Default, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct TaskForUpdate {
+	#[cfg_attr(feature = "ts", ts(optional))]
 	pub title: Option<String>,
+	#[cfg_attr(feature = "ts", ts(optional))]
 	pub done: Option<bool>,
+	/// An Rfc3339 time, or `null` to clear it (absent is unchanged).
+	#[serde(default, deserialize_with = "nullable_time")]
+	#[schema(value_type = Option<String>, format = DateTime, nullable)]
+	#[cfg_attr(feature = "ts", ts(type = "string | null", optional))]
+	pub due_date: Option<Nullable<OffsetDateTime>>,
 }
 
+/// Note: The `ctime` / `mtime` filters take Rfc3339 times
+///       (e.g., `{"mtime": {"$gte": "2023-11-01T10:00:00Z"}}`).
 #[derive(FilterNodes, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct TaskFilter {
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	id: Option<OpValsInt64>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	project_id: Option<OpValsInt64>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsString>", optional))]
 	title: Option<OpValsString>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsBool>", optional))]
 	done: Option<OpValsBool>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsTime>", optional))]
+	due_date: Option<OpValsValue>,
 
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	cid: Option<OpValsInt64>,
 	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsTime>", optional))]
 	ctime: Option<OpValsValue>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
 	mid: Option<OpValsInt64>,
 	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsTime>", optional))]
 	mtime: Option<OpValsValue>,
 }
 
@@ -55,6 +131,9 @@ pub struct TaskBmc;
 
 impl DbBmc for TaskBmc {
 	const TABLE: &'static str = "task";
+	const EVENT_PROJECT_ID_COLUMN: Option<&'static str> = Some("project_id");
+	const TOMBSTONES: bool = true;
+	const SOFT_DELETE: bool = true;
 }
 
 impl TaskBmc {
@@ -63,9 +142,27 @@ impl TaskBmc {
 		mm: &ModelManager,
 		task_c: TaskForCreate,
 	) -> Result<i64> {
+		QuotaBmc::check_tasks(ctx, mm, task_c.project_id, 1).await?;
 		base::create::<Self, _>(ctx, mm, task_c).await
 	}
 
+	/// Creates all the tasks, or none (one transaction), e.g., for the imports.
+	pub async fn create_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		tasks_c: Vec<TaskForCreate>,
+	) -> Result<Vec<i64>> {
+		let mut adding_by_project: BTreeMap<i64, i64> = BTreeMap::new();
+		for task_c in &tasks_c {
+			*adding_by_project.entry(task_c.project_id).or_default() += 1;
+		}
+		for (project_id, adding) in adding_by_project {
+			QuotaBmc::check_tasks(ctx, mm, project_id, adding).await?;
+		}
+
+		base::create_many::<Self, _>(ctx, mm, tasks_c).await
+	}
+
 	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Task> {
 		base::get::<Self, _>(ctx, mm, id).await
 	}
@@ -79,6 +176,47 @@ impl TaskBmc {
 		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
 	}
 
+	pub async fn list_paged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<TaskFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<ListPage<Task>> {
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// The tasks of a project (e.g., for its share links).
+	pub async fn list_by_project(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		project_id: i64,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Task>> {
+		let filter = TaskFilter {
+			project_id: Some(project_id.into()),
+			..Default::default()
+		};
+		base::list::<Self, _, _>(ctx, mm, Some(vec![filter]), list_options).await
+	}
+
+	/// Streams all the tasks of the projects owned by the ctx user
+	/// (matching the filter), in `id` order (e.g., for the CSV export).
+	pub fn list_stream_owned(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<TaskFilter>>,
+	) -> Result<impl Stream<Item = Result<Task>> + Send + 'static> {
+		let owned_project_ids = Query::select()
+			.column(SIden("id"))
+			.from(ProjectBmc::table_ref())
+			.and_where(Expr::col(SIden("owner_id")).eq(ctx.user_id()))
+			.to_owned();
+		let owned_cond = Condition::all()
+			.add(Expr::col(SIden("project_id")).in_subquery(owned_project_ids));
+
+		base::list_stream::<Self, _, _>(mm, filter, Some(owned_cond))
+	}
+
 	pub async fn update(
 		ctx: &Ctx,
 		mm: &ModelManager,
@@ -88,9 +226,89 @@ impl TaskBmc {
 		base::update::<Self, _>(ctx, mm, id, task_u).await
 	}
 
+	/// Updates the task only if not modified since `mtime` (e.g., REST `If-Match`).
+	pub async fn update_if_unmodified(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		mtime: OffsetDateTime,
+		task_u: TaskForUpdate,
+	) -> Result<()> {
+		base::update_if_unmodified::<Self, _>(ctx, mm, id, mtime, task_u).await
+	}
+
+	/// Sets `done` on all the (not trashed) tasks of `ids` in one statement,
+	/// and returns the ids done, i.e., without the unknown ones, and the ones
+	/// of the projects not owned by the ctx user (root can do all).
+	pub async fn complete_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		ids: &[i64],
+	) -> Result<Vec<i64>> {
+		let now = now_utc();
+		let mut tx = base::begin(ctx, mm).await?;
+		let rows = sqlx::query(
+			"UPDATE task SET done = true, mid = $1, mtime = $2 \
+			 WHERE id = ANY($3) AND deleted_at IS NULL \
+			 AND ($4 OR project_id IN (SELECT id FROM project WHERE owner_id = $1)) \
+			 RETURNING id, project_id",
+		)
+		.bind(ctx.user_id())
+		.bind(now)
+		.bind(ids)
+		.bind(ctx.is_root())
+		.fetch_all(&mut *tx)
+		.await?;
+
+		let mut done_ids = Vec::with_capacity(rows.len());
+		let mut has_event = false;
+		for row in rows {
+			done_ids.push(row.try_get(0)?);
+			has_event |= base::write_event::<Self>(
+				ctx,
+				&mut tx,
+				&row,
+				ModelEventKind::Updated,
+			)
+			.await?;
+		}
+		base::commit(mm, tx, has_event).await?;
+
+		Ok(done_ids)
+	}
+
+	/// Moves the task to the trash (see `model::trash`).
 	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
 		base::delete::<Self>(ctx, mm, id).await
 	}
+
+	/// Deletes the task only if not modified since `mtime` (e.g., REST `If-Match`).
+	pub async fn delete_if_unmodified(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		mtime: OffsetDateTime,
+	) -> Result<()> {
+		base::delete_if_unmodified::<Self>(ctx, mm, id, mtime).await
+	}
+
+	pub async fn list_trashed(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<TaskFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<TrashedTask>> {
+		base::list_trashed::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn restore(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		base::restore::<Self>(ctx, mm, id).await
+	}
+
+	/// Deletes a trashed task for good.
+	pub async fn purge(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		base::purge::<Self>(ctx, mm, id).await
+	}
 }
 // endregion: --- TaskBmc
 
@@ -100,11 +318,12 @@ mod tests {
 	#![allow(unused)]
 	use crate::{
 		_dev_utils,
-		model::{project::ProjectBmc, Error},
+		model::{event::ModelEventKind, outbox, project::ProjectBmc, Error},
 	};
 
 	use super::*;
 	use anyhow::Result;
+	use futures::TryStreamExt;
 	use lib_base::time::{format_time, now_utc};
 	use modql::filter::OpValString;
 	use serde_json::json;
@@ -140,6 +359,58 @@ mod tests {
 		Ok(())
 	}
 
+	#[serial]
+	#[tokio::test]
+	async fn test_create_many_ok_and_err_all_or_none() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_create_many_ok_and_err_all_or_none project for task",
+		)
+		.await?;
+		let fx_tasks_c = |project_ids: &[i64]| {
+			project_ids
+				.iter()
+				.enumerate()
+				.map(|(i, project_id)| TaskForCreate {
+					title: format!("test_create_many_ok_and_err_all_or_none {i}"),
+					project_id: *project_id,
+				})
+				.collect::<Vec<_>>()
+		};
+		let fx_filter = || {
+			Some(vec![TaskFilter {
+				project_id: Some(fx_project_id.into()),
+				..Default::default()
+			}])
+		};
+
+		// -- Exec
+		let res =
+			TaskBmc::create_many(&ctx, &mm, fx_tasks_c(&[fx_project_id, -1])).await;
+		let tasks_after_err = TaskBmc::list(&ctx, &mm, fx_filter(), None).await?;
+		let ids = TaskBmc::create_many(
+			&ctx,
+			&mm,
+			fx_tasks_c(&[fx_project_id, fx_project_id]),
+		)
+		.await?;
+
+		// -- Check
+		assert!(res.is_err(), "unknown project should fail");
+		assert!(tasks_after_err.is_empty(), "none should be created");
+		let tasks = TaskBmc::list(&ctx, &mm, fx_filter(), None).await?;
+		assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), ids);
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
 	#[serial]
 	#[tokio::test]
 	async fn test_get_err_not_found() -> Result<()> {
@@ -166,6 +437,33 @@ mod tests {
 		Ok(())
 	}
 
+	#[serial]
+	#[tokio::test]
+	async fn test_create_err_project_missing() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_task_c = TaskForCreate {
+			title: "test_create_err_project_missing 01".to_string(),
+			project_id: 999_999,
+		};
+
+		// -- Exec
+		let res = TaskBmc::create(&ctx, &mm, fx_task_c).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				&res,
+				Err(Error::ForeignKeyViolation { entity, field })
+					if entity == "task" && field.as_deref() == Some("project_id")
+			),
+			"{res:?}"
+		);
+
+		Ok(())
+	}
+
 	#[serial]
 	#[tokio::test]
 	async fn test_list_all_ok() -> Result<()> {
@@ -285,6 +583,214 @@ mod tests {
 		Ok(())
 	}
 
+	#[serial]
+	#[tokio::test]
+	async fn test_list_default_order_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_titles = &[
+			"test_list_default_order_ok 03",
+			"test_list_default_order_ok 01",
+			"test_list_default_order_ok 02",
+		];
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_list_default_order_ok project for task",
+		)
+		.await?;
+		_dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+
+		// -- Exec
+		let filter = TaskFilter {
+			project_id: Some(fx_project_id.into()),
+			..Default::default()
+		};
+		let list_options: ListOptions = serde_json::from_value(json!({"limit": 2}))?;
+		let tasks =
+			TaskBmc::list(&ctx, &mm, Some(vec![filter]), Some(list_options)).await?;
+
+		// -- Check
+		// Ordered by id (i.e., the seeding order).
+		let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+		assert_eq!(
+			titles,
+			&[
+				"test_list_default_order_ok 03",
+				"test_list_default_order_ok 01"
+			]
+		);
+
+		// -- Cleanup
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_limit_over_max_err() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_limit = TaskBmc::MAX_LIMIT + 1;
+
+		// -- Exec
+		let list_options: ListOptions =
+			serde_json::from_value(json!({"limit": fx_limit}))?;
+		let res = TaskBmc::list(&ctx, &mm, None, Some(list_options)).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(Error::ListLimitOverMax { max, actual })
+					if max == TaskBmc::MAX_LIMIT && actual == fx_limit
+			),
+			"should be ListLimitOverMax"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_clamp_list_options_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_limit = TaskBmc::MAX_LIMIT + 1;
+		let fx_over_max: ListOptions =
+			serde_json::from_value(json!({"limit": fx_limit, "offset": 10}))?;
+		let fx_within: ListOptions = serde_json::from_value(json!({"limit": 5}))?;
+
+		// -- Exec
+		let (over_max, over_max_clamp) =
+			base::clamp_list_options::<TaskBmc>(Some(fx_over_max));
+		let (within, within_clamp) =
+			base::clamp_list_options::<TaskBmc>(Some(fx_within));
+		let (absent, absent_clamp) = base::clamp_list_options::<TaskBmc>(None);
+
+		// -- Check
+		assert_eq!(over_max.limit, Some(TaskBmc::MAX_LIMIT));
+		assert_eq!(over_max.offset, Some(10));
+		assert_eq!(
+			over_max_clamp,
+			Some(base::LimitClamp {
+				requested: fx_limit,
+				max: TaskBmc::MAX_LIMIT
+			})
+		);
+		assert_eq!((within.limit, within_clamp), (Some(5), None));
+		assert_eq!(absent.limit, Some(TaskBmc::DEFAULT_LIMIT));
+		assert!(absent.order_bys.is_some(), "should have the default order");
+		assert_eq!(absent_clamp, None);
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_paged_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_titles = &[
+			"test_list_paged_ok 01",
+			"test_list_paged_ok 02",
+			"test_list_paged_ok 03",
+		];
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_list_paged_ok project for task",
+		)
+		.await?;
+		_dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+		let fx_filter = || TaskFilter {
+			project_id: Some(fx_project_id.into()),
+			..Default::default()
+		};
+
+		// -- Exec
+		let list_options: ListOptions =
+			serde_json::from_value(json!({"offset": 0, "limit": 2}))?;
+		let page_1 = TaskBmc::list_paged(
+			&ctx,
+			&mm,
+			Some(vec![fx_filter()]),
+			Some(list_options),
+		)
+		.await?;
+		let list_options: ListOptions =
+			serde_json::from_value(json!({"offset": 2, "limit": 2}))?;
+		let page_2 = TaskBmc::list_paged(
+			&ctx,
+			&mm,
+			Some(vec![fx_filter()]),
+			Some(list_options),
+		)
+		.await?;
+
+		// -- Check
+		assert_eq!(page_1.items.len(), 2);
+		assert_eq!(page_1.total, 3);
+		assert_eq!((page_1.offset, page_1.limit), (0, 2));
+		assert!(page_1.has_more);
+		assert_eq!(page_2.items.len(), 1);
+		assert_eq!(page_2.total, 3);
+		assert!(!page_2.has_more);
+
+		// -- Cleanup
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_stream_owned_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_titles = &[
+			"test_list_stream_owned_ok 01",
+			"test_list_stream_owned_ok 02",
+			"test_list_stream_owned_ok 03",
+		];
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_list_stream_owned_ok project for task",
+		)
+		.await?;
+		_dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+		let fx_filter = || {
+			Some(vec![TaskFilter {
+				project_id: Some(fx_project_id.into()),
+				..Default::default()
+			}])
+		};
+
+		// -- Exec
+		let owned: Vec<Task> = TaskBmc::list_stream_owned(&ctx, &mm, fx_filter())?
+			.try_collect()
+			.await?;
+		let other_ctx = Ctx::new(1000)?;
+		let not_owned: Vec<Task> =
+			TaskBmc::list_stream_owned(&other_ctx, &mm, fx_filter())?
+				.try_collect()
+				.await?;
+
+		// -- Check
+		let titles: Vec<&str> = owned.iter().map(|t| t.title.as_str()).collect();
+		assert_eq!(titles, fx_titles);
+		assert!(not_owned.is_empty());
+
+		// -- Cleanup
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
 	#[serial]
 	#[tokio::test]
 	async fn test_update_ok() -> Result<()> {
@@ -322,6 +828,110 @@ mod tests {
 		Ok(())
 	}
 
+	#[serial]
+	#[tokio::test]
+	async fn test_if_unmodified_ok_and_err_modified() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_if_unmodified_ok_and_err_modified project",
+		)
+		.await?;
+		let fx_task = _dev_utils::seed_tasks(&ctx, &mm, fx_project_id, &["task 01"])
+			.await?
+			.remove(0);
+		let fx_task_u = || TaskForUpdate {
+			title: Some("task 01 - new".to_string()),
+			..Default::default()
+		};
+
+		// -- Exec & Check - unmodified.
+		TaskBmc::update_if_unmodified(
+			&ctx,
+			&mm,
+			fx_task.id,
+			fx_task.mtime,
+			fx_task_u(),
+		)
+		.await?;
+		let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+		assert_eq!(task.title, "task 01 - new");
+
+		// -- Exec & Check - modified since (the stale `mtime`).
+		let res = TaskBmc::update_if_unmodified(
+			&ctx,
+			&mm,
+			fx_task.id,
+			fx_task.mtime,
+			fx_task_u(),
+		)
+		.await;
+		assert!(matches!(res, Err(Error::EntityModified { .. })));
+		let res =
+			TaskBmc::delete_if_unmodified(&ctx, &mm, fx_task.id, fx_task.mtime)
+				.await;
+		assert!(matches!(res, Err(Error::EntityModified { .. })));
+
+		TaskBmc::delete_if_unmodified(&ctx, &mm, fx_task.id, task.mtime).await?;
+		let res = TaskBmc::get(&ctx, &mm, fx_task.id).await;
+		assert!(matches!(res, Err(Error::EntityNotFound { .. })));
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_due_date_set_keep_clear_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_due_date = "2024-03-10T12:00:00Z";
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_update_due_date_set_keep_clear_ok project for task",
+		)
+		.await?;
+		let fx_task = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&["test_update_due_date_set_keep_clear_ok - task 01"],
+		)
+		.await?
+		.remove(0);
+
+		// -- Exec & Check - set
+		let task_u = serde_json::from_value(json!({"due_date": fx_due_date}))?;
+		TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+		let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+		assert_eq!(task.due_date.map(format_time).as_deref(), Some(fx_due_date));
+
+		// -- Exec & Check - absent is unchanged
+		let task_u = serde_json::from_value(json!({"done": true}))?;
+		TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+		let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+		assert!(task.done);
+		assert!(task.due_date.is_some(), "should have kept the due_date");
+
+		// -- Exec & Check - null clears
+		let task_u = serde_json::from_value(json!({"due_date": null}))?;
+		TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+		let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+		assert_eq!(task.due_date, None);
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
 	#[serial]
 	#[tokio::test]
 	async fn test_list_by_ctime_ok() -> Result<()> {
@@ -365,6 +975,118 @@ mod tests {
 		Ok(())
 	}
 
+	#[serial]
+	#[tokio::test]
+	async fn test_complete_many_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_complete_many_ok project")
+				.await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&["test_complete_many_ok 01", "test_complete_many_ok 02"],
+		)
+		.await?;
+		let fx_ids: Vec<i64> = fx_tasks.iter().map(|task| task.id).collect();
+		let fx_other_ctx = Ctx::new(1000)?;
+
+		// -- Exec & Check - not owned by the other user.
+		let done_ids = TaskBmc::complete_many(&fx_other_ctx, &mm, &fx_ids).await?;
+		assert!(done_ids.is_empty());
+
+		// -- Exec
+		let mut ids = fx_ids.clone();
+		ids.push(fx_ids[1] + 100_000);
+		let mut done_ids = TaskBmc::complete_many(&ctx, &mm, &ids).await?;
+
+		// -- Check
+		done_ids.sort();
+		assert_eq!(done_ids, fx_ids);
+		for id in fx_ids {
+			assert!(TaskBmc::get(&ctx, &mm, id).await?.done);
+		}
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_by_mtime_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"project for tasks test_list_by_mtime_ok",
+		)
+		.await?;
+		let fx_titles = &[
+			"test_list_by_mtime_ok 01",
+			"test_list_by_mtime_ok 02",
+			"test_list_by_mtime_ok 03",
+		];
+		let fx_tasks =
+			_dev_utils::seed_tasks(&ctx, &mm, fx_project_id, fx_titles).await?;
+
+		let time_marker = format_time(now_utc());
+		sleep(Duration::from_millis(300)).await;
+		let fx_task_u = TaskForUpdate {
+			done: Some(true),
+			..Default::default()
+		};
+		TaskBmc::update(&ctx, &mm, fx_tasks[1].id, fx_task_u).await?;
+
+		// -- Exec
+		let filter_json = json!({
+			"project_id": fx_project_id,
+			"mtime": {"$gte": time_marker},
+		});
+		let filter = vec![serde_json::from_value(filter_json)?];
+		let list_options: ListOptions =
+			serde_json::from_value(json!({"order_bys": "mtime"}))?;
+		let tasks =
+			TaskBmc::list(&ctx, &mm, Some(filter), Some(list_options)).await?;
+
+		// -- Check
+		assert_eq!(tasks.len(), 1);
+		assert_eq!(tasks[0].title, "test_list_by_mtime_ok 02");
+		assert!(tasks[0].mtime > tasks[0].ctime);
+
+		// -- Cleanup
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_deadline_ok_and_err_exceeded() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let mut ctx = Ctx::root_ctx();
+
+		// -- Exec & Check
+		ctx.set_deadline(std::time::Instant::now() + Duration::from_secs(10));
+		TaskBmc::list(&ctx, &mm, None, None).await?;
+
+		ctx.set_deadline(std::time::Instant::now());
+		let res = TaskBmc::list(&ctx, &mm, None, None).await;
+		assert!(
+			matches!(res, Err(Error::DeadlineExceeded)),
+			"should be DeadlineExceeded"
+		);
+
+		Ok(())
+	}
+
 	#[serial]
 	#[tokio::test]
 	async fn test_delete_err_not_found() -> Result<()> {
@@ -390,6 +1112,123 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_trash_restore_purge_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_trash_restore_purge_ok project for task",
+		)
+		.await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&[
+				"test_trash_restore_purge_ok 01",
+				"test_trash_restore_purge_ok 02",
+			],
+		)
+		.await?;
+		let (fx_id_1, fx_id_2) = (fx_tasks[0].id, fx_tasks[1].id);
+		let fx_filter = || {
+			Some(vec![TaskFilter {
+				project_id: Some(fx_project_id.into()),
+				..Default::default()
+			}])
+		};
+
+		// -- Exec & Check - trash
+		TaskBmc::delete(&ctx, &mm, fx_id_1).await?;
+		TaskBmc::delete(&ctx, &mm, fx_id_2).await?;
+		assert!(matches!(
+			TaskBmc::get(&ctx, &mm, fx_id_1).await,
+			Err(Error::EntityNotFound { .. })
+		));
+		assert!(TaskBmc::list(&ctx, &mm, fx_filter(), None)
+			.await?
+			.is_empty());
+		let trashed = TaskBmc::list_trashed(&ctx, &mm, fx_filter(), None).await?;
+		assert_eq!(
+			trashed.iter().map(|t| t.id).collect::<Vec<_>>(),
+			[fx_id_1, fx_id_2]
+		);
+
+		// -- Exec & Check - restore
+		TaskBmc::restore(&ctx, &mm, fx_id_1).await?;
+		assert_eq!(TaskBmc::get(&ctx, &mm, fx_id_1).await?.id, fx_id_1);
+		assert!(matches!(
+			TaskBmc::restore(&ctx, &mm, fx_id_1).await,
+			Err(Error::EntityNotFound { .. })
+		));
+
+		// -- Exec & Check - purge
+		assert!(matches!(
+			TaskBmc::purge(&ctx, &mm, fx_id_1).await,
+			Err(Error::EntityNotFound { .. })
+		));
+		TaskBmc::purge(&ctx, &mm, fx_id_2).await?;
+		assert!(TaskBmc::list_trashed(&ctx, &mm, fx_filter(), None)
+			.await?
+			.is_empty());
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_events_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id =
+			_dev_utils::seed_project(&ctx, &mm, "test_events_ok project for task ")
+				.await?;
+		// Note: No relay task in the tests, so the pending events (e.g., the
+		//       fixture project) are relayed before subscribing.
+		while outbox::relay_pending(&mm).await? > 0 {}
+		let mut events = mm.subscribe_events();
+
+		// -- Exec
+		let task_c = TaskForCreate {
+			project_id: fx_project_id,
+			title: "test_events_ok title".to_string(),
+		};
+		let id = TaskBmc::create(&ctx, &mm, task_c).await?;
+		let task_u = TaskForUpdate {
+			done: Some(true),
+			..Default::default()
+		};
+		TaskBmc::update(&ctx, &mm, id, task_u).await?;
+		TaskBmc::delete(&ctx, &mm, id).await?;
+		outbox::relay_pending(&mm).await?;
+
+		// -- Check
+		for fx_kind in [
+			ModelEventKind::Created,
+			ModelEventKind::Updated,
+			ModelEventKind::Deleted,
+		] {
+			let event = events.try_recv()?;
+			assert_eq!(event.entity, "task");
+			assert_eq!(event.id, id);
+			assert_eq!(event.project_id, fx_project_id);
+			assert_eq!(event.kind, fx_kind);
+		}
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
 }
 
 // endregion: --- TestBmc
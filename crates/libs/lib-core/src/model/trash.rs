@@ -0,0 +1,118 @@
+//! The trash of the soft deleted entities (see `DbBmc::SOFT_DELETE`), i.e., the
+//! tasks.
+//!
+//! A trashed entity can be restored (`base::restore`), or purged for good
+//! (`base::purge`), and is purged by the `spawn_trash_purger` job once older
+//! than `TRASH_RETENTION_DAYS`.
+//!
+//! NOTE: The project delete is not soft, and cascades to its tasks (trashed
+//!       or not).
+
+use crate::config::config;
+use crate::model::base::DbBmc;
+use crate::model::task::TaskBmc;
+use crate::model::{ModelManager, Result};
+use lib_base::time::now_utc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Purges the trashed entities older than the `TRASH_RETENTION_DAYS`.
+pub async fn purge_expired(mm: &ModelManager) -> Result<u64> {
+	let retention =
+		Duration::from_secs(config().TRASH_RETENTION_DAYS as u64 * 24 * 3600);
+
+	purge_trashed_before::<TaskBmc>(mm, retention).await
+}
+
+async fn purge_trashed_before<MC: DbBmc>(
+	mm: &ModelManager,
+	retention: Duration,
+) -> Result<u64> {
+	// Note: The `TABLE` is a const, so not an injection.
+	let sql = format!("DELETE FROM {} WHERE deleted_at < $1", MC::TABLE);
+	let res = sqlx::query(&sql)
+		.bind(now_utc() - retention)
+		.execute(mm.db()?)
+		.await?;
+
+	Ok(res.rows_affected())
+}
+
+/// Spawns the purge of the expired trashed entities (see `purge_expired`).
+pub fn spawn_trash_purger(mm: ModelManager) -> JoinHandle<()> {
+	tokio::spawn(async move {
+		let mut purge = tokio::time::interval(PURGE_INTERVAL);
+		loop {
+			purge.tick().await;
+			match purge_expired(&mm).await {
+				Ok(count) => debug!("trash - {count} expired entities purged"),
+				Err(ex) => warn!("trash - purge failed - {ex:?}"),
+			}
+		}
+	})
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::ctx::Ctx;
+	use crate::model::project::ProjectBmc;
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_purge_trashed_before_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_purge_trashed_before_ok project",
+		)
+		.await?;
+		let fx_tasks = _dev_utils::seed_tasks(
+			&ctx,
+			&mm,
+			fx_project_id,
+			&[
+				"test_purge_trashed_before_ok 01",
+				"test_purge_trashed_before_ok 02",
+			],
+		)
+		.await?;
+		TaskBmc::delete(&ctx, &mm, fx_tasks[0].id).await?;
+		TaskBmc::delete(&ctx, &mm, fx_tasks[1].id).await?;
+		// Trashed 2 days ago.
+		sqlx::query(
+			"UPDATE task SET deleted_at = now() - interval '2 days' WHERE id = $1",
+		)
+		.bind(fx_tasks[0].id)
+		.execute(mm.db()?)
+		.await?;
+
+		// -- Exec
+		let fx_retention = Duration::from_secs(24 * 3600);
+		purge_trashed_before::<TaskBmc>(&mm, fx_retention).await?;
+
+		// -- Check
+		let (count,): (i64,) =
+			sqlx::query_as("SELECT count(*) FROM task WHERE id = ANY($1)")
+				.bind([fx_tasks[0].id, fx_tasks[1].id])
+				.fetch_one(mm.db()?)
+				.await?;
+		assert_eq!(count, 1, "only the expired one purged");
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -1,14 +1,28 @@
+use crate::config::config;
+use crate::ctx::locale::supported_locale;
 use crate::model::base::{self, DbBmc};
+#[cfg(feature = "ts")]
+use crate::model::modql_utils::ts;
+use crate::model::secret::Secret;
 use crate::model::ModelManager;
-use crate::model::Result;
+use crate::model::{Error, Result};
 use crate::pwd::ContentToHash;
 use crate::{ctx::Ctx, pwd};
+use lib_base::time::{parse_tz, Rfc3339};
 use modql::field::{Field, Fields, HasFields};
+use modql::filter::{
+	FilterNodes, ListOptions, OpValsBool, OpValsInt64, OpValsString,
+};
 use sea_query::{Expr, Iden, PostgresQueryBuilder, Query};
 use sea_query_binder::SqlxBinder;
 use serde::{Deserialize, Serialize};
-use sqlx::postgres::PgRow;
-use sqlx::FromRow;
+use serde_with::serde_as;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgRow, PgTypeInfo, PgValueRef};
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{Decode, FromRow, Postgres, Type};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
 use uuid::Uuid;
 
 use super::base::add_timestamps_for_update;
@@ -19,6 +33,10 @@ use super::base::add_timestamps_for_update;
 pub struct User {
 	pub id: i64,
 	pub username: String,
+	/// The IANA timezone of the user times display (default `UTC`).
+	pub timezone: String,
+	/// The preferred locale, none to follow the browser one.
+	pub locale: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -32,24 +50,131 @@ struct UserForInsert {
 	pub username: String,
 }
 
+/// The user role (stored as its `as_str` name in the `role` column).
+///
+/// NOTE: In the `Ctx` extensions of the authenticated requests (see web-server
+///       `mw_auth`), e.g., for the admin rpc methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub enum UserRole {
+	User,
+	Admin,
+}
+
+impl UserRole {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::User => "user",
+			Self::Admin => "admin",
+		}
+	}
+}
+
+impl core::str::FromStr for UserRole {
+	type Err = ();
+
+	fn from_str(val: &str) -> core::result::Result<Self, Self::Err> {
+		match val {
+			"user" => Ok(Self::User),
+			"admin" => Ok(Self::Admin),
+			_ => Err(()),
+		}
+	}
+}
+
+// Note: Read as its `role` column name (see `Secret` for the sqlx impls).
+impl Type<Postgres> for UserRole {
+	fn type_info() -> PgTypeInfo {
+		<&str as Type<Postgres>>::type_info()
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		<&str as Type<Postgres>>::compatible(ty)
+	}
+}
+
+impl<'r> Decode<'r, Postgres> for UserRole {
+	fn decode(value: PgValueRef<'r>) -> core::result::Result<Self, BoxDynError> {
+		let role = <&str as Decode<Postgres>>::decode(value)?;
+		role.parse()
+			.map_err(|_| format!("unknown user role '{role}'").into())
+	}
+}
+
+impl From<UserRole> for sea_query::Value {
+	fn from(role: UserRole) -> Self {
+		role.as_str().into()
+	}
+}
+
 #[derive(Clone, FromRow, Fields, Debug)]
 pub struct UserForLogin {
 	pub id: i64,
 	pub username: String,
 
-	// -- pwd and token info
-	pub pwd: Option<String>,
-	pub pwd_salt: Uuid,
-	pub token_salt: Uuid,
+	// -- pwd and token info (never serialized, see `Secret`)
+	pub pwd: Option<Secret<String>>,
+	pub pwd_salt: Secret<Uuid>,
+	pub token_salt: Secret<Uuid>,
+
+	/// Deactivated users cannot login.
+	pub active: bool,
+	/// The user must change the pwd (see `UserBmc::change_pwd`).
+	pub must_change_pwd: bool,
 }
 
 #[derive(Debug, Clone, FromRow, Fields)]
 pub struct UserForAuth {
 	pub id: i64,
 	pub username: String,
+	pub role: UserRole,
+
+	// -- token info (never serialized, see `Secret`)
+	pub token_salt: Secret<Uuid>,
+
+	/// Deactivated users are not authenticated.
+	pub active: bool,
+	/// The user sessions are restricted to the pwd change.
+	pub must_change_pwd: bool,
+	/// The preferred locale (see `ctx::locale::resolve_locale`).
+	pub locale: Option<String>,
+}
+
+/// The user of the admin rpc methods (see web-server `admin_rpc`).
+#[serde_as]
+#[derive(Clone, Fields, FromRow, Debug, Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct UserForAdmin {
+	pub id: i64,
+	pub username: String,
+	pub role: UserRole,
+	pub active: bool,
+	pub must_change_pwd: bool,
+	pub timezone: String,
+	pub locale: Option<String>,
+
+	// -- Timestamps
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub ctime: OffsetDateTime,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
+}
 
-	// -- token info
-	pub token_salt: Uuid,
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct UserFilter {
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsInt64>", optional))]
+	id: Option<OpValsInt64>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsString>", optional))]
+	username: Option<OpValsString>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsString>", optional))]
+	role: Option<OpValsString>,
+	#[cfg_attr(feature = "ts", ts(as = "Option<ts::OpValsBool>", optional))]
+	active: Option<OpValsBool>,
 }
 
 // Marker trait
@@ -58,12 +183,19 @@ pub trait UserBy: HasFields + for<'r> FromRow<'r, PgRow> + Unpin + Send {}
 impl UserBy for User {}
 impl UserBy for UserForLogin {}
 impl UserBy for UserForAuth {}
+impl UserBy for UserForAdmin {}
 
 #[derive(Iden)]
 enum UserIden {
 	Id,
 	Username,
 	Pwd,
+	Role,
+	Active,
+	MustChangePwd,
+	Timezone,
+	Locale,
+	TokenSalt,
 }
 
 // endregion: --- User Types
@@ -72,6 +204,8 @@ pub struct UserBmc;
 
 impl DbBmc for UserBmc {
 	const TABLE: &'static str = "user";
+	const UNIQUE_FIELDS: &'static [(&'static str, &'static str)] =
+		&[("user_username_key", "username")];
 }
 
 impl UserBmc {
@@ -82,6 +216,18 @@ impl UserBmc {
 		base::get::<Self, _>(ctx, mm, id).await
 	}
 
+	pub async fn list<E>(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<UserFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<E>>
+	where
+		E: UserBy,
+	{
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
 	pub async fn first_by_username<E>(
 		_ctx: &Ctx,
 		mm: &ModelManager,
@@ -90,7 +236,7 @@ impl UserBmc {
 	where
 		E: UserBy,
 	{
-		let db = mm.db();
+		let db = mm.db()?;
 
 		// -- Build query
 		let mut query = Query::select();
@@ -108,21 +254,186 @@ impl UserBmc {
 		Ok(entity)
 	}
 
+	/// The `UserForAuth` of the per-request authentication, cached for a short
+	/// time (see `model::auth_cache`).
+	pub async fn auth_by_username(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		username: &str,
+	) -> Result<Option<UserForAuth>> {
+		if let Some(user) = mm.user_auth_cache().get(username) {
+			return Ok(Some(user));
+		}
+
+		let generation = mm.user_auth_cache().generation();
+		let user: Option<UserForAuth> =
+			Self::first_by_username(ctx, mm, username).await?;
+		if let Some(user) = &user {
+			mm.user_auth_cache().insert(user.clone(), generation);
+		}
+
+		Ok(user)
+	}
+
+	/// Creates the user, with its hashed pwd, and returns its id.
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user_c: UserForCreate,
+	) -> Result<i64> {
+		let UserForCreate {
+			username,
+			pwd_clear,
+		} = user_c;
+
+		if Self::first_by_username::<User>(ctx, mm, &username)
+			.await?
+			.is_some()
+		{
+			return Err(Error::UserAlreadyExists { username });
+		}
+
+		let id =
+			base::create::<Self, _>(ctx, mm, UserForInsert { username }).await?;
+		Self::update_pwd(ctx, mm, id, &pwd_clear).await?;
+
+		Ok(id)
+	}
+
 	pub async fn update_pwd(
 		ctx: &Ctx,
 		mm: &ModelManager,
 		id: i64,
 		pwd_clear: &str,
 	) -> Result<()> {
-		let db = mm.db();
+		let pwd = Self::pwd_field(ctx, mm, id, pwd_clear).await?;
+		Self::update_fields(ctx, mm, id, vec![pwd]).await
+	}
+
+	/// Updates the pwd changed by the user, which clears the `must_change_pwd`.
+	///
+	/// Note: The current pwd is validated by the caller (as for the login).
+	pub async fn change_pwd(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		pwd_clear: &str,
+	) -> Result<()> {
+		let pwd = Self::pwd_field(ctx, mm, id, pwd_clear).await?;
+		let must_change_pwd = Field::new(UserIden::MustChangePwd, false.into());
+		Self::update_fields(ctx, mm, id, vec![pwd, must_change_pwd]).await
+	}
+
+	/// Requires (or not) the user to change the pwd, the user sessions being
+	/// restricted to the pwd change until then.
+	pub async fn update_must_change_pwd(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		must_change_pwd: bool,
+	) -> Result<()> {
+		let must_change_pwd =
+			Field::new(UserIden::MustChangePwd, must_change_pwd.into());
+		Self::update_fields(ctx, mm, id, vec![must_change_pwd]).await
+	}
+
+	pub async fn update_role(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		role: UserRole,
+	) -> Result<()> {
+		let role = Field::new(UserIden::Role, role.as_str().into());
+		Self::update_fields(ctx, mm, id, vec![role]).await
+	}
+
+	/// Deactivates (or reactivates) the user, which cannot login or be
+	/// authenticated (with an existing token) when inactive.
+	pub async fn update_active(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		active: bool,
+	) -> Result<()> {
+		let active = Field::new(UserIden::Active, active.into());
+		Self::update_fields(ctx, mm, id, vec![active]).await
+	}
+
+	/// Logs out all the user sessions, by rotating its token salt (so its
+	/// issued tokens fail to validate).
+	pub async fn rotate_token_salt(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<()> {
+		let token_salt = Field::new(UserIden::TokenSalt, Uuid::new_v4().into());
+		Self::update_fields(ctx, mm, id, vec![token_salt]).await
+	}
+
+	/// Updates the user timezone (an IANA name, e.g., `Europe/Paris`).
+	pub async fn update_timezone(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		timezone: &str,
+	) -> Result<()> {
+		parse_tz(timezone).map_err(|_| Error::TimezoneUnknown {
+			timezone: timezone.to_string(),
+		})?;
+
+		let timezone = Field::new(UserIden::Timezone, timezone.into());
+		Self::update_fields(ctx, mm, id, vec![timezone]).await
+	}
+
+	/// Updates the user locale (one of the `LOCALES`, e.g., `fr`), none to
+	/// follow the browser one.
+	pub async fn update_locale(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		locale: Option<&str>,
+	) -> Result<()> {
+		// Note: Stored as the supported locale (e.g., `fr-FR` as `fr`).
+		let locale = locale
+			.map(|locale| {
+				supported_locale(locale, &config().LOCALES).ok_or_else(|| {
+					Error::LocaleUnsupported {
+						locale: locale.to_string(),
+					}
+				})
+			})
+			.transpose()?;
+
+		let locale = Field::new(UserIden::Locale, locale.into());
+		Self::update_fields(ctx, mm, id, vec![locale]).await
+	}
+
+	/// The hashed `pwd` field of the `pwd_clear` (with the user pwd salt).
+	async fn pwd_field(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		pwd_clear: &str,
+	) -> Result<Field> {
 		let user: UserForLogin = Self::get(ctx, mm, id).await?;
 		let pwd = pwd::hash_pwd(&ContentToHash {
 			content: pwd_clear.to_string(),
-			salt: user.pwd_salt,
+			salt: *user.pwd_salt.expose(),
 		})?;
 
+		Ok(Field::new(UserIden::Pwd, pwd.into()))
+	}
+
+	async fn update_fields(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		fields: Vec<Field>,
+	) -> Result<()> {
+		let db = mm.db()?;
+
 		// -- Prep the data
-		let mut fields = Fields::new(vec![Field::new(UserIden::Pwd, pwd.into())]);
+		let mut fields = Fields::new(fields);
 		add_timestamps_for_update(&mut fields, ctx.user_id());
 
 		// -- Build query
@@ -135,11 +446,23 @@ impl UserBmc {
 
 		// -- Exec query
 		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-		let _count = sqlx::query_with(&sql, values)
+		let count = sqlx::query_with(&sql, values)
 			.execute(db)
 			.await?
 			.rows_affected();
-		Ok(())
+
+		// Note: After the update is committed (not in a tx), and for any field
+		//       (e.g., timezone, not in the `UserForAuth`), as rare.
+		mm.user_auth_cache().invalidate(id);
+
+		if count == 0 {
+			Err(Error::EntityNotFound {
+				entity: Self::TABLE,
+				id,
+			})
+		} else {
+			Ok(())
+		}
 	}
 }
 
@@ -170,6 +493,246 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_role_active_ok() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_username = "test_create_role_active_ok-user-01";
+
+		// -- Exec
+		let id = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await?;
+		UserBmc::update_role(&ctx, &mm, id, UserRole::Admin).await?;
+		UserBmc::update_active(&ctx, &mm, id, false).await?;
+
+		// -- Check
+		let user: UserForLogin = UserBmc::get(&ctx, &mm, id).await?;
+		assert_eq!(user.username, fx_username);
+		assert!(user.pwd.is_some());
+		assert!(!user.active);
+		let res = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await;
+		assert!(
+			matches!(res, Err(Error::UserAlreadyExists { .. })),
+			"{res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_insert_err_unique_violation() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_user_i = UserForInsert {
+			username: "demo1".to_string(),
+		};
+
+		// -- Exec
+		// Note: Without the `UserBmc::create` check, so the db rejects it.
+		let res = base::create::<UserBmc, _>(&ctx, &mm, fx_user_i).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				&res,
+				Err(Error::UniqueViolation { entity, field })
+					if entity == "user" && field.as_deref() == Some("username")
+			),
+			"{res:?}"
+		);
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_timezone_ok_and_err() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let id = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: "test_update_timezone_ok-user-01".to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await?;
+
+		// -- Exec
+		let user: User = UserBmc::get(&ctx, &mm, id).await?;
+		UserBmc::update_timezone(&ctx, &mm, id, "Europe/Paris").await?;
+		let res = UserBmc::update_timezone(&ctx, &mm, id, "Mars/Olympus").await;
+
+		// -- Check
+		assert_eq!(user.timezone, "UTC");
+		let user: User = UserBmc::get(&ctx, &mm, id).await?;
+		assert_eq!(user.timezone, "Europe/Paris");
+		assert!(matches!(res, Err(Error::TimezoneUnknown { .. })), "{res:?}");
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_locale_ok_and_err() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let id = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: "test_update_locale_ok-user-01".to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await?;
+
+		// -- Exec
+		let user: User = UserBmc::get(&ctx, &mm, id).await?;
+		UserBmc::update_locale(&ctx, &mm, id, Some("fr-FR")).await?;
+		let user_fr: UserForAuth = UserBmc::get(&ctx, &mm, id).await?;
+		let res = UserBmc::update_locale(&ctx, &mm, id, Some("xx")).await;
+		UserBmc::update_locale(&ctx, &mm, id, None).await?;
+
+		// -- Check
+		assert_eq!(user.locale, None);
+		assert_eq!(user_fr.locale.as_deref(), Some("fr"));
+		assert!(
+			matches!(res, Err(Error::LocaleUnsupported { .. })),
+			"{res:?}"
+		);
+		let user: User = UserBmc::get(&ctx, &mm, id).await?;
+		assert_eq!(user.locale, None);
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_role_and_rotate_token_salt_ok() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_username = "test_list_role_and_rotate_token_salt_ok-user-01";
+		let id = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await?;
+		let user: UserForAuth = UserBmc::get(&ctx, &mm, id).await?;
+
+		// -- Exec
+		UserBmc::update_role(&ctx, &mm, id, UserRole::Admin).await?;
+		UserBmc::rotate_token_salt(&ctx, &mm, id).await?;
+		let filter: UserFilter = serde_json::from_value(serde_json::json!({
+			"username": fx_username,
+			"role": "admin",
+		}))?;
+		let users: Vec<UserForAdmin> =
+			UserBmc::list(&ctx, &mm, Some(vec![filter]), None).await?;
+
+		// -- Check
+		assert_eq!(user.role, UserRole::User);
+		assert_eq!(users.len(), 1);
+		assert_eq!(users[0].role, UserRole::Admin);
+		let user_after: UserForAuth = UserBmc::get(&ctx, &mm, id).await?;
+		assert_ne!(user_after.token_salt, user.token_salt);
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_auth_by_username_invalidated_ok() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_username = "test_auth_by_username_invalidated_ok-user-01";
+		let id = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await?;
+
+		// -- Exec
+		let user = UserBmc::auth_by_username(&ctx, &mm, fx_username)
+			.await?
+			.context("Should have the user")?;
+		let cached = mm.user_auth_cache().get(fx_username);
+		UserBmc::update_active(&ctx, &mm, id, false).await?;
+		let user_after = UserBmc::auth_by_username(&ctx, &mm, fx_username)
+			.await?
+			.context("Should have the user")?;
+
+		// -- Check
+		assert!(user.active);
+		assert_eq!(cached.map(|user| user.id), Some(id));
+		assert!(!user_after.active, "should be invalidated by the update");
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_change_pwd_clears_must_change_pwd_ok() -> Result<()> {
+		// -- Setup && Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_username = "test_change_pwd_clears_must_change_pwd_ok-user-01";
+		let id = UserBmc::create(
+			&ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "welcome".to_string(),
+			},
+		)
+		.await?;
+
+		// -- Exec
+		UserBmc::update_must_change_pwd(&ctx, &mm, id, true).await?;
+		let user: UserForLogin = UserBmc::get(&ctx, &mm, id).await?;
+		UserBmc::change_pwd(&ctx, &mm, id, "welcome-changed").await?;
+		let user_after: UserForLogin = UserBmc::get(&ctx, &mm, id).await?;
+
+		// -- Check
+		assert!(user.must_change_pwd);
+		assert!(!user_after.must_change_pwd);
+		assert_ne!(user.pwd, user_after.pwd);
+
+		Ok(())
+	}
 }
 
 // endregion: --- TestBmc
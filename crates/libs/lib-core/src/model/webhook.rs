@@ -0,0 +1,501 @@
+//! The outgoing webhooks registrations, and their deliveries.
+//!
+//! The `webhook_delivery` table is both the delivery queue (claimed by the
+//! sender with a lease, and retried with backoff) and the delivery log.
+//!
+//! NOTE: The registrations are only visible to their owner (root sees all).
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::event::DomainEvent;
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_base::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+use std::time::Duration;
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Below, a secret is too easy to brute force from a signature.
+const SECRET_MIN_LEN: usize = 16;
+const DELIVERY_LIST_LIMIT_MAX: i64 = 500;
+
+// region:    --- Webhook Types
+
+/// Note: The `secret` is write only, so never returned.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct Webhook {
+	pub id: i64,
+	pub owner_id: i64,
+	pub url: String,
+	/// Comma separated event names (e.g., `task.created,task.deleted`), or `*`.
+	pub events: String,
+	pub project_id: Option<i64>,
+	// -- Timestamps
+	//    (creator and last modified user_id/time)
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct WebhookForCreate {
+	pub url: String,
+	/// The HMAC key of the delivery signatures (min 16 chars).
+	pub secret: String,
+	/// The event names (see `DomainEvent::NAMES`), or `["*"]` for all.
+	pub events: Vec<String>,
+	/// When set, only the events of this project.
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub project_id: Option<i64>,
+}
+
+/// The validated `WebhookForCreate`, with its owner (see `ProjectForCreateInner`).
+#[derive(Fields)]
+struct WebhookForCreateInner {
+	owner_id: i64,
+	url: String,
+	secret: String,
+	events: String,
+	project_id: Option<i64>,
+}
+
+#[derive(FilterNodes, Default)]
+struct WebhookFilter {
+	owner_id: Option<OpValsInt64>,
+}
+
+// endregion: --- Webhook Types
+
+// region:    --- WebhookBmc
+
+pub struct WebhookBmc;
+
+impl DbBmc for WebhookBmc {
+	const TABLE: &'static str = "webhook";
+	// Note: A user has a few webhooks.
+	const DEFAULT_LIMIT: i64 = 100;
+	const MAX_LIMIT: i64 = 100;
+}
+
+impl WebhookBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		webhook_c: WebhookForCreate,
+	) -> Result<i64> {
+		let webhook_c = validate_for_create(ctx.user_id(), webhook_c)?;
+		base::create::<Self, _>(ctx, mm, webhook_c).await
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Webhook> {
+		let webhook: Webhook = base::get::<Self, _>(ctx, mm, id).await?;
+		if !ctx.is_root() && webhook.owner_id != ctx.user_id() {
+			return Err(Error::EntityNotFound {
+				entity: Self::TABLE,
+				id,
+			});
+		}
+
+		Ok(webhook)
+	}
+
+	/// The webhooks of the ctx user.
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Webhook>> {
+		let filter = WebhookFilter {
+			owner_id: Some(ctx.user_id().into()),
+		};
+		base::list::<Self, _, _>(ctx, mm, Some(filter), list_options).await
+	}
+
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		// Note: Checks the ownership (and the deliveries cascade).
+		Self::get(ctx, mm, id).await?;
+		base::delete::<Self>(ctx, mm, id).await
+	}
+}
+
+fn validate_for_create(
+	owner_id: i64,
+	webhook_c: WebhookForCreate,
+) -> Result<WebhookForCreateInner> {
+	let invalid = |field, reason: &str| Error::WebhookInvalid {
+		field,
+		reason: reason.to_string(),
+	};
+
+	match url::Url::parse(&webhook_c.url) {
+		Ok(url) if matches!(url.scheme(), "http" | "https") => (),
+		_ => return Err(invalid("url", "not an http(s) url")),
+	}
+	if webhook_c.secret.len() < SECRET_MIN_LEN {
+		return Err(invalid("secret", "shorter than 16 chars"));
+	}
+	if webhook_c.events.is_empty() {
+		return Err(invalid("events", "empty (use [\"*\"] for all)"));
+	}
+	let all_events = webhook_c.events.iter().any(|event| event == "*");
+	if let Some(event) = webhook_c
+		.events
+		.iter()
+		.find(|event| !all_events && !DomainEvent::NAMES.contains(&event.as_str()))
+	{
+		return Err(invalid("events", &format!("unknown event '{event}'")));
+	}
+
+	Ok(WebhookForCreateInner {
+		owner_id,
+		url: webhook_c.url,
+		secret: webhook_c.secret,
+		events: if all_events {
+			"*".to_string()
+		} else {
+			webhook_c.events.join(",")
+		},
+		project_id: webhook_c.project_id,
+	})
+}
+
+// endregion: --- WebhookBmc
+
+// region:    --- Delivery Types
+
+#[serde_as]
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct WebhookDelivery {
+	pub id: i64,
+	pub webhook_id: i64,
+	pub event: String,
+	/// The delivered json body.
+	pub payload: String,
+	/// `pending`, `succeeded`, or `failed` (after the max attempts).
+	pub status: String,
+	pub attempts: i32,
+	pub last_status_code: Option<i32>,
+	pub last_error: Option<String>,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub next_attempt_time: OffsetDateTime,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub ctime: OffsetDateTime,
+	#[serde_as(as = "Rfc3339")]
+	#[cfg_attr(feature = "ts", ts(type = "string"))]
+	pub mtime: OffsetDateTime,
+}
+
+/// A claimed delivery, with what the sender needs.
+/// (no `Debug`, as it holds the secret)
+#[derive(FromRow)]
+pub struct DeliveryJob {
+	pub id: i64,
+	pub webhook_id: i64,
+	pub event: String,
+	pub payload: String,
+	/// The attempts before this one.
+	pub attempts: i32,
+	pub url: String,
+	pub secret: String,
+}
+
+pub enum DeliveryOutcome {
+	Succeeded {
+		status_code: u16,
+	},
+	Retry {
+		status_code: Option<u16>,
+		error: String,
+		retry_in: Duration,
+	},
+	Failed {
+		status_code: Option<u16>,
+		error: String,
+	},
+}
+
+// endregion: --- Delivery Types
+
+// region:    --- WebhookDeliveryBmc
+
+pub struct WebhookDeliveryBmc;
+
+impl WebhookDeliveryBmc {
+	/// Queues a delivery of the event for each matching webhook,
+	/// and returns the number of queued deliveries.
+	pub async fn enqueue(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		event: &DomainEvent,
+	) -> Result<u64> {
+		let payload = serde_json::to_string(event)
+			.map_err(|ex| Error::WebhookPayload(ex.to_string()))?;
+
+		let res = sqlx::query(
+			"INSERT INTO webhook_delivery
+				(webhook_id, event, payload, status, next_attempt_time, ctime, mtime)
+			 SELECT id, $1, $2, 'pending', now(), now(), now() FROM webhook
+			 WHERE (events = '*' OR $1 = ANY(string_to_array(events, ',')))
+			   AND (project_id IS NULL OR project_id = $3)",
+		)
+		.bind(event.name())
+		.bind(payload)
+		.bind(event.change().project_id)
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(res.rows_affected())
+	}
+
+	/// Claims up to `limit` due deliveries for the `lease`
+	/// (after which they are due again, e.g., if the sender died).
+	pub async fn claim_due(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		limit: i64,
+		lease: Duration,
+	) -> Result<Vec<DeliveryJob>> {
+		let jobs = sqlx::query_as(
+			"WITH claimed AS (
+				UPDATE webhook_delivery
+				SET next_attempt_time = now() + make_interval(secs => $2)
+				WHERE id IN (
+					SELECT id FROM webhook_delivery
+					WHERE status = 'pending' AND next_attempt_time <= now()
+					ORDER BY id
+					LIMIT $1
+					FOR UPDATE SKIP LOCKED
+				)
+				RETURNING id, webhook_id, event, payload, attempts
+			 )
+			 SELECT c.id, c.webhook_id, c.event, c.payload, c.attempts, w.url, w.secret
+			 FROM claimed c JOIN webhook w ON w.id = c.webhook_id
+			 ORDER BY c.id",
+		)
+		.bind(limit)
+		.bind(lease.as_secs_f64())
+		.fetch_all(mm.db()?)
+		.await?;
+
+		Ok(jobs)
+	}
+
+	/// Records the outcome of a claimed delivery attempt.
+	pub async fn record_attempt(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		outcome: DeliveryOutcome,
+	) -> Result<()> {
+		let (status, status_code, error, retry_in) = match outcome {
+			DeliveryOutcome::Succeeded { status_code } => {
+				("succeeded", Some(status_code), None, Duration::ZERO)
+			}
+			DeliveryOutcome::Retry {
+				status_code,
+				error,
+				retry_in,
+			} => ("pending", status_code, Some(error), retry_in),
+			DeliveryOutcome::Failed { status_code, error } => {
+				("failed", status_code, Some(error), Duration::ZERO)
+			}
+		};
+
+		sqlx::query(
+			"UPDATE webhook_delivery
+			 SET status = $2, attempts = attempts + 1, last_status_code = $3,
+			     last_error = $4, next_attempt_time = now() + make_interval(secs => $5),
+			     mtime = now()
+			 WHERE id = $1",
+		)
+		.bind(id)
+		.bind(status)
+		.bind(status_code.map(i32::from))
+		.bind(error)
+		.bind(retry_in.as_secs_f64())
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(())
+	}
+
+	/// The latest deliveries of a webhook of the ctx user (newest first).
+	pub async fn list_for_webhook(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		webhook_id: i64,
+		limit: i64,
+	) -> Result<Vec<WebhookDelivery>> {
+		if limit > DELIVERY_LIST_LIMIT_MAX {
+			return Err(Error::ListLimitOverMax {
+				max: DELIVERY_LIST_LIMIT_MAX,
+				actual: limit,
+			});
+		}
+		// Note: Checks the ownership.
+		WebhookBmc::get(ctx, mm, webhook_id).await?;
+
+		let deliveries = sqlx::query_as(
+			"SELECT id, webhook_id, event, payload, status, attempts, last_status_code,
+			        last_error, next_attempt_time, ctime, mtime
+			 FROM webhook_delivery
+			 WHERE webhook_id = $1
+			 ORDER BY id DESC
+			 LIMIT $2",
+		)
+		.bind(webhook_id)
+		.bind(limit)
+		.fetch_all(mm.db()?)
+		.await?;
+
+		Ok(deliveries)
+	}
+
+	/// Deletes the finished (succeeded or failed) deliveries older than `age`,
+	/// and returns the number of deleted deliveries.
+	pub async fn prune_finished(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		age: Duration,
+	) -> Result<u64> {
+		let res = sqlx::query(
+			"DELETE FROM webhook_delivery
+			 WHERE status <> 'pending' AND mtime < now() - make_interval(secs => $1)",
+		)
+		.bind(age.as_secs_f64())
+		.execute(mm.db()?)
+		.await?;
+
+		Ok(res.rows_affected())
+	}
+}
+
+// endregion: --- WebhookDeliveryBmc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::event::EntityChange;
+	use anyhow::{Context, Result};
+	use serial_test::serial;
+
+	fn fx_webhook_c(events: &[&str]) -> WebhookForCreate {
+		WebhookForCreate {
+			url: "https://example.com/hooks".to_string(),
+			secret: "test_webhook_secret_01".to_string(),
+			events: events.iter().map(|event| event.to_string()).collect(),
+			project_id: None,
+		}
+	}
+
+	#[test]
+	fn test_validate_for_create_err() {
+		// -- Setup & Fixtures
+		let fx_bad_url = WebhookForCreate {
+			url: "ftp://example.com".to_string(),
+			..fx_webhook_c(&["*"])
+		};
+		let fx_short_secret = WebhookForCreate {
+			secret: "short".to_string(),
+			..fx_webhook_c(&["*"])
+		};
+
+		// -- Exec & Check
+		for (fx_webhook_c, fx_field) in [
+			(fx_bad_url, "url"),
+			(fx_short_secret, "secret"),
+			(fx_webhook_c(&[]), "events"),
+			(fx_webhook_c(&["task.created", "task.exploded"]), "events"),
+		] {
+			let res = validate_for_create(1000, fx_webhook_c);
+			assert!(
+				matches!(res, Err(Error::WebhookInvalid { field, .. }) if field == fx_field),
+				"should be invalid on {fx_field}"
+			);
+		}
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_enqueue_claim_record_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_matching_id =
+			WebhookBmc::create(&ctx, &mm, fx_webhook_c(&["task.created"])).await?;
+		let fx_other_id =
+			WebhookBmc::create(&ctx, &mm, fx_webhook_c(&["project.deleted"]))
+				.await?;
+		let fx_event = DomainEvent::TaskCreated(EntityChange {
+			id: 1001,
+			project_id: 1000,
+			user_id: 0,
+		});
+
+		// -- Exec
+		let count = WebhookDeliveryBmc::enqueue(&ctx, &mm, &fx_event).await?;
+		let jobs =
+			WebhookDeliveryBmc::claim_due(&ctx, &mm, 100, Duration::from_secs(60))
+				.await?;
+		let job = jobs
+			.iter()
+			.find(|job| job.webhook_id == fx_matching_id)
+			.context("no job for the matching webhook")?;
+		WebhookDeliveryBmc::record_attempt(
+			&ctx,
+			&mm,
+			job.id,
+			DeliveryOutcome::Retry {
+				status_code: Some(503),
+				error: "unavailable".to_string(),
+				retry_in: Duration::from_secs(60),
+			},
+		)
+		.await?;
+
+		// -- Check
+		assert_eq!(count, 1);
+		assert!(jobs.iter().all(|job| job.webhook_id != fx_other_id));
+		assert_eq!(job.event, "task.created");
+		assert!(job.payload.contains("\"event\":\"task.created\""));
+		// Note: Leased, so not claimed again.
+		let jobs =
+			WebhookDeliveryBmc::claim_due(&ctx, &mm, 100, Duration::from_secs(60))
+				.await?;
+		assert!(jobs.iter().all(|job| job.webhook_id != fx_matching_id));
+		let deliveries =
+			WebhookDeliveryBmc::list_for_webhook(&ctx, &mm, fx_matching_id, 10)
+				.await?;
+		assert_eq!(deliveries.len(), 1);
+		assert_eq!(deliveries[0].status, "pending");
+		assert_eq!(deliveries[0].attempts, 1);
+		assert_eq!(deliveries[0].last_status_code, Some(503));
+
+		// -- Clean
+		WebhookBmc::delete(&ctx, &mm, fx_matching_id).await?;
+		WebhookBmc::delete(&ctx, &mm, fx_other_id).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -7,10 +7,22 @@ pub use scheme::SchemeStatus;
 
 use lazy_regex::regex_captures;
 use std::str::FromStr;
+use std::time::Instant;
 use uuid::Uuid;
 
 // endregion: --- Modules
 
+// region:    --- Metrics
+
+/// The pwd hashing latency histograms, by `scheme` (e.g., to compare the
+/// schemes, and tune their parameters).
+///
+/// NOTE: No-ops without an installed recorder (see web-server `web::metrics`).
+pub const PWD_HASH_DURATION_SECONDS: &str = "pwd_hash_duration_seconds";
+pub const PWD_VALIDATE_DURATION_SECONDS: &str = "pwd_validate_duration_seconds";
+
+// endregion: --- Metrics
+
 // region:    --- Types
 
 pub struct ContentToHash {
@@ -34,9 +46,9 @@ pub fn validate_pwd(to_hash: &ContentToHash, pwd_ref: &str) -> Result<SchemeStat
 		raw: raw_pwd_ref,
 	} = pwd_ref.parse()?;
 
-	validate_for_scheme(&scheme_name, to_hash, &raw_pwd_ref)?;
+	let outdated = validate_for_scheme(&scheme_name, to_hash, &raw_pwd_ref)?;
 
-	if scheme_name == DEFAULT_SCHEME {
+	if scheme_name == DEFAULT_SCHEME && !outdated {
 		Ok(SchemeStatus::Ok)
 	} else {
 		Ok(SchemeStatus::Outdated)
@@ -48,18 +60,37 @@ pub fn validate_pwd(to_hash: &ContentToHash, pwd_ref: &str) -> Result<SchemeStat
 fn hash_for_scheme(scheme_name: &str, to_hash: &ContentToHash) -> Result<String> {
 	let scheme = get_scheme(scheme_name)?;
 
-	let pwd_raw = scheme.hash(to_hash)?;
+	let start = Instant::now();
+	let pwd_raw = scheme.hash(to_hash);
+	metrics::histogram!(
+		PWD_HASH_DURATION_SECONDS,
+		"scheme" => scheme_name.to_string()
+	)
+	.record(start.elapsed().as_secs_f64());
+	let pwd_raw = pwd_raw?;
 
 	Ok(format!("#{scheme_name}#{}", pwd_raw))
 }
 
+/// Returns whether the valid pwd has outdated parameters (see `Scheme::is_outdated`).
 fn validate_for_scheme(
 	scheme_name: &str,
 	to_hash: &ContentToHash,
 	raw_pwd_ref: &str,
-) -> Result<()> {
-	get_scheme(scheme_name)?.validate(to_hash, raw_pwd_ref)?;
-	Ok(())
+) -> Result<bool> {
+	let scheme = get_scheme(scheme_name)?;
+
+	// Note: Also the failed validations, which cost the same.
+	let start = Instant::now();
+	let validate_res = scheme.validate(to_hash, raw_pwd_ref);
+	metrics::histogram!(
+		PWD_VALIDATE_DURATION_SECONDS,
+		"scheme" => scheme_name.to_string()
+	)
+	.record(start.elapsed().as_secs_f64());
+	validate_res?;
+
+	Ok(scheme.is_outdated(raw_pwd_ref))
 }
 
 struct PwdParts {
@@ -114,5 +145,35 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[test]
+	fn test_legacy_scheme_outdated_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::new_v4(),
+		};
+		let fx_pwd_ref =
+			"#b1#$2b$04$b520Qoln/REqUR4n4B0X1OHBl/IvHJLSvGhi3zfRjug.WBAoKNRzW";
+
+		// -- Exec
+		let status = validate_pwd(&fx_to_hash, fx_pwd_ref)?;
+		let hash_res = hash_for_scheme("b1", &fx_to_hash);
+
+		// -- Check
+		assert!(
+			matches!(status, SchemeStatus::Outdated),
+			"status should be SchemeStatus::Outdated"
+		);
+		assert!(
+			matches!(
+				hash_res,
+				Err(Error::Scheme(scheme::Error::SchemeValidateOnly("b1")))
+			),
+			"hash should be SchemeValidateOnly"
+		);
+
+		Ok(())
+	}
 }
 // endregion: --- Tests
@@ -9,6 +9,8 @@ pub enum Error {
 	Hash,
 	PwdValidate,
 	SchemeNotFound(String),
+	/// A legacy scheme, which only validates the migrated pwds.
+	SchemeValidateOnly(&'static str),
 }
 
 // region:    --- Error Boilerplate
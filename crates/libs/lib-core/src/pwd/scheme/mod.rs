@@ -1,18 +1,23 @@
 mod error;
 mod scheme_01;
 mod scheme_02;
+mod scheme_b1;
+mod scheme_s1;
 
 use enum_dispatch::enum_dispatch;
 
 pub use self::error::{Error, Result};
 use crate::pwd::ContentToHash;
 
-pub const DEFAULT_SCHEME: &str = "01";
+pub const DEFAULT_SCHEME: &str = "02";
 
 pub fn get_scheme(scheme_name: &str) -> Result<impl Scheme> {
 	match scheme_name {
 		"01" => Ok(SchemeDispatcher::Scheme01(scheme_01::Scheme01)),
 		"02" => Ok(SchemeDispatcher::Scheme02(scheme_02::Scheme02)),
+		// -- Legacy (validate only, see their modules)
+		"b1" => Ok(SchemeDispatcher::SchemeB1(scheme_b1::SchemeB1)),
+		"s1" => Ok(SchemeDispatcher::SchemeS1(scheme_s1::SchemeS1)),
 		_ => Err(Error::SchemeNotFound(scheme_name.to_string())),
 	}
 }
@@ -21,6 +26,8 @@ pub fn get_scheme(scheme_name: &str) -> Result<impl Scheme> {
 enum SchemeDispatcher {
 	Scheme01(scheme_01::Scheme01),
 	Scheme02(scheme_02::Scheme02),
+	SchemeB1(scheme_b1::SchemeB1),
+	SchemeS1(scheme_s1::SchemeS1),
 }
 
 #[enum_dispatch]
@@ -28,6 +35,12 @@ pub trait Scheme {
 	fn hash(&self, to_hash: &ContentToHash) -> Result<String>;
 
 	fn validate(&self, to_hash: &ContentToHash, raw_pwd_ref: &str) -> Result<()>;
+
+	/// The (valid) pwd is hashed with outdated parameters (e.g., other argon2
+	/// costs), so should be re-hashed.
+	fn is_outdated(&self, _raw_pwd_ref: &str) -> bool {
+		false
+	}
 }
 
 /// SchemeStatus is the return value of validate_pwd telling the caller if the
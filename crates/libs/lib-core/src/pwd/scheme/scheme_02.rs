@@ -44,6 +44,35 @@ impl Scheme for Scheme02 {
 			.verify_password(to_hash.content.as_bytes(), &parsed_hash_ref)
 			.map_err(|_| Error::PwdValidate)
 	}
+
+	fn is_outdated(&self, raw_pwd_ref: &str) -> bool {
+		let Ok(parsed_hash_ref) = PasswordHash::new(raw_pwd_ref) else {
+			return false;
+		};
+		let Ok(params_ref) = Params::try_from(&parsed_hash_ref) else {
+			return false;
+		};
+
+		!same_costs(&params_ref, get_argon2().params())
+	}
+}
+
+fn same_costs(params_a: &Params, params_b: &Params) -> bool {
+	params_a.m_cost() == params_b.m_cost()
+		&& params_a.t_cost() == params_b.t_cost()
+		&& params_a.p_cost() == params_b.p_cost()
+}
+
+/// The argon2 costs of the config (validated at the config load).
+fn config_params() -> Params {
+	let config = config();
+	Params::new(
+		config.PWD_ARGON2_MEMORY_KIB,
+		config.PWD_ARGON2_ITERATIONS,
+		config.PWD_ARGON2_PARALLELISM,
+		None,
+	)
+	.unwrap_or_default()
 }
 
 /// Note: The `argon2` crate allows to reuse the `Argon2` object for password hashing.
@@ -57,7 +86,7 @@ fn get_argon2() -> &'static Argon2<'static> {
 			key,
 			Algorithm::Argon2id, // Same as Argon2::default()
 			Version::V0x13,      // Same as Argon2::default()
-			Params::default(),
+			config_params(),
 		)
 		.unwrap() // FIXME: Needs to remove that, and probably return an Result
 	});
@@ -90,5 +119,27 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[test]
+	fn test_scheme_02_is_outdated_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::new_v4(),
+		};
+		// Note: The `.cargo/config.toml` costs are the argon2 defaults.
+		let fx_other_costs = "$argon2id$v=19$m=8192,t=3,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4";
+
+		// -- Exec
+		let scheme = Scheme02;
+		let pwd_ref = scheme.hash(&fx_to_hash)?;
+
+		// -- Check
+		assert!(!scheme.is_outdated(&pwd_ref));
+		assert!(scheme.is_outdated(fx_other_costs));
+		assert!(!scheme.is_outdated("not-a-phc-hash"));
+
+		Ok(())
+	}
 }
 // endregion: --- Tests
@@ -0,0 +1,61 @@
+//! Legacy bcrypt scheme (validate only), for the pwds migrated as-is from older
+//! systems (e.g., `#b1#$2b$12$...`), upgraded to the default scheme on the
+//! first successful login.
+//!
+//! NOTE: The bcrypt hash has its own salt, so the user `pwd_salt` (and the
+//!       `PWD_KEY`) are not used.
+
+use crate::pwd::scheme::{Error, Result, Scheme};
+use crate::pwd::ContentToHash;
+
+pub struct SchemeB1;
+
+impl Scheme for SchemeB1 {
+	fn hash(&self, _to_hash: &ContentToHash) -> Result<String> {
+		Err(Error::SchemeValidateOnly("b1"))
+	}
+
+	fn validate(&self, to_hash: &ContentToHash, raw_pwd_ref: &str) -> Result<()> {
+		match bcrypt::verify(&to_hash.content, raw_pwd_ref) {
+			Ok(true) => Ok(()),
+			Ok(false) => Err(Error::PwdValidate),
+			Err(_) => Err(Error::Hash),
+		}
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use uuid::Uuid;
+
+	#[test]
+	fn test_scheme_b1_validate_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::new_v4(),
+		};
+		let fx_pwd_ref =
+			"$2b$04$b520Qoln/REqUR4n4B0X1OHBl/IvHJLSvGhi3zfRjug.WBAoKNRzW";
+
+		// -- Exec & Check
+		SchemeB1.validate(&fx_to_hash, fx_pwd_ref)?;
+		let res = SchemeB1.validate(
+			&ContentToHash {
+				content: "hello world!".to_string(),
+				..fx_to_hash
+			},
+			fx_pwd_ref,
+		);
+		assert!(
+			matches!(res, Err(Error::PwdValidate)),
+			"should be PwdValidate"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
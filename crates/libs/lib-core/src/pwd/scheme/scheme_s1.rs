@@ -0,0 +1,63 @@
+//! Legacy scrypt scheme (validate only), for the pwds migrated as-is from older
+//! systems, in the PHC string format (e.g., `#s1#$scrypt$ln=15,r=8,p=1$...`),
+//! upgraded to the default scheme on the first successful login.
+//!
+//! NOTE: The scrypt hash has its own salt and params, so the user `pwd_salt`
+//!       (and the `PWD_KEY`) are not used.
+
+use crate::pwd::scheme::{Error, Result, Scheme};
+use crate::pwd::ContentToHash;
+use scrypt::password_hash::{PasswordHash, PasswordVerifier};
+use scrypt::Scrypt;
+
+pub struct SchemeS1;
+
+impl Scheme for SchemeS1 {
+	fn hash(&self, _to_hash: &ContentToHash) -> Result<String> {
+		Err(Error::SchemeValidateOnly("s1"))
+	}
+
+	fn validate(&self, to_hash: &ContentToHash, raw_pwd_ref: &str) -> Result<()> {
+		let parsed_hash_ref =
+			PasswordHash::new(raw_pwd_ref).map_err(|_| Error::Hash)?;
+
+		Scrypt
+			.verify_password(to_hash.content.as_bytes(), &parsed_hash_ref)
+			.map_err(|_| Error::PwdValidate)
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use uuid::Uuid;
+
+	#[test]
+	fn test_scheme_s1_validate_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_to_hash = ContentToHash {
+			content: "hello world".to_string(),
+			salt: Uuid::new_v4(),
+		};
+		let fx_pwd_ref = "$scrypt$ln=10,r=8,p=1$bGVnYWN5LXNhbHQtMDAwMQ$1cyOGu62XQVcKOYyGtkvqqCs97x1lrAVsNz3A8DYcoQ";
+
+		// -- Exec & Check
+		SchemeS1.validate(&fx_to_hash, fx_pwd_ref)?;
+		let res = SchemeS1.validate(
+			&ContentToHash {
+				content: "hello world!".to_string(),
+				..fx_to_hash
+			},
+			fx_pwd_ref,
+		);
+		assert!(
+			matches!(res, Err(Error::PwdValidate)),
+			"should be PwdValidate"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -5,9 +5,10 @@ use std::{fmt::Display, str::FromStr};
 use hmac::{Hmac, Mac};
 use lib_base::{
 	b64::{b64u_decode_to_string, b64u_encode},
-	time::{now_utc, now_utc_plus_sec_str, parse_utc},
+	time::{format_time, is_expired, now_utc_plus_sec_str, parse_utc, remaining},
 };
 use sha2::Sha512;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::config::config;
@@ -67,7 +68,9 @@ impl Display for Token {
 
 pub fn generate_web_token(user: &str, salt: Uuid) -> Result<Token> {
 	let config = &config();
-	_generate_token(user, config.TOKEN_DURATION_SEC, salt, &config.TOKEN_KEY)
+	// Note: The token duration is reloadable.
+	let duration_sec = config.RELOADABLE.current().TOKEN_DURATION_SEC;
+	_generate_token(user, duration_sec, salt, &config.TOKEN_KEY)
 }
 
 pub fn validate_web_token(origin_token: &Token, salt: Uuid) -> Result<()> {
@@ -77,8 +80,42 @@ pub fn validate_web_token(origin_token: &Token, salt: Uuid) -> Result<()> {
 	Ok(())
 }
 
+/// Whether a valid web token should be re-issued, i.e., when it expires within
+/// the `TOKEN_REFRESH_WINDOW_SEC` (so not on each request).
+pub fn is_web_token_refresh_due(token: &Token) -> bool {
+	let window_sec = config().RELOADABLE.current().TOKEN_REFRESH_WINDOW_SEC;
+	_is_refresh_due(token, window_sec)
+}
+
 // endregion: --- Web Token Gen and Validation
 
+// region:    --- Share Token Gen and Validation
+
+/// The token of a project share link (see `model::project_share`), with the
+/// share id as ident, and the share `token_salt` as salt (so the link is
+/// revoked by deleting the share).
+pub fn generate_share_token(
+	share_id: i64,
+	exp: OffsetDateTime,
+	salt: Uuid,
+) -> Result<Token> {
+	let ident = share_id.to_string();
+	let exp = format_time(exp);
+	let sign_b64u = _token_sign_into_b64u(&ident, &exp, salt, &config().TOKEN_KEY)?;
+
+	Ok(Token {
+		ident,
+		exp,
+		sign_b64u,
+	})
+}
+
+pub fn validate_share_token(origin_token: &Token, salt: Uuid) -> Result<()> {
+	_validate_token_sign_and_exp(origin_token, salt, &config().TOKEN_KEY)
+}
+
+// endregion: --- Share Token Gen and Validation
+
 // region:    --- (private) Token Gen and Validation
 
 fn _generate_token(
@@ -116,15 +153,22 @@ fn _validate_token_sign_and_exp(
 
 	// -- Validate expiration.
 	let origin_exp = parse_utc(&origin_token.exp).map_err(|_| Error::ExpNotIso)?;
-	let now = now_utc();
 
-	if origin_exp < now {
+	if is_expired(origin_exp) {
 		return Err(Error::Expired);
 	}
 
 	Ok(())
 }
 
+/// Note: A token with a bad `exp` is due (it fails the validation anyway).
+fn _is_refresh_due(token: &Token, window_sec: f64) -> bool {
+	let Ok(exp) = parse_utc(&token.exp) else {
+		return true;
+	};
+	remaining(exp).as_seconds_f64() < window_sec
+}
+
 /// Create token signature from token parts
 /// and salt.
 fn _token_sign_into_b64u(
@@ -158,6 +202,7 @@ fn _token_sign_into_b64u(
 mod tests {
 	use super::*;
 	use anyhow::Result;
+	use lib_base::time::{duration_sec, now_utc_plus};
 	use std::thread;
 	use std::time::Duration;
 
@@ -242,5 +287,51 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[test]
+	fn test_validate_share_token_ok_and_err() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_salt =
+			Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453").unwrap();
+		let fx_other_salt =
+			Uuid::parse_str("6c2b7e7f-0a6e-4f57-9d4a-1c8f3f3b2a10").unwrap();
+		let fx_exp = now_utc_plus(duration_sec(60.));
+		let fx_expired = now_utc_plus(duration_sec(-60.));
+
+		// -- Exec
+		let token = generate_share_token(1000, fx_exp, fx_salt)?;
+		let expired = generate_share_token(1000, fx_expired, fx_salt)?;
+
+		// -- Check
+		assert_eq!(token.ident, "1000");
+		validate_share_token(&token, fx_salt)?;
+		assert!(matches!(
+			validate_share_token(&token, fx_other_salt),
+			Err(Error::SignatureNotMatching)
+		));
+		assert!(matches!(
+			validate_share_token(&expired, fx_salt),
+			Err(Error::Expired)
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_is_refresh_due_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_token = |duration_sec: f64| Token {
+			ident: "fx-ident-01".to_string(),
+			exp: now_utc_plus_sec_str(duration_sec),
+			sign_b64u: "some-sign-b64u-encoded".to_string(),
+		};
+
+		// -- Exec & Check
+		assert!(!_is_refresh_due(&fx_token(1800.), 900.), "far from exp");
+		assert!(_is_refresh_due(&fx_token(600.), 900.), "within the window");
+		assert!(_is_refresh_due(&fx_token(-1.), 900.), "expired");
+
+		Ok(())
+	}
 }
 // endregion: --- Tests
@@ -0,0 +1,21 @@
+use prost::Message;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let protos = ["proto/app.proto"];
+	let includes = ["proto"];
+
+	// Note: protox compiles the protos in rust (no `protoc` needed), and
+	//       prost-build then reads its file descriptor set.
+	let fds = protox::compile(protos, includes)?;
+	let fds_path = PathBuf::from(std::env::var("OUT_DIR")?).join("app.fds");
+	std::fs::write(&fds_path, fds.encode_to_vec())?;
+
+	let mut config = prost_build::Config::new();
+	config.file_descriptor_set_path(&fds_path).skip_protoc_run();
+	tonic_build::configure().compile_with_config(config, &protos, &includes)?;
+
+	println!("cargo:rerun-if-changed=proto");
+
+	Ok(())
+}
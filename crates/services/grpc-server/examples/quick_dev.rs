@@ -0,0 +1,93 @@
+#![allow(unused)] // For beginning only.
+
+use tonic::metadata::MetadataValue;
+use tonic::Request;
+
+pub mod proto {
+	tonic::include_proto!("app.v1");
+}
+
+use proto::auth_service_client::AuthServiceClient;
+use proto::project_service_client::ProjectServiceClient;
+use proto::task_service_client::TaskServiceClient;
+use proto::{
+	CreateProjectRequest, CreateTaskRequest, IdRequest, ListRequest, LoginRequest,
+	UpdateTaskRequest,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let addr = "http://127.0.0.1:50051";
+
+	// -- Login
+	let mut auth_client = AuthServiceClient::connect(addr).await?;
+	let token = auth_client
+		.login(LoginRequest {
+			username: "demo1".to_string(),
+			pwd: "welcome".to_string(),
+		})
+		.await?
+		.into_inner()
+		.token;
+	let authorization: MetadataValue<_> = format!("Bearer {token}").parse()?;
+	#[allow(clippy::result_large_err)]
+	let with_auth = move |mut req: Request<()>| -> Result<Request<()>, tonic::Status> {
+		req.metadata_mut()
+			.insert("authorization", authorization.clone());
+		Ok(req)
+	};
+
+	// -- Project & Tasks
+	let channel = tonic::transport::Channel::from_static(addr)
+		.connect()
+		.await?;
+	let mut project_client =
+		ProjectServiceClient::with_interceptor(channel.clone(), with_auth.clone());
+	let mut task_client = TaskServiceClient::with_interceptor(channel, with_auth);
+
+	let project = project_client
+		.create_project(CreateProjectRequest {
+			name: "project GRPC".to_string(),
+		})
+		.await?
+		.into_inner();
+	println!("->> project: {project:?}");
+
+	let task = task_client
+		.create_task(CreateTaskRequest {
+			project_id: project.id,
+			title: "task GRPC".to_string(),
+		})
+		.await?
+		.into_inner();
+	task_client
+		.update_task(UpdateTaskRequest {
+			id: task.id,
+			title: None,
+			done: Some(true),
+		})
+		.await?;
+
+	let tasks = task_client
+		.list_tasks(ListRequest {
+			filters_json: Some(format!(r#"{{"project_id": {}}}"#, project.id)),
+			list_options_json: None,
+		})
+		.await?
+		.into_inner()
+		.tasks;
+	println!("->> tasks: {tasks:?}");
+
+	project_client
+		.delete_project(IdRequest { id: project.id })
+		.await?;
+	let res = project_client
+		.get_project(IdRequest { id: project.id })
+		.await;
+	println!(
+		"->> get deleted project: {:?}",
+		res.err().map(|s| s.message().to_string())
+	);
+
+	Ok(())
+}
@@ -0,0 +1,91 @@
+//! Token auth, with the same tokens as the web-server `auth-token` cookie.
+//!
+//! - `auth_interceptor` requires the `authorization: Bearer <token>` metadata,
+//!   and sets the parsed `Token` in the request extensions.
+//! - `ctx_resolve` then validates it against the user token salt, and returns
+//!   the request `Ctx`.
+//!
+//! NOTE: The validation needs a db lookup, and the tonic interceptors are sync,
+//!       hence the split, with `ctx_resolve` called by each service method.
+
+use crate::{Error, Result};
+use lib_core::ctx::Ctx;
+use lib_core::model::user::{UserBmc, UserForAuth};
+use lib_core::model::ModelManager;
+use lib_core::token::{validate_web_token, Token};
+use tonic::{Request, Status};
+
+pub const AUTHORIZATION: &str = "authorization";
+
+#[allow(clippy::result_large_err)] // The tonic interceptor signature.
+pub fn auth_interceptor(
+	mut req: Request<()>,
+) -> core::result::Result<Request<()>, Status> {
+	let token = parse_bearer_token(&req)?;
+	req.extensions_mut().insert(token);
+
+	Ok(req)
+}
+
+fn parse_bearer_token<T>(req: &Request<T>) -> Result<Token> {
+	let authorization = req
+		.metadata()
+		.get(AUTHORIZATION)
+		.ok_or(Error::TokenNotInMetadata)?
+		.to_str()
+		.map_err(|_| Error::TokenWrongFormat)?;
+
+	authorization
+		.strip_prefix("Bearer ")
+		.ok_or(Error::TokenWrongFormat)?
+		.parse::<Token>()
+		.map_err(|_| Error::TokenWrongFormat)
+}
+
+pub async fn ctx_resolve<T>(mm: &ModelManager, req: &Request<T>) -> Result<Ctx> {
+	let token = req
+		.extensions()
+		.get::<Token>()
+		.ok_or(Error::TokenNotInMetadata)?;
+
+	let user: UserForAuth =
+		UserBmc::auth_by_username(&Ctx::root_ctx(), mm, &token.ident)
+			.await?
+			.ok_or(Error::UserNotFound)?;
+	validate_web_token(token, *user.token_salt.expose())?;
+	if !user.active {
+		return Err(Error::UserInactive);
+	}
+	// Note: No pwd change over grpc, so done with the web api first.
+	if user.must_change_pwd {
+		return Err(Error::PwdChangeRequired);
+	}
+
+	Ok(Ctx::new(user.id)?)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_auth_interceptor_err() {
+		// -- Exec & Check
+		let res = auth_interceptor(Request::new(()));
+		assert_eq!(
+			res.err().map(|s| s.code()),
+			Some(tonic::Code::Unauthenticated)
+		);
+
+		let mut req = Request::new(());
+		req.metadata_mut()
+			.insert(AUTHORIZATION, "Basic dXNlcjpwd2Q=".parse().unwrap());
+		let res = auth_interceptor(req);
+		assert_eq!(
+			res.err().map(|s| s.code()),
+			Some(tonic::Code::Unauthenticated)
+		);
+	}
+}
+// endregion: --- Tests
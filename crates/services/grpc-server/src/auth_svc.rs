@@ -0,0 +1,69 @@
+use crate::proto::auth_service_server::AuthService;
+use crate::proto::{LoginRequest, LoginResponse};
+use crate::Error;
+use lib_core::ctx::Ctx;
+use lib_core::model::user::{UserBmc, UserForLogin};
+use lib_core::model::ModelManager;
+use lib_core::pwd::{self, ContentToHash, SchemeStatus};
+use lib_core::token::generate_web_token;
+use tonic::{Request, Response, Status};
+use tracing::debug;
+
+pub struct AuthSvc {
+	pub mm: ModelManager,
+}
+
+#[tonic::async_trait]
+impl AuthService for AuthSvc {
+	async fn login(
+		&self,
+		req: Request<LoginRequest>,
+	) -> Result<Response<LoginResponse>, Status> {
+		debug!("{:<12} - grpc login", "HANDLER");
+
+		let LoginRequest {
+			username,
+			pwd: pwd_clear,
+		} = req.into_inner();
+		let root_ctx = Ctx::root_ctx();
+		let mm = &self.mm;
+
+		// -- Get the user.
+		// Note: Same LOGIN_FAIL for all cases, not to leak which usernames exist.
+		let user: UserForLogin =
+			UserBmc::first_by_username(&root_ctx, mm, &username)
+				.await
+				.map_err(Error::from)?
+				.ok_or(Error::LoginFail)?;
+		let pwd = user.pwd.ok_or(Error::LoginFail)?;
+
+		// -- Validate the password.
+		let scheme_status = pwd::validate_pwd(
+			&ContentToHash {
+				salt: *user.pwd_salt.expose(),
+				content: pwd_clear.clone(),
+			},
+			pwd.expose(),
+		)
+		.map_err(|_| Error::LoginFail)?;
+		// Note: Only disclosed with the right pwd (as the web login).
+		if !user.active {
+			return Err(Error::UserInactive.into());
+		}
+
+		// -- Update password scheme if need
+		if let SchemeStatus::Outdated = scheme_status {
+			debug!("pwd encrypt scheme outdated, upgrading.");
+			UserBmc::update_pwd(&root_ctx, mm, user.id, &pwd_clear)
+				.await
+				.map_err(Error::from)?;
+		}
+
+		let token = generate_web_token(&user.username, *user.token_salt.expose())
+			.map_err(Error::from)?;
+
+		Ok(Response::new(LoginResponse {
+			token: token.to_string(),
+		}))
+	}
+}
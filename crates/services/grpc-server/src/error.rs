@@ -0,0 +1,137 @@
+use lib_core::config::config;
+use lib_core::{ctx, model, pwd, token};
+use tonic::Status;
+use tracing::debug;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+	// -- Config
+	SecretsInit(String),
+
+	// -- Auth
+	TokenNotInMetadata,
+	TokenWrongFormat,
+	UserNotFound,
+	UserInactive,
+	PwdChangeRequired,
+	LoginFail,
+
+	// -- List
+	ListParamsWrongFormat(String),
+
+	// -- Read only
+	ReadOnlyMode { mutation: &'static str },
+
+	// -- Modules
+	Ctx(ctx::Error),
+	Model(model::Error),
+	Pwd(pwd::Error),
+	Token(token::Error),
+	Transport(tonic::transport::Error),
+}
+
+// region:    --- Froms
+impl From<ctx::Error> for Error {
+	fn from(val: ctx::Error) -> Self {
+		Self::Ctx(val)
+	}
+}
+
+impl From<model::Error> for Error {
+	fn from(val: model::Error) -> Self {
+		Self::Model(val)
+	}
+}
+
+impl From<pwd::Error> for Error {
+	fn from(val: pwd::Error) -> Self {
+		Self::Pwd(val)
+	}
+}
+
+impl From<token::Error> for Error {
+	fn from(val: token::Error) -> Self {
+		Self::Token(val)
+	}
+}
+
+impl From<tonic::transport::Error> for Error {
+	fn from(val: tonic::transport::Error) -> Self {
+		Self::Transport(val)
+	}
+}
+// endregion: --- Froms
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+	fn fmt(
+		&self,
+		fmt: &mut core::fmt::Formatter,
+	) -> core::result::Result<(), core::fmt::Error> {
+		write!(fmt, "{self:?}")
+	}
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
+
+// region:    --- Grpc Status
+
+/// From the root error to the grpc status, with the same client messages as
+/// the web-server `ClientError` (the internal errors are not exposed).
+impl From<Error> for Status {
+	fn from(val: Error) -> Self {
+		debug!("{:<12} - grpc::Error {val:?}", "INTO_STATUS");
+
+		match val {
+			Error::TokenNotInMetadata
+			| Error::TokenWrongFormat
+			| Error::UserNotFound
+			| Error::Token(_) => Status::unauthenticated("NO_AUTH"),
+
+			Error::UserInactive => Status::permission_denied("ACCOUNT_DISABLED"),
+
+			Error::LoginFail => Status::permission_denied("LOGIN_FAIL"),
+			Error::PwdChangeRequired => {
+				Status::permission_denied("PWD_CHANGE_REQUIRED")
+			}
+
+			Error::ReadOnlyMode { .. } => Status::unavailable("READ_ONLY_MODE"),
+
+			Error::ListParamsWrongFormat(cause) => {
+				Status::invalid_argument(format!("INVALID_PARAMS - {cause}"))
+			}
+
+			Error::Model(model::Error::EntityNotFound { entity, id }) => {
+				Status::not_found(format!("ENTITY_NOT_FOUND - {entity} {id}"))
+			}
+			Error::Model(model::Error::UniqueViolation { entity, field }) => {
+				Status::already_exists(format!(
+					"ENTITY_ALREADY_EXISTS - {entity} {}",
+					field.unwrap_or_default()
+				))
+			}
+			Error::Model(model::Error::EntityInUse {
+				entity, dependent, ..
+			}) => Status::failed_precondition(format!(
+				"ENTITY_IN_USE - {entity} used by {dependent}"
+			)),
+			Error::Model(model::Error::ForeignKeyViolation { entity, field }) => {
+				Status::failed_precondition(format!(
+					"ENTITY_REFERENCE_VIOLATION - {entity} {}",
+					field.unwrap_or_default()
+				))
+			}
+
+			// Note: The server error detail is only sent when enabled (e.g., dev env).
+			other if config().ERROR_DETAIL => {
+				Status::internal(format!("SERVICE_ERROR - {other:?}"))
+			}
+			_ => Status::internal("SERVICE_ERROR"),
+		}
+	}
+}
+
+// endregion: --- Grpc Status
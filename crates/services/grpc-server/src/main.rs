@@ -0,0 +1,68 @@
+mod auth;
+mod auth_svc;
+mod error;
+mod params;
+mod project_svc;
+mod read_only;
+mod task_svc;
+
+pub use self::error::{Error, Result};
+
+/// The generated (tonic-build) grpc types and services.
+pub mod proto {
+	tonic::include_proto!("app.v1");
+}
+
+use crate::auth::auth_interceptor;
+use crate::auth_svc::AuthSvc;
+use crate::project_svc::ProjectSvc;
+use crate::proto::auth_service_server::AuthServiceServer;
+use crate::proto::project_service_server::ProjectServiceServer;
+use crate::proto::task_service_server::TaskServiceServer;
+use crate::task_svc::TaskSvc;
+
+use lib_core::config;
+use lib_core::model::ModelManager;
+use std::net::SocketAddr;
+use tonic::transport::Server;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+	tracing_subscriber::fmt()
+		.without_time() // For early local development.
+		.with_target(false)
+		.with_env_filter(EnvFilter::from_default_env())
+		.init();
+
+	// Note: Unlike the web-server, no `_dev_utils::init_dev()` here,
+	//       as it would reset the dev db under the web-server.
+
+	// -- External secret manager (must be before the first `config()`)
+	config::init_secrets()
+		.await
+		.map_err(|ex| Error::SecretsInit(ex.to_string()))?;
+
+	// Initialze ModelManager.
+	let mm = ModelManager::new().await?;
+
+	// region:    --- Start Server
+	let addr = SocketAddr::from(([127, 0, 0, 1], 50051));
+	info!("{:<12} - {addr} (grpc)\n", "LISTENING");
+	Server::builder()
+		.add_service(AuthServiceServer::new(AuthSvc { mm: mm.clone() }))
+		.add_service(ProjectServiceServer::with_interceptor(
+			ProjectSvc { mm: mm.clone() },
+			auth_interceptor,
+		))
+		.add_service(TaskServiceServer::with_interceptor(
+			TaskSvc { mm },
+			auth_interceptor,
+		))
+		.serve(addr)
+		.await?;
+	// endregion: --- Start Server
+
+	Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::proto::ListRequest;
+use crate::{Error, Result};
+use modql::filter::ListOptions;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// The `ListRequest` json (same as the json-rpc list params) to the modql filters.
+pub fn list_params<F>(
+	list_req: ListRequest,
+) -> Result<(Option<Vec<F>>, Option<ListOptions>)>
+where
+	F: DeserializeOwned,
+{
+	let filters = list_req
+		.filters_json
+		.map(|filters| {
+			// Note: One filter object, or an array of them.
+			match serde_json::from_str::<Value>(&filters)? {
+				Value::Array(filters) => filters
+					.into_iter()
+					.map(serde_json::from_value)
+					.collect::<serde_json::Result<Vec<F>>>(),
+				filter => serde_json::from_value(filter).map(|filter| vec![filter]),
+			}
+		})
+		.transpose()
+		.map_err(|ex| Error::ListParamsWrongFormat(ex.to_string()))?;
+
+	let list_options = list_req
+		.list_options_json
+		.map(|list_options| serde_json::from_str(&list_options))
+		.transpose()
+		.map_err(|ex| Error::ListParamsWrongFormat(ex.to_string()))?;
+
+	Ok((filters, list_options))
+}
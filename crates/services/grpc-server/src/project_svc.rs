@@ -0,0 +1,125 @@
+use crate::auth::ctx_resolve;
+use crate::params::list_params;
+use crate::proto::project_service_server::ProjectService;
+use crate::proto::{
+	CreateProjectRequest, IdRequest, ListProjectsResponse, ListRequest, Project,
+	UpdateProjectRequest,
+};
+use crate::read_only::check_writable;
+use crate::Result;
+use lib_base::time::format_time;
+use lib_core::model::project::{
+	self, ProjectBmc, ProjectFilter, ProjectForCreate, ProjectForUpdate,
+};
+use lib_core::model::ModelManager;
+use tonic::{Request, Response, Status};
+
+pub struct ProjectSvc {
+	pub mm: ModelManager,
+}
+
+#[tonic::async_trait]
+impl ProjectService for ProjectSvc {
+	async fn create_project(
+		&self,
+		req: Request<CreateProjectRequest>,
+	) -> core::result::Result<Response<Project>, Status> {
+		Ok(Response::new(self.create(req).await?))
+	}
+
+	async fn get_project(
+		&self,
+		req: Request<IdRequest>,
+	) -> core::result::Result<Response<Project>, Status> {
+		Ok(Response::new(self.get(req).await?))
+	}
+
+	async fn list_projects(
+		&self,
+		req: Request<ListRequest>,
+	) -> core::result::Result<Response<ListProjectsResponse>, Status> {
+		Ok(Response::new(self.list(req).await?))
+	}
+
+	async fn update_project(
+		&self,
+		req: Request<UpdateProjectRequest>,
+	) -> core::result::Result<Response<Project>, Status> {
+		Ok(Response::new(self.update(req).await?))
+	}
+
+	async fn delete_project(
+		&self,
+		req: Request<IdRequest>,
+	) -> core::result::Result<Response<Project>, Status> {
+		Ok(Response::new(self.delete(req).await?))
+	}
+}
+
+// Note: The implementations, with the app `Result` (to the grpc `Status` above).
+impl ProjectSvc {
+	async fn create(&self, req: Request<CreateProjectRequest>) -> Result<Project> {
+		check_writable("create_project")?;
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let CreateProjectRequest { name } = req.into_inner();
+
+		let id = ProjectBmc::create(&ctx, mm, ProjectForCreate { name }).await?;
+		let project = ProjectBmc::get(&ctx, mm, id).await?;
+
+		Ok(project.into())
+	}
+
+	async fn get(&self, req: Request<IdRequest>) -> Result<Project> {
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let IdRequest { id } = req.into_inner();
+
+		Ok(ProjectBmc::get(&ctx, mm, id).await?.into())
+	}
+
+	async fn list(&self, req: Request<ListRequest>) -> Result<ListProjectsResponse> {
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let (filters, list_options) =
+			list_params::<ProjectFilter>(req.into_inner())?;
+
+		let projects = ProjectBmc::list(&ctx, mm, filters, list_options).await?;
+
+		Ok(ListProjectsResponse {
+			projects: projects.into_iter().map(Project::from).collect(),
+		})
+	}
+
+	async fn update(&self, req: Request<UpdateProjectRequest>) -> Result<Project> {
+		check_writable("update_project")?;
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let UpdateProjectRequest { id, name, owner_id } = req.into_inner();
+
+		ProjectBmc::update(&ctx, mm, id, ProjectForUpdate { name, owner_id })
+			.await?;
+		let project = ProjectBmc::get(&ctx, mm, id).await?;
+
+		Ok(project.into())
+	}
+
+	async fn delete(&self, req: Request<IdRequest>) -> Result<Project> {
+		check_writable("delete_project")?;
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let IdRequest { id } = req.into_inner();
+
+		let project = ProjectBmc::get(&ctx, mm, id).await?;
+		ProjectBmc::delete(&ctx, mm, id).await?;
+
+		Ok(project.into())
+	}
+}
+
+impl From<project::Project> for Project {
+	fn from(val: project::Project) -> Self {
+		Self {
+			id: val.id,
+			name: val.name,
+			owner_id: val.owner_id,
+			ctime: format_time(val.ctime),
+			mtime: format_time(val.mtime),
+		}
+	}
+}
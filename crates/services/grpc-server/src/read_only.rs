@@ -0,0 +1,16 @@
+//! The read-only mode (the reloadable `READ_ONLY` config), as the web-server
+//! one: the create/update/delete fail with `Error::ReadOnlyMode`
+//! (`UNAVAILABLE` `READ_ONLY_MODE`), and the reads are served.
+
+use crate::{Error, Result};
+use lib_core::config::config;
+
+/// Fails with `ReadOnlyMode` when in the read-only mode (first thing of the
+/// mutation handlers).
+pub fn check_writable(mutation: &'static str) -> Result<()> {
+	if config().RELOADABLE.current().READ_ONLY {
+		return Err(Error::ReadOnlyMode { mutation });
+	}
+
+	Ok(())
+}
@@ -0,0 +1,130 @@
+use crate::auth::ctx_resolve;
+use crate::params::list_params;
+use crate::proto::task_service_server::TaskService;
+use crate::proto::{
+	CreateTaskRequest, IdRequest, ListRequest, ListTasksResponse, Task,
+	UpdateTaskRequest,
+};
+use crate::read_only::check_writable;
+use crate::Result;
+use lib_base::time::format_time;
+use lib_core::model::task::{
+	self, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate,
+};
+use lib_core::model::ModelManager;
+use tonic::{Request, Response, Status};
+
+pub struct TaskSvc {
+	pub mm: ModelManager,
+}
+
+#[tonic::async_trait]
+impl TaskService for TaskSvc {
+	async fn create_task(
+		&self,
+		req: Request<CreateTaskRequest>,
+	) -> core::result::Result<Response<Task>, Status> {
+		Ok(Response::new(self.create(req).await?))
+	}
+
+	async fn get_task(
+		&self,
+		req: Request<IdRequest>,
+	) -> core::result::Result<Response<Task>, Status> {
+		Ok(Response::new(self.get(req).await?))
+	}
+
+	async fn list_tasks(
+		&self,
+		req: Request<ListRequest>,
+	) -> core::result::Result<Response<ListTasksResponse>, Status> {
+		Ok(Response::new(self.list(req).await?))
+	}
+
+	async fn update_task(
+		&self,
+		req: Request<UpdateTaskRequest>,
+	) -> core::result::Result<Response<Task>, Status> {
+		Ok(Response::new(self.update(req).await?))
+	}
+
+	async fn delete_task(
+		&self,
+		req: Request<IdRequest>,
+	) -> core::result::Result<Response<Task>, Status> {
+		Ok(Response::new(self.delete(req).await?))
+	}
+}
+
+// Note: The implementations, with the app `Result` (to the grpc `Status` above).
+impl TaskSvc {
+	async fn create(&self, req: Request<CreateTaskRequest>) -> Result<Task> {
+		check_writable("create_task")?;
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let CreateTaskRequest { project_id, title } = req.into_inner();
+
+		let task_c = TaskForCreate { title, project_id };
+		let id = TaskBmc::create(&ctx, mm, task_c).await?;
+		let task = TaskBmc::get(&ctx, mm, id).await?;
+
+		Ok(task.into())
+	}
+
+	async fn get(&self, req: Request<IdRequest>) -> Result<Task> {
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let IdRequest { id } = req.into_inner();
+
+		Ok(TaskBmc::get(&ctx, mm, id).await?.into())
+	}
+
+	async fn list(&self, req: Request<ListRequest>) -> Result<ListTasksResponse> {
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let (filters, list_options) = list_params::<TaskFilter>(req.into_inner())?;
+
+		let tasks = TaskBmc::list(&ctx, mm, filters, list_options).await?;
+
+		Ok(ListTasksResponse {
+			tasks: tasks.into_iter().map(Task::from).collect(),
+		})
+	}
+
+	async fn update(&self, req: Request<UpdateTaskRequest>) -> Result<Task> {
+		check_writable("update_task")?;
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let UpdateTaskRequest { id, title, done } = req.into_inner();
+
+		let task_u = TaskForUpdate {
+			title,
+			done,
+			..Default::default()
+		};
+		TaskBmc::update(&ctx, mm, id, task_u).await?;
+		let task = TaskBmc::get(&ctx, mm, id).await?;
+
+		Ok(task.into())
+	}
+
+	async fn delete(&self, req: Request<IdRequest>) -> Result<Task> {
+		check_writable("delete_task")?;
+		let (mm, ctx) = (&self.mm, ctx_resolve(&self.mm, &req).await?);
+		let IdRequest { id } = req.into_inner();
+
+		let task = TaskBmc::get(&ctx, mm, id).await?;
+		TaskBmc::delete(&ctx, mm, id).await?;
+
+		Ok(task.into())
+	}
+}
+
+impl From<task::Task> for Task {
+	fn from(val: task::Task) -> Self {
+		Self {
+			id: val.id,
+			project_id: val.project_id,
+			title: val.title,
+			done: val.done,
+			ctime: format_time(val.ctime),
+			mtime: format_time(val.mtime),
+		}
+	}
+}
@@ -0,0 +1,138 @@
+//! The runtime config reload triggers and appliers
+//! (see lib-core `ReloadableConfig` and `SecretCache`).
+//!
+//! Note: The other components (e.g., RateLimiter, token generation) read the
+//!       `ConfigHandle` current values on use.
+
+use lib_core::config::{ConfigHandle, ReloadableConfig, SecretCache};
+use lib_core::model::ModelManager;
+use tracing::level_filters::LevelFilter;
+use tracing::{info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Reloads the config on each SIGHUP.
+#[cfg(unix)]
+pub fn spawn_sighup_reload(config: ConfigHandle) -> std::io::Result<()> {
+	use tokio::signal::unix::{signal, SignalKind};
+
+	let mut sighup = signal(SignalKind::hangup())?;
+	tokio::spawn(async move {
+		while sighup.recv().await.is_some() {
+			match config.reload() {
+				Ok(_) => info!("{:<12} - config reloaded (SIGHUP)", "CONFIG"),
+				Err(ex) => warn!("config reload failed, kept current - {ex:?}"),
+			}
+		}
+	});
+
+	Ok(())
+}
+
+/// The `LOG_FILTER`, and the `LOG_SAMPLE_FILTER` directives scoped to the sampled
+/// request spans (see mw_req_stamp), e.g., `web_server=debug` is applied as
+/// `web_server[req{log_sampled=true}]=debug`.
+pub fn log_env_filter(config: &ReloadableConfig) -> EnvFilter {
+	let sample_filter = (config.LOG_SAMPLE_PERCENT > 0.)
+		.then_some(config.LOG_SAMPLE_FILTER.as_str());
+
+	// Note: The filters are validated at config load.
+	EnvFilter::new(log_directives(&config.LOG_FILTER, sample_filter))
+}
+
+fn log_directives(log_filter: &str, sample_filter: Option<&str>) -> String {
+	const SAMPLED_SPAN: &str = "[req{log_sampled=true}]";
+
+	let sample_directives = sample_filter
+		.unwrap_or_default()
+		.split(',')
+		.map(str::trim)
+		.filter(|directive| !directive.is_empty())
+		.map(|directive| match directive.split_once('=') {
+			Some((target, level)) => format!("{target}{SAMPLED_SPAN}={level}"),
+			// A level alone (all targets), or a target alone (all levels).
+			None if directive.parse::<LevelFilter>().is_ok() => {
+				format!("{SAMPLED_SPAN}={directive}")
+			}
+			None => format!("{directive}{SAMPLED_SPAN}"),
+		});
+
+	std::iter::once(log_filter.to_string())
+		.chain(sample_directives)
+		.filter(|directive| !directive.is_empty())
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+/// Applies the reloaded `LOG_FILTER` (and log sampling) to the tracing subscriber.
+pub fn spawn_log_filter_reload(
+	config: ConfigHandle,
+	log_filter_handle: reload::Handle<EnvFilter, Registry>,
+) {
+	let mut changes = config.subscribe();
+	tokio::spawn(async move {
+		while changes.changed().await.is_ok() {
+			let log_filter = log_env_filter(&changes.borrow_and_update());
+			if let Err(ex) = log_filter_handle.reload(log_filter) {
+				warn!("log filter reload failed - {ex}");
+			}
+		}
+	});
+}
+
+/// Applies the rotated secrets (see lib-core `SecretCache`).
+///
+/// Note: Only the `DB_URL` applies at runtime (for the new db connections).
+///       The `PWD_KEY` and `TOKEN_KEY` are loaded once, and need a restart.
+pub fn spawn_secret_rotation(secret_cache: &'static SecretCache, mm: ModelManager) {
+	let mut rotations = secret_cache.subscribe_rotations();
+	tokio::spawn(async move {
+		while rotations.changed().await.is_ok() {
+			let rotated = rotations.borrow_and_update().clone();
+			for name in rotated {
+				if name != "SERVICE_DB_URL" {
+					warn!("{name} rotated, applies on the next restart");
+					continue;
+				}
+				let Some(db_url) = secret_cache.get(&name) else {
+					continue;
+				};
+				match mm.set_db_url(&db_url) {
+					Ok(()) => info!("{:<12} - db url rotated", "CONFIG"),
+					Err(ex) => warn!("db url rotation failed - {ex:?}"),
+				}
+			}
+		}
+	});
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_log_directives_sampled_ok() {
+		let fx_cases = [
+			("web_server=info", None, "web_server=info"),
+			(
+				"web_server=info",
+				Some("web_server=debug, lib_core=trace"),
+				"web_server=info,web_server[req{log_sampled=true}]=debug,\
+				 lib_core[req{log_sampled=true}]=trace",
+			),
+			("", Some("debug"), "[req{log_sampled=true}]=debug"),
+			(
+				"info",
+				Some("web_server"),
+				"info,web_server[req{log_sampled=true}]",
+			),
+		];
+
+		for (log_filter, sample_filter, expected) in fx_cases {
+			let directives = log_directives(log_filter, sample_filter);
+			assert_eq!(directives, expected);
+			assert!(EnvFilter::try_new(&directives).is_ok(), "{directives}");
+		}
+	}
+}
+// endregion: --- Tests
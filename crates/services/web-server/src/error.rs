@@ -7,6 +7,26 @@ pub enum Error {
 	// -- Config
 	ConfigMissingEnv(&'static str),
 	ConfigWrongFormat(&'static str),
+	SecretsInit(String),
+
+	// -- Tls
+	TlsConfigLoad(String),
+
+	// -- Admin
+	MetricsRecorderInstall(String),
+	AdminBind(String),
+
+	// -- Reload
+	SignalListen(String),
+
+	// -- Request log
+	RequestLogInit(String),
+
+	// -- Webhooks
+	WebhooksInit(String),
+
+	// -- Broker
+	BrokerConnect(String),
 
 	// -- Modules
 	Model(model::Error),
@@ -1,10 +1,17 @@
+mod redact;
+mod writer;
+
+pub use self::redact::{redact_path, redact_value};
+pub use self::writer::spawn_request_log_writer;
+
 use crate::{
-	web::{rpc::RpcInfo, ReqStamp},
+	web::{client_ip::ClientIp, rpc::RpcInfo, ReqStamp},
 	Result,
 };
-use axum::http::{Method, Uri};
+use axum::http::{Method, StatusCode, Uri};
 use lib_base::time::{format_time, now_utc};
 use lib_core::ctx::Ctx;
+use lib_core::model::request_log::RequestLogForCreate;
 use serde::Serialize;
 use serde_json::{json, Value};
 use serde_with::skip_serializing_none;
@@ -13,10 +20,13 @@ use tracing::debug;
 
 use crate::web::{self, ClientError};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn log_request(
 	http_method: Method,
 	uri: Uri,
+	http_status: StatusCode,
 	req_stamp: ReqStamp,
+	client_ip: Option<ClientIp>,
 	rpc_info: Option<&RpcInfo>,
 	ctx: Option<Ctx>,
 	web_error: Option<&web::Error>,
@@ -25,10 +35,16 @@ pub async fn log_request(
 	let error_type = web_error.map(|se| se.as_ref().to_string());
 	let error_data = serde_json::to_value(web_error)
 		.ok()
-		.and_then(|mut v| v.get_mut("data").map(|v| v.take()));
+		.and_then(|mut v| v.get_mut("data").map(|v| v.take()))
+		.map(|mut v| {
+			redact_value(&mut v);
+			v
+		});
 
 	// -- Prep Req Information
-	let ReqStamp { uuid, time_in } = req_stamp;
+	let ReqStamp {
+		req_id, time_in, ..
+	} = req_stamp;
 	let now = now_utc();
 	let duration: Duration = now - time_in;
 	// duration_ms in milliseconds with microseconds precision.
@@ -36,18 +52,20 @@ pub async fn log_request(
 
 	// Create the RequestLogLine
 	let log_line = RequestLogLine {
-		uuid: uuid.to_string(),
+		req_id,
 		timestamp: format_time(now), // LogLine timestamp ("time_out")
 		time_in: format_time(time_in),
 		duration_ms,
 
-		http_path: uri.to_string(),
+		http_path: redact_path(&uri.to_string()),
 		http_method: http_method.to_string(),
+		http_status: http_status.as_u16(),
 
 		rpc_id: rpc_info.and_then(|rpc| rpc.id.as_ref().map(|id| id.to_string())),
 		rpc_method: rpc_info.map(|rpc| rpc.method.to_string()),
 
 		user_id: ctx.map(|c| c.user_id()),
+		client_ip: client_ip.map(|ClientIp(ip)| ip.to_string()),
 
 		// -- rpc info
 		client_error_type: client_error.map(|e| e.as_ref().to_string()),
@@ -58,7 +76,22 @@ pub async fn log_request(
 
 	debug!("REQUEST LOG LINE:\n{}", json!(log_line));
 
-	// TODO - Send to cloud-watch.
+	writer::queue(RequestLogForCreate {
+		req_id: log_line.req_id,
+		time_in,
+		duration_ms,
+		user_id: log_line.user_id,
+		client_ip: log_line.client_ip,
+		http_method: log_line.http_method,
+		http_path: log_line.http_path,
+		http_status: log_line.http_status,
+		rpc_id: log_line.rpc_id,
+		rpc_method: log_line.rpc_method,
+		client_error_type: log_line.client_error_type,
+		error_type: log_line.error_type,
+		error_data: log_line.error_data.map(|data| data.to_string()),
+	})
+	.await;
 
 	Ok(())
 }
@@ -66,17 +99,19 @@ pub async fn log_request(
 #[skip_serializing_none]
 #[derive(Serialize)]
 struct RequestLogLine {
-	uuid: String,      // uuid string formatted
+	req_id: String, // x-request-id (uuid string formatted when generated)
 	timestamp: String, // (Rfc3339)
-	time_in: String,   // (Rfc3339)
+	time_in: String, // (Rfc3339)
 	duration_ms: f64,
 
 	// -- User and context attributes.
 	user_id: Option<i64>,
+	client_ip: Option<String>,
 
 	// -- http request attributes.
 	http_path: String,
 	http_method: String,
+	http_status: u16,
 
 	rpc_id: Option<String>,
 	rpc_method: Option<String>,
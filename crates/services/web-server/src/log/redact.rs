@@ -0,0 +1,72 @@
+use lib_core::config;
+use serde_json::Value;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// The path prefix of the share links, which have their token in the path
+/// (see `routes_share`).
+const SHARED_PATH_PREFIX: &str = "/api/shared/";
+
+/// Redacts, in place and at any depth, the values of the object properties
+/// matching (case insensitive) the `LOG_REDACT_FIELDS` config denylist
+/// (e.g., `pwd`, `token`, `authorization`, `cookie`).
+pub fn redact_value(value: &mut Value) {
+	redact_value_with(value, &config().LOG_REDACT_FIELDS)
+}
+
+fn redact_value_with(value: &mut Value, denylist: &[String]) {
+	match value {
+		Value::Object(map) => {
+			for (name, val) in map.iter_mut() {
+				if denylist
+					.iter()
+					.any(|field| field.eq_ignore_ascii_case(name))
+				{
+					*val = Value::String(REDACTED.to_string());
+				} else {
+					redact_value_with(val, denylist);
+				}
+			}
+		}
+		Value::Array(items) => {
+			for item in items.iter_mut() {
+				redact_value_with(item, denylist);
+			}
+		}
+		_ => (),
+	}
+}
+
+/// Redacts the secrets of a request path, i.e., the share link tokens.
+pub fn redact_path(path: &str) -> String {
+	let Some(rest) = path.strip_prefix(SHARED_PATH_PREFIX) else {
+		return path.to_string();
+	};
+	let token_end = rest.find(['/', '?']).unwrap_or(rest.len());
+
+	format!("{SHARED_PATH_PREFIX}{REDACTED}{}", &rest[token_end..])
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_redact_path() {
+		let fx_cases = [
+			("/api/rpc", "/api/rpc"),
+			("/api/shared/abc.def.ghi", "/api/shared/[REDACTED]"),
+			(
+				"/api/shared/abc.def.ghi/tasks",
+				"/api/shared/[REDACTED]/tasks",
+			),
+			("/api/shared/abc?x=1", "/api/shared/[REDACTED]?x=1"),
+		];
+
+		for (fx_path, expected) in fx_cases {
+			assert_eq!(redact_path(fx_path), expected);
+		}
+	}
+}
+// endregion: --- Tests
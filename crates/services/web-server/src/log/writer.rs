@@ -0,0 +1,126 @@
+//! The buffered request log writer.
+//!
+//! `log_request` queues the lines in a bounded channel, and the writer task
+//! inserts them in batches (at `REQUEST_LOG_BATCH_SIZE` lines, or every
+//! `REQUEST_LOG_FLUSH_MS`), so the request never waits for the db.
+//!
+//! When the buffer is full (e.g., db down), the `REQUEST_LOG_OVERFLOW` policy
+//! drops the line (counted by the `request_logs_dropped_total` metric),
+//! or blocks the request until there is room.
+
+use crate::web::metrics;
+use crate::{Error, Result};
+use lib_core::config::{config, RequestLogOverflow};
+use lib_core::ctx::Ctx;
+use lib_core::model::request_log::{RequestLogBmc, RequestLogForCreate};
+use lib_core::model::ModelManager;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, warn};
+
+/// The request logs are kept 30 days.
+const RETENTION: Duration = Duration::from_secs(30 * 24 * 3600);
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Set by `spawn_request_log_writer` (otherwise, e.g., in tests, the lines
+/// are only traced).
+static WRITER: OnceLock<mpsc::Sender<RequestLogForCreate>> = OnceLock::new();
+
+/// Spawns the request log writer (once, the next calls are no-ops).
+pub fn spawn_request_log_writer(mm: ModelManager) -> Result<()> {
+	let ctx = Ctx::service("request_log")
+		.map_err(|ex| Error::RequestLogInit(format!("{ex:?}")))?;
+	let config = config();
+	let (tx, rx) = mpsc::channel(config.REQUEST_LOG_BUFFER_SIZE as usize);
+	if WRITER.set(tx).is_err() {
+		return Ok(());
+	}
+
+	tokio::spawn(write_loop(
+		ctx,
+		mm,
+		rx,
+		config.REQUEST_LOG_BATCH_SIZE as usize,
+		Duration::from_millis(config.REQUEST_LOG_FLUSH_MS.into()),
+	));
+
+	Ok(())
+}
+
+/// Queues the log line for the writer, per the `REQUEST_LOG_OVERFLOW` policy.
+pub(super) async fn queue(log: RequestLogForCreate) {
+	let Some(writer) = WRITER.get() else {
+		return;
+	};
+
+	match config().REQUEST_LOG_OVERFLOW {
+		RequestLogOverflow::Drop => match writer.try_send(log) {
+			Ok(()) => (),
+			Err(TrySendError::Full(_)) => metrics::record_request_logs_dropped(1),
+			Err(TrySendError::Closed(_)) => warn!("request log - writer closed"),
+		},
+		RequestLogOverflow::Block => {
+			if writer.send(log).await.is_err() {
+				warn!("request log - writer closed");
+			}
+		}
+	}
+}
+
+async fn write_loop(
+	ctx: Ctx,
+	mm: ModelManager,
+	mut rx: mpsc::Receiver<RequestLogForCreate>,
+	batch_size: usize,
+	flush_interval: Duration,
+) {
+	let mut batch = Vec::with_capacity(batch_size);
+	let mut flush = tokio::time::interval(flush_interval);
+	let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+	loop {
+		tokio::select! {
+			log = rx.recv() => match log {
+				Some(log) => {
+					batch.push(log);
+					if batch.len() < batch_size {
+						continue;
+					}
+				}
+				None => {
+					flush_batch(&ctx, &mm, &mut batch).await;
+					break;
+				}
+			},
+			_ = flush.tick() => {}
+			_ = prune.tick() => {
+				match RequestLogBmc::prune(&ctx, &mm, RETENTION).await {
+					Ok(count) => debug!("request log - {count} rows pruned"),
+					Err(ex) => warn!("request log - prune failed - {ex:?}"),
+				}
+				continue;
+			}
+		}
+
+		flush_batch(&ctx, &mm, &mut batch).await;
+	}
+}
+
+/// Writes and clears the batch.
+///
+/// NOTE: A failed batch is dropped (and counted), as retrying would only
+///       grow the backlog while the db is down.
+async fn flush_batch(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	batch: &mut Vec<RequestLogForCreate>,
+) {
+	if batch.is_empty() {
+		return;
+	}
+	if let Err(ex) = RequestLogBmc::create_batch(ctx, mm, batch).await {
+		warn!("request log - {} lines not written - {ex:?}", batch.len());
+		metrics::record_request_logs_dropped(batch.len() as u64);
+	}
+	batch.clear();
+}
@@ -1,63 +1,284 @@
+mod config_reload;
 mod error;
 mod log;
 mod web;
+mod webhooks;
 
 use crate::web::{
-	mw_auth::{mw_ctx_require, mw_ctx_resolve},
+	catch_panic::catch_panic_layer,
+	compression::compression_layer,
+	metrics,
+	mw_auth::{mw_ctx_require, mw_ctx_resolve, mw_pwd_change_guard},
+	mw_capture::mw_capture,
+	mw_ip_filter::{mw_ip_filter, IpFilter},
+	mw_load_shed::{mw_load_shed, LoadShedder},
+	mw_rate_limit::{mw_rate_limit, RateLimiter},
+	mw_read_only::mw_read_only,
 	mw_req_stamp::mw_req_stamp,
+	mw_req_timeout::mw_req_timeout,
 	mw_res_map::mw_reponse_map,
-	routes_login, routes_static,
+	mw_slow_req::mw_slow_req,
+	openapi,
+	proxy_protocol::ProxyProtocolAcceptor,
+	routes_admin, routes_errors, routes_export,
+	routes_health::{self, Readiness},
+	routes_hooks, routes_import, routes_login, routes_pages, routes_rest,
+	routes_share, routes_static, routes_ws,
 	rpc::{self, RpcState},
 };
 
 pub use self::error::{Error, Result};
 
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use axum::{middleware, Router};
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
 
-use lib_core::{_dev_utils, model::ModelManager};
+use lib_core::config::LogFormat;
+use lib_core::model::{event, migration, outbox, sync, trash, ModelManager};
+use lib_core::{broker, config};
 use tower_cookies::CookieManagerLayer;
 
-use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing::{error, info, warn};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-	tracing_subscriber::fmt()
-		.without_time()
-		.with_target(false)
-		.with_env_filter(EnvFilter::from_default_env())
-		.init();
+	// -- External secret manager (must be before the first `config()`)
+	let secret_cache = config::init_secrets()
+		.await
+		.map_err(|ex| Error::SecretsInit(ex.to_string()))?;
+
+	// Note: The log filter is reloadable (see `config_reload` module).
+	let config_handle = config().RELOADABLE.clone();
+	let (log_filter, log_filter_handle) =
+		reload::Layer::new(config_reload::log_env_filter(&config_handle.current()));
+	let tracing_registry = tracing_subscriber::registry().with(log_filter);
+	match config().LOG_FORMAT {
+		// Note: The current span holds the request_id and user_id fields.
+		LogFormat::Json => tracing_registry
+			.with(
+				fmt::layer()
+					.json()
+					.with_current_span(true)
+					.with_span_list(false),
+			)
+			.init(),
+		LogFormat::Pretty => tracing_registry
+			.with(fmt::layer().without_time().with_target(false))
+			.init(),
+	}
+
+	// -- Config reload (SIGHUP, or admin `POST /config/reload`)
+	config_reload::spawn_log_filter_reload(config_handle.clone(), log_filter_handle);
+	#[cfg(unix)]
+	config_reload::spawn_sighup_reload(config_handle.clone())
+		.map_err(|ex| Error::SignalListen(ex.to_string()))?;
 
-	// -- FOR DEV ONLY
-	_dev_utils::init_dev().await;
+	// -- FOR DEV ONLY (recreates the db, `dev-utils` feature)
+	#[cfg(feature = "dev-utils")]
+	if config().ENV == lib_core::config::AppEnv::Dev {
+		lib_core::_dev_utils::init_dev().await;
+	}
 
 	// Initialze ModelManager.
 	let mm = ModelManager::new().await?;
+	// -- Check the db schema (the readiness stays false when not matching).
+	let schema_ok = check_schema(&mm).await?;
+	if let Some(secret_cache) = secret_cache {
+		config_reload::spawn_secret_rotation(secret_cache, mm.clone());
+	}
+	// -- Background: publish the model events (see lib-core `model::outbox`).
+	outbox::spawn_relay(mm.clone());
+	event::spawn_subscriber(&mm, metrics::DomainEventMetrics);
+	webhooks::spawn_webhooks(mm.clone())?;
+	broker::spawn_broker_publisher(&mm)
+		.await
+		.map_err(|ex| Error::BrokerConnect(ex.to_string()))?;
+	// -- Background: prune the delta sync tombstones (see lib-core `model::sync`).
+	sync::spawn_tombstone_pruner(mm.clone());
+	// -- Background: purge the expired trashed tasks (see lib-core `model::trash`).
+	trash::spawn_trash_purger(mm.clone());
+	// -- Background: write the request logs (see `log::writer`).
+	log::spawn_request_log_writer(mm.clone())?;
+	let readiness = Readiness::default();
 	// -- Define Routes
 	let rpc_state = RpcState { mm: mm.clone() };
 
 	let routes_rpc =
 		rpc::routes(rpc_state).route_layer(middleware::from_fn(mw_ctx_require));
+	let routes_rest = routes_rest::routes(mm.clone())
+		.merge(routes_export::routes(mm.clone()))
+		.merge(routes_import::routes(mm.clone()))
+		.route_layer(middleware::from_fn(mw_read_only));
+	#[cfg(feature = "graphql")]
+	let routes_rest = routes_rest.merge(web::graphql::routes(mm.clone()));
+	let routes_rest = routes_rest
+		.route_layer(middleware::from_fn(mw_pwd_change_guard))
+		.route_layer(middleware::from_fn(mw_ctx_require));
 
-	let routes_all = Router::new()
+	// -- The /api tree (rate limited)
+	let rate_limiter = RateLimiter::new(config_handle.clone());
+	let routes_api = Router::new()
 		.merge(routes_login::routes(mm.clone()))
-		.nest("/api", routes_rpc)
+		.merge(routes_share::routes(mm.clone()))
+		.merge(
+			routes_hooks::routes(mm.clone())
+				.route_layer(middleware::from_fn(mw_read_only)),
+		)
+		.nest("/api", routes_rpc.merge(routes_rest))
+		.layer(middleware::from_fn_with_state(
+			rate_limiter.clone(),
+			mw_rate_limit,
+		));
+
+	// -- The operational routes, on the admin listener when configured.
+	let admin_addr = config().ADMIN_ADDR;
+	let routes_ops = match admin_addr {
+		Some(admin_addr) => {
+			let metrics = metrics::install_recorder()?;
+			let routes_admin = routes_admin::routes(
+				mm.clone(),
+				readiness.clone(),
+				metrics,
+				config_handle.clone(),
+			);
+			// Note: Bound here, so a bind failure fails the startup.
+			let admin_server = axum::Server::try_bind(&admin_addr)
+				.map_err(|ex| Error::AdminBind(ex.to_string()))?;
+			info!("{:<12} - {admin_addr} (admin)\n", "LISTENING");
+			tokio::spawn(async move {
+				if let Err(ex) =
+					admin_server.serve(routes_admin.into_make_service()).await
+				{
+					error!("{:<12} - admin server fail - {ex:?}", "ADMIN");
+				}
+			});
+			Router::new()
+		}
+		None => routes_health::routes(mm.clone(), readiness.clone()),
+	};
+
+	let routes_all = Router::new()
+		.merge(routes_api)
+		.merge(routes_ops)
+		.merge(
+			routes_ws::routes(mm.clone())
+				.route_layer(middleware::from_fn(mw_pwd_change_guard)),
+		)
+		.merge(routes_pages::routes(mm.clone()))
+		.merge(openapi::routes())
+		.merge(routes_errors::routes())
+		.layer(middleware::from_fn(mw_req_timeout))
+		.layer(catch_panic_layer())
+		.layer(middleware::from_fn(mw_slow_req))
 		.layer(middleware::map_response(mw_reponse_map))
 		.layer(middleware::from_fn_with_state(mm.clone(), mw_ctx_resolve))
+		.layer(middleware::from_fn(mw_capture))
 		.layer(middleware::from_fn(mw_req_stamp))
 		.layer(CookieManagerLayer::new())
-		.fallback_service(routes_static::serve_dir());
+		.fallback_service(routes_static::serve_dir())
+		.layer(compression_layer())
+		// Note: Outermost, so the shed and denied requests do no work
+		//       (see mw_load_shed, mw_ip_filter).
+		.layer(middleware::from_fn_with_state(
+			LoadShedder::from_config(),
+			mw_load_shed,
+		))
+		.layer(middleware::from_fn_with_state(
+			IpFilter::from_config(),
+			mw_ip_filter,
+		));
+
+	// -- Background: prune the rate limiter buckets.
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(Duration::from_secs(60));
+		loop {
+			interval.tick().await;
+			rate_limiter.retain_recent();
+		}
+	});
 
 	// region:    --- Start Server
-	let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
-	info!("{:<12} - {addr}\n", "LISTENING");
-	axum::Server::bind(&addr)
-		.serve(routes_all.into_make_service())
-		.await
-		.unwrap();
+	let addr = config().WEB_ADDR;
+	// All startup tasks done, the instance can take traffic (if the schema matches).
+	readiness.set_ready(schema_ok);
+	let make_service =
+		routes_all.into_make_service_with_connect_info::<SocketAddr>();
+	let proxy_protocol = config().PROXY_PROTOCOL;
+	match (load_tls_config().await?, proxy_protocol) {
+		(Some(tls_config), true) => {
+			info!("{:<12} - {addr} (https, proxy protocol)\n", "LISTENING");
+			let acceptor =
+				RustlsAcceptor::new(tls_config).acceptor(ProxyProtocolAcceptor);
+			axum_server::bind(addr)
+				.acceptor(acceptor)
+				.serve(make_service)
+				.await
+				.unwrap();
+		}
+		(Some(tls_config), false) => {
+			info!("{:<12} - {addr} (https)\n", "LISTENING");
+			axum_server::bind_rustls(addr, tls_config)
+				.serve(make_service)
+				.await
+				.unwrap();
+		}
+		(None, true) => {
+			info!("{:<12} - {addr} (proxy protocol)\n", "LISTENING");
+			axum_server::bind(addr)
+				.acceptor(ProxyProtocolAcceptor)
+				.serve(make_service)
+				.await
+				.unwrap();
+		}
+		(None, false) => {
+			info!("{:<12} - {addr}\n", "LISTENING");
+			axum::Server::bind(&addr).serve(make_service).await.unwrap();
+		}
+	}
 	// endregion: --- Start Server
 
 	Ok(())
 }
+
+/// Checks the applied db migrations match the embedded ones
+/// (see lib-core `model::migration`), unless `MIGRATION_CHECK_OVERRIDE`.
+async fn check_schema(mm: &ModelManager) -> Result<bool> {
+	let status = migration::check_migrations(mm).await?;
+	if status.is_up_to_date() {
+		return Ok(true);
+	}
+
+	if config().MIGRATION_CHECK_OVERRIDE {
+		warn!(
+			"{:<12} - db schema not matching (overridden) - {status:?}",
+			"STARTUP"
+		);
+		Ok(true)
+	} else {
+		error!(
+			"{:<12} - db schema not matching, not ready - {status:?}",
+			"STARTUP"
+		);
+		Ok(false)
+	}
+}
+
+/// Returns the rustls config when the tls cert/key paths are configured.
+async fn load_tls_config() -> Result<Option<RustlsConfig>> {
+	let config = config();
+	let (Some(cert_path), Some(key_path)) =
+		(&config.TLS_CERT_PATH, &config.TLS_KEY_PATH)
+	else {
+		return Ok(None);
+	};
+
+	let tls_config = RustlsConfig::from_pem_file(cert_path, key_path)
+		.await
+		.map_err(|ex| Error::TlsConfigLoad(ex.to_string()))?;
+
+	Ok(Some(tls_config))
+}
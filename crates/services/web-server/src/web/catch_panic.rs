@@ -0,0 +1,75 @@
+use crate::web::Error;
+use axum::response::{IntoResponse, Response};
+use lib_core::config::config;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use tower_http::catch_panic::CatchPanicLayer;
+use tracing::error;
+
+type PanicHandler = fn(Box<dyn Any + Send + 'static>) -> Response;
+
+/// Catches the handler (and inner middleware) panics, so they become a
+/// `web::Error::Panic`, which mw_res_map turns into the standard 500 error
+/// envelope (and request log line), rather than a dropped connection.
+///
+/// NOTE: Must be layered inside mw_res_map and mw_req_stamp, so the error is
+///       mapped, and logged within the request span (with the request_id).
+pub fn catch_panic_layer() -> CatchPanicLayer<PanicHandler> {
+	CatchPanicLayer::custom(handle_panic as PanicHandler)
+}
+
+fn handle_panic(panic: Box<dyn Any + Send + 'static>) -> Response {
+	let message = panic
+		.downcast_ref::<String>()
+		.cloned()
+		.or_else(|| panic.downcast_ref::<&str>().map(|msg| msg.to_string()))
+		.unwrap_or_else(|| "unknown panic payload".to_string());
+
+	let backtrace = config()
+		.ERROR_DETAIL
+		.then(|| Backtrace::force_capture().to_string());
+
+	error!(panic = %message, "HANDLER PANIC");
+
+	Error::Panic { message, backtrace }.into_response()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use axum::body::Body;
+	use axum::http::{Request, StatusCode};
+	use axum::routing::get;
+	use axum::Router;
+	use tower::ServiceExt;
+
+	#[tokio::test]
+	async fn test_catch_panic_layer_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let app = Router::new()
+			.route("/panic", get(fx_panic_handler))
+			.layer(catch_panic_layer());
+
+		// -- Exec
+		let res = app
+			.oneshot(Request::get("/panic").body(Body::empty())?)
+			.await?;
+
+		// -- Check
+		assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+		let Some(Error::Panic { message, .. }) = res.extensions().get::<Error>()
+		else {
+			panic!("web::Error::Panic not in the response extensions");
+		};
+		assert_eq!(message, "fx panic");
+
+		Ok(())
+	}
+
+	async fn fx_panic_handler() -> &'static str {
+		panic!("fx panic")
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,234 @@
+use crate::web::proxy_protocol::ProxiedAddr;
+use crate::web::{Error, Result};
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use ipnet::IpNet;
+use lib_core::config::{config, ClientIpHeader};
+use std::net::{IpAddr, SocketAddr};
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const FORWARDED: &str = "forwarded";
+
+/// The client ip, resolved from the peer address (or the PROXY protocol one),
+/// and from the `CLIENT_IP_HEADER` when the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+// region:    --- ClientIp Extractor
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+	type Rejection = Error;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+		// Note: The PROXY protocol address is from a trusted proxy
+		//       (see `ProxyProtocolAcceptor`).
+		let proxied_ip = parts
+			.extensions
+			.get::<ProxiedAddr>()
+			.and_then(|ProxiedAddr(addr)| addr.map(|addr| addr.ip()));
+		let peer_ip = match proxied_ip {
+			Some(ip) => ip,
+			None => parts
+				.extensions
+				.get::<ConnectInfo<SocketAddr>>()
+				.map(|ConnectInfo(addr)| addr.ip())
+				.ok_or(Error::ClientIpNotInRequestExt)?,
+		};
+
+		let config = config();
+		let ip = resolve_client_ip(
+			&parts.headers,
+			peer_ip,
+			&config.TRUSTED_PROXIES,
+			config.CLIENT_IP_HEADER,
+		);
+
+		Ok(ClientIp(ip))
+	}
+}
+// endregion: --- ClientIp Extractor
+
+/// Walks the forwarded chain (of the `header`) from the right (closest hop),
+/// skipping the trusted proxies. The first untrusted hop is the client.
+/// An unparseable hop stops the walk (the hops on its left might be spoofed),
+/// so the last trusted one is taken.
+///
+/// NOTE: The header is only honored when the peer itself is a trusted proxy,
+///       as otherwise any client could spoof it.
+fn resolve_client_ip(
+	headers: &HeaderMap,
+	peer_ip: IpAddr,
+	trusted_proxies: &[IpNet],
+	header: ClientIpHeader,
+) -> IpAddr {
+	let is_trusted =
+		|ip: &IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+
+	if !is_trusted(&peer_ip) {
+		return peer_ip;
+	}
+
+	let mut last_trusted = peer_ip;
+	for hop in forwarded_ips(headers, header).into_iter().rev() {
+		match hop {
+			Some(ip) if is_trusted(&ip) => last_trusted = ip,
+			Some(ip) => return ip,
+			None => break,
+		}
+	}
+
+	// All hops trusted (the leftmost one), or stopped at an unparseable hop.
+	last_trusted
+}
+
+/// The hop ips of the forwarded chain (the client first), `None` for the
+/// unparseable hops, e.g., unknown or obfuscated (`for=unknown`, `for=_hidden`).
+fn forwarded_ips(
+	headers: &HeaderMap,
+	header: ClientIpHeader,
+) -> Vec<Option<IpAddr>> {
+	let name = match header {
+		ClientIpHeader::XForwardedFor => X_FORWARDED_FOR,
+		ClientIpHeader::Forwarded => FORWARDED,
+	};
+	let mut hops = Vec::new();
+	for val in headers.get_all(name) {
+		let Ok(val) = val.to_str() else {
+			hops.push(None);
+			continue;
+		};
+		hops.extend(val.split(',').map(|hop| match header {
+			ClientIpHeader::XForwardedFor => hop.trim().parse::<IpAddr>().ok(),
+			ClientIpHeader::Forwarded => parse_forwarded_for(hop),
+		}));
+	}
+
+	hops
+}
+
+/// The `for` ip of a `Forwarded` element, e.g., `for=192.0.2.60;proto=http`,
+/// or `for="[2001:db8::17]:4711"`.
+fn parse_forwarded_for(element: &str) -> Option<IpAddr> {
+	let node = element.split(';').find_map(|pair| {
+		let (key, value) = pair.trim().split_once('=')?;
+		key.eq_ignore_ascii_case("for")
+			.then(|| value.trim_matches('"'))
+	})?;
+
+	// `[ipv6]` or `[ipv6]:port`
+	if let Some(rest) = node.strip_prefix('[') {
+		return rest.split_once(']')?.0.parse().ok();
+	}
+	// `ipv4` or `ipv4:port`
+	node.split(':').next()?.parse().ok()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use axum::http::HeaderValue;
+
+	#[test]
+	fn test_parse_forwarded_for_ok() {
+		let fx_cases = [
+			(
+				"for=192.0.2.60;proto=http;by=203.0.113.43",
+				Some("192.0.2.60"),
+			),
+			("For=\"192.0.2.60:8080\"", Some("192.0.2.60")),
+			(
+				"for=\"[2001:db8:cafe::17]:4711\"",
+				Some("2001:db8:cafe::17"),
+			),
+			(" proto=https;for=198.51.100.17", Some("198.51.100.17")),
+			("for=unknown", None),
+			("for=_hidden", None),
+			("proto=https", None),
+		];
+
+		for (element, expected) in fx_cases {
+			let expected = expected.map(|ip| ip.parse::<IpAddr>().unwrap());
+			assert_eq!(parse_forwarded_for(element), expected, "{element}");
+		}
+	}
+
+	#[test]
+	fn test_resolve_client_ip_forwarded_ok() {
+		// -- Setup & Fixtures
+		let fx_trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+		let fx_peer: IpAddr = "10.0.0.2".parse().unwrap();
+		let mut fx_headers = HeaderMap::new();
+		fx_headers.append(
+			FORWARDED,
+			HeaderValue::from_static("for=198.51.100.17, for=10.0.0.3"),
+		);
+		fx_headers.append(FORWARDED, HeaderValue::from_static("for=10.0.0.1"));
+		fx_headers.append(X_FORWARDED_FOR, HeaderValue::from_static("192.0.2.1"));
+
+		// -- Exec & Check
+		let ip = resolve_client_ip(
+			&fx_headers,
+			fx_peer,
+			&fx_trusted,
+			ClientIpHeader::Forwarded,
+		);
+		assert_eq!(ip, "198.51.100.17".parse::<IpAddr>().unwrap());
+
+		let ip = resolve_client_ip(
+			&fx_headers,
+			fx_peer,
+			&fx_trusted,
+			ClientIpHeader::XForwardedFor,
+		);
+		assert_eq!(ip, "192.0.2.1".parse::<IpAddr>().unwrap());
+
+		// Untrusted peer, the header is ignored.
+		let fx_peer: IpAddr = "192.0.2.9".parse().unwrap();
+		let ip = resolve_client_ip(
+			&fx_headers,
+			fx_peer,
+			&fx_trusted,
+			ClientIpHeader::Forwarded,
+		);
+		assert_eq!(ip, fx_peer);
+	}
+
+	#[test]
+	fn test_resolve_client_ip_unparseable_hop_ok() {
+		// -- Setup & Fixtures
+		let fx_trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+		let fx_peer: IpAddr = "10.0.0.2".parse().unwrap();
+		let mut fx_headers = HeaderMap::new();
+		// The client spoofed ip, then a garbage hop, then the trusted proxy.
+		fx_headers.append(
+			X_FORWARDED_FOR,
+			HeaderValue::from_static("203.0.113.7, garbage, 10.0.0.1"),
+		);
+		fx_headers.append(
+			FORWARDED,
+			HeaderValue::from_static("for=203.0.113.7, for=_hidden"),
+		);
+
+		// -- Exec & Check - stops at the unparseable hop, the last trusted one.
+		let ip = resolve_client_ip(
+			&fx_headers,
+			fx_peer,
+			&fx_trusted,
+			ClientIpHeader::XForwardedFor,
+		);
+		assert_eq!(ip, "10.0.0.1".parse::<IpAddr>().unwrap());
+
+		// -- Exec & Check - the peer, when the nearest hop is unparseable.
+		let ip = resolve_client_ip(
+			&fx_headers,
+			fx_peer,
+			&fx_trusted,
+			ClientIpHeader::Forwarded,
+		);
+		assert_eq!(ip, fx_peer);
+	}
+}
+// endregion: --- Tests
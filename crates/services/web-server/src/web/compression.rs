@@ -0,0 +1,71 @@
+use axum::http::{header, Response};
+use lib_core::config;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+
+/// Builds the gzip/brotli response compression layer from the config.
+///
+/// NOTE: When disabled, the layer is still applied (keeping one router type)
+///       but with all the algorithms turned off, so it is a pass-through.
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+	let config = config();
+	let enabled = config.COMPRESSION_ENABLED;
+
+	let predicate = SizeAbove::new(config.COMPRESSION_MIN_SIZE)
+		.and(NotForContentType::GRPC)
+		.and(NotForContentType::IMAGES)
+		.and(NotForContentType::const_new("text/event-stream"))
+		.and(NotAlreadyCompressed)
+		.and(NotPartialContent);
+
+	CompressionLayer::new()
+		.gzip(enabled)
+		.br(enabled)
+		.compress_when(predicate)
+}
+
+/// Content type prefixes which are already compressed, so not worth re-compressing.
+/// (images, except svg, are excluded by `NotForContentType::IMAGES`)
+const ALREADY_COMPRESSED_CONTENT_TYPES: &[&str] = &[
+	"application/gzip",
+	"application/zip",
+	"application/x-brotli",
+	"application/pdf",
+	"font/woff",
+	"audio/",
+	"video/",
+];
+
+#[derive(Clone, Copy)]
+struct NotAlreadyCompressed;
+
+impl Predicate for NotAlreadyCompressed {
+	fn should_compress<B>(&self, response: &Response<B>) -> bool
+	where
+		B: axum::body::HttpBody,
+	{
+		let content_type = response
+			.headers()
+			.get(header::CONTENT_TYPE)
+			.and_then(|val| val.to_str().ok())
+			.unwrap_or_default();
+
+		!ALREADY_COMPRESSED_CONTENT_TYPES
+			.iter()
+			.any(|prefix| content_type.starts_with(prefix))
+	}
+}
+
+/// Range responses (`206`, `416`) have a `Content-Range` about the identity
+/// bytes, so they must never be compressed.
+#[derive(Clone, Copy)]
+struct NotPartialContent;
+
+impl Predicate for NotPartialContent {
+	fn should_compress<B>(&self, response: &Response<B>) -> bool
+	where
+		B: axum::body::HttpBody,
+	{
+		!response.headers().contains_key(header::CONTENT_RANGE)
+	}
+}
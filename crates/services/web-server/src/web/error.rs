@@ -4,10 +4,12 @@ use axum::{
 	response::{IntoResponse, Response},
 };
 use derive_more::From;
-use lib_core::{model, pwd, token};
+use lib_core::config::config;
+use lib_core::{ctx, model, pwd, token};
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
 use tracing::debug;
+use utoipa::ToSchema;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -18,26 +20,122 @@ pub enum Error {
 	// -- RPC
 	RpcMethodUnknown(String),
 	RpcIntoParamsMissing,
+	/// The `params` json does not deserialize into the method params.
+	RpcIntoParamsInvalid(#[serde_as(as = "DisplayFromStr")] serde_json::Error),
+	/// The method requires a user role (see `RpcRouter::require_role`).
+	RoleRequired {
+		method: String,
+		role: &'static str,
+	},
+
+	// -- Admin
+	/// An admin cannot disable (or demote) itself (see `admin_rpc`).
+	AdminSelfLockout {
+		method: &'static str,
+	},
 
 	// -- Login
 	LoginFailUsernameNotFound,
 	LoginFailUserHasNoPwd {
 		user_id: i64,
 	},
+	LoginFailUserInactive {
+		user_id: i64,
+	},
 	LoginFail {
 		user_id: i64,
 		cause: pwd::Error,
 	},
 
+	// -- Pwd change
+	PwdChangeRequired,
+	ChangePwdUserHasNoPwd {
+		user_id: i64,
+	},
+	ChangePwdFail {
+		user_id: i64,
+		cause: pwd::Error,
+	},
+	ChangePwdInvalid {
+		reason: &'static str,
+	},
+
 	// -- ReqStamp
 	ReqStampNotInResponseExt,
 
+	// -- ClientIp
+	ClientIpNotInRequestExt,
+	IpNotAllowed {
+		ip: String,
+	},
+
+	// -- RateLimit
+	RateLimited {
+		retry_after_sec: u64,
+	},
+
+	// -- Timeout (mw_req_timeout)
+	RequestTimeout,
+
+	// -- Load shedding (mw_load_shed)
+	Overloaded {
+		retry_after_sec: u64,
+	},
+
+	// -- Read-only mode (see `mw_read_only`)
+	ReadOnlyMode {
+		/// The rpc method, or the http method and path.
+		mutation: String,
+	},
+
+	// -- Rest
+	RestInvalidQuery {
+		param: &'static str,
+		cause: String,
+	},
+	RestPreconditionRequired,
+	RestPreconditionFailed {
+		/// The current entity etag.
+		etag: String,
+	},
+
+	// -- Import
+	ImportInvalidFile {
+		reason: String,
+	},
+
+	// -- Hooks (routes_hooks)
+	HookSourceUnknown(String),
+	HookSignatureInvalid {
+		hook_source: &'static str,
+	},
+	HookPayloadInvalid {
+		reason: String,
+	},
+
+	// -- Templates
+	TemplateRender(String),
+
+	// -- Panic (caught by catch_panic_layer)
+	Panic {
+		message: String,
+		/// Captured in the `dev` env only.
+		backtrace: Option<String>,
+	},
+
+	// -- Ws
+	WsEventsInvalidProjectId {
+		value: String,
+	},
+
 	// -- CtxExtError
 	#[from]
 	CtxExt(web::mw_auth::CtxExtError),
 
 	// -- Modules
 	#[from]
+	Ctx(ctx::Error),
+	#[from]
 	Model(model::Error),
 	#[from]
 	Pwd(pwd::Error),
@@ -82,6 +180,16 @@ impl std::error::Error for Error {}
 
 /// From the root error to the http status code and ClientError
 impl Error {
+	/// The full server error (e.g., db error, panic backtrace) for the error
+	/// responses, only when `ERROR_DETAIL` is enabled (e.g., dev env).
+	pub fn dev_detail(&self) -> Option<serde_json::Value> {
+		if config().ERROR_DETAIL {
+			serde_json::to_value(self).ok()
+		} else {
+			None
+		}
+	}
+
 	pub fn client_status_and_error(&self) -> (StatusCode, ClientError) {
 		use web::Error::*;
 
@@ -92,14 +200,247 @@ impl Error {
 			| LoginFailUserHasNoPwd { .. }
 			| LoginFail { .. } => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
 
+			// -- Account disabled
+			//    (at login with the right pwd, and for a valid token)
+			LoginFailUserInactive { .. }
+			| CtxExt(web::mw_auth::CtxExtError::UserInactive) => {
+				(StatusCode::FORBIDDEN, ClientError::ACCOUNT_DISABLED)
+			}
+
 			//-- Auth
-			CtxExt(_) => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+			CtxExt(_)
+			| Model(model::Error::ShareLinkInvalid)
+			| HookSourceUnknown(_)
+			| HookSignatureInvalid { .. } => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+
+			// -- Pwd change
+			PwdChangeRequired => {
+				(StatusCode::FORBIDDEN, ClientError::PWD_CHANGE_REQUIRED)
+			}
+
+			// -- Role
+			RoleRequired { role, .. } => {
+				(StatusCode::FORBIDDEN, ClientError::ROLE_REQUIRED { role })
+			}
+			AdminSelfLockout { .. } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("id".to_string()),
+					reason: "cannot be the ctx user".to_string(),
+				},
+			),
+			ChangePwdUserHasNoPwd { .. } | ChangePwdFail { .. } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("pwd_old".to_string()),
+					reason: "not the current pwd".to_string(),
+				},
+			),
+			ChangePwdInvalid { reason } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("pwd_new".to_string()),
+					reason: reason.to_string(),
+				},
+			),
+
+			// -- ClientIp
+			// Note: An unknown client ip cannot be allowed (fails closed).
+			IpNotAllowed { .. } | ClientIpNotInRequestExt => {
+				(StatusCode::FORBIDDEN, ClientError::IP_NOT_ALLOWED)
+			}
+
+			// -- RateLimit
+			RateLimited { retry_after_sec } => (
+				StatusCode::TOO_MANY_REQUESTS,
+				ClientError::RATE_LIMITED {
+					retry_after_sec: *retry_after_sec,
+				},
+			),
+
+			// -- Rpc
+			RpcMethodUnknown(method) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::RPC_METHOD_UNKNOWN {
+					method: method.to_string(),
+				},
+			),
+
+			// -- Invalid params
+			RpcIntoParamsMissing => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("params".to_string()),
+					reason: "missing".to_string(),
+				},
+			),
+			RpcIntoParamsInvalid(ex) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: None,
+					reason: ex.to_string(),
+				},
+			),
+			RestInvalidQuery { param, cause } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some(param.to_string()),
+					reason: cause.to_string(),
+				},
+			),
+			ImportInvalidFile { reason } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("file".to_string()),
+					reason: reason.to_string(),
+				},
+			),
+			HookPayloadInvalid { reason } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("body".to_string()),
+					reason: reason.to_string(),
+				},
+			),
+			WsEventsInvalidProjectId { value } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("project_ids".to_string()),
+					reason: format!("not a project id: {value}"),
+				},
+			),
+			Model(model::Error::ListLimitOverMax { max, actual }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("list_options.limit".to_string()),
+					reason: format!("{actual} is over the max of {max}"),
+				},
+			),
+			Model(model::Error::ListOrderByUnknown { column }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("list_options.order_bys".to_string()),
+					reason: format!("unknown column '{column}'"),
+				},
+			),
+			Model(model::Error::SyncCursorExpired { max_age_days }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("since".to_string()),
+					reason: format!(
+						"older than {max_age_days} days, a full sync is required"
+					),
+				},
+			),
+			Model(model::Error::WebhookInvalid { field, reason }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some(format!("data.{field}")),
+					reason: reason.to_string(),
+				},
+			),
+			Model(model::Error::ShareLinkDurationInvalid { max_days }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("data.duration_days".to_string()),
+					reason: format!("not between 1 and {max_days}"),
+				},
+			),
+			Model(model::Error::TimezoneUnknown { timezone }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("timezone".to_string()),
+					reason: format!("unknown timezone '{timezone}'"),
+				},
+			),
+			Model(model::Error::LocaleUnsupported { locale }) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("locale".to_string()),
+					reason: format!("unsupported locale '{locale}'"),
+				},
+			),
+			Model(model::Error::ModqlIntoSea(ex)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::INVALID_PARAMS {
+					field: Some("filters".to_string()),
+					reason: ex.to_string(),
+				},
+			),
+
+			// -- Load shedding
+			Overloaded { retry_after_sec } => (
+				StatusCode::SERVICE_UNAVAILABLE,
+				ClientError::SERVICE_OVERLOADED {
+					retry_after_sec: *retry_after_sec,
+				},
+			),
+
+			// -- Read-only mode
+			ReadOnlyMode { .. } => {
+				(StatusCode::SERVICE_UNAVAILABLE, ClientError::READ_ONLY_MODE)
+			}
+
+			// -- Rest If-Match
+			RestPreconditionRequired => (
+				StatusCode::PRECONDITION_REQUIRED,
+				ClientError::PRECONDITION_REQUIRED,
+			),
+			RestPreconditionFailed { .. }
+			| Model(model::Error::EntityModified { .. }) => (
+				StatusCode::PRECONDITION_FAILED,
+				ClientError::PRECONDITION_FAILED,
+			),
+
+			Model(model::Error::ServiceUnavailable { retry_after_sec }) => (
+				StatusCode::SERVICE_UNAVAILABLE,
+				ClientError::SERVICE_UNAVAILABLE {
+					retry_after_sec: *retry_after_sec,
+				},
+			),
+
+			// -- Timeout
+			RequestTimeout | Model(model::Error::DeadlineExceeded) => (
+				StatusCode::SERVICE_UNAVAILABLE,
+				ClientError::REQUEST_TIMEOUT,
+			),
 
 			// -- Model
 			Model(model::Error::EntityNotFound { entity, id }) => (
 				StatusCode::BAD_REQUEST,
 				ClientError::ENTITY_NOT_FOUND { entity, id: *id },
 			),
+			Model(model::Error::UniqueViolation { entity, field }) => (
+				StatusCode::CONFLICT,
+				ClientError::ENTITY_ALREADY_EXISTS {
+					entity: entity.to_string(),
+					field: field.clone(),
+				},
+			),
+			Model(model::Error::UserAlreadyExists { .. }) => (
+				StatusCode::CONFLICT,
+				ClientError::ENTITY_ALREADY_EXISTS {
+					entity: "user".to_string(),
+					field: Some("username".to_string()),
+				},
+			),
+			Model(model::Error::EntityInUse {
+				entity, dependent, ..
+			}) => (
+				StatusCode::CONFLICT,
+				ClientError::ENTITY_IN_USE { entity, dependent },
+			),
+			Model(model::Error::QuotaExceeded { quota, max }) => (
+				StatusCode::FORBIDDEN,
+				ClientError::QUOTA_EXCEEDED { quota, max: *max },
+			),
+			Model(model::Error::ForeignKeyViolation { entity, field }) => (
+				StatusCode::CONFLICT,
+				ClientError::ENTITY_REFERENCE_VIOLATION {
+					entity: entity.to_string(),
+					field: field.clone(),
+				},
+			),
 
 			// -- Fallback.
 			_ => (
@@ -110,14 +451,383 @@ impl Error {
 	}
 }
 
+/// The errors sent to the clients, where the variant name is the `message`,
+/// and the eventual fields the `detail` of the error envelope.
+///
+/// IMPORTANT: The codes and messages are a client contract, so they can be added,
+///            but not renamed or removed (see `CLIENT_ERROR_CATALOG`).
 #[derive(Debug, Serialize, strum_macros::AsRefStr)]
 #[serde(tag = "message", content = "detail")]
 #[allow(non_camel_case_types)]
 pub enum ClientError {
 	LOGIN_FAIL,
 	NO_AUTH,
-	ENTITY_NOT_FOUND { entity: &'static str, id: i64 },
+	ACCOUNT_DISABLED,
+	PWD_CHANGE_REQUIRED,
+	ROLE_REQUIRED {
+		role: &'static str,
+	},
+	ENTITY_NOT_FOUND {
+		entity: &'static str,
+		id: i64,
+	},
+	/// `field` is the unique key columns, when known.
+	ENTITY_ALREADY_EXISTS {
+		entity: String,
+		field: Option<String>,
+	},
+	/// `dependent` is the entity referencing it.
+	ENTITY_IN_USE {
+		entity: &'static str,
+		dependent: &'static str,
+	},
+	/// `field` is the reference columns, when known.
+	ENTITY_REFERENCE_VIOLATION {
+		entity: String,
+		field: Option<String>,
+	},
+	QUOTA_EXCEEDED {
+		quota: &'static str,
+		max: u32,
+	},
+	PRECONDITION_REQUIRED,
+	PRECONDITION_FAILED,
+	IP_NOT_ALLOWED,
+	RATE_LIMITED {
+		retry_after_sec: u64,
+	},
+	/// `field` is the path of the invalid param, when known.
+	INVALID_PARAMS {
+		field: Option<String>,
+		reason: String,
+	},
+	RPC_METHOD_UNKNOWN {
+		method: String,
+	},
+	REQUEST_TIMEOUT,
+	READ_ONLY_MODE,
+	SERVICE_OVERLOADED {
+		retry_after_sec: u64,
+	},
+	SERVICE_UNAVAILABLE {
+		retry_after_sec: u64,
+	},
 
 	SERVICE_ERROR,
 }
+
+impl ClientError {
+	/// The stable, machine-readable code of this error.
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::LOGIN_FAIL => "auth.login_fail",
+			Self::NO_AUTH => "auth.no_auth",
+			Self::ACCOUNT_DISABLED => "auth.account_disabled",
+			Self::PWD_CHANGE_REQUIRED => "auth.pwd_change_required",
+			Self::ROLE_REQUIRED { .. } => "auth.role_required",
+			Self::ENTITY_NOT_FOUND { .. } => "entity.not_found",
+			Self::ENTITY_ALREADY_EXISTS { .. } => "entity.already_exists",
+			Self::ENTITY_IN_USE { .. } => "entity.in_use",
+			Self::ENTITY_REFERENCE_VIOLATION { .. } => "entity.reference_violation",
+			Self::QUOTA_EXCEEDED { .. } => "quota.exceeded",
+			Self::PRECONDITION_REQUIRED => "entity.precondition_required",
+			Self::PRECONDITION_FAILED => "entity.precondition_failed",
+			Self::IP_NOT_ALLOWED => "access.ip_not_allowed",
+			Self::RATE_LIMITED { .. } => "access.rate_limited",
+			Self::INVALID_PARAMS { .. } => "params.invalid",
+			Self::RPC_METHOD_UNKNOWN { .. } => "rpc.method_unknown",
+			Self::REQUEST_TIMEOUT => "service.timeout",
+			Self::READ_ONLY_MODE => "service.read_only",
+			Self::SERVICE_OVERLOADED { .. } => "service.overloaded",
+			Self::SERVICE_UNAVAILABLE { .. } => "service.unavailable",
+			Self::SERVICE_ERROR => "service.error",
+		}
+	}
+}
+
 // endregion: --- Client Error
+
+// region:    --- Client Error Catalog
+
+/// The documentation of one `ClientError`, as listed by `/api/errors`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClientErrorInfo {
+	pub code: &'static str,
+	pub message: &'static str,
+	pub status: u16,
+	/// The field names of the `detail` object (empty when no detail).
+	pub detail: &'static [&'static str],
+	pub description: &'static str,
+}
+
+pub const CLIENT_ERROR_CATALOG: &[ClientErrorInfo] = &[
+	ClientErrorInfo {
+		code: "auth.login_fail",
+		message: "LOGIN_FAIL",
+		status: 403,
+		detail: &[],
+		description: "Wrong username or password.",
+	},
+	ClientErrorInfo {
+		code: "auth.no_auth",
+		message: "NO_AUTH",
+		status: 403,
+		detail: &[],
+		description: "Missing, invalid, or expired auth token.",
+	},
+	ClientErrorInfo {
+		code: "auth.account_disabled",
+		message: "ACCOUNT_DISABLED",
+		status: 403,
+		detail: &[],
+		description: "The user is deactivated (by an admin), so cannot login.",
+	},
+	ClientErrorInfo {
+		code: "auth.pwd_change_required",
+		message: "PWD_CHANGE_REQUIRED",
+		status: 403,
+		detail: &[],
+		description: "The user must change the pwd first (`change_pwd` rpc method).",
+	},
+	ClientErrorInfo {
+		code: "auth.role_required",
+		message: "ROLE_REQUIRED",
+		status: 403,
+		detail: &["role"],
+		description: "The method requires the user `role` (e.g., `admin`).",
+	},
+	ClientErrorInfo {
+		code: "entity.not_found",
+		message: "ENTITY_NOT_FOUND",
+		status: 400,
+		detail: &["entity", "id"],
+		description: "The entity does not exist, or is not visible to the user.",
+	},
+	ClientErrorInfo {
+		code: "entity.already_exists",
+		message: "ENTITY_ALREADY_EXISTS",
+		status: 409,
+		detail: &["entity", "field"],
+		description:
+			"An entity with the same unique `field` value exists (e.g., `username`).",
+	},
+	ClientErrorInfo {
+		code: "entity.in_use",
+		message: "ENTITY_IN_USE",
+		status: 409,
+		detail: &["entity", "dependent"],
+		description: "The entity is still referenced by `dependent` entities, so cannot be deleted.",
+	},
+	ClientErrorInfo {
+		code: "entity.reference_violation",
+		message: "ENTITY_REFERENCE_VIOLATION",
+		status: 409,
+		detail: &["entity", "field"],
+		description: "The `field` references a missing entity, or the entity is still referenced.",
+	},
+	ClientErrorInfo {
+		code: "quota.exceeded",
+		message: "QUOTA_EXCEEDED",
+		status: 403,
+		detail: &["quota", "max"],
+		description: "The create would go over the user `quota` (`projects`, or `tasks_per_project`), see the `get_usage` rpc.",
+	},
+	ClientErrorInfo {
+		code: "entity.precondition_required",
+		message: "PRECONDITION_REQUIRED",
+		status: 428,
+		detail: &[],
+		description: "The REST update / delete has no `If-Match` (the entity `ETag`).",
+	},
+	ClientErrorInfo {
+		code: "entity.precondition_failed",
+		message: "PRECONDITION_FAILED",
+		status: 412,
+		detail: &[],
+		description: "The REST `If-Match` does not match the entity `ETag` (modified since), get it again.",
+	},
+	ClientErrorInfo {
+		code: "access.ip_not_allowed",
+		message: "IP_NOT_ALLOWED",
+		status: 403,
+		detail: &[],
+		description: "The client ip is denied, not in the allow list, or unknown.",
+	},
+	ClientErrorInfo {
+		code: "access.rate_limited",
+		message: "RATE_LIMITED",
+		status: 429,
+		detail: &["retry_after_sec"],
+		description: "Too many requests, retry after `retry_after_sec`.",
+	},
+	ClientErrorInfo {
+		code: "params.invalid",
+		message: "INVALID_PARAMS",
+		status: 400,
+		detail: &["field", "reason"],
+		description:
+			"A request param is missing or invalid (`field` is null when unknown).",
+	},
+	ClientErrorInfo {
+		code: "rpc.method_unknown",
+		message: "RPC_METHOD_UNKNOWN",
+		status: 400,
+		detail: &["method"],
+		description: "The json-rpc method does not exist in this api version.",
+	},
+	ClientErrorInfo {
+		code: "service.timeout",
+		message: "REQUEST_TIMEOUT",
+		status: 503,
+		detail: &[],
+		description: "The request was not done within the server request timeout.",
+	},
+	ClientErrorInfo {
+		code: "service.read_only",
+		message: "READ_ONLY_MODE",
+		status: 503,
+		detail: &[],
+		description: "The api is in read-only mode (e.g., during a failover), only the reads are served.",
+	},
+	ClientErrorInfo {
+		code: "service.overloaded",
+		message: "SERVICE_OVERLOADED",
+		status: 503,
+		detail: &["retry_after_sec"],
+		description: "The server is at its concurrent requests limit, retry after `retry_after_sec`.",
+	},
+	ClientErrorInfo {
+		code: "service.unavailable",
+		message: "SERVICE_UNAVAILABLE",
+		status: 503,
+		detail: &["retry_after_sec"],
+		description: "The database is not reachable, retry after `retry_after_sec`.",
+	},
+	ClientErrorInfo {
+		code: "service.error",
+		message: "SERVICE_ERROR",
+		status: 500,
+		detail: &[],
+		description:
+			"Unexpected server error (see the server logs with the req_uuid).",
+	},
+];
+
+// endregion: --- Client Error Catalog
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::web::mw_auth::CtxExtError;
+	use std::collections::HashSet;
+
+	#[test]
+	fn test_client_error_catalog_ok() {
+		// -- Setup & Fixtures
+		let fx_errors = [
+			Error::LoginFailUsernameNotFound,
+			Error::CtxExt(CtxExtError::TokenNotInCookie),
+			Error::CtxExt(CtxExtError::UserInactive),
+			Error::PwdChangeRequired,
+			Error::RoleRequired {
+				method: "list_users".to_string(),
+				role: "admin",
+			},
+			Error::AdminSelfLockout {
+				method: "disable_user",
+			},
+			Error::Model(model::Error::EntityNotFound {
+				entity: "task",
+				id: 1,
+			}),
+			Error::Model(model::Error::UniqueViolation {
+				entity: "user".to_string(),
+				field: Some("username".to_string()),
+			}),
+			Error::Model(model::Error::ForeignKeyViolation {
+				entity: "task".to_string(),
+				field: None,
+			}),
+			Error::Model(model::Error::EntityInUse {
+				entity: "project",
+				id: 1000,
+				dependent: "task",
+			}),
+			Error::IpNotAllowed {
+				ip: "10.0.0.1".to_string(),
+			},
+			Error::RateLimited { retry_after_sec: 1 },
+			Error::RpcIntoParamsMissing,
+			Error::RpcMethodUnknown("nope".to_string()),
+			Error::ReqStampNotInResponseExt,
+			Error::RequestTimeout,
+			Error::Overloaded { retry_after_sec: 1 },
+			Error::ReadOnlyMode {
+				mutation: "create_task".to_string(),
+			},
+			Error::RestPreconditionRequired,
+			Error::RestPreconditionFailed {
+				etag: "\"1000-1\"".to_string(),
+			},
+			Error::Model(model::Error::EntityModified {
+				entity: "task",
+				id: 1000,
+			}),
+			Error::Model(model::Error::ServiceUnavailable { retry_after_sec: 5 }),
+			Error::Model(model::Error::TimezoneUnknown {
+				timezone: "Mars/Olympus".to_string(),
+			}),
+			Error::Model(model::Error::LocaleUnsupported {
+				locale: "xx".to_string(),
+			}),
+			Error::Model(model::Error::DeadlineExceeded),
+			Error::ImportInvalidFile {
+				reason: "no 'file' part".to_string(),
+			},
+			Error::Model(model::Error::ShareLinkInvalid),
+			Error::Model(model::Error::ShareLinkDurationInvalid { max_days: 30 }),
+			Error::Model(model::Error::QuotaExceeded {
+				quota: "projects",
+				max: 100,
+			}),
+			Error::HookSourceUnknown("nope".to_string()),
+			Error::HookSignatureInvalid {
+				hook_source: "github",
+			},
+			Error::HookPayloadInvalid {
+				reason: "no 'title'".to_string(),
+			},
+		];
+
+		// -- Check the catalog codes and messages are unique.
+		let codes: HashSet<_> =
+			CLIENT_ERROR_CATALOG.iter().map(|i| i.code).collect();
+		let messages: HashSet<_> =
+			CLIENT_ERROR_CATALOG.iter().map(|i| i.message).collect();
+		assert_eq!(codes.len(), CLIENT_ERROR_CATALOG.len());
+		assert_eq!(messages.len(), CLIENT_ERROR_CATALOG.len());
+
+		// -- Check every client error matches its catalog entry.
+		for error in fx_errors {
+			let (status, client_error) = error.client_status_and_error();
+			let info = CLIENT_ERROR_CATALOG
+				.iter()
+				.find(|info| info.code == client_error.code())
+				.unwrap_or_else(|| panic!("{} not in catalog", client_error.code()));
+			assert_eq!(info.message, client_error.as_ref());
+			assert_eq!(info.status, status.as_u16(), "{}", info.code);
+
+			let detail =
+				serde_json::to_value(&client_error).unwrap()["detail"].clone();
+			let detail_fields: Vec<_> = detail
+				.as_object()
+				.map(|obj| obj.keys().map(|key| key.as_str()).collect())
+				.unwrap_or_default();
+			let mut expected_fields = info.detail.to_vec();
+			expected_fields.sort();
+			assert_eq!(detail_fields, expected_fields, "{}", info.code);
+		}
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,113 @@
+//! The entities fields visibility by user role, enforced once on the serialized
+//! results (the json-rpc results, see `RpcRouter::call`, and the REST and share
+//! link responses, see `visible_json`), rather than by each handler.
+//!
+//! NOTE: The rules match the fields by name, in any (nested) object of the
+//!       result, e.g., the `cid` / `mid` of the tasks, projects, and shares.
+//!
+//! NOTE: The graphql types expose their fields one by one (see `graphql`),
+//!       so without the hidden ones.
+
+use crate::web::Result;
+use axum::Json;
+use lib_core::ctx::Ctx;
+use lib_core::model::user::UserRole;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Fields visible only to one role (and removed for the others).
+struct FieldRule {
+	fields: &'static [&'static str],
+	visible_to: UserRole,
+}
+
+const FIELD_RULES: &[FieldRule] = &[
+	// The creator / modifier user ids (the audit fields).
+	FieldRule {
+		fields: &["cid", "mid"],
+		visible_to: UserRole::Admin,
+	},
+];
+
+/// The role of the ctx user (see mw_auth), none for the services.
+pub fn ctx_role(ctx: &Ctx) -> Option<UserRole> {
+	ctx.extensions().get::<UserRole>().copied()
+}
+
+/// The json of the entity (or entities), without the fields hidden to the role.
+pub fn visible_json<E: Serialize>(
+	role: Option<UserRole>,
+	entity: E,
+) -> Result<Json<Value>> {
+	let mut value = serde_json::to_value(entity)?;
+	redact_fields(role, &mut value);
+
+	Ok(Json(value))
+}
+
+/// Removes the fields not visible to the role (see `FIELD_RULES`).
+pub fn redact_fields(role: Option<UserRole>, value: &mut Value) {
+	match value {
+		Value::Object(object) => {
+			for rule in FIELD_RULES
+				.iter()
+				.filter(|rule| Some(rule.visible_to) != role)
+			{
+				for field in rule.fields {
+					object.remove(*field);
+				}
+			}
+			for value in object.values_mut() {
+				redact_fields(role, value);
+			}
+		}
+		Value::Array(values) => {
+			for value in values {
+				redact_fields(role, value);
+			}
+		}
+		_ => (),
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_redact_fields_ok() {
+		// -- Setup & Fixtures
+		let fx_result = json!({
+			"data": [
+				{"id": 1000, "title": "task 01", "cid": 1000, "mid": 1001},
+				{"id": 1001, "title": "task 02", "cid": 1000, "mid": 1000},
+			],
+			"total": 2,
+		});
+
+		// -- Exec
+		let mut user_result = fx_result.clone();
+		redact_fields(Some(UserRole::User), &mut user_result);
+		let mut admin_result = fx_result.clone();
+		redact_fields(Some(UserRole::Admin), &mut admin_result);
+		let mut no_role_result = fx_result.clone();
+		redact_fields(None, &mut no_role_result);
+
+		// -- Check
+		assert_eq!(
+			user_result,
+			json!({
+				"data": [
+					{"id": 1000, "title": "task 01"},
+					{"id": 1001, "title": "task 02"},
+				],
+				"total": 2,
+			})
+		);
+		assert_eq!(admin_result, fx_result);
+		assert_eq!(no_role_result, user_result);
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,414 @@
+//! `/api/graphql` - GraphQL schema over the BMC layer (`graphql` feature).
+//!
+//! - Queries: `project(s)`, `task(s)` (`filters` / `listOptions` as json, same
+//!   format as the json-rpc list params, so mapped to the modql filters),
+//!   `user(id)`, and `me`.
+//! - Mutations: create/update/delete of the projects and tasks (BMC calls),
+//!   which fail in the read-only mode (see `mw_read_only`).
+//!
+//! NOTE: The route is behind `mw_ctx_require`, and the request `Ctx` is set as the
+//!       query data, so the BMC calls have the same rights as the json-rpc ones.
+
+use crate::web::{self, mw_auth::CtxW, mw_read_only};
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+	Context, EmptySubscription, ErrorExtensions, InputObject, Json, Object, Result,
+	Schema,
+};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::extract::State;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Router;
+use lib_base::time::format_time;
+use lib_core::ctx::Ctx;
+use lib_core::model::project::{
+	Project, ProjectBmc, ProjectFilter, ProjectForCreate, ProjectForUpdate,
+};
+use lib_core::model::task::{
+	Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate,
+};
+use lib_core::model::user::{User, UserBmc};
+use lib_core::model::ModelManager;
+use modql::filter::ListOptions;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tracing::debug;
+
+pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn routes(mm: ModelManager) -> Router {
+	let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+		.data(mm)
+		.finish();
+
+	Router::new()
+		.route("/graphql", get(graphiql_handler).post(graphql_handler))
+		.with_state(schema)
+}
+
+async fn graphql_handler(
+	State(schema): State<AppSchema>,
+	ctx: CtxW,
+	req: GraphQLRequest,
+) -> GraphQLResponse {
+	debug!("{:<12} - graphql_handler", "HANDLER");
+
+	schema.execute(req.into_inner().data(ctx.0)).await.into()
+}
+
+/// The GraphiQL playground.
+async fn graphiql_handler() -> impl IntoResponse {
+	Html(GraphiQLSource::build().endpoint("/api/graphql").finish())
+}
+
+// region:    --- Context Utils
+
+fn ctx_mm<'a>(gql_ctx: &Context<'a>) -> Result<(&'a Ctx, &'a ModelManager)> {
+	Ok((gql_ctx.data::<Ctx>()?, gql_ctx.data::<ModelManager>()?))
+}
+
+/// Same as `ctx_mm`, but fails in the read-only mode (for the mutations).
+fn ctx_mm_writable<'a>(
+	gql_ctx: &Context<'a>,
+) -> Result<(&'a Ctx, &'a ModelManager)> {
+	mw_read_only::check_writable(gql_ctx.field().name()).map_err(client_error)?;
+	ctx_mm(gql_ctx)
+}
+
+/// The json list params (as in json-rpc `ParamsList`) to the modql filters.
+fn list_params<F: DeserializeOwned>(
+	filters: Option<Json<Value>>,
+	list_options: Option<Json<Value>>,
+) -> Result<(Option<Vec<F>>, Option<ListOptions>)> {
+	let filters = filters
+		.map(|Json(filters)| match filters {
+			Value::Array(_) => serde_json::from_value(filters),
+			filter => serde_json::from_value(filter).map(|filter| vec![filter]),
+		})
+		.transpose()?;
+	let list_options = list_options
+		.map(|Json(list_options)| serde_json::from_value(list_options))
+		.transpose()?;
+
+	Ok((filters, list_options))
+}
+
+/// The model errors as the `ClientError` (message, and `code`/`detail` extensions),
+/// so the internal errors are not exposed (same as the other apis, and the
+/// `server_error` extension is only set in the dev env).
+fn client_error(ex: impl Into<web::Error>) -> async_graphql::Error {
+	let web_error: web::Error = ex.into();
+	debug!("{:<12} - graphql error {web_error:?}", "INTO_RES");
+	let (_, client_error) = web_error.client_status_and_error();
+	let server_error = web_error.dev_detail();
+	let detail = serde_json::to_value(&client_error)
+		.ok()
+		.and_then(|val| val.get("detail").cloned());
+
+	async_graphql::Error::new(client_error.as_ref()).extend_with(|_, ext| {
+		ext.set("code", client_error.code());
+		if let Some(detail) =
+			detail.and_then(|d| async_graphql::Value::from_json(d).ok())
+		{
+			ext.set("detail", detail);
+		}
+		if let Some(server_error) =
+			server_error.and_then(|se| async_graphql::Value::from_json(se).ok())
+		{
+			ext.set("server_error", server_error);
+		}
+	})
+}
+
+// endregion: --- Context Utils
+
+// region:    --- Queries
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+	async fn project(&self, gql_ctx: &Context<'_>, id: i64) -> Result<ProjectGql> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		Ok(ProjectGql(
+			ProjectBmc::get(ctx, mm, id).await.map_err(client_error)?,
+		))
+	}
+
+	async fn projects(
+		&self,
+		gql_ctx: &Context<'_>,
+		filters: Option<Json<Value>>,
+		list_options: Option<Json<Value>>,
+	) -> Result<Vec<ProjectGql>> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		let (filters, list_options) =
+			list_params::<ProjectFilter>(filters, list_options)?;
+		let projects = ProjectBmc::list(ctx, mm, filters, list_options)
+			.await
+			.map_err(client_error)?;
+
+		Ok(projects.into_iter().map(ProjectGql).collect())
+	}
+
+	async fn task(&self, gql_ctx: &Context<'_>, id: i64) -> Result<TaskGql> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		Ok(TaskGql(
+			TaskBmc::get(ctx, mm, id).await.map_err(client_error)?,
+		))
+	}
+
+	async fn tasks(
+		&self,
+		gql_ctx: &Context<'_>,
+		filters: Option<Json<Value>>,
+		list_options: Option<Json<Value>>,
+	) -> Result<Vec<TaskGql>> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		let (filters, list_options) =
+			list_params::<TaskFilter>(filters, list_options)?;
+		let tasks = TaskBmc::list(ctx, mm, filters, list_options)
+			.await
+			.map_err(client_error)?;
+
+		Ok(tasks.into_iter().map(TaskGql).collect())
+	}
+
+	async fn user(&self, gql_ctx: &Context<'_>, id: i64) -> Result<UserGql> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		Ok(UserGql(
+			UserBmc::get::<User>(ctx, mm, id)
+				.await
+				.map_err(client_error)?,
+		))
+	}
+
+	/// The logged in user.
+	async fn me(&self, gql_ctx: &Context<'_>) -> Result<UserGql> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		Ok(UserGql(
+			UserBmc::get::<User>(ctx, mm, ctx.user_id())
+				.await
+				.map_err(client_error)?,
+		))
+	}
+}
+
+// endregion: --- Queries
+
+// region:    --- Mutations
+
+pub struct MutationRoot;
+
+#[derive(InputObject)]
+struct ProjectCreateInput {
+	name: String,
+}
+
+#[derive(InputObject)]
+struct ProjectUpdateInput {
+	name: Option<String>,
+	owner_id: Option<i64>,
+}
+
+#[derive(InputObject)]
+struct TaskCreateInput {
+	project_id: i64,
+	title: String,
+}
+
+#[derive(InputObject)]
+struct TaskUpdateInput {
+	title: Option<String>,
+	done: Option<bool>,
+}
+
+#[Object]
+impl MutationRoot {
+	async fn create_project(
+		&self,
+		gql_ctx: &Context<'_>,
+		data: ProjectCreateInput,
+	) -> Result<ProjectGql> {
+		let (ctx, mm) = ctx_mm_writable(gql_ctx)?;
+		let project_c = ProjectForCreate { name: data.name };
+		let id = ProjectBmc::create(ctx, mm, project_c)
+			.await
+			.map_err(client_error)?;
+
+		Ok(ProjectGql(
+			ProjectBmc::get(ctx, mm, id).await.map_err(client_error)?,
+		))
+	}
+
+	async fn update_project(
+		&self,
+		gql_ctx: &Context<'_>,
+		id: i64,
+		data: ProjectUpdateInput,
+	) -> Result<ProjectGql> {
+		let (ctx, mm) = ctx_mm_writable(gql_ctx)?;
+		let project_u = ProjectForUpdate {
+			name: data.name,
+			owner_id: data.owner_id,
+		};
+		ProjectBmc::update(ctx, mm, id, project_u)
+			.await
+			.map_err(client_error)?;
+
+		Ok(ProjectGql(
+			ProjectBmc::get(ctx, mm, id).await.map_err(client_error)?,
+		))
+	}
+
+	async fn delete_project(
+		&self,
+		gql_ctx: &Context<'_>,
+		id: i64,
+	) -> Result<ProjectGql> {
+		let (ctx, mm) = ctx_mm_writable(gql_ctx)?;
+		let project = ProjectBmc::get(ctx, mm, id).await.map_err(client_error)?;
+		ProjectBmc::delete(ctx, mm, id)
+			.await
+			.map_err(client_error)?;
+
+		Ok(ProjectGql(project))
+	}
+
+	async fn create_task(
+		&self,
+		gql_ctx: &Context<'_>,
+		data: TaskCreateInput,
+	) -> Result<TaskGql> {
+		let (ctx, mm) = ctx_mm_writable(gql_ctx)?;
+		let task_c = TaskForCreate {
+			project_id: data.project_id,
+			title: data.title,
+		};
+		let id = TaskBmc::create(ctx, mm, task_c)
+			.await
+			.map_err(client_error)?;
+
+		Ok(TaskGql(
+			TaskBmc::get(ctx, mm, id).await.map_err(client_error)?,
+		))
+	}
+
+	async fn update_task(
+		&self,
+		gql_ctx: &Context<'_>,
+		id: i64,
+		data: TaskUpdateInput,
+	) -> Result<TaskGql> {
+		let (ctx, mm) = ctx_mm_writable(gql_ctx)?;
+		let task_u = TaskForUpdate {
+			title: data.title,
+			done: data.done,
+			..Default::default()
+		};
+		TaskBmc::update(ctx, mm, id, task_u)
+			.await
+			.map_err(client_error)?;
+
+		Ok(TaskGql(
+			TaskBmc::get(ctx, mm, id).await.map_err(client_error)?,
+		))
+	}
+
+	async fn delete_task(&self, gql_ctx: &Context<'_>, id: i64) -> Result<TaskGql> {
+		let (ctx, mm) = ctx_mm_writable(gql_ctx)?;
+		let task = TaskBmc::get(ctx, mm, id).await.map_err(client_error)?;
+		TaskBmc::delete(ctx, mm, id).await.map_err(client_error)?;
+
+		Ok(TaskGql(task))
+	}
+}
+
+// endregion: --- Mutations
+
+// region:    --- Object Types
+
+/// Note: Wrappers, so lib-core does not depend on async-graphql.
+struct ProjectGql(Project);
+
+#[Object(name = "Project")]
+impl ProjectGql {
+	async fn id(&self) -> i64 {
+		self.0.id
+	}
+
+	async fn name(&self) -> &str {
+		&self.0.name
+	}
+
+	async fn owner_id(&self) -> i64 {
+		self.0.owner_id
+	}
+
+	/// Rfc3339 formatted.
+	async fn ctime(&self) -> String {
+		format_time(self.0.ctime)
+	}
+
+	/// Rfc3339 formatted.
+	async fn mtime(&self) -> String {
+		format_time(self.0.mtime)
+	}
+
+	async fn tasks(&self, gql_ctx: &Context<'_>) -> Result<Vec<TaskGql>> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		let filter: TaskFilter =
+			serde_json::from_value(json!({ "project_id": self.0.id }))?;
+		let tasks = TaskBmc::list(ctx, mm, Some(vec![filter]), None)
+			.await
+			.map_err(client_error)?;
+
+		Ok(tasks.into_iter().map(TaskGql).collect())
+	}
+}
+
+struct TaskGql(Task);
+
+#[Object(name = "Task")]
+impl TaskGql {
+	async fn id(&self) -> i64 {
+		self.0.id
+	}
+
+	async fn project_id(&self) -> i64 {
+		self.0.project_id
+	}
+
+	async fn title(&self) -> &str {
+		&self.0.title
+	}
+
+	async fn done(&self) -> bool {
+		self.0.done
+	}
+
+	async fn project(&self, gql_ctx: &Context<'_>) -> Result<ProjectGql> {
+		let (ctx, mm) = ctx_mm(gql_ctx)?;
+		Ok(ProjectGql(
+			ProjectBmc::get(ctx, mm, self.0.project_id)
+				.await
+				.map_err(client_error)?,
+		))
+	}
+}
+
+struct UserGql(User);
+
+#[Object(name = "User")]
+impl UserGql {
+	async fn id(&self) -> i64 {
+		self.0.id
+	}
+
+	async fn username(&self) -> &str {
+		&self.0.username
+	}
+}
+
+// endregion: --- Object Types
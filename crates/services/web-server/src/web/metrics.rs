@@ -0,0 +1,128 @@
+//! Prometheus metrics, recorded with the `metrics` facade macros anywhere
+//! in the app, and rendered by the admin `/metrics` endpoint.
+//!
+//! NOTE: Without an installed recorder (no admin listener), the macros are no-ops.
+
+use async_trait::async_trait;
+use axum::http::{Method, StatusCode};
+use lib_core::model::event::{DomainEvent, EventSubscriber};
+use lib_core::model::DbPoolStats;
+use lib_core::pwd::{PWD_HASH_DURATION_SECONDS, PWD_VALIDATE_DURATION_SECONDS};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::time::Duration;
+
+const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+const DOMAIN_EVENTS_TOTAL: &str = "domain_events_total";
+const RPC_DEPRECATED_CALLS_TOTAL: &str = "rpc_deprecated_calls_total";
+const REQUEST_LOGS_DROPPED_TOTAL: &str = "request_logs_dropped_total";
+const HTTP_REQUESTS_SHED_TOTAL: &str = "http_requests_shed_total";
+const SLOW_REQUESTS_TOTAL: &str = "slow_requests_total";
+const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+const DB_POOL_IDLE_CONNECTIONS: &str = "db_pool_idle_connections";
+const DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+const DB_POOL_ACQUIRE_WAIT_SECONDS: &str = "db_pool_acquire_wait_seconds";
+
+/// Seconds buckets for the request durations (and db acquire wait) histograms.
+const DURATION_BUCKETS: &[f64] =
+	&[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10.];
+
+/// Seconds buckets for the pwd hashing histograms (see lib-core `pwd`), where
+/// the argon2 scheme is in the tens of ms.
+const PWD_DURATION_BUCKETS: &[f64] =
+	&[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5];
+
+/// Installs the global prometheus recorder, and returns the handle to render it.
+pub fn install_recorder() -> crate::Result<PrometheusHandle> {
+	PrometheusBuilder::new()
+		.set_buckets_for_metric(
+			Matcher::Full(HTTP_REQUEST_DURATION_SECONDS.to_string()),
+			DURATION_BUCKETS,
+		)
+		.and_then(|builder| {
+			builder.set_buckets_for_metric(
+				Matcher::Full(DB_POOL_ACQUIRE_WAIT_SECONDS.to_string()),
+				DURATION_BUCKETS,
+			)
+		})
+		.and_then(|builder| {
+			builder.set_buckets_for_metric(
+				Matcher::Full(PWD_HASH_DURATION_SECONDS.to_string()),
+				PWD_DURATION_BUCKETS,
+			)
+		})
+		.and_then(|builder| {
+			builder.set_buckets_for_metric(
+				Matcher::Full(PWD_VALIDATE_DURATION_SECONDS.to_string()),
+				PWD_DURATION_BUCKETS,
+			)
+		})
+		.and_then(|builder| builder.install_recorder())
+		.map_err(|ex| crate::Error::MetricsRecorderInstall(ex.to_string()))
+}
+
+/// Records one served request (called by mw_res_map).
+///
+/// NOTE: No path label, to keep the cardinality bounded.
+pub fn record_request(method: &Method, status: StatusCode, duration: Duration) {
+	let method = method.to_string();
+	metrics::counter!(
+		HTTP_REQUESTS_TOTAL,
+		"method" => method.clone(),
+		"status" => status.as_u16().to_string()
+	)
+	.increment(1);
+	metrics::histogram!(HTTP_REQUEST_DURATION_SECONDS, "method" => method)
+		.record(duration.as_secs_f64());
+}
+
+/// Counts one call of a deprecated rpc method (see `RpcRouter::deprecate`).
+///
+/// NOTE: The method label is bounded, as only the router methods are deprecated.
+pub fn record_rpc_deprecated_call(method: &str) {
+	metrics::counter!(RPC_DEPRECATED_CALLS_TOTAL, "method" => method.to_string())
+		.increment(1);
+}
+
+/// Counts the request log lines not written (see `log::writer`).
+pub fn record_request_logs_dropped(count: u64) {
+	metrics::counter!(REQUEST_LOGS_DROPPED_TOTAL).increment(count);
+}
+
+/// Counts one request rejected by mw_load_shed.
+pub fn record_request_shed() {
+	metrics::counter!(HTTP_REQUESTS_SHED_TOTAL).increment(1);
+}
+
+/// Counts one request slower than `SLOW_REQUEST_MS` (see mw_slow_req).
+pub fn record_slow_request(method: &Method) {
+	metrics::counter!(SLOW_REQUESTS_TOTAL, "method" => method.to_string())
+		.increment(1);
+}
+
+/// Sets the db pool gauges (called by the admin `/metrics`, before rendering).
+pub fn record_db_pool(stats: DbPoolStats) {
+	metrics::gauge!(DB_POOL_CONNECTIONS).set(stats.size);
+	metrics::gauge!(DB_POOL_IDLE_CONNECTIONS).set(stats.idle);
+	metrics::gauge!(DB_POOL_MAX_CONNECTIONS).set(stats.max);
+}
+
+/// Records one db connection acquire wait (of the health probes db ping).
+pub fn record_db_acquire_wait(acquire_wait: Duration) {
+	metrics::histogram!(DB_POOL_ACQUIRE_WAIT_SECONDS)
+		.record(acquire_wait.as_secs_f64());
+}
+
+/// Counts the domain events, by name (see `event::spawn_subscriber`).
+pub struct DomainEventMetrics;
+
+#[async_trait]
+impl EventSubscriber for DomainEventMetrics {
+	fn name(&self) -> &'static str {
+		"metrics"
+	}
+
+	async fn handle(&self, event: &DomainEvent) {
+		metrics::counter!(DOMAIN_EVENTS_TOTAL, "event" => event.name()).increment(1);
+	}
+}
@@ -1,14 +1,43 @@
+pub mod catch_panic;
+pub mod client_ip;
+pub mod compression;
 mod error;
+mod field_policy;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod metrics;
 pub mod mw_auth;
+pub mod mw_capture;
+pub mod mw_ip_filter;
+pub mod mw_load_shed;
+pub mod mw_rate_limit;
+pub mod mw_read_only;
 pub mod mw_req_stamp;
+pub mod mw_req_timeout;
 pub mod mw_res_map;
+pub mod mw_slow_req;
+pub mod openapi;
+pub mod proxy_protocol;
+pub mod routes_admin;
+pub mod routes_errors;
+pub mod routes_export;
+pub mod routes_health;
+pub mod routes_hooks;
+pub mod routes_import;
 pub mod routes_login;
+pub mod routes_pages;
+pub mod routes_rest;
+pub mod routes_share;
 pub mod routes_static;
+pub mod routes_ws;
 pub mod rpc;
+pub mod templates;
 
 pub use self::error::ClientError;
 pub use self::error::{Error, Result};
+use lib_core::config::config;
 use lib_core::token::generate_web_token;
+use std::time::Instant;
 use time::OffsetDateTime;
 use tower_cookies::{Cookie, Cookies};
 use uuid::Uuid;
@@ -20,6 +49,7 @@ fn set_token_cookie(cookies: &Cookies, user: &str, salt: Uuid) -> Result<()> {
 
 	let mut cookie = Cookie::new(AUTH_TOKEN, token.to_string());
 	cookie.set_http_only(true);
+	cookie.set_secure(config().COOKIE_SECURE);
 	cookie.set_path("/");
 
 	cookies.add(cookie);
@@ -38,10 +68,14 @@ fn remove_token_cookie(cookies: &Cookies) -> Result<()> {
 // region:    --- ReqStamp
 
 /// Resolved by mw_req_stamp.
+/// (also set in the `Ctx` extensions by mw_ctx_resolve)
 #[derive(Debug, Clone)]
 pub struct ReqStamp {
-	pub uuid: Uuid,
+	/// The `x-request-id` (incoming one, or a new uuid string).
+	pub req_id: String,
 	pub time_in: OffsetDateTime,
+	/// `time_in` + `REQUEST_TIMEOUT_SEC` (see mw_req_timeout).
+	pub deadline: Instant,
 }
 
 // endregion: --- ReqStamp
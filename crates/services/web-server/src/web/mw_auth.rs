@@ -1,23 +1,23 @@
 use async_trait::async_trait;
 use axum::{
 	extract::{FromRequestParts, State},
-	http::{request::Parts, Request},
+	http::{header::ACCEPT_LANGUAGE, request::Parts, Request},
 	middleware::Next,
 	response::Response,
 };
 use lib_core::{
-	ctx::Ctx,
+	ctx::{locale::resolve_locale, Ctx},
 	model::{
 		user::{UserBmc, UserForAuth},
 		ModelManager,
 	},
-	token::{validate_web_token, Token},
+	token::{is_web_token_refresh_due, validate_web_token, Token},
 };
 use serde::Serialize;
 use tower_cookies::{Cookie, Cookies};
-use tracing::debug;
+use tracing::{debug, Span};
 
-use crate::web::{set_token_cookie, Error, Result, AUTH_TOKEN};
+use crate::web::{set_token_cookie, Error, ReqStamp, Result, AUTH_TOKEN};
 
 #[allow(dead_code)] // For now, until we have the rpc.
 pub async fn mw_ctx_require<B>(
@@ -32,6 +32,26 @@ pub async fn mw_ctx_require<B>(
 	Ok(next.run(req).await)
 }
 
+/// Rejects the sessions which must change the pwd (for the non rpc routes,
+/// the rpc handler allowing the `change_pwd` method).
+///
+/// NOTE: Must be layered inside mw_ctx_require.
+pub async fn mw_pwd_change_guard<B>(
+	ctx: Result<CtxW>,
+	req: Request<B>,
+	next: Next<B>,
+) -> Result<Response> {
+	debug!("{:<12} - mw_pwd_change_guard", "MIDDLEWARE");
+
+	if let Ok(CtxW(ctx)) = ctx {
+		if ctx.extensions().get::<PwdChangeRequired>().is_some() {
+			return Err(Error::PwdChangeRequired);
+		}
+	}
+
+	Ok(next.run(req).await)
+}
+
 pub async fn mw_ctx_resolve<B>(
 	mm: State<ModelManager>,
 	cookies: Cookies,
@@ -40,7 +60,11 @@ pub async fn mw_ctx_resolve<B>(
 ) -> Result<Response> {
 	debug!("{:<12} - mw_ctx_resolve", "MIDDLEWARE");
 
-	let ctx_ext_result = _ctx_resolve(mm, &cookies).await;
+	let accept_language = req
+		.headers()
+		.get(ACCEPT_LANGUAGE)
+		.and_then(|val| val.to_str().ok());
+	let mut ctx_ext_result = _ctx_resolve(mm, &cookies, accept_language).await;
 
 	if ctx_ext_result.is_err()
 		&& !matches!(ctx_ext_result, Err(CtxExtError::TokenNotInCookie))
@@ -48,6 +72,19 @@ pub async fn mw_ctx_resolve<B>(
 		cookies.remove(Cookie::named(AUTH_TOKEN))
 	}
 
+	// -- Attach the request stamp (req_id) to the Ctx.
+	if let (Ok(CtxW(ctx)), Some(req_stamp)) =
+		(ctx_ext_result.as_mut(), req.extensions().get::<ReqStamp>())
+	{
+		ctx.set_deadline(req_stamp.deadline);
+		ctx.extensions_mut().insert(req_stamp.clone());
+	}
+
+	// -- Record the user_id on the request span (see mw_req_stamp).
+	if let Ok(CtxW(ctx)) = &ctx_ext_result {
+		Span::current().record("user_id", ctx.user_id());
+	}
+
 	// Store the ctx_ext_result in the request extension
 	// (for Ctx extractor)
 	req.extensions_mut().insert(ctx_ext_result);
@@ -75,7 +112,11 @@ impl<S: Send + Sync> FromRequestParts<S> for CtxW {
 	}
 }
 
-async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResult {
+async fn _ctx_resolve(
+	mm: State<ModelManager>,
+	cookies: &Cookies,
+	accept_language: Option<&str>,
+) -> CtxExtResult {
 	// -- Get Token String
 	let token = cookies
 		.get(AUTH_TOKEN)
@@ -87,23 +128,42 @@ async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResul
 		.map_err(|_| CtxExtError::TokenWrongFormat)?;
 	// -- Get UserForAuth
 	let user: UserForAuth =
-		UserBmc::first_by_username(&Ctx::root_ctx(), &mm, &token.ident)
+		UserBmc::auth_by_username(&Ctx::root_ctx(), &mm, &token.ident)
 			.await
 			.map_err(|ex| CtxExtError::ModelAccessError(ex.to_string()))?
 			.ok_or(CtxExtError::UserNotFound)?;
 	// -- Validate Token
-	validate_web_token(&token, user.token_salt)
+	validate_web_token(&token, *user.token_salt.expose())
 		.map_err(|_| CtxExtError::FailValidate)?;
+	// -- Refuse the deactivated users
+	//    (after the token validation, as the login)
+	if !user.active {
+		return Err(CtxExtError::UserInactive);
+	}
 
 	// -- Update Token
-	set_token_cookie(cookies, &user.username, user.token_salt)
-		.map_err(|_| CtxExtError::CannotSetTokenCookie)?;
+	//    (only when close to expiry, see `TOKEN_REFRESH_WINDOW_SEC`)
+	if is_web_token_refresh_due(&token) {
+		set_token_cookie(cookies, &user.username, *user.token_salt.expose())
+			.map_err(|_| CtxExtError::CannotSetTokenCookie)?;
+	}
 	// -- Create CtxExtResult
-	Ctx::new(user.id)
-		.map(CtxW)
-		.map_err(|ex| CtxExtError::CtxCreateFail(ex.to_string()))
+	let mut ctx = Ctx::new(user.id)
+		.map_err(|ex| CtxExtError::CtxCreateFail(ex.to_string()))?;
+	if user.must_change_pwd {
+		ctx.extensions_mut().insert(PwdChangeRequired);
+	}
+	ctx.extensions_mut().insert(user.role);
+	ctx.set_locale(resolve_locale(user.locale.as_deref(), accept_language));
+
+	Ok(CtxW(ctx))
 }
 
+/// In the `Ctx` extensions when the user must change the pwd, so the session
+/// is restricted to the `change_pwd` rpc method (see `mw_pwd_change_guard`).
+#[derive(Debug, Clone)]
+pub struct PwdChangeRequired;
+
 // endregion: --- Ctx Extractor
 
 // region:    --- Ctx Extractor Result/Error
@@ -117,6 +177,7 @@ pub enum CtxExtError {
 	CtxNotInRequestExt,
 
 	UserNotFound,
+	UserInactive,
 	ModelAccessError(String),
 	FailValidate,
 	CannotSetTokenCookie,
@@ -0,0 +1,200 @@
+//! The request capture (debug, `CAPTURE_DIR`), which records the request and
+//! response pairs of the flagged browser sessions, to reproduce the hard to
+//! trigger bugs (replayed with `cargo xtask replay`).
+//!
+//! A session is flagged with a `capture=<label>` cookie (e.g., set from the
+//! browser devtools), and its requests are written as
+//! `{CAPTURE_DIR}/{label}/{time_in}-{req_id}.json` files.
+//!
+//! NOTE: The captures are redacted as the logs (see `LOG_REDACT_FIELDS`, and
+//!       `redact_path`), so the cookies, tokens, and pwds are never written.
+//!
+//! NOTE: The bodies over `CAPTURE_BODY_MAX` (or streamed, e.g., websockets)
+//!       are not captured (`null`).
+
+use crate::log::{redact_path, redact_value};
+use crate::web::ReqStamp;
+use axum::body::{boxed, Body, Bytes, Full, HttpBody};
+use axum::http::{HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use lib_base::time::format_time;
+use lib_core::config::config;
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+use tower_cookies::Cookies;
+use tracing::{debug, warn};
+
+const CAPTURE_COOKIE: &str = "capture";
+const CAPTURE_LABEL_MAX_LEN: usize = 64;
+const CAPTURE_BODY_MAX: u64 = 1024 * 1024;
+
+pub async fn mw_capture(
+	cookies: Cookies,
+	req: Request<Body>,
+	next: Next<Body>,
+) -> Response {
+	let (Some(capture_dir), Some(label)) = (
+		config().CAPTURE_DIR.as_ref(),
+		cookies
+			.get(CAPTURE_COOKIE)
+			.map(|cookie| cookie.value().to_string())
+			.filter(|label| is_valid_label(label)),
+	) else {
+		return next.run(req).await;
+	};
+	debug!("{:<12} - mw_capture - {label}", "MIDDLEWARE");
+
+	// -- Capture the request.
+	let Some(req_stamp) = req.extensions().get::<ReqStamp>().cloned() else {
+		return next.run(req).await;
+	};
+	let (parts, body) = req.into_parts();
+	let (req_body, body) = buffer_req_body(body).await;
+	let request = json!({
+		"method": parts.method.as_str(),
+		"uri": redact_path(&parts.uri.to_string()),
+		"headers": headers_value(&parts.headers),
+		"body": body_value(req_body.as_ref()),
+	});
+	let res = next.run(Request::from_parts(parts, body)).await;
+
+	// -- Capture the response.
+	let (parts, body) = res.into_parts();
+	let (res_body, body) = buffer_body(body).await;
+	let response = json!({
+		"status": parts.status.as_u16(),
+		"headers": headers_value(&parts.headers),
+		"body": body_value(res_body.as_ref()),
+	});
+
+	// -- Write the capture (not in the request path).
+	let mut capture = json!({
+		"req_id": req_stamp.req_id,
+		"time_in": format_time(req_stamp.time_in),
+		"request": request,
+		"response": response,
+	});
+	redact_value(&mut capture);
+	let file = PathBuf::from(capture_dir).join(&label).join(format!(
+		"{}-{}.json",
+		req_stamp.time_in.unix_timestamp_nanos() / 1_000_000,
+		req_stamp.req_id
+	));
+	tokio::spawn(async move {
+		if let Err(ex) = write_capture(&file, &capture).await {
+			warn!("capture write failed ({}) - {ex}", file.display());
+		}
+	});
+
+	Response::from_parts(parts, body)
+}
+
+/// As `buffer_body`, for the request (which must stay a `Body`).
+async fn buffer_req_body(body: Body) -> (Option<Bytes>, Body) {
+	if !is_small_body(&body) {
+		return (None, body);
+	}
+
+	match hyper::body::to_bytes(body).await {
+		Ok(bytes) => (Some(bytes.clone()), Body::from(bytes)),
+		Err(ex) => {
+			warn!("capture body read failed - {ex}");
+			(None, Body::empty())
+		}
+	}
+}
+
+/// The buffered body (when not over `CAPTURE_BODY_MAX`), and the body to pass on.
+async fn buffer_body<B>(body: B) -> (Option<Bytes>, axum::body::BoxBody)
+where
+	B: HttpBody<Data = Bytes> + Send + 'static,
+	B::Error: Into<axum::BoxError>,
+{
+	if !is_small_body(&body) {
+		return (None, boxed(body));
+	}
+
+	match hyper::body::to_bytes(body).await {
+		Ok(bytes) => (Some(bytes.clone()), boxed(Full::from(bytes))),
+		// Note: The body is consumed, so the error is passed on as an empty body.
+		Err(ex) => {
+			let ex: axum::BoxError = ex.into();
+			warn!("capture body read failed - {ex}");
+			(None, boxed(Full::from(Bytes::new())))
+		}
+	}
+}
+
+fn is_small_body(body: &impl HttpBody) -> bool {
+	body.size_hint()
+		.upper()
+		.is_some_and(|upper| upper <= CAPTURE_BODY_MAX)
+}
+
+async fn write_capture(file: &PathBuf, capture: &Value) -> std::io::Result<()> {
+	if let Some(dir) = file.parent() {
+		tokio::fs::create_dir_all(dir).await?;
+	}
+	let content = serde_json::to_vec_pretty(capture)?;
+	tokio::fs::write(file, content).await
+}
+
+fn headers_value(headers: &HeaderMap) -> Value {
+	let mut map = Map::new();
+	for (name, value) in headers {
+		let value = Value::String(String::from_utf8_lossy(value.as_bytes()).into());
+		map.insert(name.to_string(), value);
+	}
+	Value::Object(map)
+}
+
+/// The json body as json, otherwise as a string (or `null` if not utf8).
+fn body_value(body: Option<&Bytes>) -> Value {
+	let Some(body) = body.filter(|body| !body.is_empty()) else {
+		return Value::Null;
+	};
+
+	serde_json::from_slice(body).unwrap_or_else(|_| {
+		match std::str::from_utf8(body) {
+			Ok(text) => Value::String(text.to_string()),
+			Err(_) => Value::Null,
+		}
+	})
+}
+
+/// The label is a dir name, so only `[a-zA-Z0-9_-]`.
+fn is_valid_label(label: &str) -> bool {
+	!label.is_empty()
+		&& label.len() <= CAPTURE_LABEL_MAX_LEN
+		&& label
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_body_value() {
+		let fx_json = Bytes::from_static(br#"{"method":"list_tasks"}"#);
+		let fx_text = Bytes::from_static(b"plain");
+		let fx_bin = Bytes::from_static(&[0xff, 0xfe]);
+
+		assert_eq!(body_value(Some(&fx_json)), json!({"method": "list_tasks"}));
+		assert_eq!(body_value(Some(&fx_text)), json!("plain"));
+		assert_eq!(body_value(Some(&fx_bin)), Value::Null);
+		assert_eq!(body_value(None), Value::Null);
+	}
+
+	#[test]
+	fn test_is_valid_label() {
+		assert!(is_valid_label("bug-123_a"));
+		assert!(!is_valid_label(""));
+		assert!(!is_valid_label("../etc"));
+		assert!(!is_valid_label(&"a".repeat(65)));
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,112 @@
+use crate::web::client_ip::ClientIp;
+use crate::web::mw_res_map::early_error_response;
+use crate::web::{Error, Result};
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use ipnet::IpNet;
+use lib_core::config;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Client ip allow/deny lists (cidrs).
+///
+/// - The deny list takes precedence.
+/// - An empty allow list allows all (non denied) ips.
+///
+/// Can be instantiated with other lists to restrict a sub router
+/// (e.g., admin routes to the internal ranges).
+#[derive(Clone, Default)]
+pub struct IpFilter {
+	allow: Arc<Vec<IpNet>>,
+	deny: Arc<Vec<IpNet>>,
+}
+
+impl IpFilter {
+	pub fn new(allow: Vec<IpNet>, deny: Vec<IpNet>) -> Self {
+		Self {
+			allow: Arc::new(allow),
+			deny: Arc::new(deny),
+		}
+	}
+
+	/// From the `IP_ALLOW_LIST` and `IP_DENY_LIST` config.
+	pub fn from_config() -> Self {
+		let config = config();
+		Self::new(config.IP_ALLOW_LIST.clone(), config.IP_DENY_LIST.clone())
+	}
+
+	pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+		if self.deny.iter().any(|net| net.contains(ip)) {
+			return false;
+		}
+		self.allow.is_empty() || self.allow.iter().any(|net| net.contains(ip))
+	}
+}
+
+/// Fails the request with `web::Error::IpNotAllowed` when the client ip is not
+/// allowed (or with the `ClientIp` rejection when unknown).
+///
+/// NOTE: Must be the outermost layer (with mw_load_shed), so a denied request
+///       does no work (e.g., no ctx resolve db query). Hence, its error
+///       response is built here (see `early_error_response`).
+pub async fn mw_ip_filter<B>(
+	State(ip_filter): State<IpFilter>,
+	client_ip: Result<ClientIp>,
+	req: Request<B>,
+	next: Next<B>,
+) -> Response {
+	let ip = match client_ip {
+		Ok(ClientIp(ip)) => ip,
+		Err(ex) => return early_error_response(ex),
+	};
+	debug!("{:<12} - mw_ip_filter - {ip}", "MIDDLEWARE");
+
+	if !ip_filter.is_allowed(&ip) {
+		warn!("{:<12} - mw_ip_filter - ip {ip} not allowed", "MIDDLEWARE");
+		return early_error_response(Error::IpNotAllowed { ip: ip.to_string() });
+	}
+
+	next.run(req).await
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use axum::body::Body;
+	use axum::http::StatusCode;
+	use axum::routing::get;
+	use axum::{middleware, Router};
+	use serde_json::Value;
+	use tower::ServiceExt;
+
+	#[tokio::test]
+	async fn test_mw_ip_filter_unknown_ip() -> Result<()> {
+		// -- Setup & Fixtures
+		// Note: No `ConnectInfo` (nor PROXY protocol address) in the request.
+		let app = Router::new()
+			.route("/api/fx", get(|| async { "fx" }))
+			.layer(middleware::from_fn_with_state(
+				IpFilter::default(),
+				mw_ip_filter,
+			));
+
+		// -- Exec
+		let res = app
+			.oneshot(Request::get("/api/fx").body(Body::empty())?)
+			.await?;
+
+		// -- Check - the json error response, built without mw_res_map.
+		assert_eq!(res.status(), StatusCode::FORBIDDEN);
+		let body = hyper::body::to_bytes(res.into_body()).await?;
+		let body: Value = serde_json::from_slice(&body)?;
+		assert_eq!(body["error"]["code"], "access.ip_not_allowed");
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,138 @@
+use crate::web::mw_res_map::early_error_response;
+use crate::web::Error;
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use lib_core::config;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, warn};
+
+/// The `Retry-After` of the shed requests.
+const SHED_RETRY_AFTER_SEC: u64 = 1;
+
+/// The probes are never shed (a saturated instance is still alive).
+const UNLIMITED_PATHS: &[&str] = &["/healthz", "/livez", "/readyz"];
+
+/// The global in flight requests ceiling (`MAX_CONCURRENT_REQUESTS`), above
+/// which the requests are rejected at once, rather than queued until the db
+/// pool starves.
+#[derive(Clone)]
+pub struct LoadShedder {
+	permits: Arc<Semaphore>,
+}
+
+impl LoadShedder {
+	pub fn new(max_concurrent: u32) -> Self {
+		Self {
+			permits: Arc::new(Semaphore::new(max_concurrent as usize)),
+		}
+	}
+
+	/// From the `MAX_CONCURRENT_REQUESTS` config.
+	pub fn from_config() -> Self {
+		Self::new(config().MAX_CONCURRENT_REQUESTS)
+	}
+
+	/// A request slot, held until dropped, or None when saturated.
+	fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+		self.permits.clone().try_acquire_owned().ok()
+	}
+}
+
+/// Fails the request with `web::Error::Overloaded` (503, with a `Retry-After`)
+/// when `MAX_CONCURRENT_REQUESTS` requests are already in flight.
+///
+/// NOTE: As mw_req_timeout, only the response headers are counted, so the
+///       websocket and streamed responses do not hold a slot.
+///
+/// NOTE: Must be the outermost layer (with mw_ip_filter), so a shed request
+///       does no work (e.g., no ctx resolve db query). Hence, its error
+///       response is built here (see `early_error_response`).
+pub async fn mw_load_shed<B>(
+	State(load_shedder): State<LoadShedder>,
+	req: Request<B>,
+	next: Next<B>,
+) -> Response {
+	debug!("{:<12} - mw_load_shed", "MIDDLEWARE");
+
+	if UNLIMITED_PATHS.contains(&req.uri().path()) {
+		return next.run(req).await;
+	}
+
+	let Some(_permit) = load_shedder.try_acquire() else {
+		warn!("{:<12} - mw_load_shed - request shed", "MIDDLEWARE");
+		crate::web::metrics::record_request_shed();
+		return early_error_response(Error::Overloaded {
+			retry_after_sec: SHED_RETRY_AFTER_SEC,
+		});
+	};
+
+	next.run(req).await
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use axum::body::Body;
+	use axum::http::{header, StatusCode};
+	use axum::routing::get;
+	use axum::{middleware, Router};
+	use serde_json::Value;
+	use tower::ServiceExt;
+
+	#[test]
+	fn test_load_shedder_try_acquire() {
+		// -- Setup & Fixtures
+		let fx_shedder = LoadShedder::new(2);
+
+		// -- Exec & Check
+		let permit_1 = fx_shedder.try_acquire();
+		let permit_2 = fx_shedder.clone().try_acquire();
+		assert!(permit_1.is_some() && permit_2.is_some());
+		assert!(fx_shedder.try_acquire().is_none(), "should be saturated");
+
+		drop(permit_1);
+		assert!(
+			fx_shedder.try_acquire().is_some(),
+			"should have a slot back"
+		);
+	}
+
+	#[tokio::test]
+	async fn test_mw_load_shed_saturated() -> Result<()> {
+		// -- Setup & Fixtures
+		let app = Router::new()
+			.route("/api/fx", get(|| async { "fx" }))
+			.route("/readyz", get(|| async { "ready" }))
+			.layer(middleware::from_fn_with_state(
+				LoadShedder::new(0),
+				mw_load_shed,
+			));
+
+		// -- Exec
+		let res = app
+			.clone()
+			.oneshot(Request::get("/api/fx").body(Body::empty())?)
+			.await?;
+
+		// -- Check - the error response, built without mw_res_map.
+		assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+		assert_eq!(res.headers()[header::RETRY_AFTER], "1");
+		let body = hyper::body::to_bytes(res.into_body()).await?;
+		let body: Value = serde_json::from_slice(&body)?;
+		assert_eq!(body["error"]["code"], "service.overloaded");
+
+		// -- Check - the probes are never shed.
+		let res = app
+			.oneshot(Request::get("/readyz").body(Body::empty())?)
+			.await?;
+		assert_eq!(res.status(), StatusCode::OK);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
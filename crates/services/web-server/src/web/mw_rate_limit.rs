@@ -0,0 +1,174 @@
+use crate::web::client_ip::ClientIp;
+use crate::web::mw_auth::CtxW;
+use crate::web::{Error, Result};
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultKeyedRateLimiter, Quota};
+use lib_core::config::{ConfigHandle, ReloadableConfig};
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+
+// region:    --- RateLimiter
+
+/// Token buckets (per client ip, and per authenticated user) for the `/api` tree,
+/// with the sustained rate and burst from the reloadable config.
+///
+/// Note: The buckets are rebuilt (so reset) when a config reload changes the rates.
+#[derive(Clone)]
+pub struct RateLimiter {
+	config: ConfigHandle,
+	limiters: Arc<RwLock<Arc<Limiters>>>,
+}
+
+struct Limiters {
+	per_sec: u32,
+	burst: u32,
+	by_ip: DefaultKeyedRateLimiter<IpAddr>,
+	by_user: DefaultKeyedRateLimiter<i64>,
+}
+
+impl Limiters {
+	fn new(reloadable: &ReloadableConfig) -> Self {
+		Self::with_rates(reloadable.RATE_LIMIT_PER_SEC, reloadable.RATE_LIMIT_BURST)
+	}
+
+	fn with_rates(per_sec: u32, burst: u32) -> Self {
+		// Note: Config values are validated as non zero at load time.
+		let quota =
+			Quota::per_second(NonZeroU32::new(per_sec).unwrap_or(NonZeroU32::MIN))
+				.allow_burst(NonZeroU32::new(burst).unwrap_or(NonZeroU32::MIN));
+
+		Self {
+			per_sec,
+			burst,
+			by_ip: DefaultKeyedRateLimiter::keyed(quota),
+			by_user: DefaultKeyedRateLimiter::keyed(quota),
+		}
+	}
+
+	/// Takes one token from the user bucket if any, then from the ip bucket.
+	/// On failure, returns the number of seconds the client should wait.
+	///
+	/// Note: The user bucket first, so a throttled user does not spend the
+	///       tokens of its ip, shared with the other users behind it.
+	fn check(
+		&self,
+		ip: IpAddr,
+		user_id: Option<i64>,
+	) -> core::result::Result<(), u64> {
+		let clock = DefaultClock::default();
+		let wait_secs = |not_until: governor::NotUntil<_>| {
+			not_until.wait_time_from(clock.now()).as_secs().max(1)
+		};
+
+		if let Some(user_id) = user_id {
+			self.by_user.check_key(&user_id).map_err(wait_secs)?;
+		}
+		self.by_ip.check_key(&ip).map_err(wait_secs)?;
+
+		Ok(())
+	}
+}
+
+impl RateLimiter {
+	pub fn new(config: ConfigHandle) -> Self {
+		let limiters = Limiters::new(&config.current());
+		Self {
+			config,
+			limiters: Arc::new(RwLock::new(Arc::new(limiters))),
+		}
+	}
+
+	/// The `Limiters::check`, with the current limiters (if enabled).
+	fn check(
+		&self,
+		ip: IpAddr,
+		user_id: Option<i64>,
+	) -> core::result::Result<(), u64> {
+		let reloadable = self.config.current();
+		if !reloadable.RATE_LIMIT_ENABLED {
+			return Ok(());
+		}
+
+		self.limiters_for(&reloadable).check(ip, user_id)
+	}
+
+	/// Returns the limiters, rebuilt if the reloaded rates changed.
+	fn limiters_for(&self, reloadable: &ReloadableConfig) -> Arc<Limiters> {
+		let limiters = self
+			.limiters
+			.read()
+			.unwrap_or_else(|e| e.into_inner())
+			.clone();
+		if limiters.per_sec == reloadable.RATE_LIMIT_PER_SEC
+			&& limiters.burst == reloadable.RATE_LIMIT_BURST
+		{
+			return limiters;
+		}
+
+		let limiters = Arc::new(Limiters::new(reloadable));
+		*self.limiters.write().unwrap_or_else(|e| e.into_inner()) = limiters.clone();
+		limiters
+	}
+
+	/// Drops the buckets which are back to full, to keep memory bounded.
+	pub fn retain_recent(&self) {
+		let limiters = self
+			.limiters
+			.read()
+			.unwrap_or_else(|e| e.into_inner())
+			.clone();
+		limiters.by_ip.retain_recent();
+		limiters.by_user.retain_recent();
+	}
+}
+
+// endregion: --- RateLimiter
+
+pub async fn mw_rate_limit<B>(
+	State(rate_limiter): State<RateLimiter>,
+	ClientIp(ip): ClientIp,
+	ctx: Option<CtxW>,
+	req: Request<B>,
+	next: Next<B>,
+) -> Result<Response> {
+	debug!("{:<12} - mw_rate_limit", "MIDDLEWARE");
+
+	let user_id = ctx.map(|CtxW(ctx)| ctx.user_id());
+	rate_limiter
+		.check(ip, user_id)
+		.map_err(|retry_after_sec| Error::RateLimited { retry_after_sec })?;
+
+	Ok(next.run(req).await)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_throttled_user_keeps_ip_tokens() {
+		// -- Setup & Fixtures
+		let limiters = Limiters::with_rates(1, 2);
+		let fx_ip_1: IpAddr = "192.0.2.1".parse().unwrap();
+		let fx_ip_2: IpAddr = "192.0.2.2".parse().unwrap();
+		// The user spends its burst, from two ips (one token left on each).
+		assert!(limiters.check(fx_ip_1, Some(1000)).is_ok());
+		assert!(limiters.check(fx_ip_2, Some(1000)).is_ok());
+
+		// -- Exec
+		let res = limiters.check(fx_ip_1, Some(1000));
+
+		// -- Check - throttled, but the ip token is left for the other users.
+		assert!(res.is_err());
+		assert!(limiters.check(fx_ip_1, Some(1001)).is_ok());
+		assert!(limiters.check(fx_ip_1, Some(1001)).is_err());
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,62 @@
+//! The read-only api mode (the reloadable `READ_ONLY` config), e.g., during a
+//! failover or a data migration, where the mutations fail with
+//! `web::Error::ReadOnlyMode` (503 `READ_ONLY_MODE`), and the reads are served.
+//!
+//! - json-rpc - the mutation methods (see `RpcMethodKind`).
+//! - REST (and hooks, import) - the verbs other than `GET`, `HEAD`, `OPTIONS`
+//!   (see `mw_read_only`).
+//! - GraphQL - the mutations (see `graphql::MutationRoot`).
+
+use crate::web::{Error, Result};
+use axum::http::{Method, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use lib_core::config::config;
+use tracing::debug;
+
+pub fn is_read_only() -> bool {
+	config().RELOADABLE.current().READ_ONLY
+}
+
+/// Fails with `ReadOnlyMode` when in the read-only mode (e.g., for the
+/// handlers writing on any verb).
+pub fn check_writable(mutation: impl Into<String>) -> Result<()> {
+	if is_read_only() {
+		return Err(Error::ReadOnlyMode {
+			mutation: mutation.into(),
+		});
+	}
+
+	Ok(())
+}
+
+/// Fails the mutating verbs in the read-only mode (see module doc).
+pub async fn mw_read_only<B>(req: Request<B>, next: Next<B>) -> Result<Response> {
+	debug!("{:<12} - mw_read_only", "MIDDLEWARE");
+
+	if !is_safe_method(req.method()) {
+		check_writable(format!("{} {}", req.method(), req.uri().path()))?;
+	}
+
+	Ok(next.run(req).await)
+}
+
+fn is_safe_method(method: &Method) -> bool {
+	matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_safe_method() {
+		assert!(is_safe_method(&Method::GET));
+		assert!(is_safe_method(&Method::HEAD));
+		assert!(!is_safe_method(&Method::POST));
+		assert!(!is_safe_method(&Method::PATCH));
+		assert!(!is_safe_method(&Method::DELETE));
+	}
+}
+// endregion: --- Tests
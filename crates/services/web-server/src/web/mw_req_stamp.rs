@@ -1,14 +1,25 @@
+use crate::log::redact_path;
 use crate::web::{Error, ReqStamp, Result};
 use async_trait::async_trait;
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
-use axum::http::Request;
+use axum::http::{HeaderName, HeaderValue, Request};
 use axum::middleware::Next;
 use axum::response::Response;
 use lib_base::time::now_utc;
-use tracing::debug;
+use lib_core::config::config;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::{debug, info_span, Instrument};
 use uuid::Uuid;
 
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Max length of an incoming `x-request-id` to be accepted as is.
+const REQ_ID_MAX_LEN: usize = 128;
+
 pub async fn mw_req_stamp<B>(
 	mut req: Request<B>,
 	next: Next<B>,
@@ -16,11 +27,70 @@ pub async fn mw_req_stamp<B>(
 	debug!("{:<12} - mw_req_stamp_resolver", "MIDDLEWARE");
 
 	let time_in = now_utc();
-	let uuid = Uuid::new_v4();
+	let deadline =
+		Instant::now() + Duration::from_secs_f64(config().REQUEST_TIMEOUT_SEC);
+	// -- Accept the incoming request id (e.g., from a proxy) or create one.
+	let req_id = req
+		.headers()
+		.get(&X_REQUEST_ID)
+		.and_then(|val| val.to_str().ok())
+		.filter(|req_id| is_valid_req_id(req_id))
+		.map(|req_id| req_id.to_string())
+		.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+	req.extensions_mut().insert(ReqStamp {
+		req_id: req_id.clone(),
+		time_in,
+		deadline,
+	});
+
+	// -- Exec the request within a span, so every log line has the request_id,
+	//    and the logs can be filtered by its fields, e.g.,
+	//    `web_server[req{rpc.method=create_task}]=debug`.
+	//    (user_id is recorded by mw_ctx_resolve, rpc.method by the rpc handler,
+	//    and the path is redacted, see `redact_path`)
+	//    The `log_sampled` requests are also logged with the `LOG_SAMPLE_FILTER`
+	//    (see config_reload `log_env_filter`).
+	let log_sampled =
+		is_log_sampled(&req_id, config().RELOADABLE.current().LOG_SAMPLE_PERCENT);
+	let span = info_span!(
+		"req",
+		request_id = %req_id,
+		log_sampled,
+		http.method = %req.method(),
+		http.path = redact_path(req.uri().path()),
+		user_id = Empty,
+		rpc.method = Empty,
+	);
+	let mut res = next.run(req).instrument(span).await;
+
+	// -- Propagate the request id to the client.
+	if let Ok(req_id) = HeaderValue::from_str(&req_id) {
+		res.headers_mut().insert(X_REQUEST_ID, req_id);
+	}
+
+	Ok(res)
+}
 
-	req.extensions_mut().insert(ReqStamp { uuid, time_in });
+/// Sampled by the request id hash, so the services sharing an `x-request-id`
+/// sample the same requests.
+fn is_log_sampled(req_id: &str, sample_percent: f64) -> bool {
+	if sample_percent <= 0. {
+		return false;
+	}
+
+	let mut hasher = DefaultHasher::new();
+	req_id.hash(&mut hasher);
+	// In hundredths of percent.
+	(hasher.finish() % 10_000) < (sample_percent * 100.) as u64
+}
 
-	Ok(next.run(req).await)
+fn is_valid_req_id(req_id: &str) -> bool {
+	!req_id.is_empty()
+		&& req_id.len() <= REQ_ID_MAX_LEN
+		&& req_id
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
 }
 
 // region:    --- ReqStamp Extractor
@@ -39,3 +109,29 @@ impl<S: Send + Sync> FromRequestParts<S> for ReqStamp {
 	}
 }
 // endregion: --- ReqStamp Extractor
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_log_sampled_percent() {
+		let fx_req_ids: Vec<String> =
+			(0..1000).map(|_| Uuid::new_v4().to_string()).collect();
+		let sampled_count = |percent: f64| {
+			fx_req_ids
+				.iter()
+				.filter(|req_id| is_log_sampled(req_id, percent))
+				.count()
+		};
+
+		assert_eq!(sampled_count(0.), 0);
+		assert_eq!(sampled_count(100.), 1000);
+		let count = sampled_count(10.);
+		assert!((30..=200).contains(&count), "10% sampled {count} of 1000");
+		// The same request id, the same decision.
+		assert_eq!(is_log_sampled("req-01", 50.), is_log_sampled("req-01", 50.));
+	}
+}
+// endregion: --- Tests
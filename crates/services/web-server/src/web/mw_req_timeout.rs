@@ -0,0 +1,29 @@
+use crate::web::{Error, ReqStamp, Result};
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use tokio::time::{timeout_at, Instant};
+use tracing::debug;
+
+/// Fails the request with `web::Error::RequestTimeout` (503) when its response
+/// is not ready by the `ReqStamp` deadline (i.e., `REQUEST_TIMEOUT_SEC`).
+///
+/// The same deadline is set on the `Ctx` (see mw_ctx_resolve), and applied as
+/// the db `statement_timeout` by the model, so the db work stops as well.
+///
+/// NOTE: Only the response headers are timed, so the websocket and streamed
+///       responses are not cut.
+///
+/// NOTE: Must be layered inside mw_res_map and mw_req_stamp (as catch_panic).
+pub async fn mw_req_timeout<B>(req: Request<B>, next: Next<B>) -> Result<Response> {
+	debug!("{:<12} - mw_req_timeout", "MIDDLEWARE");
+
+	let Some(deadline) = req.extensions().get::<ReqStamp>().map(|rs| rs.deadline)
+	else {
+		return Ok(next.run(req).await);
+	};
+
+	timeout_at(Instant::from_std(deadline), next.run(req))
+		.await
+		.map_err(|_| Error::RequestTimeout)
+}
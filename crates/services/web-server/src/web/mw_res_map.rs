@@ -1,28 +1,36 @@
 use axum::{
-	http::{Method, Uri},
+	http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri},
 	response::{IntoResponse, Response},
 	Json,
 };
 
-use serde_json::{json, to_value};
+use lib_base::time::now_utc;
+use lib_core::model;
+use serde_json::{json, to_value, Value};
 use tracing::{debug, error};
-use uuid::Uuid;
 
 use crate::{
 	log::log_request,
-	web::{self, mw_auth::CtxW, rpc::RpcInfo, ReqStamp},
+	web::{
+		self,
+		client_ip::ClientIp,
+		mw_auth::CtxW,
+		rpc::{self, RpcInfo},
+		templates, ClientError, ReqStamp,
+	},
 };
 
 pub async fn mw_reponse_map(
 	ctx: Option<CtxW>,
+	client_ip: Option<ClientIp>,
 	uri: Uri,
 	req_method: Method,
 	req_stamp: ReqStamp,
+	req_headers: HeaderMap,
 	res: Response,
 ) -> Response {
 	let ctx = ctx.map(|c| c.0);
 	debug!("{:<12} - mw_reponse_map", "RES_MAPPER");
-	let uuid = Uuid::new_v4();
 
 	let rpc_info = res.extensions().get::<RpcInfo>();
 
@@ -35,34 +43,48 @@ pub async fn mw_reponse_map(
 		client_status_error
 			.as_ref()
 			.map(|(status_code, client_error)| {
-				let client_error = to_value(client_error).ok();
-				let message = client_error.as_ref().and_then(|v| v.get("message"));
-				let detail = client_error.as_ref().and_then(|v| v.get("detail"));
-
-				let client_error_body = json!({
-					"id": rpc_info.as_ref().map(|rpc| rpc.id.clone()),
-					"error": {
-						"message": message, // Variant name
-						"data": {
-							"req_uuid": uuid.to_string(),
-							"detail": detail
-						},
-					}
-				});
+				// -- Browser page navigation, the error page.
+				if rpc_info.is_none() && templates::accepts_html(&req_headers) {
+					return templates::render_error_page(
+						*status_code,
+						client_error.as_ref(),
+						&req_stamp.req_id,
+					);
+				}
+
+				let rpc_id = rpc_info.and_then(|rpc| rpc.id.clone());
+				let mut response = client_error_response(
+					*status_code,
+					client_error,
+					web_error,
+					rpc_id,
+					Some(&req_stamp.req_id),
+				);
 
-				debug!("CLIENT ERROR BODY:\n{client_error_body}");
+				// -- Keep the eventual api version deprecation headers.
+				for name in [rpc::DEPRECATION, header::LINK] {
+					if let Some(value) = res.headers().get(&name) {
+						response.headers_mut().insert(name, value.clone());
+					}
+				}
 
-				// Build the new response from the client_error_body
-				(*status_code, Json(client_error_body)).into_response()
+				response
 			});
 
+	// -- Record the request metrics.
+	let status = error_response.as_ref().unwrap_or(&res).status();
+	let duration = (now_utc() - req_stamp.time_in).unsigned_abs();
+	web::metrics::record_request(&req_method, status, duration);
+
 	// -- Build and log the server log line.
 	let client_error = client_status_error.unzip().1;
 	// TODO: Need to hander if log_request fail (but should not fail request)
 	let log_request_res = log_request(
 		req_method,
 		uri,
+		status,
 		req_stamp,
+		client_ip,
 		rpc_info,
 		ctx,
 		web_error,
@@ -79,3 +101,65 @@ pub async fn mw_reponse_map(
 
 	error_response.unwrap_or(res)
 }
+
+/// The client error response of an error failing the request before the
+/// mw_reponse_map layer (e.g., mw_ip_filter, mw_load_shed, which are the
+/// outermost layers), so without the `req_uuid` nor the request log line.
+pub fn early_error_response(web_error: web::Error) -> Response {
+	let (status_code, client_error) = web_error.client_status_and_error();
+
+	client_error_response(status_code, &client_error, Some(&web_error), None, None)
+}
+
+/// The json error envelope, with the eventual client error headers.
+fn client_error_response(
+	status_code: StatusCode,
+	client_error: &ClientError,
+	web_error: Option<&web::Error>,
+	rpc_id: Option<Value>,
+	req_id: Option<&str>,
+) -> Response {
+	let code = client_error.code();
+	let server_error = web_error.and_then(|we| we.dev_detail());
+	let client_error = to_value(client_error).ok();
+	let message = client_error.as_ref().and_then(|v| v.get("message"));
+	let detail = client_error.as_ref().and_then(|v| v.get("detail"));
+
+	let client_error_body = json!({
+		"id": rpc_id,
+		"error": {
+			"code": code, // Stable code (see /api/errors)
+			"message": message, // Variant name
+			"data": {
+				"req_uuid": req_id,
+				"detail": detail,
+				// Only in the dev env.
+				"server_error": server_error,
+			},
+		}
+	});
+
+	debug!("CLIENT ERROR BODY:\n{client_error_body}");
+
+	// Build the new response from the client_error_body
+	let mut response = (status_code, Json(client_error_body)).into_response();
+
+	// -- Add the eventual client error headers.
+	if let Some(
+		web::Error::RateLimited { retry_after_sec }
+		| web::Error::Overloaded { retry_after_sec }
+		| web::Error::Model(model::Error::ServiceUnavailable { retry_after_sec }),
+	) = web_error
+	{
+		response
+			.headers_mut()
+			.insert(header::RETRY_AFTER, HeaderValue::from(*retry_after_sec));
+	}
+	if let Some(web::Error::RestPreconditionFailed { etag }) = web_error {
+		if let Ok(etag) = HeaderValue::from_str(etag) {
+			response.headers_mut().insert(header::ETAG, etag);
+		}
+	}
+
+	response
+}
@@ -0,0 +1,78 @@
+use crate::log::redact_path;
+use crate::web::mw_auth::CtxW;
+use crate::web::rpc::RpcInfo;
+use crate::web::ReqStamp;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use lib_base::time::now_utc;
+use lib_core::config::config;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Logs a warning, and counts it in `slow_requests_total`, for the requests
+/// whose response took more than `SLOW_REQUEST_MS` (since mw_req_stamp).
+///
+/// NOTE: As mw_req_timeout, only the response headers are timed.
+///
+/// NOTE: Must be layered inside mw_res_map (for the `RpcInfo` of the error
+///       responses), and mw_ctx_resolve (for the user).
+pub async fn mw_slow_req<B>(
+	ctx: Option<CtxW>,
+	req: Request<B>,
+	next: Next<B>,
+) -> Response {
+	debug!("{:<12} - mw_slow_req", "MIDDLEWARE");
+
+	let Some(time_in) = req.extensions().get::<ReqStamp>().map(|rs| rs.time_in)
+	else {
+		return next.run(req).await;
+	};
+	let method = req.method().clone();
+	let path = redact_path(req.uri().path());
+
+	let res = next.run(req).await;
+
+	let duration = (now_utc() - time_in).unsigned_abs();
+	let threshold = Duration::from_millis(config().SLOW_REQUEST_MS.into());
+	if is_slow(duration, threshold) {
+		let rpc_method = res
+			.extensions()
+			.get::<RpcInfo>()
+			.map(|rpc| rpc.method.as_str());
+		warn!(
+			http.method = %method,
+			http.path = path,
+			rpc.method = rpc_method,
+			user_id = ctx.map(|ctx| ctx.0.user_id()),
+			duration_ms = duration.as_millis() as u64,
+			"{:<12} - mw_slow_req - slow request",
+			"MIDDLEWARE"
+		);
+		crate::web::metrics::record_slow_request(&method);
+	}
+
+	res
+}
+
+fn is_slow(duration: Duration, threshold: Duration) -> bool {
+	duration > threshold
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_slow() {
+		// -- Setup & Fixtures
+		let fx_threshold = Duration::from_millis(1000);
+
+		// -- Exec & Check
+		assert!(is_slow(Duration::from_millis(1001), fx_threshold));
+		assert!(!is_slow(Duration::from_millis(1000), fx_threshold));
+		assert!(!is_slow(Duration::from_millis(20), fx_threshold));
+	}
+}
+// endregion: --- Tests
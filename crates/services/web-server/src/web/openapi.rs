@@ -0,0 +1,142 @@
+//! The OpenAPI document of the http routes (json-rpc excluded),
+//! served at `/api/openapi.json`, with a Swagger UI page at `/api/docs`.
+//!
+//! The schemas are derived (utoipa `ToSchema`) from the same serde types
+//! the routes use (e.g., `Task`, `TaskForCreate`).
+
+use crate::web::{
+	error::ClientErrorInfo, routes_errors, routes_export, routes_health,
+	routes_hooks, routes_import, routes_login, routes_rest, routes_share, templates,
+	Result,
+};
+use axum::{response::Html, routing::get, Json, Router};
+use minijinja::context;
+use std::sync::OnceLock;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::OpenApi;
+
+const OPENAPI_JSON_PATH: &str = "/api/openapi.json";
+
+#[derive(OpenApi)]
+#[openapi(
+	info(title = "web-server"),
+	paths(
+		routes_login::api_login_handler,
+		routes_login::api_logoff_handler,
+		routes_health::healthz_handler,
+		routes_health::livez_handler,
+		routes_health::readyz_handler,
+		routes_errors::api_errors_handler,
+		routes_export::export_tasks_csv_handler,
+		routes_import::import_tasks_handler,
+		routes_share::shared_project_handler,
+		routes_share::shared_tasks_handler,
+		routes_hooks::hook_handler,
+	),
+	components(schemas(
+		routes_login::LoginPayload,
+		routes_login::LogoffPayload,
+		routes_import::ImportReport,
+		routes_import::RejectedRow,
+		routes_hooks::HookResult,
+		ClientErrorInfo
+	)),
+	tags(
+		(name = "auth", description = "Login / logoff (auth-token cookie)"),
+		(name = "health", description = "Health, liveness, and readiness probes"),
+		(name = "errors", description = "The client error catalog"),
+		(name = "tasks", description = "Tasks REST CRUD"),
+		(name = "projects", description = "Projects REST CRUD"),
+		(name = "shared", description = "The read-only project share links (no login)"),
+		(name = "hooks", description = "The inbound hooks (signed, no login)"),
+	)
+)]
+struct ApiDoc;
+
+/// The full OpenAPI document (built once).
+pub fn openapi() -> &'static utoipa::openapi::OpenApi {
+	static INSTANCE: OnceLock<utoipa::openapi::OpenApi> = OnceLock::new();
+
+	INSTANCE.get_or_init(|| {
+		let mut openapi = ApiDoc::openapi();
+		routes_rest::add_openapi(&mut openapi);
+
+		openapi
+			.components
+			.get_or_insert_with(Default::default)
+			.add_security_scheme(
+				"auth_token",
+				SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(
+					crate::web::AUTH_TOKEN,
+				))),
+			);
+
+		openapi
+	})
+}
+
+pub fn routes() -> Router {
+	Router::new()
+		.route(OPENAPI_JSON_PATH, get(|| async { Json(openapi()) }))
+		.route("/api/docs", get(swagger_ui_handler))
+}
+
+async fn swagger_ui_handler() -> Result<Html<String>> {
+	templates::render(
+		"swagger.html",
+		context! { openapi_url => OPENAPI_JSON_PATH },
+	)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use serde_json::Value;
+
+	#[test]
+	fn test_openapi_refs_resolve_ok() -> Result<()> {
+		// -- Exec
+		let openapi = serde_json::to_value(openapi())?;
+
+		// -- Check
+		for path in ["/api/login", "/healthz", "/api/tasks", "/api/projects/{id}"] {
+			assert!(openapi["paths"][path].is_object(), "missing path {path}");
+		}
+
+		// All the `$ref` must point to a component schema.
+		let mut refs = Vec::new();
+		collect_refs(&openapi, &mut refs);
+		assert!(!refs.is_empty());
+		for schema_ref in refs {
+			let name = schema_ref
+				.strip_prefix("#/components/schemas/")
+				.unwrap_or(&schema_ref);
+			assert!(
+				openapi["components"]["schemas"][name].is_object(),
+				"unresolved ref {schema_ref}"
+			);
+		}
+
+		Ok(())
+	}
+
+	fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+		match value {
+			Value::Object(map) => {
+				for (key, val) in map {
+					match (key.as_str(), val) {
+						("$ref", Value::String(schema_ref)) => {
+							refs.push(schema_ref.to_string())
+						}
+						_ => collect_refs(val, refs),
+					}
+				}
+			}
+			Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+			_ => {}
+		}
+	}
+}
+// endregion: --- Tests
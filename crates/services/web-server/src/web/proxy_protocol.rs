@@ -0,0 +1,209 @@
+//! The PROXY protocol (v1 and v2) acceptor, for the `SERVICE_PROXY_PROTOCOL`
+//! listeners (e.g., behind a L4 load balancer, which has no http headers).
+//!
+//! The header, sent by the proxy before the connection data, has the address of
+//! the client, set as the `ProxiedAddr` request extension of the connection
+//! (see `ClientIp`).
+//!
+//! NOTE: The connections from the peers not in the `TRUSTED_PROXIES`, or without
+//!       a valid header, are closed (a required header cannot be guessed).
+//!
+//! Spec: https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+
+use axum::middleware::AddExtension;
+use axum::Extension;
+use axum_server::accept::Accept;
+use futures::future::BoxFuture;
+use hyper::server::conn::AddrStream;
+use lib_core::config::config;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tower::Layer;
+
+const HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The v1 header max length (with the `\r\n`).
+const V1_MAX_LEN: usize = 107;
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The client address from the PROXY protocol header.
+/// (none for the `UNKNOWN` / `LOCAL` ones, e.g., the proxy health checks)
+#[derive(Debug, Clone, Copy)]
+pub struct ProxiedAddr(pub Option<SocketAddr>);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProxyProtocolAcceptor;
+
+impl<S: Send + 'static> Accept<AddrStream, S> for ProxyProtocolAcceptor {
+	type Stream = AddrStream;
+	type Service = AddExtension<S, ProxiedAddr>;
+	type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+	fn accept(&self, mut stream: AddrStream, service: S) -> Self::Future {
+		Box::pin(async move {
+			let peer_ip = stream.remote_addr().ip();
+			if !config()
+				.TRUSTED_PROXIES
+				.iter()
+				.any(|net| net.contains(&peer_ip))
+			{
+				return Err(invalid_data("PROXY protocol peer not trusted"));
+			}
+
+			let addr =
+				tokio::time::timeout(HEADER_TIMEOUT, read_header(&mut stream))
+					.await
+					.map_err(|_| invalid_data("PROXY protocol header timeout"))??;
+
+			Ok((stream, Extension(ProxiedAddr(addr)).layer(service)))
+		})
+	}
+}
+
+/// Reads the header (and only it), and returns its source address.
+async fn read_header<R: AsyncRead + Unpin>(
+	stream: &mut R,
+) -> io::Result<Option<SocketAddr>> {
+	// Note: The shortest header (`PROXY UNKNOWN\r\n`) is over 12 bytes.
+	let mut start = [0u8; 12];
+	stream.read_exact(&mut start).await?;
+
+	if start.starts_with(V1_PREFIX) {
+		let mut line = start.to_vec();
+		while !line.ends_with(b"\r\n") {
+			if line.len() >= V1_MAX_LEN {
+				return Err(invalid_data("PROXY v1 header too long"));
+			}
+			line.push(stream.read_u8().await?);
+		}
+		let line = std::str::from_utf8(&line)
+			.map_err(|_| invalid_data("PROXY v1 header not utf8"))?;
+		parse_v1(line)
+	} else if start == V2_SIGNATURE {
+		let mut head = [0u8; 4];
+		stream.read_exact(&mut head).await?;
+		let mut body = vec![0u8; u16::from_be_bytes([head[2], head[3]]) as usize];
+		stream.read_exact(&mut body).await?;
+		parse_v2(head[0], head[1], &body)
+	} else {
+		Err(invalid_data("PROXY protocol header missing"))
+	}
+}
+
+/// e.g., `PROXY TCP4 203.0.113.7 10.0.0.1 51234 443\r\n`
+fn parse_v1(line: &str) -> io::Result<Option<SocketAddr>> {
+	let mut parts = line.trim_end_matches("\r\n").split(' ').skip(1);
+
+	match parts.next() {
+		Some("TCP4" | "TCP6") => (),
+		Some("UNKNOWN") => return Ok(None),
+		_ => return Err(invalid_data("PROXY v1 protocol unknown")),
+	}
+	let (Some(src_ip), Some(_dst_ip), Some(src_port)) =
+		(parts.next(), parts.next(), parts.next())
+	else {
+		return Err(invalid_data("PROXY v1 header incomplete"));
+	};
+	let ip: IpAddr = src_ip
+		.parse()
+		.map_err(|_| invalid_data("PROXY v1 source ip invalid"))?;
+	let port: u16 = src_port
+		.parse()
+		.map_err(|_| invalid_data("PROXY v1 source port invalid"))?;
+
+	Ok(Some(SocketAddr::new(ip, port)))
+}
+
+/// The v2 binary header, after the signature (the TLVs are ignored).
+fn parse_v2(ver_cmd: u8, family: u8, body: &[u8]) -> io::Result<Option<SocketAddr>> {
+	match ver_cmd {
+		0x21 => (),              // v2 PROXY
+		0x20 => return Ok(None), // v2 LOCAL
+		_ => return Err(invalid_data("PROXY v2 version or command unknown")),
+	}
+
+	let port_at = |at: usize| u16::from_be_bytes([body[at], body[at + 1]]);
+	match family {
+		// TCP4 / UDP4: src ip, dst ip, src port, dst port
+		0x11 | 0x12 if body.len() >= 12 => {
+			let ip = Ipv4Addr::new(body[0], body[1], body[2], body[3]);
+			Ok(Some(SocketAddr::new(ip.into(), port_at(8))))
+		}
+		// TCP6 / UDP6
+		0x21 | 0x22 if body.len() >= 36 => {
+			let mut octets = [0u8; 16];
+			octets.copy_from_slice(&body[..16]);
+			let ip = Ipv6Addr::from(octets);
+			Ok(Some(SocketAddr::new(ip.into(), port_at(32))))
+		}
+		// UNSPEC (or unix sockets), no ip.
+		0x00 | 0x31 | 0x32 => Ok(None),
+		_ => Err(invalid_data("PROXY v2 address family invalid")),
+	}
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_read_header_v1_ok() -> io::Result<()> {
+		// -- Setup & Fixtures
+		let fx_data =
+			b"PROXY TCP4 203.0.113.7 10.0.0.1 51234 443\r\nGET / HTTP/1.1\r\n";
+		let mut fx_stream = &fx_data[..];
+
+		// -- Exec
+		let addr = read_header(&mut fx_stream).await?;
+
+		// -- Check
+		assert_eq!(addr, Some("203.0.113.7:51234".parse().unwrap()));
+		// Only the header is read.
+		assert_eq!(fx_stream, b"GET / HTTP/1.1\r\n");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_read_header_v2_ok() -> io::Result<()> {
+		// -- Setup & Fixtures
+		let mut fx_data = V2_SIGNATURE.to_vec();
+		fx_data.extend([0x21, 0x11, 0, 12]);
+		fx_data.extend([203, 0, 113, 7, 10, 0, 0, 1]);
+		fx_data.extend(51234u16.to_be_bytes());
+		fx_data.extend(443u16.to_be_bytes());
+		fx_data.extend(b"GET");
+		let mut fx_stream = &fx_data[..];
+
+		// -- Exec
+		let addr = read_header(&mut fx_stream).await?;
+
+		// -- Check
+		assert_eq!(addr, Some("203.0.113.7:51234".parse().unwrap()));
+		assert_eq!(fx_stream, b"GET");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_read_header_unknown_and_err() {
+		let mut fx_stream = &b"PROXY UNKNOWN\r\n"[..];
+		assert!(matches!(read_header(&mut fx_stream).await, Ok(None)));
+
+		let mut fx_stream = &b"GET / HTTP/1.1\r\nHost: a\r\n"[..];
+		assert!(read_header(&mut fx_stream).await.is_err());
+
+		let fx_long = format!("PROXY TCP4 {}\r\n", "1".repeat(120));
+		let mut fx_stream = fx_long.as_bytes();
+		assert!(read_header(&mut fx_stream).await.is_err());
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,104 @@
+//! The operational endpoints of the admin listener (see `SERVICE_ADMIN_ADDR`),
+//! which are not served on the public address.
+//!
+//! - `/metrics` - prometheus text format.
+//! - `/debug/info` - process information.
+//! - `/config` - the current reloadable config, and `POST /config/reload` to reload it.
+//! - the health endpoints (`/healthz`, `/livez`, `/readyz`).
+
+use crate::web::routes_health::{self, Readiness};
+use axum::{
+	extract::State,
+	http::{header, StatusCode},
+	response::IntoResponse,
+	routing::{get, post},
+	Json, Router,
+};
+use lib_core::config::ConfigHandle;
+use lib_core::model::ModelManager;
+use metrics_exporter_prometheus::PrometheusHandle;
+use serde_json::{json, Value};
+use std::time::Instant;
+use tracing::{debug, warn};
+
+#[derive(Clone)]
+struct AdminState {
+	mm: ModelManager,
+	metrics: PrometheusHandle,
+	config: ConfigHandle,
+	started_at: Instant,
+}
+
+pub fn routes(
+	mm: ModelManager,
+	readiness: Readiness,
+	metrics: PrometheusHandle,
+	config: ConfigHandle,
+) -> Router {
+	let admin_state = AdminState {
+		mm: mm.clone(),
+		metrics,
+		config,
+		started_at: Instant::now(),
+	};
+
+	Router::new()
+		.route("/metrics", get(metrics_handler))
+		.route("/debug/info", get(debug_info_handler))
+		.route("/config", get(config_handler))
+		.route("/config/reload", post(config_reload_handler))
+		.with_state(admin_state)
+		.merge(routes_health::routes(mm, readiness))
+}
+
+async fn metrics_handler(
+	State(admin_state): State<AdminState>,
+) -> impl IntoResponse {
+	debug!("{:<12} - metrics_handler", "HANDLER");
+
+	// Note: The pool gauges are point in time, so set at the scrape.
+	crate::web::metrics::record_db_pool(admin_state.mm.db_pool_stats());
+
+	(
+		[(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+		admin_state.metrics.render(),
+	)
+}
+
+async fn debug_info_handler(State(admin_state): State<AdminState>) -> Json<Value> {
+	debug!("{:<12} - debug_info_handler", "HANDLER");
+
+	Json(json!({
+		"version": env!("CARGO_PKG_VERSION"),
+		"pid": std::process::id(),
+		"uptime_sec": admin_state.started_at.elapsed().as_secs(),
+		"available_parallelism": std::thread::available_parallelism()
+			.map(|val| val.get())
+			.ok(),
+	}))
+}
+
+async fn config_handler(State(admin_state): State<AdminState>) -> Json<Value> {
+	debug!("{:<12} - config_handler", "HANDLER");
+
+	Json(json!(*admin_state.config.current()))
+}
+
+/// Re-reads the config sources (same as SIGHUP).
+/// On error, the current values are kept, and the errors are returned.
+async fn config_reload_handler(
+	State(admin_state): State<AdminState>,
+) -> (StatusCode, Json<Value>) {
+	debug!("{:<12} - config_reload_handler", "HANDLER");
+
+	match admin_state.config.reload() {
+		Ok(reloadable) => (StatusCode::OK, Json(json!(*reloadable))),
+		Err(ex) => {
+			warn!("config reload failed - {ex:?}");
+			(
+				StatusCode::BAD_REQUEST,
+				Json(json!({"error": format!("{ex:?}")})),
+			)
+		}
+	}
+}
@@ -0,0 +1,21 @@
+use crate::web::error::{ClientErrorInfo, CLIENT_ERROR_CATALOG};
+use axum::{routing::get, Json, Router};
+use tracing::debug;
+
+pub fn routes() -> Router {
+	Router::new().route("/api/errors", get(api_errors_handler))
+}
+
+/// The catalog of the client errors (stable `code`, envelope `message`,
+/// http status, and `detail` fields), so clients can code against it.
+#[utoipa::path(
+	get,
+	path = "/api/errors",
+	tag = "errors",
+	responses((status = 200, description = "The client error catalog", body = [ClientErrorInfo]))
+)]
+pub(super) async fn api_errors_handler() -> Json<&'static [ClientErrorInfo]> {
+	debug!("{:<12} - api_errors_handler", "HANDLER");
+
+	Json(CLIENT_ERROR_CATALOG)
+}
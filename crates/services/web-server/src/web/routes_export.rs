@@ -0,0 +1,149 @@
+//! The CSV exports (e.g., for the spreadsheets), streamed as the rows are
+//! fetched (see lib-core `base::list_stream`), so they are not size limited.
+//!
+//! - `GET /api/export/tasks.csv` - the tasks of the user projects
+//!   (`?filters=<json>`, as the REST list).
+//!
+//! The times are in the user timezone (see `UserBmc::update_timezone`).
+//!
+//! NOTE: A db error in the middle of the stream aborts the response (the
+//!       status is already sent), so a truncated file is never a valid one.
+
+use crate::web::mw_auth::CtxW;
+use crate::web::routes_rest::parse_query_json;
+use crate::web::rpc::ParamsList;
+use crate::web::{Error, Result};
+use axum::body::StreamBody;
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use futures::{stream, StreamExt, TryStreamExt};
+use lib_base::time::{format_time, format_time_in_tz};
+use lib_core::model::task::{Task, TaskBmc, TaskFilter};
+use lib_core::model::user::{User, UserBmc};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+use serde_json::json;
+use std::borrow::Cow;
+use tracing::{debug, warn};
+
+const TASKS_CSV_HEADER: &str = "id,project_id,title,done,ctime,mtime\r\n";
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/export/tasks.csv", get(export_tasks_csv_handler))
+		.with_state(mm)
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+	filters: Option<String>,
+}
+
+/// The tasks of the user projects, as CSV (in `id` order).
+#[utoipa::path(
+	get,
+	path = "/api/export/tasks.csv",
+	tag = "tasks",
+	params(
+		("filters" = Option<String>, Query, description = "TaskFilter json (as the REST list)"),
+	),
+	responses(
+		(status = 200, description = "The tasks CSV", content_type = "text/csv", body = String),
+	),
+	security(("auth_token" = []))
+)]
+async fn export_tasks_csv_handler(
+	State(mm): State<ModelManager>,
+	ctx: CtxW,
+	Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse> {
+	debug!("{:<12} - export_tasks_csv_handler", "HANDLER");
+
+	let params: ParamsList<TaskFilter> = serde_json::from_value(json!({
+		"filters": parse_query_json("filters", query.filters)?,
+	}))
+	.map_err(|ex| Error::RestInvalidQuery {
+		param: "filters",
+		cause: ex.to_string(),
+	})?;
+
+	let user: User = UserBmc::get(&ctx.0, &mm, ctx.0.user_id()).await?;
+	let rows = TaskBmc::list_stream_owned(&ctx.0, &mm, params.filters)?
+		.map_ok(move |task| task_csv_row(&task, &user.timezone))
+		.map_err(|ex| {
+			warn!("export tasks.csv aborted - {ex:?}");
+			Error::Model(ex)
+		});
+	let body = stream::once(async { Ok(TASKS_CSV_HEADER.to_string()) }).chain(rows);
+
+	Ok((
+		[
+			(header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+			(
+				header::CONTENT_DISPOSITION,
+				"attachment; filename=\"tasks.csv\"",
+			),
+		],
+		StreamBody::new(body),
+	))
+}
+
+fn task_csv_row(task: &Task, tz_name: &str) -> String {
+	// Note: The timezone is validated when set, so UTC is only a fallback.
+	let time_in_tz = |time| {
+		format_time_in_tz(time, tz_name).unwrap_or_else(|_| format_time(time))
+	};
+	format!(
+		"{},{},{},{},{},{}\r\n",
+		task.id,
+		task.project_id,
+		csv_field(&task.title),
+		task.done,
+		time_in_tz(task.ctime),
+		time_in_tz(task.mtime)
+	)
+}
+
+/// The CSV (RFC 4180) field, quoted when needed.
+///
+/// Note: The texts starting as a formula (`=`, `+`, `-`, `@`) are prefixed
+///       with a `'`, so the spreadsheets do not evaluate them.
+fn csv_field(val: &str) -> Cow<'_, str> {
+	let val: Cow<str> = if val.starts_with(['=', '+', '-', '@']) {
+		format!("'{val}").into()
+	} else {
+		val.into()
+	};
+
+	if val.contains([',', '"', '\r', '\n']) {
+		format!("\"{}\"", val.replace('"', "\"\"")).into()
+	} else {
+		val
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_csv_field() {
+		let fx_cases = [
+			("plain title", "plain title"),
+			("a, b", "\"a, b\""),
+			("say \"hi\"", "\"say \"\"hi\"\"\""),
+			("two\nlines", "\"two\nlines\""),
+			("=SUM(A1:A2)", "'=SUM(A1:A2)"),
+			("-1,2", "\"'-1,2\""),
+		];
+
+		for (fx_val, expected) in fx_cases {
+			assert_eq!(csv_field(fx_val), expected, "{fx_val:?}");
+		}
+	}
+}
+// endregion: --- Tests
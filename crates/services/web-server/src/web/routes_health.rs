@@ -0,0 +1,242 @@
+use crate::web::metrics::record_db_acquire_wait;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use lib_core::config::config;
+use lib_core::model::{DbPoolStats, ModelManager};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Max time the `SELECT 1` db ping can take before being reported as failing.
+const DB_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The db check failure reason (the error detail is only logged, as the probes
+/// are not authenticated).
+const DB_FAIL_REASON: &str = "db not reachable";
+
+// region:    --- Readiness
+
+/// Shared readiness flag, false until the startup tasks (e.g., migrations)
+/// are done, so `/readyz` does not route traffic to a starting instance.
+#[derive(Clone, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+	pub fn set_ready(&self, ready: bool) {
+		self.0.store(ready, Ordering::Relaxed);
+	}
+
+	pub fn is_ready(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+// endregion: --- Readiness
+
+#[derive(Clone)]
+struct HealthState {
+	mm: ModelManager,
+	readiness: Readiness,
+	started_at: Instant,
+}
+
+pub fn routes(mm: ModelManager, readiness: Readiness) -> Router {
+	let health_state = HealthState {
+		mm,
+		readiness,
+		started_at: Instant::now(),
+	};
+
+	Router::new()
+		.route("/healthz", get(healthz_handler))
+		.route("/livez", get(livez_handler))
+		.route("/readyz", get(readyz_handler))
+		.with_state(health_state)
+}
+
+/// The process is alive (no dependency checks).
+#[utoipa::path(
+	get,
+	path = "/livez",
+	tag = "health",
+	responses((status = 200, description = "Alive", body = Value))
+)]
+async fn livez_handler() -> Json<Value> {
+	debug!("{:<12} - livez_handler", "HANDLER");
+
+	Json(json!({"status": "ok"}))
+}
+
+/// The instance can take traffic: startup tasks done, db reachable, and db pool
+/// not saturated (see `DB_POOL_ACQUIRE_WAIT_MAX_MS`).
+#[utoipa::path(
+	get,
+	path = "/readyz",
+	tag = "health",
+	responses(
+		(status = 200, description = "Ready", body = Value),
+		(status = 503, description = "Starting, db not reachable, or db pool saturated", body = Value),
+	)
+)]
+async fn readyz_handler(
+	State(health_state): State<HealthState>,
+) -> (StatusCode, Json<Value>) {
+	debug!("{:<12} - readyz_handler", "HANDLER");
+
+	let HealthState { mm, readiness, .. } = health_state;
+
+	let startup_ok = readiness.is_ready();
+	// Note: No need to hit the db if the instance is still starting.
+	let db_res = if startup_ok {
+		Some(mm.db_ping(DB_PING_TIMEOUT).await)
+	} else {
+		None
+	};
+	if let Some(Err(ex)) = &db_res {
+		warn!("{:<12} - readyz - db ping fail - {ex:?}", "HANDLER");
+	}
+
+	let acquire_wait = db_res.as_ref().and_then(|res| res.as_ref().ok()).copied();
+	if let Some(acquire_wait) = acquire_wait {
+		record_db_acquire_wait(acquire_wait);
+	}
+	let max_acquire_wait =
+		Duration::from_millis(config().DB_POOL_ACQUIRE_WAIT_MAX_MS.into());
+	let db_pool_status = db_pool_status(acquire_wait, max_acquire_wait);
+
+	let ready = startup_ok && db_pool_status == "ok";
+	let status_code = if ready {
+		StatusCode::OK
+	} else {
+		StatusCode::SERVICE_UNAVAILABLE
+	};
+
+	let body = json!({
+		"status": if ready { "ok" } else { "fail" },
+		"checks": {
+			"startup": if startup_ok { "ok" } else { "pending" },
+			"db": match &db_res {
+				Some(Ok(_)) => json!({"status": "ok"}),
+				Some(Err(_)) => db_fail_check(),
+				None => json!({"status": "not checked"}),
+			},
+			"db_pool": db_pool_check(
+				db_pool_status,
+				mm.db_pool_stats(),
+				acquire_wait,
+			),
+		}
+	});
+
+	(status_code, Json(body))
+}
+
+/// Returns the build version, uptime, and db status.
+/// (503 when the db cannot be reached, for load balancers and k8s probes)
+#[utoipa::path(
+	get,
+	path = "/healthz",
+	tag = "health",
+	responses(
+		(status = 200, description = "Healthy", body = Value),
+		(status = 503, description = "Db not reachable", body = Value),
+	)
+)]
+async fn healthz_handler(
+	State(health_state): State<HealthState>,
+) -> (StatusCode, Json<Value>) {
+	debug!("{:<12} - healthz_handler", "HANDLER");
+
+	let HealthState { mm, started_at, .. } = health_state;
+
+	// -- Check the db.
+	let ping_start = Instant::now();
+	let db_res = mm.db_ping(DB_PING_TIMEOUT).await;
+	let db_latency_ms = ping_start.elapsed().as_secs_f64() * 1_000.;
+
+	let (status_code, status, db_check) = match db_res {
+		Ok(acquire_wait) => {
+			record_db_acquire_wait(acquire_wait);
+			(
+				StatusCode::OK,
+				"ok",
+				json!({"status": "ok", "latency_ms": db_latency_ms}),
+			)
+		}
+		Err(ex) => {
+			warn!("{:<12} - healthz - db ping fail - {ex:?}", "HANDLER");
+			(StatusCode::SERVICE_UNAVAILABLE, "fail", db_fail_check())
+		}
+	};
+
+	let body = json!({
+		"status": status,
+		"version": env!("CARGO_PKG_VERSION"),
+		"uptime_sec": started_at.elapsed().as_secs(),
+		"checks": {
+			"db": db_check,
+		}
+	});
+
+	(status_code, Json(body))
+}
+
+// region:    --- Support
+
+/// The db pool status, `saturated` when the ping connection acquire wait is over
+/// the max, and `not checked` without ping (e.g., starting, or db down).
+fn db_pool_status(
+	acquire_wait: Option<Duration>,
+	max_acquire_wait: Duration,
+) -> &'static str {
+	match acquire_wait {
+		None => "not checked",
+		Some(acquire_wait) if acquire_wait > max_acquire_wait => "saturated",
+		Some(_) => "ok",
+	}
+}
+
+/// The failed db check, with the fixed reason (not the db error detail).
+fn db_fail_check() -> Value {
+	json!({"status": "fail", "reason": DB_FAIL_REASON})
+}
+
+fn db_pool_check(
+	status: &str,
+	stats: DbPoolStats,
+	acquire_wait: Option<Duration>,
+) -> Value {
+	json!({
+		"status": status,
+		"size": stats.size,
+		"idle": stats.idle,
+		"max": stats.max,
+		"acquire_wait_ms": acquire_wait
+			.map(|acquire_wait| acquire_wait.as_secs_f64() * 1_000.),
+	})
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_db_pool_status() {
+		// -- Setup & Fixtures
+		let fx_max = Duration::from_millis(500);
+
+		// -- Exec & Check
+		assert_eq!(db_pool_status(Some(Duration::from_millis(3)), fx_max), "ok");
+		assert_eq!(db_pool_status(Some(fx_max), fx_max), "ok");
+		assert_eq!(
+			db_pool_status(Some(Duration::from_millis(501)), fx_max),
+			"saturated"
+		);
+		assert_eq!(db_pool_status(None, fx_max), "not checked");
+	}
+}
+// endregion: --- Tests
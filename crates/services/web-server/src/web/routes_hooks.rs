@@ -0,0 +1,286 @@
+//! The inbound hooks, for the external systems to push their work items in,
+//! as tasks of the source project (see the lib-core `config::HookSource`).
+//!
+//! - `POST /api/hooks/:source` - e.g., `/api/hooks/github`
+//!
+//! The requests are signed with the source secret, in the GitHub way, i.e.,
+//! `X-Hub-Signature-256: sha256=<hex>`, the HMAC-SHA256 of the body.
+//!
+//! The sources payloads:
+//! - `github` - the `issues` events (`X-GitHub-Event`), where the `opened` and
+//!   `reopened` issues are created as `#<number> <title>` tasks. The other
+//!   events and actions (e.g., `ping`, `closed`) are ignored.
+//! - `generic` - a `{"title": "..."}` json, created as a task.
+//!
+//! NOTE: An unknown (or not enabled) source, and a bad signature, are a `403`
+//!       `NO_AUTH`, and the ignored events a `200` without `task_id`.
+
+use crate::web::{Error, Result};
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use hmac::{Hmac, Mac};
+use lib_core::config::{config, HookSource};
+use lib_core::ctx::Ctx;
+use lib_core::model::task::{TaskBmc, TaskForCreate};
+use lib_core::model::ModelManager;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::debug;
+use utoipa::ToSchema;
+
+const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const GITHUB_EVENT_HEADER: &str = "x-github-event";
+const TASK_TITLE_MAX_LEN: usize = 256;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/api/hooks/:source", post(hook_handler))
+		.with_state(mm)
+}
+
+// region:    --- Hook Types
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(super) struct HookResult {
+	/// The created task, none when the event is ignored.
+	task_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct GithubIssuesEvent {
+	action: String,
+	issue: GithubIssue,
+}
+
+#[derive(Deserialize)]
+struct GithubIssue {
+	number: i64,
+	title: String,
+}
+
+#[derive(Deserialize)]
+struct GenericEvent {
+	title: String,
+}
+
+// endregion: --- Hook Types
+
+/// Verifies the hook signature, and creates the task of the payload.
+#[utoipa::path(
+	post,
+	path = "/api/hooks/{source}",
+	tag = "hooks",
+	params(
+		("source" = String, Path, description = "The hook source, e.g., `github`"),
+		("X-Hub-Signature-256" = String, Header, description = "`sha256=<hex>`, the HMAC-SHA256 of the body"),
+	),
+	request_body(content = String, description = "The source json payload", content_type = "application/json"),
+	responses(
+		(status = 200, description = "The created task id (none when ignored)", body = HookResult),
+		(status = 400, description = "INVALID_PARAMS (payload)"),
+		(status = 403, description = "NO_AUTH (unknown source, or bad signature)"),
+	)
+)]
+async fn hook_handler(
+	State(mm): State<ModelManager>,
+	Path(source): Path<String>,
+	headers: HeaderMap,
+	body: Bytes,
+) -> Result<Json<HookResult>> {
+	debug!("{:<12} - hook_handler", "HANDLER");
+
+	let hook_source = config()
+		.HOOK_SOURCES
+		.iter()
+		.find(|hook_source| hook_source.name == source)
+		.ok_or(Error::HookSourceUnknown(source))?;
+
+	let signature = headers
+		.get(SIGNATURE_HEADER)
+		.and_then(|val| val.to_str().ok());
+	if !verify_signature(&hook_source.secret, signature, &body) {
+		return Err(Error::HookSignatureInvalid {
+			hook_source: hook_source.name,
+		});
+	}
+
+	let event = headers
+		.get(GITHUB_EVENT_HEADER)
+		.and_then(|val| val.to_str().ok());
+	let Some(title) = task_title(hook_source, event, &body)? else {
+		debug!("{:<12} - hook_handler - event ignored", "HANDLER");
+		return Ok(Json(HookResult { task_id: None }));
+	};
+
+	let ctx = Ctx::service("hooks")?;
+	let task_c = TaskForCreate {
+		title,
+		project_id: hook_source.project_id,
+	};
+	let task_id = TaskBmc::create(&ctx, &mm, task_c).await?;
+
+	Ok(Json(HookResult {
+		task_id: Some(task_id),
+	}))
+}
+
+// region:    --- Support
+
+/// Checks the `sha256=<hex>` signature of the body (in constant time).
+fn verify_signature(secret: &str, signature: Option<&str>, body: &[u8]) -> bool {
+	let Some(signature) = signature
+		.and_then(|signature| signature.strip_prefix("sha256="))
+		.and_then(hex_decode)
+	else {
+		return false;
+	};
+	let Ok(mut hmac_sha256) = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+	else {
+		return false;
+	};
+	hmac_sha256.update(body);
+
+	hmac_sha256.verify_slice(&signature).is_ok()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+	if !hex.len().is_multiple_of(2) {
+		return None;
+	}
+	(0..hex.len())
+		.step_by(2)
+		.map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+		.collect()
+}
+
+/// The title of the task to create, none when the event is ignored.
+fn task_title(
+	hook_source: &HookSource,
+	event: Option<&str>,
+	body: &[u8],
+) -> Result<Option<String>> {
+	let title = match hook_source.name {
+		"github" => {
+			if event != Some("issues") {
+				return Ok(None);
+			}
+			let event: GithubIssuesEvent = parse_payload(body)?;
+			if !matches!(event.action.as_str(), "opened" | "reopened") {
+				return Ok(None);
+			}
+			format!("#{} {}", event.issue.number, event.issue.title.trim())
+		}
+		_ => {
+			let event: GenericEvent = parse_payload(body)?;
+			event.title.trim().to_string()
+		}
+	};
+
+	if title.is_empty() {
+		return Err(Error::HookPayloadInvalid {
+			reason: "title is empty".to_string(),
+		});
+	}
+
+	Ok(Some(title.chars().take(TASK_TITLE_MAX_LEN).collect()))
+}
+
+fn parse_payload<T: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<T> {
+	serde_json::from_slice(body).map_err(|ex| Error::HookPayloadInvalid {
+		reason: ex.to_string(),
+	})
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fx_hook_source(name: &'static str) -> HookSource {
+		HookSource {
+			name,
+			secret: "test_hook_secret_01".to_string(),
+			project_id: 1000,
+		}
+	}
+
+	#[test]
+	fn test_verify_signature_ok_and_err() {
+		// -- Setup & Fixtures
+		// Note: `echo -n '{"title":"fx"}' | openssl dgst -sha256 -hmac 'test_hook_secret_01'`
+		let fx_signature =
+			"sha256=8f4c1bca7648f992e767db22bf070783c34034938db98a19bd6b8ec00137b405";
+		let fx_body = br#"{"title":"fx"}"#;
+
+		// -- Exec & Check
+		assert!(verify_signature(
+			"test_hook_secret_01",
+			Some(fx_signature),
+			fx_body
+		));
+		assert!(!verify_signature(
+			"test_hook_secret_02",
+			Some(fx_signature),
+			fx_body
+		));
+		assert!(!verify_signature(
+			"test_hook_secret_01",
+			Some(fx_signature),
+			br#"{"title":"fx2"}"#
+		));
+		assert!(!verify_signature("test_hook_secret_01", None, fx_body));
+		assert!(!verify_signature(
+			"test_hook_secret_01",
+			Some("sha256=zz"),
+			fx_body
+		));
+	}
+
+	#[test]
+	fn test_task_title_github_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_source = fx_hook_source("github");
+		let fx_opened =
+			br#"{"action":"opened","issue":{"number":42,"title":" Fix login "}}"#;
+		let fx_closed =
+			br#"{"action":"closed","issue":{"number":42,"title":"Fix login"}}"#;
+
+		// -- Exec & Check
+		assert_eq!(
+			task_title(&fx_source, Some("issues"), fx_opened)?.as_deref(),
+			Some("#42 Fix login")
+		);
+		assert_eq!(task_title(&fx_source, Some("issues"), fx_closed)?, None);
+		assert_eq!(task_title(&fx_source, Some("ping"), b"{}")?, None);
+		assert!(matches!(
+			task_title(&fx_source, Some("issues"), b"{}"),
+			Err(Error::HookPayloadInvalid { .. })
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_task_title_generic_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_source = fx_hook_source("generic");
+
+		// -- Exec & Check
+		assert_eq!(
+			task_title(&fx_source, None, br#"{"title":"Call back"}"#)?.as_deref(),
+			Some("Call back")
+		);
+		assert!(matches!(
+			task_title(&fx_source, None, br#"{"title":" "}"#),
+			Err(Error::HookPayloadInvalid { .. })
+		));
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,329 @@
+//! The imports (e.g., from the spreadsheets), the counterpart of `routes_export`.
+//!
+//! - `POST /api/import/tasks` - multipart, with a `file` part, either a CSV
+//!   with a header row (e.g., a `tasks.csv` export), or a JSON array of
+//!   `TaskForCreate`.
+//!
+//! The rows are validated as `TaskForCreate` (in a project of the user), the
+//! valid ones created in one transaction (see `TaskBmc::create_many`), and the
+//! rejected ones reported by `row`, i.e., the CSV file line, or the JSON array
+//! position (from 1).
+//!
+//! NOTE: The file is limited by the request body limit (2 MB by default), and
+//!       to `IMPORT_MAX_ROWS` rows.
+
+use crate::web::mw_auth::CtxW;
+use crate::web::{Error, Result};
+use axum::extract::{Multipart, State};
+use axum::routing::post;
+use axum::{Json, Router};
+use lib_core::model::project::ProjectBmc;
+use lib_core::model::task::{TaskBmc, TaskForCreate};
+use lib_core::model::ModelManager;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+use utoipa::ToSchema;
+
+const IMPORT_FILE_PART: &str = "file";
+const IMPORT_MAX_ROWS: usize = 5000;
+const TASK_TITLE_MAX_LEN: usize = 256;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/import/tasks", post(import_tasks_handler))
+		.with_state(mm)
+}
+
+// region:    --- Import Types
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(super) struct ImportReport {
+	/// The ids of the created tasks (in the file order).
+	ids: Vec<i64>,
+	rejected: Vec<RejectedRow>,
+}
+
+#[derive(Debug, PartialEq, Serialize, ToSchema)]
+pub(super) struct RejectedRow {
+	row: u64,
+	reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImportFormat {
+	Csv,
+	Json,
+}
+
+impl ImportFormat {
+	/// From the file name extension, or else its content type.
+	fn detect(file_name: Option<&str>, content_type: Option<&str>) -> Option<Self> {
+		let ext = file_name
+			.and_then(|name| name.rsplit_once('.'))
+			.map(|(_, ext)| ext.to_ascii_lowercase());
+		match (ext.as_deref(), content_type) {
+			(Some("csv"), _) | (_, Some("text/csv")) => Some(Self::Csv),
+			(Some("json"), _) | (_, Some("application/json")) => Some(Self::Json),
+			_ => None,
+		}
+	}
+}
+
+// endregion: --- Import Types
+
+/// Creates the valid tasks of the file, and reports the rejected rows.
+#[utoipa::path(
+	post,
+	path = "/api/import/tasks",
+	tag = "tasks",
+	request_body(content = String, description = "Multipart, with a `file` part (.csv or .json)", content_type = "multipart/form-data"),
+	responses(
+		(status = 200, description = "The created ids, and the rejected rows", body = ImportReport),
+		(status = 400, description = "INVALID_PARAMS (e.g., not a csv or json file)"),
+	),
+	security(("auth_token" = []))
+)]
+async fn import_tasks_handler(
+	State(mm): State<ModelManager>,
+	ctx: CtxW,
+	mut multipart: Multipart,
+) -> Result<Json<ImportReport>> {
+	debug!("{:<12} - import_tasks_handler", "HANDLER");
+	let ctx = ctx.0;
+
+	// -- Read the file part.
+	let (format, content) = loop {
+		let field = multipart.next_field().await.map_err(import_invalid)?;
+		let Some(field) = field else {
+			return Err(import_invalid(format!("no '{IMPORT_FILE_PART}' part")));
+		};
+		if field.name() != Some(IMPORT_FILE_PART) {
+			continue;
+		}
+		let format = ImportFormat::detect(field.file_name(), field.content_type())
+			.ok_or_else(|| import_invalid("not a .csv or .json file"))?;
+		break (format, field.bytes().await.map_err(import_invalid)?);
+	};
+
+	// -- Parse & validate the rows.
+	let (rows, mut rejected) = match format {
+		ImportFormat::Csv => parse_csv_rows(&content)?,
+		ImportFormat::Json => parse_json_rows(&content)?,
+	};
+	if rows.len() + rejected.len() > IMPORT_MAX_ROWS {
+		return Err(import_invalid(format!("over {IMPORT_MAX_ROWS} rows")));
+	}
+
+	// -- Check the projects are the user ones.
+	let mut project_ids: Vec<i64> = rows.iter().map(|(_, t)| t.project_id).collect();
+	project_ids.sort_unstable();
+	project_ids.dedup();
+	let owned_ids = ProjectBmc::owned_ids(&ctx, &mm, &project_ids).await?;
+	let (rows, not_owned): (Vec<_>, Vec<_>) = rows
+		.into_iter()
+		.partition(|(_, task_c)| owned_ids.contains(&task_c.project_id));
+	rejected.extend(not_owned.into_iter().map(|(row, task_c)| RejectedRow {
+		row,
+		reason: format!("project {} not found", task_c.project_id),
+	}));
+	rejected.sort_by_key(|rejected| rejected.row);
+
+	// -- Create the valid rows (all or none).
+	let tasks_c: Vec<TaskForCreate> =
+		rows.into_iter().map(|(_, task_c)| task_c).collect();
+	let ids = if tasks_c.is_empty() {
+		Vec::new()
+	} else {
+		TaskBmc::create_many(&ctx, &mm, tasks_c).await?
+	};
+
+	Ok(Json(ImportReport { ids, rejected }))
+}
+
+// region:    --- Parsing
+
+type ParsedRows = (Vec<(u64, TaskForCreate)>, Vec<RejectedRow>);
+
+/// The rows by file line (the header is line 1).
+fn parse_csv_rows(content: &[u8]) -> Result<ParsedRows> {
+	let mut reader = csv::Reader::from_reader(content);
+	let headers = reader.headers().map_err(import_invalid)?.clone();
+	for column in ["title", "project_id"] {
+		if !headers.iter().any(|header| header == column) {
+			return Err(import_invalid(format!("no '{column}' column")));
+		}
+	}
+
+	let mut rows = Vec::new();
+	let mut rejected = Vec::new();
+	for record in reader.records() {
+		let (row, task_c) = match record {
+			Ok(record) => (
+				csv_line(content, record.position()),
+				record
+					.deserialize::<TaskForCreate>(Some(&headers))
+					.map_err(|ex| ex.to_string()),
+			),
+			Err(ex) => (csv_line(content, ex.position()), Err(ex.to_string())),
+		};
+		match task_c.and_then(validate_task_c) {
+			Ok(task_c) => rows.push((row, task_c)),
+			Err(reason) => rejected.push(RejectedRow { row, reason }),
+		}
+	}
+
+	Ok((rows, rejected))
+}
+
+/// The file line of a record.
+///
+/// Note: Not the csv `Position::line`, which is off by one after a `\r\n`
+///       (the record position is at its `\n`), as in the exports.
+fn csv_line(content: &[u8], pos: Option<&csv::Position>) -> u64 {
+	let Some(pos) = pos else {
+		return 0;
+	};
+	let start = (pos.byte() as usize).min(content.len());
+	let start = start
+		+ content[start..]
+			.iter()
+			.take_while(|b| matches!(b, b'\r' | b'\n'))
+			.count();
+
+	content[..start].iter().filter(|b| **b == b'\n').count() as u64 + 1
+}
+
+/// The rows by array position (from 1).
+fn parse_json_rows(content: &[u8]) -> Result<ParsedRows> {
+	let items: Vec<Value> = serde_json::from_slice(content)
+		.map_err(|ex| import_invalid(format!("not a json array - {ex}")))?;
+
+	let mut rows = Vec::new();
+	let mut rejected = Vec::new();
+	for (idx, item) in items.into_iter().enumerate() {
+		let row = idx as u64 + 1;
+		let task_c = serde_json::from_value::<TaskForCreate>(item)
+			.map_err(|ex| ex.to_string())
+			.and_then(validate_task_c);
+		match task_c {
+			Ok(task_c) => rows.push((row, task_c)),
+			Err(reason) => rejected.push(RejectedRow { row, reason }),
+		}
+	}
+
+	Ok((rows, rejected))
+}
+
+fn validate_task_c(
+	mut task_c: TaskForCreate,
+) -> core::result::Result<TaskForCreate, String> {
+	// Note: The `'` formula escape of the exports (see `routes_export::csv_field`).
+	if task_c.title.starts_with("'=")
+		|| task_c.title.starts_with("'+")
+		|| task_c.title.starts_with("'-")
+		|| task_c.title.starts_with("'@")
+	{
+		task_c.title.remove(0);
+	}
+
+	if task_c.title.trim().is_empty() {
+		return Err("title is empty".to_string());
+	}
+	if task_c.title.chars().count() > TASK_TITLE_MAX_LEN {
+		return Err(format!("title is over {TASK_TITLE_MAX_LEN} chars"));
+	}
+
+	Ok(task_c)
+}
+
+fn import_invalid(reason: impl ToString) -> Error {
+	Error::ImportInvalidFile {
+		reason: reason.to_string(),
+	}
+}
+
+// endregion: --- Parsing
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_csv_rows_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_csv = "id,project_id,title,done\r\n\
+			1,100,task 01,false\r\n\
+			2,abc,task 02,false\r\n\
+			3,100,,false\r\n\
+			4,100,\"'=SUM(A1:A2), b\",true\r\n";
+
+		// -- Exec
+		let (rows, rejected) = parse_csv_rows(fx_csv.as_bytes())?;
+
+		// -- Check
+		let rows: Vec<_> = rows
+			.iter()
+			.map(|(row, t)| (*row, t.project_id, t.title.as_str()))
+			.collect();
+		assert_eq!(rows, [(2, 100, "task 01"), (5, 100, "=SUM(A1:A2), b")]);
+		let rejected_rows: Vec<_> = rejected.iter().map(|r| r.row).collect();
+		assert_eq!(rejected_rows, [3, 4]);
+		assert_eq!(rejected[1].reason, "title is empty");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse_csv_rows_err_no_column() {
+		let res = parse_csv_rows(b"id,name\r\n1,task 01\r\n");
+
+		assert!(
+			matches!(res, Err(Error::ImportInvalidFile { .. })),
+			"should be ImportInvalidFile"
+		);
+	}
+
+	#[test]
+	fn test_parse_json_rows_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_json = br#"[
+			{"title": "task 01", "project_id": 100},
+			{"title": "task 02"},
+			{"title": "task 03", "project_id": 101}
+		]"#;
+
+		// -- Exec
+		let (rows, rejected) = parse_json_rows(fx_json)?;
+
+		// -- Check
+		let rows: Vec<_> =
+			rows.iter().map(|(row, t)| (*row, t.project_id)).collect();
+		assert_eq!(rows, [(1, 100), (3, 101)]);
+		assert_eq!(rejected.len(), 1);
+		assert_eq!(rejected[0].row, 2);
+		assert!(rejected[0].reason.contains("project_id"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_import_format_detect() {
+		let fx_cases = [
+			(Some("tasks.CSV"), None, Some(ImportFormat::Csv)),
+			(
+				Some("tasks.json"),
+				Some("text/plain"),
+				Some(ImportFormat::Json),
+			),
+			(None, Some("text/csv"), Some(ImportFormat::Csv)),
+			(Some("tasks.xlsx"), None, None),
+		];
+
+		for (fx_name, fx_content_type, expected) in fx_cases {
+			assert_eq!(ImportFormat::detect(fx_name, fx_content_type), expected);
+		}
+	}
+}
+// endregion: --- Tests
@@ -1,4 +1,4 @@
-use crate::web::{self, remove_token_cookie, Error, Result};
+use crate::web::{self, mw_read_only, remove_token_cookie, Error, Result};
 use axum::{extract::State, routing::post, Json, Router};
 use lib_core::ctx::Ctx;
 use lib_core::model::user::{UserBmc, UserForLogin};
@@ -8,6 +8,7 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 use tower_cookies::Cookies;
 use tracing::debug;
+use utoipa::ToSchema;
 
 pub fn routes(mm: ModelManager) -> Router {
 	Router::new()
@@ -18,6 +19,17 @@ pub fn routes(mm: ModelManager) -> Router {
 
 // region:    --- Login
 
+/// Sets the `auth-token` cookie on success.
+#[utoipa::path(
+	post,
+	path = "/api/login",
+	tag = "auth",
+	request_body = LoginPayload,
+	responses(
+		(status = 200, description = "Logged in (auth-token cookie set)", body = Value),
+		(status = 403, description = "LOGIN_FAIL, or ACCOUNT_DISABLED"),
+	)
+)]
 async fn api_login_handler(
 	mm: State<ModelManager>,
 	cookies: Cookies,
@@ -44,41 +56,72 @@ async fn api_login_handler(
 
 	let scheme_status = pwd::validate_pwd(
 		&ContentToHash {
-			salt: user.pwd_salt,
+			salt: *user.pwd_salt.expose(),
 			content: pwd_clear.clone(),
 		},
-		&pwd,
+		pwd.expose(),
 	)
 	.map_err(|cause| Error::LoginFail { user_id, cause })?;
 
+	// -- Refuse the deactivated users.
+	//    (after the pwd validation, so their state is not disclosed otherwise)
+	if !user.active {
+		return Err(Error::LoginFailUserInactive { user_id });
+	}
+
 	// -- Update password scheme if need
-	if let SchemeStatus::Outdated = scheme_status {
+	//    (later, as a write, in the read-only mode)
+	if matches!(scheme_status, SchemeStatus::Outdated)
+		&& !mw_read_only::is_read_only()
+	{
 		debug!("pwd encrypt scheme outdated, upgrading.");
 		UserBmc::update_pwd(&root_ctx, &mm, user.id, &pwd_clear).await?;
 	}
 
 	// -- Set web token.
-	web::set_token_cookie(&cookies, &user.username, user.token_salt)?;
+	web::set_token_cookie(&cookies, &user.username, *user.token_salt.expose())?;
 	// Create the success body.
+	// (`must_change_pwd`, the session is restricted to the `change_pwd` rpc)
 	let body = Json(json!({
 		"result": {
-			"success": true
+			"success": true,
+			"must_change_pwd": user.must_change_pwd,
 		}
 	}));
 
 	Ok(body)
 }
 
-#[derive(Debug, Deserialize)]
-struct LoginPayload {
+#[derive(Deserialize, ToSchema)]
+pub(super) struct LoginPayload {
 	username: String,
 	pwd: String,
 }
 
+/// Manual Debug, so the clear pwd can never end up in a log line.
+impl core::fmt::Debug for LoginPayload {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("LoginPayload")
+			.field("username", &self.username)
+			.field("pwd", &"[REDACTED]")
+			.finish()
+	}
+}
+
 // endregion: --- Login
 
 // region:    --- Logoff
 
+/// Removes the `auth-token` cookie when `logoff` is true.
+#[utoipa::path(
+	post,
+	path = "/api/logoff",
+	tag = "auth",
+	request_body = LogoffPayload,
+	responses(
+		(status = 200, description = "Logged off", body = Value),
+	)
+)]
 async fn api_logoff_handler(
 	cookies: Cookies,
 	Json(payload): Json<LogoffPayload>,
@@ -100,8 +143,8 @@ async fn api_logoff_handler(
 	Ok(body)
 }
 
-#[derive(Debug, Deserialize)]
-struct LogoffPayload {
+#[derive(Debug, Deserialize, ToSchema)]
+pub(super) struct LogoffPayload {
 	logoff: bool,
 }
 
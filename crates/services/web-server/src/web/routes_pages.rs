@@ -0,0 +1,104 @@
+//! Server-side rendered pages (see `templates`).
+
+use crate::web::mw_auth::{mw_pwd_change_guard, CtxW};
+use crate::web::{templates, Result};
+use axum::extract::{Query, State};
+use axum::{middleware, response::Html, routing::get, Router};
+use lib_core::config::config;
+use lib_core::model::project::ProjectBmc;
+use lib_core::model::task::{Task, TaskBmc};
+use lib_core::model::ModelManager;
+use minijinja::context;
+use modql::filter::ListOptions;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::debug;
+
+/// The json-rpc api version called by the playground.
+const RPC_PLAYGROUND_URL: &str = "/api/v1/rpc";
+
+/// The tasks of a `/tasks` page (see `TasksPageQuery`).
+const TASKS_PAGE_LIMIT: i64 = 100;
+
+pub fn routes(mm: ModelManager) -> Router {
+	// Note: Only the data pages are guarded (the playground calls `change_pwd`).
+	let mut router = Router::new()
+		.route("/login", get(login_page_handler))
+		.route(
+			"/tasks",
+			get(tasks_page_handler)
+				.route_layer(middleware::from_fn(mw_pwd_change_guard)),
+		);
+	if config().RPC_PLAYGROUND {
+		router = router.route("/rpc-playground", get(rpc_playground_page_handler));
+	}
+
+	router.with_state(mm)
+}
+
+async fn login_page_handler() -> Result<Html<String>> {
+	debug!("{:<12} - login_page_handler", "HANDLER");
+
+	templates::render("login.html", context! {})
+}
+
+/// The json-rpc console, which lists the methods (with `rpc.discover`), and
+/// calls them with the user session (so, login first).
+async fn rpc_playground_page_handler() -> Result<Html<String>> {
+	debug!("{:<12} - rpc_playground_page_handler", "HANDLER");
+
+	templates::render(
+		"rpc_playground.html",
+		context! { rpc_url => RPC_PLAYGROUND_URL },
+	)
+}
+
+#[derive(Deserialize)]
+struct TasksPageQuery {
+	offset: Option<i64>,
+}
+
+/// The task list, grouped by project, by pages of `TASKS_PAGE_LIMIT` tasks
+/// (`?offset=`), with the link to the next page.
+///
+/// Note: The first page also lists the projects without tasks.
+async fn tasks_page_handler(
+	State(mm): State<ModelManager>,
+	ctx: CtxW,
+	Query(query): Query<TasksPageQuery>,
+) -> Result<Html<String>> {
+	debug!("{:<12} - tasks_page_handler", "HANDLER");
+
+	let ctx = ctx.0;
+	let offset = query.offset.unwrap_or(0).max(0);
+	let projects = ProjectBmc::list(&ctx, &mm, None, None).await?;
+	let list_options = ListOptions {
+		limit: Some(TASKS_PAGE_LIMIT),
+		offset: Some(offset),
+		order_bys: None,
+	};
+	let page = TaskBmc::list_paged(&ctx, &mm, None, Some(list_options)).await?;
+
+	let projects: Vec<_> = projects
+		.into_iter()
+		.filter_map(|project| {
+			let tasks: Vec<&Task> = page
+				.items
+				.iter()
+				.filter(|task| task.project_id == project.id)
+				.collect();
+			(offset == 0 || !tasks.is_empty())
+				.then(|| json!({ "project": project, "tasks": tasks }))
+		})
+		.collect();
+	let next_offset = page.has_more.then_some(offset + TASKS_PAGE_LIMIT);
+
+	templates::render(
+		"tasks.html",
+		context! {
+			projects => projects,
+			total => page.total,
+			next_offset => next_offset,
+		},
+	)
+}
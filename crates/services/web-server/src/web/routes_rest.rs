@@ -0,0 +1,699 @@
+//! Plain REST CRUD routes, generated from the BMCs, for the clients preferring
+//! REST over the JSON-RPC `/api/rpc` (same BMC calls and semantic).
+//!
+//! For a `RestBmc` with `REST_PATH = "tasks"` (nested under `/api`):
+//!
+//! - `GET    /tasks`     - list (`?filters=<json>&list_options=<json>`)
+//! - `POST   /tasks`     - create, `201` with the created entity
+//! - `GET    /tasks/:id` - get
+//! - `PATCH  /tasks/:id` - update, with the updated entity
+//! - `DELETE /tasks/:id` - delete, with the deleted entity
+//!
+//! The entity responses have an `ETag` (from the entity `mtime`), and the
+//! `PATCH` / `DELETE` require a matching `If-Match` (`428` when missing, `412`
+//! with the current `ETag` when the entity was modified since), so concurrent
+//! edits are not lost. The write itself is conditional on the matched version
+//! (see `base::update_if_unmodified`), so two racing writes cannot both pass.
+//!
+//! NOTE: The entities are returned without the fields hidden to the user role
+//!       (see `field_policy`).
+
+use crate::web::field_policy::{ctx_role, visible_json};
+use crate::web::mw_auth::CtxW;
+use crate::web::rpc::ParamsList;
+use crate::web::{Error, Result};
+use async_trait::async_trait;
+use axum::{
+	extract::{Path, Query, State},
+	http::{header, HeaderMap, StatusCode},
+	response::{IntoResponse, Response},
+	routing::get,
+	Json, Router,
+};
+use lib_core::ctx::Ctx;
+use lib_core::model::project::{
+	Project, ProjectBmc, ProjectFilter, ProjectForCreate, ProjectForUpdate,
+};
+use lib_core::model::task::{
+	Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate,
+};
+use lib_core::model::{self, ModelManager};
+use modql::filter::ListOptions;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::marker::PhantomData;
+use time::OffsetDateTime;
+use tracing::debug;
+use utoipa::openapi::path::{
+	OperationBuilder, ParameterBuilder, ParameterIn, PathItemBuilder, PathItemType,
+};
+use utoipa::openapi::request_body::RequestBodyBuilder;
+use utoipa::openapi::security::SecurityRequirement;
+use utoipa::openapi::{
+	ArrayBuilder, ContentBuilder, KnownFormat, ObjectBuilder, OpenApi, Ref,
+	Required, ResponseBuilder, SchemaFormat, SchemaType,
+};
+use utoipa::ToSchema;
+
+/// All the generated REST routes.
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.merge(rest_routes::<
+			TaskBmc,
+			Task,
+			TaskForCreate,
+			TaskForUpdate,
+			TaskFilter,
+		>(mm.clone()))
+		.merge(rest_routes::<
+			ProjectBmc,
+			Project,
+			ProjectForCreate,
+			ProjectForUpdate,
+			ProjectFilter,
+		>(mm))
+}
+
+/// Adds the OpenAPI paths (and schemas) of all the generated REST routes.
+pub fn add_openapi(openapi: &mut OpenApi) {
+	add_rest_openapi::<TaskBmc, Task, TaskForCreate, TaskForUpdate, TaskFilter>(
+		openapi,
+	);
+	add_rest_openapi::<
+		ProjectBmc,
+		Project,
+		ProjectForCreate,
+		ProjectForUpdate,
+		ProjectFilter,
+	>(openapi);
+}
+
+// region:    --- RestBmc
+
+/// The BMC CRUD functions backing the generated REST routes.
+///
+/// - `E` the entity, `C` / `U` the create / update data, `F` the list filter.
+#[async_trait]
+pub trait RestBmc<E, C, U, F>: Send + Sync + 'static {
+	/// The collection path segment (e.g., `tasks`).
+	const REST_PATH: &'static str;
+
+	async fn create(ctx: &Ctx, mm: &ModelManager, data: C) -> model::Result<i64>;
+
+	async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> model::Result<E>;
+
+	/// The `ETag` of the entity version (see `rest_etag`).
+	fn etag(entity: &E) -> String;
+
+	/// The entity version, for the conditional writes.
+	fn mtime(entity: &E) -> OffsetDateTime;
+
+	async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filters: Option<Vec<F>>,
+		list_options: Option<ListOptions>,
+	) -> model::Result<Vec<E>>;
+
+	/// Fails with `model::Error::EntityModified` if modified since `mtime`.
+	async fn update_if_unmodified(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		mtime: OffsetDateTime,
+		data: U,
+	) -> model::Result<()>;
+
+	/// Fails with `model::Error::EntityModified` if modified since `mtime`.
+	async fn delete_if_unmodified(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		mtime: OffsetDateTime,
+	) -> model::Result<()>;
+}
+
+/// Implements `RestBmc` by delegating to the BMC associated functions.
+macro_rules! impl_rest_bmc {
+	($bmc:ty, $path:literal, $entity:ty, $for_create:ty, $for_update:ty, $filter:ty) => {
+		#[async_trait]
+		impl RestBmc<$entity, $for_create, $for_update, $filter> for $bmc {
+			const REST_PATH: &'static str = $path;
+
+			async fn create(
+				ctx: &Ctx,
+				mm: &ModelManager,
+				data: $for_create,
+			) -> model::Result<i64> {
+				<$bmc>::create(ctx, mm, data).await
+			}
+
+			async fn get(
+				ctx: &Ctx,
+				mm: &ModelManager,
+				id: i64,
+			) -> model::Result<$entity> {
+				<$bmc>::get(ctx, mm, id).await
+			}
+
+			fn etag(entity: &$entity) -> String {
+				rest_etag(entity.id, entity.mtime)
+			}
+
+			fn mtime(entity: &$entity) -> OffsetDateTime {
+				entity.mtime
+			}
+
+			async fn list(
+				ctx: &Ctx,
+				mm: &ModelManager,
+				filters: Option<Vec<$filter>>,
+				list_options: Option<ListOptions>,
+			) -> model::Result<Vec<$entity>> {
+				<$bmc>::list(ctx, mm, filters, list_options).await
+			}
+
+			async fn update_if_unmodified(
+				ctx: &Ctx,
+				mm: &ModelManager,
+				id: i64,
+				mtime: OffsetDateTime,
+				data: $for_update,
+			) -> model::Result<()> {
+				<$bmc>::update_if_unmodified(ctx, mm, id, mtime, data).await
+			}
+
+			async fn delete_if_unmodified(
+				ctx: &Ctx,
+				mm: &ModelManager,
+				id: i64,
+				mtime: OffsetDateTime,
+			) -> model::Result<()> {
+				<$bmc>::delete_if_unmodified(ctx, mm, id, mtime).await
+			}
+		}
+	};
+}
+
+impl_rest_bmc!(
+	TaskBmc,
+	"tasks",
+	Task,
+	TaskForCreate,
+	TaskForUpdate,
+	TaskFilter
+);
+impl_rest_bmc!(
+	ProjectBmc,
+	"projects",
+	Project,
+	ProjectForCreate,
+	ProjectForUpdate,
+	ProjectFilter
+);
+
+// endregion: --- RestBmc
+
+// region:    --- Rest Routes
+
+/// Builds the REST CRUD routes of a `RestBmc` (see module doc).
+pub fn rest_routes<B, E, C, U, F>(mm: ModelManager) -> Router
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize + Send + 'static,
+	C: DeserializeOwned + Send + 'static,
+	U: DeserializeOwned + Send + 'static,
+	F: DeserializeOwned + Default + Send + 'static,
+{
+	let collection_path = format!("/{}", B::REST_PATH);
+	let entity_path = format!("/{}/:id", B::REST_PATH);
+
+	Router::new()
+		.route(
+			&collection_path,
+			get(list_handler::<B, E, C, U, F>).post(create_handler::<B, E, C, U, F>),
+		)
+		.route(
+			&entity_path,
+			get(get_handler::<B, E, C, U, F>)
+				.patch(update_handler::<B, E, C, U, F>)
+				.delete(delete_handler::<B, E, C, U, F>),
+		)
+		.with_state(RestState {
+			mm,
+			_bmc: PhantomData,
+		})
+}
+
+/// Marker of the `RestBmc` and its types (`fn()` so it is always Send + Sync).
+type RestTypes<B, E, C, U, F> = PhantomData<fn() -> (B, E, C, U, F)>;
+
+/// The handlers state, typed by the `RestBmc` (and its types).
+struct RestState<B, E, C, U, F> {
+	mm: ModelManager,
+	_bmc: RestTypes<B, E, C, U, F>,
+}
+
+// Note: Manual impl, as the derive would require all the type params to be Clone.
+impl<B, E, C, U, F> Clone for RestState<B, E, C, U, F> {
+	fn clone(&self) -> Self {
+		Self {
+			mm: self.mm.clone(),
+			_bmc: PhantomData,
+		}
+	}
+}
+
+/// The list query params, with the `ParamsList` json values as strings.
+#[derive(Deserialize)]
+struct RestListQuery {
+	filters: Option<String>,
+	list_options: Option<String>,
+}
+
+async fn list_handler<B, E, C, U, F>(
+	State(state): State<RestState<B, E, C, U, F>>,
+	ctx: CtxW,
+	Query(query): Query<RestListQuery>,
+) -> Result<Json<Value>>
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize,
+	F: DeserializeOwned + Default,
+{
+	debug!("{:<12} - rest list {}", "HANDLER", B::REST_PATH);
+
+	let params: ParamsList<F> = serde_json::from_value(json!({
+		"filters": parse_query_json("filters", query.filters)?,
+		"list_options": parse_query_json("list_options", query.list_options)?,
+	}))
+	.map_err(|ex| Error::RestInvalidQuery {
+		param: "filters/list_options",
+		cause: ex.to_string(),
+	})?;
+
+	let ctx = ctx.0;
+	let entities =
+		B::list(&ctx, &state.mm, params.filters, params.list_options).await?;
+
+	visible_json(ctx_role(&ctx), entities)
+}
+
+async fn create_handler<B, E, C, U, F>(
+	State(state): State<RestState<B, E, C, U, F>>,
+	ctx: CtxW,
+	Json(data): Json<C>,
+) -> Result<Response>
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize,
+{
+	debug!("{:<12} - rest create {}", "HANDLER", B::REST_PATH);
+
+	let ctx = ctx.0;
+	let id = B::create(&ctx, &state.mm, data).await?;
+	let entity = B::get(&ctx, &state.mm, id).await?;
+
+	let location = format!("/api/{}/{id}", B::REST_PATH);
+	let etag = B::etag(&entity);
+	Ok((
+		StatusCode::CREATED,
+		[(header::LOCATION, location), (header::ETAG, etag)],
+		visible_json(ctx_role(&ctx), entity)?,
+	)
+		.into_response())
+}
+
+async fn get_handler<B, E, C, U, F>(
+	State(state): State<RestState<B, E, C, U, F>>,
+	ctx: CtxW,
+	Path(id): Path<i64>,
+) -> Result<Response>
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize,
+{
+	debug!("{:<12} - rest get {}", "HANDLER", B::REST_PATH);
+
+	let ctx = ctx.0;
+	let entity = B::get(&ctx, &state.mm, id).await?;
+
+	etag_json_response::<B, E, C, U, F>(&ctx, entity)
+}
+
+async fn update_handler<B, E, C, U, F>(
+	State(state): State<RestState<B, E, C, U, F>>,
+	ctx: CtxW,
+	Path(id): Path<i64>,
+	headers: HeaderMap,
+	Json(data): Json<U>,
+) -> Result<Response>
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize,
+{
+	debug!("{:<12} - rest update {}", "HANDLER", B::REST_PATH);
+
+	let ctx = ctx.0;
+	let entity = B::get(&ctx, &state.mm, id).await?;
+	check_if_match(&headers, &B::etag(&entity))?;
+
+	let res =
+		B::update_if_unmodified(&ctx, &state.mm, id, B::mtime(&entity), data).await;
+	check_written::<B, E, C, U, F>(&ctx, &state.mm, id, res).await?;
+	let entity = B::get(&ctx, &state.mm, id).await?;
+
+	etag_json_response::<B, E, C, U, F>(&ctx, entity)
+}
+
+async fn delete_handler<B, E, C, U, F>(
+	State(state): State<RestState<B, E, C, U, F>>,
+	ctx: CtxW,
+	Path(id): Path<i64>,
+	headers: HeaderMap,
+) -> Result<Json<Value>>
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize,
+{
+	debug!("{:<12} - rest delete {}", "HANDLER", B::REST_PATH);
+
+	let ctx = ctx.0;
+	let entity = B::get(&ctx, &state.mm, id).await?;
+	check_if_match(&headers, &B::etag(&entity))?;
+
+	let res = B::delete_if_unmodified(&ctx, &state.mm, id, B::mtime(&entity)).await;
+	check_written::<B, E, C, U, F>(&ctx, &state.mm, id, res).await?;
+
+	visible_json(ctx_role(&ctx), entity)
+}
+
+/// The visible entity json, with its `ETag` header.
+fn etag_json_response<B, E, C, U, F>(ctx: &Ctx, entity: E) -> Result<Response>
+where
+	B: RestBmc<E, C, U, F>,
+	E: Serialize,
+{
+	let etag = B::etag(&entity);
+	let json = visible_json(ctx_role(ctx), entity)?;
+
+	Ok(([(header::ETAG, etag)], json).into_response())
+}
+
+pub(super) fn parse_query_json(
+	param: &'static str,
+	value: Option<String>,
+) -> Result<Value> {
+	value
+		.map(|value| serde_json::from_str(&value))
+		.transpose()
+		.map(Option::unwrap_or_default)
+		.map_err(|ex| Error::RestInvalidQuery {
+			param,
+			cause: ex.to_string(),
+		})
+}
+
+// endregion: --- Rest Routes
+
+// region:    --- ETag
+
+/// The strong `ETag` of an entity version (the `mtime` changes on each update).
+pub fn rest_etag(id: i64, mtime: OffsetDateTime) -> String {
+	format!("\"{id}-{}\"", mtime.unix_timestamp_nanos())
+}
+
+/// Fails with `RestPreconditionRequired` without `If-Match`, and with
+/// `RestPreconditionFailed` when none of its etags matches `etag`.
+fn check_if_match(headers: &HeaderMap, etag: &str) -> Result<()> {
+	let if_match = headers
+		.get(header::IF_MATCH)
+		.ok_or(Error::RestPreconditionRequired)?
+		.to_str()
+		.unwrap_or_default();
+
+	if if_match_matches(if_match, etag) {
+		Ok(())
+	} else {
+		Err(Error::RestPreconditionFailed {
+			etag: etag.to_string(),
+		})
+	}
+}
+
+/// Fails with `RestPreconditionFailed` (with the current etag) when the
+/// conditional write failed, as modified since the `If-Match` check.
+async fn check_written<B, E, C, U, F>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	res: model::Result<()>,
+) -> Result<()>
+where
+	B: RestBmc<E, C, U, F>,
+{
+	match res {
+		Err(model::Error::EntityModified { .. }) => {
+			// Note: Fails with `EntityNotFound` if deleted since.
+			let entity = B::get(ctx, mm, id).await?;
+			Err(Error::RestPreconditionFailed {
+				etag: B::etag(&entity),
+			})
+		}
+		res => Ok(res?),
+	}
+}
+
+/// `If-Match` is `*` or a list of etags, compared strongly (so the weak `W/`
+/// etags never match).
+fn if_match_matches(if_match: &str, etag: &str) -> bool {
+	if_match
+		.split(',')
+		.map(str::trim)
+		.any(|candidate| candidate == "*" || candidate == etag)
+}
+
+// endregion: --- ETag
+
+// region:    --- OpenApi
+
+/// Adds the OpenAPI paths of the `rest_routes` of a `RestBmc`
+/// (described from the same `E`, `C`, `U` types, as `ToSchema`).
+pub fn add_rest_openapi<B, E, C, U, F>(openapi: &mut OpenApi)
+where
+	B: RestBmc<E, C, U, F>,
+	E: ToSchema<'static>,
+	C: ToSchema<'static>,
+	U: ToSchema<'static>,
+{
+	let tag = B::REST_PATH;
+	let (entity_name, entity_schema) = E::schema();
+	let (for_create_name, for_create_schema) = C::schema();
+	let (for_update_name, for_update_schema) = U::schema();
+
+	// -- The schemas
+	let components = openapi.components.get_or_insert_with(Default::default);
+	components
+		.schemas
+		.insert(entity_name.to_string(), entity_schema);
+	components
+		.schemas
+		.insert(for_create_name.to_string(), for_create_schema);
+	components
+		.schemas
+		.insert(for_update_name.to_string(), for_update_schema);
+
+	// -- The operation building blocks
+	let json_body = |schema_name: &str| {
+		RequestBodyBuilder::new()
+			.content(
+				"application/json",
+				ContentBuilder::new()
+					.schema(Ref::from_schema_name(schema_name))
+					.build(),
+			)
+			.required(Some(Required::True))
+			.build()
+	};
+	let entity_res = |description: &str| {
+		ResponseBuilder::new()
+			.description(description)
+			.content(
+				"application/json",
+				ContentBuilder::new()
+					.schema(Ref::from_schema_name(entity_name))
+					.build(),
+			)
+			.build()
+	};
+	let operation = |summary: String| {
+		OperationBuilder::new()
+			.tag(tag)
+			.summary(Some(summary))
+			.security(SecurityRequirement::new("auth_token", Vec::<String>::new()))
+			.response("403", ResponseBuilder::new().description("NO_AUTH").build())
+	};
+	let id_param = ParameterBuilder::new()
+		.name("id")
+		.parameter_in(ParameterIn::Path)
+		.required(Required::True)
+		.schema(Some(
+			ObjectBuilder::new()
+				.schema_type(SchemaType::Integer)
+				.format(Some(SchemaFormat::KnownFormat(KnownFormat::Int64))),
+		))
+		.build();
+	let if_match_param = ParameterBuilder::new()
+		.name("If-Match")
+		.parameter_in(ParameterIn::Header)
+		.required(Required::True)
+		.description(Some("The entity `ETag` (from its get), or `*`"))
+		.schema(Some(ObjectBuilder::new().schema_type(SchemaType::String)))
+		.build();
+	let json_query_param = |name: &str, description: &str| {
+		ParameterBuilder::new()
+			.name(name)
+			.parameter_in(ParameterIn::Query)
+			.required(Required::False)
+			.description(Some(description))
+			.schema(Some(ObjectBuilder::new().schema_type(SchemaType::String)))
+			.build()
+	};
+
+	// -- The collection path
+	let list_op = operation(format!("List the {tag}"))
+		.parameter(json_query_param(
+			"filters",
+			"json filters (same as the json-rpc list `filters`)",
+		))
+		.parameter(json_query_param(
+			"list_options",
+			"json list options (e.g., `{\"limit\": 10, \"order_bys\": \"!id\"}`)",
+		))
+		.response(
+			"200",
+			ResponseBuilder::new()
+				.description("The list")
+				.content(
+					"application/json",
+					ContentBuilder::new()
+						.schema(
+							ArrayBuilder::new()
+								.items(Ref::from_schema_name(entity_name)),
+						)
+						.build(),
+				)
+				.build(),
+		)
+		.response(
+			"400",
+			ResponseBuilder::new().description("INVALID_PARAMS").build(),
+		);
+	let conflict = || {
+		ResponseBuilder::new()
+			.description("ENTITY_ALREADY_EXISTS, or ENTITY_REFERENCE_VIOLATION")
+			.build()
+	};
+	let read_only = || ResponseBuilder::new().description("READ_ONLY_MODE").build();
+	let create_op = operation(format!("Create a {entity_name}"))
+		.request_body(Some(json_body(for_create_name)))
+		.response("201", entity_res("The created entity"))
+		.response(
+			"403",
+			ResponseBuilder::new()
+				.description("NO_AUTH, or QUOTA_EXCEEDED")
+				.build(),
+		)
+		.response("409", conflict())
+		.response("503", read_only());
+
+	openapi.paths.paths.insert(
+		format!("/api/{tag}"),
+		PathItemBuilder::new()
+			.operation(PathItemType::Get, list_op)
+			.operation(PathItemType::Post, create_op)
+			.build(),
+	);
+
+	// -- The entity path
+	let not_found = || {
+		ResponseBuilder::new()
+			.description("ENTITY_NOT_FOUND")
+			.build()
+	};
+	let precondition_failed = || {
+		ResponseBuilder::new()
+			.description("PRECONDITION_FAILED (modified since, with the current ETag)")
+			.build()
+	};
+	let precondition_required = || {
+		ResponseBuilder::new()
+			.description("PRECONDITION_REQUIRED (no If-Match)")
+			.build()
+	};
+	let get_op = operation(format!("Get a {entity_name}"))
+		.parameter(id_param.clone())
+		.response("200", entity_res("The entity (with its ETag)"))
+		.response("400", not_found());
+	let update_op = operation(format!("Update a {entity_name}"))
+		.parameter(id_param.clone())
+		.parameter(if_match_param.clone())
+		.request_body(Some(json_body(for_update_name)))
+		.response("200", entity_res("The updated entity (with its new ETag)"))
+		.response("400", not_found())
+		.response("409", conflict())
+		.response("412", precondition_failed())
+		.response("428", precondition_required())
+		.response("503", read_only());
+	let delete_op = operation(format!("Delete a {entity_name}"))
+		.parameter(id_param)
+		.parameter(if_match_param)
+		.response("200", entity_res("The deleted entity"))
+		.response("400", not_found())
+		.response(
+			"409",
+			ResponseBuilder::new().description("ENTITY_IN_USE").build(),
+		)
+		.response("412", precondition_failed())
+		.response("428", precondition_required())
+		.response("503", read_only());
+
+	openapi.paths.paths.insert(
+		format!("/api/{tag}/{{id}}"),
+		PathItemBuilder::new()
+			.operation(PathItemType::Get, get_op)
+			.operation(PathItemType::Patch, update_op)
+			.operation(PathItemType::Delete, delete_op)
+			.build(),
+	);
+}
+
+// endregion: --- OpenApi
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_if_match_matches_ok() {
+		// -- Setup & Fixtures
+		let fx_mtime =
+			OffsetDateTime::from_unix_timestamp_nanos(1704164645000000006).unwrap();
+		let fx_etag = rest_etag(1000, fx_mtime);
+
+		// -- Check
+		assert_eq!(fx_etag, "\"1000-1704164645000000006\"");
+		assert!(if_match_matches(&fx_etag, &fx_etag));
+		assert!(if_match_matches("*", &fx_etag));
+		assert!(if_match_matches(
+			&format!("\"1000-1\", {fx_etag}"),
+			&fx_etag
+		));
+		assert!(!if_match_matches("\"1000-1\"", &fx_etag));
+		assert!(!if_match_matches(&format!("W/{fx_etag}"), &fx_etag));
+		assert!(!if_match_matches("", &fx_etag));
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,77 @@
+//! The read-only routes of the project share links (no login, the link token
+//! is the access, see lib-core `model::project_share`).
+//!
+//! - `GET /api/shared/:token`       - the shared project
+//! - `GET /api/shared/:token/tasks` - its tasks (in `id` order)
+//!
+//! NOTE: An invalid, expired, or revoked token is a `403` `NO_AUTH`.
+//!
+//! NOTE: The entities are returned as to a user without role
+//!       (see `field_policy`).
+
+use crate::web::field_policy::visible_json;
+use crate::web::Result;
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use lib_core::ctx::Ctx;
+use lib_core::model::project::ProjectBmc;
+use lib_core::model::project_share::ProjectShareBmc;
+use lib_core::model::task::TaskBmc;
+use lib_core::model::ModelManager;
+use serde_json::Value;
+use tracing::debug;
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/api/shared/:token", get(shared_project_handler))
+		.route("/api/shared/:token/tasks", get(shared_tasks_handler))
+		.with_state(mm)
+}
+
+/// The project of the share link.
+#[utoipa::path(
+	get,
+	path = "/api/shared/{token}",
+	tag = "shared",
+	params(("token" = String, Path, description = "The share link token")),
+	responses(
+		(status = 200, description = "The shared project", body = Project),
+		(status = 403, description = "NO_AUTH (invalid, expired, or revoked link)"),
+	)
+)]
+async fn shared_project_handler(
+	State(mm): State<ModelManager>,
+	Path(token): Path<String>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - shared_project_handler", "HANDLER");
+
+	let project_id = ProjectShareBmc::resolve_token(&mm, &token).await?;
+	let project = ProjectBmc::get(&Ctx::root_ctx(), &mm, project_id).await?;
+
+	visible_json(None, project)
+}
+
+/// The tasks of the share link project.
+#[utoipa::path(
+	get,
+	path = "/api/shared/{token}/tasks",
+	tag = "shared",
+	params(("token" = String, Path, description = "The share link token")),
+	responses(
+		(status = 200, description = "The shared project tasks", body = [Task]),
+		(status = 403, description = "NO_AUTH (invalid, expired, or revoked link)"),
+	)
+)]
+async fn shared_tasks_handler(
+	State(mm): State<ModelManager>,
+	Path(token): Path<String>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - shared_tasks_handler", "HANDLER");
+
+	let project_id = ProjectShareBmc::resolve_token(&mm, &token).await?;
+	let tasks =
+		TaskBmc::list_by_project(&Ctx::root_ctx(), &mm, project_id, None).await?;
+
+	visible_json(None, tasks)
+}
@@ -1,18 +1,214 @@
 use axum::{
+	body::{Body, HttpBody},
 	handler::HandlerWithoutStateExt,
-	http::StatusCode,
-	routing::{any_service, MethodRouter},
+	http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+	middleware::{self, Next},
+	response::{IntoResponse, Response},
+	routing::{any, MethodRouter},
+	BoxError,
 };
 use lib_core::config;
+use std::convert::Infallible;
+use std::time::UNIX_EPOCH;
+use tower::{Service, ServiceExt};
 use tower_http::services::ServeDir;
 
+/// Serves the `WEB_FOLDER` files.
+///
+/// Note: `ServeDir` already emits `Last-Modified` and honors `If-Modified-Since`,
+///       `mw_static_etag` adds the `ETag` / `If-None-Match` support on top,
+///       and `mw_static_cache_control` the config `Cache-Control` policies.
+///       `ServeDir` also answers the (single) `Range` requests with `206`,
+///       and `serve_file` adds the `If-Range` support.
+///
+/// When `STATIC_PRECOMPRESSED`, the build time `.br` / `.gz` variants of a file
+/// (e.g., `app.js.br`) are served to the clients accepting them (the
+/// `compression_layer` then leaves those responses as is).
 pub fn serve_dir() -> MethodRouter {
 	async fn handle_404() -> (StatusCode, &'static str) {
 		(StatusCode::NOT_FOUND, "Resource not found.")
 	}
 
-	any_service(
-		ServeDir::new(&config().WEB_FOLDER)
-			.not_found_service(handle_404.into_service()),
-	)
+	let mut serve_dir = ServeDir::new(&config().WEB_FOLDER);
+	if config().STATIC_PRECOMPRESSED {
+		serve_dir = serve_dir.precompressed_br().precompressed_gzip();
+	}
+
+	let serve_dir = serve_dir.not_found_service(handle_404.into_service());
+
+	any(move |req: Request<Body>| serve_file(serve_dir.clone(), req))
+		.layer(middleware::from_fn(mw_static_etag))
+		.layer(middleware::from_fn(mw_static_cache_control))
+		.layer(middleware::from_fn(mw_static_vary))
 }
+
+/// Serves the file, falling back to the full file when the request `If-Range`
+/// does not match the current file (so resumed downloads never mix versions).
+async fn serve_file<S, ResBody>(serve_dir: S, req: Request<Body>) -> Response
+where
+	S: Service<Request<Body>, Response = Response<ResBody>, Error = Infallible>
+		+ Clone,
+	ResBody: HttpBody<Data = axum::body::Bytes> + Send + 'static,
+	ResBody::Error: Into<BoxError>,
+{
+	let Some(if_range) = req.headers().get(header::IF_RANGE).cloned() else {
+		return serve_dir.oneshot(req).await.into_response();
+	};
+
+	// Note: The full file request (only what `ServeDir` reads from the request).
+	let mut full_req = Request::new(Body::empty());
+	*full_req.method_mut() = req.method().clone();
+	*full_req.uri_mut() = req.uri().clone();
+	*full_req.headers_mut() = req.headers().clone();
+	full_req.headers_mut().remove(header::RANGE);
+
+	let res = serve_dir.clone().oneshot(req).await.into_response();
+
+	if res.status() == StatusCode::PARTIAL_CONTENT
+		&& !if_range_matches(&if_range, res.headers())
+	{
+		return serve_dir.oneshot(full_req).await.into_response();
+	}
+
+	res
+}
+
+/// `If-Range` is either an etag or a http date (compared to `Last-Modified`).
+///
+/// NOTE: Our etags are weak only because the full responses might be compressed
+///       on the fly, but range responses are always the identity bytes, so the
+///       etag validator (file size & mtime) is accepted here as well.
+fn if_range_matches(if_range: &HeaderValue, res_headers: &HeaderMap) -> bool {
+	let Ok(if_range) = if_range.to_str() else {
+		return false;
+	};
+
+	if if_range.starts_with("W/") || if_range.starts_with('"') {
+		etag_from_headers(res_headers)
+			.and_then(|etag| etag.to_str().ok().map(str::to_string))
+			.is_some_and(|etag| {
+				etag.trim_start_matches("W/") == if_range.trim_start_matches("W/")
+			})
+	} else {
+		res_headers
+			.get(header::LAST_MODIFIED)
+			.is_some_and(|last_modified| last_modified == if_range)
+	}
+}
+
+// region:    --- Vary
+
+/// With precompressed variants, the same url has different bodies (and etags)
+/// by `Accept-Encoding`, so shared caches must key on it as well.
+async fn mw_static_vary<B>(req: Request<B>, next: Next<B>) -> Response {
+	let mut res = next.run(req).await;
+
+	if config().STATIC_PRECOMPRESSED
+		&& matches!(res.status(), StatusCode::OK | StatusCode::NOT_MODIFIED)
+	{
+		res.headers_mut()
+			.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+	}
+
+	res
+}
+
+// endregion: --- Vary
+
+// region:    --- Cache-Control
+
+/// Sets the `Cache-Control` of the first `STATIC_CACHE_RULES` matching the path.
+async fn mw_static_cache_control<B>(req: Request<B>, next: Next<B>) -> Response {
+	// Note: Directory paths are served with their index.html.
+	let mut path = req.uri().path().trim_start_matches('/').to_string();
+	if path.is_empty() || path.ends_with('/') {
+		path.push_str("index.html");
+	}
+
+	let mut res = next.run(req).await;
+
+	if !matches!(res.status(), StatusCode::OK | StatusCode::NOT_MODIFIED) {
+		return res;
+	}
+
+	let cache_control = config()
+		.STATIC_CACHE_RULES
+		.iter()
+		.find(|rule| rule.matches(&path))
+		.and_then(|rule| HeaderValue::from_str(&rule.cache_control).ok());
+	if let Some(cache_control) = cache_control {
+		res.headers_mut()
+			.insert(header::CACHE_CONTROL, cache_control);
+	}
+
+	res
+}
+
+// endregion: --- Cache-Control
+
+// region:    --- ETag
+
+/// Adds a weak `ETag` (from the file size and modification time) to the
+/// static file responses, and answers `304 Not Modified` when it matches
+/// the request `If-None-Match`.
+async fn mw_static_etag<B>(req: Request<B>, next: Next<B>) -> Response {
+	let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+	let is_get_or_head = matches!(*req.method(), Method::GET | Method::HEAD);
+
+	let mut res = next.run(req).await;
+
+	if !matches!(res.status(), StatusCode::OK | StatusCode::PARTIAL_CONTENT) {
+		return res;
+	}
+	let Some(etag) = etag_from_headers(res.headers()) else {
+		return res;
+	};
+
+	if is_get_or_head && if_none_match.is_some_and(|val| etag_matches(&val, &etag)) {
+		let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+		let headers = not_modified.headers_mut();
+		if let Some(last_modified) = res.headers().get(header::LAST_MODIFIED) {
+			headers.insert(header::LAST_MODIFIED, last_modified.clone());
+		}
+		headers.insert(header::ETAG, etag);
+		return not_modified;
+	}
+
+	res.headers_mut().insert(header::ETAG, etag);
+	res
+}
+
+/// Weak etag `W/"<size hex>-<mtime secs hex>"` (same validator as `Last-Modified`).
+///
+/// Note: For the `206` responses, the size is the full file one (from `Content-Range`).
+fn etag_from_headers(headers: &HeaderMap) -> Option<HeaderValue> {
+	let size: u64 = match headers.get(header::CONTENT_RANGE) {
+		Some(content_range) => content_range.to_str().ok()?.rsplit_once('/')?.1,
+		None => headers.get(header::CONTENT_LENGTH)?.to_str().ok()?,
+	}
+	.parse()
+	.ok()?;
+	let last_modified = headers.get(header::LAST_MODIFIED)?.to_str().ok()?;
+	let mtime_secs = httpdate::parse_http_date(last_modified)
+		.ok()?
+		.duration_since(UNIX_EPOCH)
+		.ok()?
+		.as_secs();
+
+	HeaderValue::from_str(&format!("W/\"{size:x}-{mtime_secs:x}\"")).ok()
+}
+
+/// Weak comparison of the `If-None-Match` list (or `*`) against the etag.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+	let (Ok(if_none_match), Ok(etag)) = (if_none_match.to_str(), etag.to_str())
+	else {
+		return false;
+	};
+	let etag = etag.trim_start_matches("W/");
+
+	if_none_match.split(',').map(str::trim).any(|candidate| {
+		candidate == "*" || candidate.trim_start_matches("W/") == etag
+	})
+}
+
+// endregion: --- ETag
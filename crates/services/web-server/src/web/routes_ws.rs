@@ -0,0 +1,375 @@
+//! `/ws/events` - Live model change events (see `lib_core::model::event`).
+//!
+//! Protocol (json text messages, tagged by `type`):
+//!
+//! - Connect (authenticated) with an optional `?project_ids=1000,1001` filter.
+//!   No project filter means the events of the projects owned by the user
+//!   (all of them for root).
+//! - Client: `{"type": "subscribe", "data": {"project_ids": [1002]}}`
+//!   and `{"type": "unsubscribe", "data": {"project_ids": [1000]}}`.
+//!   Only the owned projects can be subscribed to (see `ProjectBmc::check_owner`).
+//! - Server: `{"type": "event", "data": {entity, id, project_id, kind, user_id}}`,
+//!   `subscribed` (current filter), `lagged` (events were missed, UI should
+//!   refetch), and `error` messages.
+//!
+//! The subscribed `project_ids` are also the connection presence (see
+//! `lib_core::model::presence`, listed by the `list_project_presence` rpc).
+
+use crate::web::mw_auth::CtxW;
+use crate::web::{Error, Result};
+use axum::{
+	extract::{
+		ws::{Message, WebSocket, WebSocketUpgrade},
+		Query, State,
+	},
+	response::Response,
+	routing::get,
+	Router,
+};
+use lib_core::ctx::Ctx;
+use lib_core::model::event::{ModelEvent, ModelEventKind};
+use lib_core::model::presence::{PresenceBmc, PRESENCE_TTL};
+use lib_core::model::project::ProjectBmc;
+use lib_core::model::ModelManager;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, warn};
+
+pub fn routes(mm: ModelManager) -> Router {
+	Router::new()
+		.route("/ws/events", get(ws_events_handler))
+		.with_state(mm)
+}
+
+// region:    --- Messages
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum WsClientMessage {
+	Subscribe { project_ids: Vec<i64> },
+	Unsubscribe { project_ids: Vec<i64> },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum WsServerMessage<'a> {
+	Event(&'a ModelEvent),
+	Subscribed { project_ids: &'a BTreeSet<i64> },
+	Lagged { missed: u64 },
+	Error { message: String },
+}
+
+// endregion: --- Messages
+
+#[derive(Deserialize)]
+struct WsEventsParams {
+	/// Comma separated project ids.
+	project_ids: Option<String>,
+}
+
+async fn ws_events_handler(
+	State(mm): State<ModelManager>,
+	ctx: CtxW,
+	Query(params): Query<WsEventsParams>,
+	ws: WebSocketUpgrade,
+) -> Result<Response> {
+	debug!("{:<12} - ws_events_handler", "HANDLER");
+
+	let ctx = ctx.0;
+
+	let mut project_ids = BTreeSet::new();
+	for project_id in params.project_ids.iter().flat_map(|ids| ids.split(',')) {
+		let project_id = project_id.trim().parse::<i64>().map_err(|_| {
+			Error::WsEventsInvalidProjectId {
+				value: project_id.to_string(),
+			}
+		})?;
+		// Note: Fails (before the upgrade) if the project is not owned.
+		ProjectBmc::check_owner(&ctx, &mm, project_id).await?;
+		project_ids.insert(project_id);
+	}
+
+	Ok(ws.on_upgrade(move |socket| ws_events_loop(socket, mm, ctx, project_ids)))
+}
+
+async fn ws_events_loop(
+	mut socket: WebSocket,
+	mm: ModelManager,
+	ctx: Ctx,
+	mut project_ids: BTreeSet<i64>,
+) {
+	let mut events = mm.subscribe_events();
+	let mut owned_projects = OwnedProjects::default();
+
+	let presence_id = PresenceBmc::new_conn_id(&mm);
+	// Note: The first tick is immediate, so it also sets the initial presence.
+	let mut presence_refresh = interval(PRESENCE_TTL.unsigned_abs() / 2);
+	presence_refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+	loop {
+		tokio::select! {
+			_ = presence_refresh.tick() => {
+				PresenceBmc::set_viewing(&ctx, &mm, presence_id, project_ids.iter().copied());
+			}
+
+			event = events.recv() => {
+				let msg = match event {
+					Ok(event) => {
+						owned_projects.evict_on(&event);
+						let visible = if project_ids.is_empty() {
+							owned_projects.contains(&mm, &ctx, event.project_id).await
+						} else {
+							project_ids.contains(&event.project_id)
+						};
+						if !visible {
+							continue;
+						}
+						send_msg(&mut socket, &WsServerMessage::Event(&event)).await
+					}
+					Err(RecvError::Lagged(missed)) => {
+						warn!("ws events - user_id {} lagged by {missed}", ctx.user_id());
+						send_msg(&mut socket, &WsServerMessage::Lagged { missed }).await
+					}
+					Err(RecvError::Closed) => break,
+				};
+				if msg.is_err() {
+					break;
+				}
+			}
+
+			msg = socket.recv() => {
+				let text = match msg {
+					Some(Ok(Message::Text(text))) => text,
+					// Note: Ping/Pong are answered by axum.
+					Some(Ok(Message::Binary(_) | Message::Ping(_) | Message::Pong(_))) => continue,
+					Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+				};
+
+				let res = match handle_client_msg(&mm, &ctx, &mut project_ids, &text).await {
+					Ok(()) => {
+						PresenceBmc::set_viewing(&ctx, &mm, presence_id, project_ids.iter().copied());
+
+						let msg = WsServerMessage::Subscribed {
+							project_ids: &project_ids,
+						};
+						send_msg(&mut socket, &msg).await
+					}
+					Err(message) => {
+						send_msg(&mut socket, &WsServerMessage::Error { message }).await
+					}
+				};
+				if res.is_err() {
+					break;
+				}
+			}
+		}
+	}
+
+	PresenceBmc::leave(&mm, presence_id);
+
+	debug!("ws events - user_id {} disconnected", ctx.user_id());
+}
+
+/// Applies the client (un)subscribe message, or returns the error message.
+async fn handle_client_msg(
+	mm: &ModelManager,
+	ctx: &Ctx,
+	project_ids: &mut BTreeSet<i64>,
+	text: &str,
+) -> core::result::Result<(), String> {
+	let msg: WsClientMessage =
+		serde_json::from_str(text).map_err(|ex| ex.to_string())?;
+
+	match msg {
+		WsClientMessage::Subscribe { project_ids: ids } => {
+			for id in ids {
+				ProjectBmc::check_owner(ctx, mm, id).await.map_err(|ex| {
+					format!("cannot subscribe to project {id}: {ex}")
+				})?;
+				project_ids.insert(id);
+			}
+		}
+		WsClientMessage::Unsubscribe { project_ids: ids } => {
+			for id in ids {
+				project_ids.remove(&id);
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// The projects owned by the connection user, looked up (then cached) as their
+/// events come, for the connections without a project filter.
+///
+/// The project `Updated`/`Deleted` events evict their entry (e.g., an owner
+/// change), so it is looked up again.
+#[derive(Default)]
+struct OwnedProjects {
+	owned_by_id: HashMap<i64, bool>,
+}
+
+impl OwnedProjects {
+	fn evict_on(&mut self, event: &ModelEvent) {
+		if event.entity == "project"
+			&& matches!(
+				event.kind,
+				ModelEventKind::Updated | ModelEventKind::Deleted
+			) {
+			self.owned_by_id.remove(&event.id);
+		}
+	}
+
+	async fn contains(
+		&mut self,
+		mm: &ModelManager,
+		ctx: &Ctx,
+		project_id: i64,
+	) -> bool {
+		if ctx.is_root() {
+			return true;
+		}
+		if let Some(owned) = self.owned_by_id.get(&project_id) {
+			return *owned;
+		}
+
+		match ProjectBmc::owned_ids(ctx, mm, &[project_id]).await {
+			Ok(owned_ids) => {
+				let owned = !owned_ids.is_empty();
+				self.owned_by_id.insert(project_id, owned);
+				owned
+			}
+			// Note: Not cached, so looked up again on the next event.
+			Err(ex) => {
+				warn!("ws events - owned project lookup fail - {ex:?}");
+				false
+			}
+		}
+	}
+}
+
+async fn send_msg(
+	socket: &mut WebSocket,
+	msg: &WsServerMessage<'_>,
+) -> core::result::Result<(), axum::Error> {
+	// Note: Our message types always serialize.
+	let text = serde_json::to_string(msg).unwrap_or_default();
+	socket.send(Message::Text(text)).await
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use lib_core::_dev_utils;
+	use lib_core::model::project::ProjectForUpdate;
+	use serial_test::serial;
+
+	fn fx_event(project_id: i64) -> ModelEvent {
+		ModelEvent {
+			entity: "project".to_string(),
+			id: project_id,
+			project_id,
+			kind: ModelEventKind::Updated,
+			user_id: 1000,
+		}
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_owned_projects_other_user_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::new(1000)?;
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_owned_projects_other_user_ok project",
+		)
+		.await?;
+		let fx_event = fx_event(fx_project_id);
+
+		// -- Exec & Check - the owner, and root.
+		let mut owned_projects = OwnedProjects::default();
+		assert!(
+			owned_projects
+				.contains(&mm, &ctx, fx_event.project_id)
+				.await
+		);
+		let mut owned_projects = OwnedProjects::default();
+		let root_ctx = Ctx::root_ctx();
+		assert!(
+			owned_projects
+				.contains(&mm, &root_ctx, fx_event.project_id)
+				.await
+		);
+
+		// -- Exec & Check - a second user does not receive the events.
+		let mut owned_projects = OwnedProjects::default();
+		let other_ctx = Ctx::new(1001)?;
+		assert!(
+			!owned_projects
+				.contains(&mm, &other_ctx, fx_event.project_id)
+				.await
+		);
+
+		// -- Exec & Check - nor can subscribe to the project.
+		let mut project_ids = BTreeSet::new();
+		let text = format!(
+			r#"{{"type": "subscribe", "data": {{"project_ids": [{fx_project_id}]}}}}"#
+		);
+		let res = handle_client_msg(&mm, &other_ctx, &mut project_ids, &text).await;
+		assert!(res.is_err());
+		assert!(project_ids.is_empty());
+
+		// -- Clean
+		ProjectBmc::delete(&ctx, &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_owned_projects_owner_change_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::new(1000)?;
+		let fx_project_id = _dev_utils::seed_project(
+			&ctx,
+			&mm,
+			"test_owned_projects_owner_change_ok project",
+		)
+		.await?;
+		let fx_event = fx_event(fx_project_id);
+		let mut owned_projects = OwnedProjects::default();
+		assert!(
+			owned_projects
+				.contains(&mm, &ctx, fx_event.project_id)
+				.await
+		);
+
+		// -- Exec
+		let project_u = ProjectForUpdate {
+			name: None,
+			owner_id: Some(1001),
+		};
+		ProjectBmc::update(&ctx, &mm, fx_project_id, project_u).await?;
+		owned_projects.evict_on(&fx_event);
+
+		// -- Check
+		assert!(
+			!owned_projects
+				.contains(&mm, &ctx, fx_event.project_id)
+				.await
+		);
+
+		// -- Clean
+		ProjectBmc::delete(&Ctx::root_ctx(), &mm, fx_project_id).await?;
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
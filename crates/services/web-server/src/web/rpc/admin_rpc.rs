@@ -0,0 +1,104 @@
+//! The user administration methods, only for the `admin` users
+//! (see `RpcRouter::require_role`).
+//!
+//! NOTE: The changes are stamped (mid) with the admin user id, and an admin
+//!       cannot disable or demote itself (`AdminSelfLockout`).
+
+use crate::rpc_router;
+use crate::web::{Error, Result};
+use lib_core::ctx::Ctx;
+use lib_core::model::user::{UserBmc, UserFilter, UserForAdmin, UserRole};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::web::rpc::params::{ParamsIded, ParamsList};
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(
+		queries: [list_users, get_user],
+		mutations: [disable_user, set_user_role, force_logout_user],
+	)
+	.require_role(UserRole::Admin)
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsSetUserRole {
+	pub id: i64,
+	pub role: UserRole,
+}
+
+impl IntoParams for ParamsSetUserRole {}
+
+pub async fn list_users(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<UserFilter>,
+) -> Result<Vec<UserForAdmin>> {
+	let (filters, list_options) = params.into_list_parts::<UserBmc>(&ctx);
+	let users = UserBmc::list(&ctx, &mm, filters, Some(list_options)).await?;
+
+	Ok(users)
+}
+
+pub async fn get_user(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<UserForAdmin> {
+	let user = UserBmc::get(&ctx, &mm, params.id).await?;
+
+	Ok(user)
+}
+
+/// Deactivates the user, which cannot login, and whose sessions fail.
+pub async fn disable_user(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<UserForAdmin> {
+	let ParamsIded { id } = params;
+	if id == ctx.user_id() {
+		return Err(Error::AdminSelfLockout {
+			method: "disable_user",
+		});
+	}
+
+	UserBmc::update_active(&ctx, &mm, id, false).await?;
+	let user = UserBmc::get(&ctx, &mm, id).await?;
+
+	Ok(user)
+}
+
+pub async fn set_user_role(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsSetUserRole,
+) -> Result<UserForAdmin> {
+	let ParamsSetUserRole { id, role } = params;
+	if id == ctx.user_id() && role != UserRole::Admin {
+		return Err(Error::AdminSelfLockout {
+			method: "set_user_role",
+		});
+	}
+
+	UserBmc::update_role(&ctx, &mm, id, role).await?;
+	let user = UserBmc::get(&ctx, &mm, id).await?;
+
+	Ok(user)
+}
+
+/// Logs out all the user sessions (its tokens are invalidated).
+pub async fn force_logout_user(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<Value> {
+	UserBmc::rotate_token_salt(&ctx, &mm, params.id).await?;
+
+	Ok(json!({ "success": true }))
+}
@@ -0,0 +1,68 @@
+//! The json-rpc response `meta` member, for the handlers to report how the
+//! request was adjusted (e.g., the `limit_clamped` of the list methods, see
+//! `ParamsList::into_list_parts`), next to their `result`.
+
+use lib_core::ctx::Ctx;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::{Arc, Mutex};
+
+/// In the `Ctx` extensions of the json-rpc calls (see `rpc_axum_handler`).
+#[derive(Clone, Default)]
+pub struct RpcMeta(Arc<Mutex<Map<String, Value>>>);
+
+impl RpcMeta {
+	/// Sets a meta member, when the ctx is of a json-rpc call (a no-op otherwise).
+	pub fn insert(ctx: &Ctx, key: &str, value: impl Serialize) {
+		let Some(rpc_meta) = ctx.extensions().get::<RpcMeta>() else {
+			return;
+		};
+		let Ok(value) = serde_json::to_value(value) else {
+			return;
+		};
+		rpc_meta
+			.0
+			.lock()
+			.unwrap_or_else(|e| e.into_inner())
+			.insert(key.to_string(), value);
+	}
+
+	/// The meta object, none when empty.
+	pub fn take(&self) -> Option<Value> {
+		let meta =
+			std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()));
+
+		(!meta.is_empty()).then_some(Value::Object(meta))
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_rpc_meta_insert_take_ok() -> crate::web::Result<()> {
+		// -- Setup & Fixtures
+		let fx_rpc_meta = RpcMeta::default();
+		let mut fx_ctx = Ctx::new(1000)?;
+		fx_ctx.extensions_mut().insert(fx_rpc_meta.clone());
+		let fx_service_ctx = Ctx::new(1000)?;
+
+		// -- Exec
+		assert_eq!(fx_rpc_meta.take(), None);
+		let handler_ctx = fx_ctx.clone();
+		RpcMeta::insert(&handler_ctx, "limit_clamped", json!({"max": 10}));
+		RpcMeta::insert(&fx_service_ctx, "ignored", true);
+
+		// -- Check
+		assert_eq!(
+			fx_rpc_meta.take(),
+			Some(json!({"limit_clamped": {"max": 10}}))
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -1,24 +1,39 @@
 // region:    --- Modules
 
-use crate::web::mw_auth::CtxW;
+use crate::web::metrics;
+use crate::web::mw_auth::{CtxW, PwdChangeRequired};
+use crate::web::Error;
 use axum::{
 	extract::State,
+	http::{header, HeaderName, HeaderValue},
 	response::{IntoResponse, Response},
 	routing::post,
 	Json, Router,
 };
-use lib_core::model::ModelManager;
 
 use serde::Deserialize;
+use serde_json::value::RawValue;
 use serde_json::{json, Value};
 use std::sync::Arc;
+use tower_cookies::Cookies;
+use tracing::Span;
 
+mod admin_rpc;
+mod meta;
 mod params;
 mod project_rpc;
+mod project_share_rpc;
+mod request_log_rpc;
 mod router;
+mod search_rpc;
 mod state;
+mod sync_rpc;
 mod task_rpc;
+mod user_rpc;
+mod webhook_rpc;
+pub use meta::RpcMeta;
 pub use params::*;
+pub use router::RpcMethodKind;
 pub use state::*;
 
 use crate::web::rpc::router::RpcRouter;
@@ -30,9 +45,14 @@ use crate::web::rpc::router::RpcRouter;
 struct RpcRequest {
 	id: Option<Value>,
 	method: String,
-	params: Option<Value>,
+	/// Kept raw, and deserialized once into the handler params type.
+	params: Option<Box<RawValue>>,
 }
 
+/// The introspection method, which lists the methods of the api version
+/// (e.g., for the `/rpc-playground` page).
+pub const RPC_DISCOVER: &str = "rpc.discover";
+
 /// RPC basic information containing the id and method for additional logging purposes.
 #[derive(Debug)]
 pub struct RpcInfo {
@@ -44,27 +64,99 @@ pub struct RpcInfo {
 
 // endregion: --- RpcState
 
-pub fn routes(rpc_state: RpcState) -> Router {
-	// Build the combined RpcRouter.
-	let rpc_router = RpcRouter::new()
+// region:    --- Api Versions
+
+/// The `Deprecation` response header (RFC 9745).
+pub const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+
+/// One json-rpc api version, served at `/{name}/rpc`.
+struct RpcApiVersion {
+	name: &'static str,
+	rpc_router: Arc<RpcRouter>,
+	/// When set, the version is deprecated in favor of this one.
+	successor: Option<&'static str>,
+}
+
+/// The v1 methods.
+///
+/// A method rename keeps the previous name as a deprecated alias, e.g.,
+/// `.deprecated_alias("list_tasks", "search_tasks")`, until the clients moved.
+/// (the `RpcRouter` alias functions are `cfg(test)` until the first rename)
+fn rpc_router_v1() -> RpcRouter {
+	RpcRouter::new()
 		.extend(task_rpc::rpc_router())
-		.extend(project_rpc::rpc_router());
+		.extend(project_rpc::rpc_router())
+		.extend(project_share_rpc::rpc_router())
+		.extend(webhook_rpc::rpc_router())
+		.extend(sync_rpc::rpc_router())
+		.extend(user_rpc::rpc_router())
+		.extend(admin_rpc::rpc_router())
+		.extend(request_log_rpc::rpc_router())
+		.extend(search_rpc::rpc_router())
+}
 
-	// Build the Acum Router for '/rpc'
-	Router::new()
-		.route("/rpc", post(rpc_axum_handler))
-		.with_state((rpc_state, Arc::new(rpc_router)))
+/// The mounted api versions.
+///
+/// A breaking change is rolled out as a new version, which starts from the
+/// previous router (sharing the unchanged handlers) and overrides only the
+/// changed methods, e.g., `rpc_router_v1().extend(task_rpc_v2::rpc_router())`.
+/// The previous version then gets `successor: Some("v2")`, until removed.
+fn rpc_api_versions() -> Vec<RpcApiVersion> {
+	vec![RpcApiVersion {
+		name: "v1",
+		rpc_router: Arc::new(rpc_router_v1()),
+		successor: None,
+	}]
 }
 
-#[derive(Clone)]
-struct RpcStates(ModelManager, Arc<RpcRouter>);
+// endregion: --- Api Versions
+
+type RpcAxumState = (RpcState, Arc<RpcRouter>, Option<&'static str>);
+
+pub fn routes(rpc_state: RpcState) -> Router {
+	let versions = rpc_api_versions();
+
+	// The unversioned '/rpc' is the v1 api, kept for the existing clients.
+	let legacy_router = versions
+		.iter()
+		.find(|version| version.name == "v1")
+		.map(|version| version.rpc_router.clone())
+		.unwrap_or_else(|| Arc::new(rpc_router_v1()));
+
+	// Build the Acum Router for '/rpc' and the '/{version}/rpc'
+	let mut router = Router::new().route(
+		"/rpc",
+		post(rpc_axum_handler).with_state((
+			rpc_state.clone(),
+			legacy_router,
+			Some("v1"),
+		)),
+	);
+	for version in versions {
+		router = router.route(
+			&format!("/{}/rpc", version.name),
+			post(rpc_axum_handler).with_state((
+				rpc_state.clone(),
+				version.rpc_router,
+				version.successor,
+			)),
+		);
+	}
+
+	router
+}
 
 async fn rpc_axum_handler(
-	State((rpc_state, rpc_router)): State<(RpcState, Arc<RpcRouter>)>,
+	State((rpc_state, rpc_router, successor)): State<RpcAxumState>,
 	ctx: CtxW,
+	cookies: Cookies,
 	Json(rpc_req): Json<RpcRequest>,
 ) -> Response {
-	let ctx = ctx.0;
+	let mut ctx = ctx.0;
+	let rpc_meta = RpcMeta::default();
+	ctx.extensions_mut().insert(rpc_meta.clone());
+	// Note: For the handlers re-issuing the auth cookie (e.g., `rotate_token_salt`).
+	ctx.extensions_mut().insert(cookies);
 
 	// -- Create the RPC Info
 	//    (will be set to the response.extensions)
@@ -72,17 +164,41 @@ async fn rpc_axum_handler(
 		id: rpc_req.id.clone(),
 		method: rpc_req.method.clone(),
 	};
+	// -- Record the method on the request span (see mw_req_stamp).
+	Span::current().record("rpc.method", rpc_info.method.as_str());
+	// -- Count the deprecated method calls
+	let warning = rpc_router.deprecation(&rpc_info.method).map(|deprecation| {
+		metrics::record_rpc_deprecated_call(&rpc_info.method);
+		deprecation.warning(&rpc_info.method)
+	});
+
 	// -- Exec Rpc Route
-	let res = rpc_router
-		.call(&rpc_info.method, ctx, rpc_state, rpc_req.params)
-		.await;
+	//    (only the pwd change, when required)
+	let res = if ctx.extensions().get::<PwdChangeRequired>().is_some()
+		&& rpc_info.method != user_rpc::CHANGE_PWD
+	{
+		Err(Error::PwdChangeRequired)
+	} else if rpc_info.method == RPC_DISCOVER {
+		Ok(json!({ "methods": rpc_router.methods() }))
+	} else {
+		rpc_router
+			.call(&rpc_info.method, ctx, rpc_state, rpc_req.params)
+			.await
+	};
 
 	// -- Build Rpc Success Response
+	let is_deprecated = warning.is_some();
 	let res = res.map(|v| {
-		let body_response = json!({
+		let mut body_response = json!({
 			"id": rpc_info.id,
 			"result": v
 		});
+		if let Some(warning) = warning {
+			body_response["warning"] = json!(warning);
+		}
+		if let Some(meta) = rpc_meta.take() {
+			body_response["meta"] = meta;
+		}
 		Json(body_response)
 	});
 
@@ -90,5 +206,174 @@ async fn rpc_axum_handler(
 	let mut res = res.into_response();
 	res.extensions_mut().insert(rpc_info);
 
+	// -- Flag the deprecated versions and methods
+	//    (carried over to the error responses by mw_res_map)
+	if is_deprecated {
+		res.headers_mut()
+			.insert(DEPRECATION, HeaderValue::from_static("true"));
+	}
+	if let Some(successor) = successor {
+		let headers = res.headers_mut();
+		headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+		if let Ok(link) = HeaderValue::from_str(&format!(
+			"</api/{successor}/rpc>; rel=\"successor-version\""
+		)) {
+			headers.insert(header::LINK, link);
+		}
+	}
+
 	res
 }
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::web::rpc::router::RpcHandler;
+	use lib_core::ctx::Ctx;
+	use lib_core::model::user::UserRole;
+	use lib_core::model::ModelManager;
+
+	#[test]
+	fn test_rpc_api_versions_successors_ok() {
+		let versions = rpc_api_versions();
+		let names: Vec<_> = versions.iter().map(|version| version.name).collect();
+
+		// -- Check
+		assert!(names.contains(&"v1"), "v1 backs the legacy '/rpc'");
+		for version in &versions {
+			if let Some(successor) = version.successor {
+				assert!(
+					names.contains(&successor),
+					"{} successor {successor} not mounted",
+					version.name
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn test_rpc_router_v1_kinds_ok() {
+		let rpc_router = rpc_router_v1();
+
+		// -- Check
+		assert_eq!(rpc_router.kind("list_tasks"), Some(RpcMethodKind::Query));
+		assert_eq!(rpc_router.kind("sync_changes"), Some(RpcMethodKind::Query));
+		assert_eq!(
+			rpc_router.kind("create_task"),
+			Some(RpcMethodKind::Mutation)
+		);
+		assert_eq!(rpc_router.kind("change_pwd"), Some(RpcMethodKind::Mutation));
+		assert_eq!(rpc_router.kind("nope"), None);
+	}
+
+	#[test]
+	fn test_rpc_router_deprecated_alias_ok() {
+		// -- Setup & Fixtures
+		async fn search_items(
+			_ctx: Ctx,
+			_mm: ModelManager,
+		) -> crate::web::Result<()> {
+			Ok(())
+		}
+		let rpc_router = RpcRouter::new()
+			.add("search_items", search_items.into_box())
+			.deprecated_alias("list_items", "search_items")
+			.alias("find_items", "search_items")
+			.deprecate("find_items", None);
+
+		// -- Exec
+		let deprecation = rpc_router.deprecation("list_items");
+
+		// -- Check
+		let deprecation = deprecation.expect("list_items should be deprecated");
+		assert_eq!(deprecation.replacement, Some("search_items"));
+		assert_eq!(
+			deprecation.warning("list_items"),
+			"'list_items' is deprecated, use 'search_items' instead"
+		);
+		assert!(rpc_router.deprecation("search_items").is_none());
+		assert_eq!(
+			rpc_router
+				.deprecation("find_items")
+				.map(|d| d.warning("find_items")),
+			Some("'find_items' is deprecated".to_string())
+		);
+	}
+
+	#[test]
+	fn test_rpc_router_require_role_ok() -> crate::web::Result<()> {
+		// -- Setup & Fixtures
+		async fn list_items(_ctx: Ctx, _mm: ModelManager) -> crate::web::Result<()> {
+			Ok(())
+		}
+		let rpc_router = RpcRouter::new()
+			.add("purge_items", list_items.into_box())
+			.require_role(UserRole::Admin)
+			.add("list_items", list_items.into_box())
+			.alias("wipe_items", "purge_items");
+		let mut fx_user_ctx = Ctx::new(1000)?;
+		fx_user_ctx.extensions_mut().insert(UserRole::User);
+		let mut fx_admin_ctx = Ctx::new(1001)?;
+		fx_admin_ctx.extensions_mut().insert(UserRole::Admin);
+
+		// -- Exec & Check
+		assert!(matches!(
+			rpc_router.check_role("purge_items", &fx_user_ctx),
+			Err(Error::RoleRequired { role: "admin", .. })
+		));
+		assert!(rpc_router.check_role("wipe_items", &fx_user_ctx).is_err());
+		assert!(rpc_router.check_role("list_items", &fx_user_ctx).is_ok());
+		assert!(rpc_router.check_role("purge_items", &fx_admin_ctx).is_ok());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_rpc_router_check_read_only_ok() {
+		// -- Setup & Fixtures
+		async fn list_items(_ctx: Ctx, _mm: ModelManager) -> crate::web::Result<()> {
+			Ok(())
+		}
+		let rpc_router = RpcRouter::new()
+			.add_with_kind("list_items", RpcMethodKind::Query, list_items.into_box())
+			.add("purge_items", list_items.into_box())
+			.alias("find_items", "list_items");
+
+		// -- Exec & Check
+		assert!(rpc_router.check_read_only("list_items", true).is_ok());
+		assert!(rpc_router.check_read_only("find_items", true).is_ok());
+		assert!(matches!(
+			rpc_router.check_read_only("purge_items", true),
+			Err(Error::ReadOnlyMode { mutation }) if mutation == "purge_items"
+		));
+		assert!(rpc_router.check_read_only("purge_items", false).is_ok());
+	}
+
+	#[test]
+	fn test_rpc_router_methods_ok() {
+		// -- Setup & Fixtures
+		async fn search_items(
+			_ctx: Ctx,
+			_mm: ModelManager,
+		) -> crate::web::Result<()> {
+			Ok(())
+		}
+		let rpc_router = RpcRouter::new()
+			.add("search_items", search_items.into_box())
+			.deprecated_alias("list_items", "search_items");
+
+		// -- Exec
+		let methods = serde_json::to_value(rpc_router.methods()).unwrap();
+
+		// -- Check
+		assert_eq!(
+			methods,
+			json!([
+				{"name": "list_items", "kind": "mutation", "deprecated": true, "replacement": "search_items"},
+				{"name": "search_items", "kind": "mutation", "deprecated": false},
+			])
+		);
+	}
+}
+// endregion: --- Tests
@@ -1,12 +1,21 @@
 use crate::web::rpc::router::{IntoDefaultParams, IntoParams};
+use crate::web::rpc::RpcMeta;
+use lib_core::ctx::Ctx;
+#[cfg(feature = "ts")]
+use lib_core::model::modql_utils::ts;
+use lib_core::model::{clamp_list_options, DbBmc};
 use modql::filter::ListOptions;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde_json::value::RawValue;
 use serde_json::Value;
 use serde_with::{serde_as, OneOrMany};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
 
 /// Params structure for any RPC Create call.
 #[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct ParamsForCreate<D> {
 	pub data: D,
 }
@@ -15,6 +24,7 @@ impl<D> IntoParams for ParamsForCreate<D> where D: DeserializeOwned + Send {}
 
 /// Params structure for any RPC Update call.
 #[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct ParamsForUpdate<D> {
 	pub id: i64,
 	pub data: D,
@@ -24,6 +34,7 @@ impl<D> IntoParams for ParamsForUpdate<D> where D: DeserializeOwned + Send {}
 
 /// Params structure for any RPC Update call.
 #[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
 pub struct ParamsIded {
 	pub id: i64,
 }
@@ -41,6 +52,40 @@ where
 	pub list_options: Option<ListOptions>,
 }
 
+impl<F> ParamsList<F>
+where
+	F: DeserializeOwned,
+{
+	/// The filters, and the list options with the `MC` defaults (when absent),
+	/// where a limit over `MC::MAX_LIMIT` is clamped to it, and reported as the
+	/// response `meta.limit_clamped` (see `RpcMeta`).
+	pub fn into_list_parts<MC: DbBmc>(
+		self,
+		ctx: &Ctx,
+	) -> (Option<Vec<F>>, ListOptions) {
+		let (list_options, clamp) = clamp_list_options::<MC>(self.list_options);
+		if let Some(clamp) = clamp {
+			RpcMeta::insert(ctx, "limit_clamped", clamp);
+		}
+
+		(self.filters, list_options)
+	}
+}
+
+/// The TypeScript type of `ParamsList` (its `F: DeserializeOwned` bound does not
+/// fit the ts-rs derive).
+#[cfg(feature = "ts")]
+#[allow(dead_code)] // Only exported.
+#[derive(TS)]
+#[ts(export, rename = "ParamsList")]
+pub struct ParamsListTs<F: TS> {
+	/// One filter, or many (OR-ed).
+	#[ts(type = "F | Array<F>", optional)]
+	pub filters: Option<Vec<F>>,
+	#[ts(as = "Option<ts::ListOptions>", optional)]
+	pub list_options: Option<ListOptions>,
+}
+
 impl<D> IntoDefaultParams for ParamsList<D> where D: DeserializeOwned + Send + Default
 {}
 
@@ -56,8 +101,11 @@ where
 	D: DeserializeOwned + Send,
 	D: IntoParams,
 {
-	fn into_params(value: Option<Value>) -> crate::web::Result<Self> {
-		let value = value.map(|v| serde_json::from_value(v)).transpose()?;
+	fn into_params(value: Option<Box<RawValue>>) -> crate::web::Result<Self> {
+		let value = value
+			.map(|v| serde_json::from_str(v.get()))
+			.transpose()
+			.map_err(crate::web::Error::RpcIntoParamsInvalid)?;
 		Ok(value)
 	}
 }
@@ -68,4 +116,4 @@ where
 ///       allow for rpc_handlers, prefering to have everything strongly type.
 ///       In this case, just remove this impelementation
 impl IntoParams for Value {}
-// endregion: --- General Implementations
\ No newline at end of file
+// endregion: --- General Implementations
@@ -1,25 +1,47 @@
 use crate::rpc_router;
 use crate::web::Result;
 use lib_core::ctx::Ctx;
+use lib_core::model::presence::{PresenceBmc, ProjectPresence};
 use lib_core::model::project::{
 	Project, ProjectBmc, ProjectFilter, ProjectForCreate, ProjectForUpdate,
 };
-use lib_core::model::ModelManager;
+use lib_core::model::{ListPage, ModelManager};
+use serde::Deserialize;
 
 use crate::web::rpc::params::{
 	ParamsForCreate, ParamsForUpdate, ParamsIded, ParamsList,
 };
-use crate::web::rpc::router::{RpcHandler, RpcRouter};
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
 
 pub fn rpc_router() -> RpcRouter {
 	rpc_router!(
-		create_project,
-		list_projects,
-		update_project,
-		delete_project
+		queries: [list_projects, list_projects_paged, list_project_presence],
+		mutations: [
+			create_project,
+			update_project,
+			delete_project,
+			duplicate_project
+		],
 	)
 }
 
+/// Params of `duplicate_project` (the copy is named `"{name} (copy)"` by
+/// default).
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsDuplicateProject {
+	pub id: i64,
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub name: Option<String>,
+	#[serde(default)]
+	#[cfg_attr(feature = "ts", ts(as = "Option<bool>", optional))]
+	pub reset_done: bool,
+}
+
+impl IntoParams for ParamsDuplicateProject {}
+
 pub async fn create_project(
 	ctx: Ctx,
 	mm: ModelManager,
@@ -38,12 +60,37 @@ pub async fn list_projects(
 	mm: ModelManager,
 	params: ParamsList<ProjectFilter>,
 ) -> Result<Vec<Project>> {
-	let projects =
-		ProjectBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+	let (filters, list_options) = params.into_list_parts::<ProjectBmc>(&ctx);
+	let projects = ProjectBmc::list(&ctx, &mm, filters, Some(list_options)).await?;
 
 	Ok(projects)
 }
 
+pub async fn list_projects_paged(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<ProjectFilter>,
+) -> Result<ListPage<Project>> {
+	let (filters, list_options) = params.into_list_parts::<ProjectBmc>(&ctx);
+	let page =
+		ProjectBmc::list_paged(&ctx, &mm, filters, Some(list_options)).await?;
+
+	Ok(page)
+}
+
+/// The users currently viewing the project (see `model::presence`).
+pub async fn list_project_presence(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<Vec<ProjectPresence>> {
+	let ParamsIded { id } = params;
+
+	let presences = PresenceBmc::list_for_project(&ctx, &mm, id).await?;
+
+	Ok(presences)
+}
+
 pub async fn update_project(
 	ctx: Ctx,
 	mm: ModelManager,
@@ -70,3 +117,20 @@ pub async fn delete_project(
 
 	Ok(project)
 }
+
+pub async fn duplicate_project(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsDuplicateProject,
+) -> Result<Project> {
+	let ParamsDuplicateProject {
+		id,
+		name,
+		reset_done,
+	} = params;
+
+	let copy_id = ProjectBmc::duplicate(&ctx, &mm, id, name, reset_done).await?;
+	let project = ProjectBmc::get(&ctx, &mm, copy_id).await?;
+
+	Ok(project)
+}
@@ -0,0 +1,81 @@
+use crate::rpc_router;
+use crate::web::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::project_share::{
+	ProjectShare, ProjectShareBmc, ProjectShareForCreate,
+};
+use lib_core::model::ModelManager;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::web::rpc::params::{ParamsForCreate, ParamsIded};
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(
+		queries: [list_project_shares],
+		mutations: [create_project_share, revoke_project_share],
+	)
+}
+
+/// The created share, with its link token (served at `path`).
+#[derive(Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ProjectShareLink {
+	#[serde(flatten)]
+	pub share: ProjectShare,
+	pub token: String,
+	pub path: String,
+}
+
+/// Params of `list_project_shares`.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsProjectShares {
+	pub project_id: i64,
+}
+
+impl IntoParams for ParamsProjectShares {}
+
+pub async fn create_project_share(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<ProjectShareForCreate>,
+) -> Result<ProjectShareLink> {
+	let ParamsForCreate { data } = params;
+
+	let id = ProjectShareBmc::create(&ctx, &mm, data).await?;
+	let share = ProjectShareBmc::get(&ctx, &mm, id).await?;
+	let token = ProjectShareBmc::token(&ctx, &mm, id).await?;
+
+	Ok(ProjectShareLink {
+		share,
+		path: format!("/api/shared/{token}"),
+		token,
+	})
+}
+
+pub async fn list_project_shares(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsProjectShares,
+) -> Result<Vec<ProjectShare>> {
+	let shares = ProjectShareBmc::list(&ctx, &mm, params.project_id, None).await?;
+
+	Ok(shares)
+}
+
+/// Revokes the share link (its token is then invalid).
+pub async fn revoke_project_share(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<Value> {
+	let ParamsIded { id } = params;
+
+	ProjectShareBmc::delete(&ctx, &mm, id).await?;
+
+	Ok(json!({ "success": true }))
+}
@@ -0,0 +1,29 @@
+//! The request logs audit, only for the `admin` users
+//! (see `RpcRouter::require_role`).
+
+use crate::rpc_router;
+use crate::web::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::request_log::{RequestLog, RequestLogBmc, RequestLogFilter};
+use lib_core::model::user::UserRole;
+use lib_core::model::ModelManager;
+
+use crate::web::rpc::params::ParamsList;
+use crate::web::rpc::router::{RpcHandler, RpcRouter};
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(queries: [list_request_logs], mutations: [])
+		.require_role(UserRole::Admin)
+}
+
+/// The request logs, newest first (100 by default).
+pub async fn list_request_logs(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<RequestLogFilter>,
+) -> Result<Vec<RequestLog>> {
+	let (filters, list_options) = params.into_list_parts::<RequestLogBmc>(&ctx);
+	let logs = RequestLogBmc::list(&ctx, &mm, filters, Some(list_options)).await?;
+
+	Ok(logs)
+}
@@ -1,14 +1,19 @@
+use crate::web::field_policy;
+use crate::web::mw_read_only;
 use crate::web::rpc::RpcState;
 use crate::web::{Error, Result};
 use futures::Future;
 use lib_core::ctx::Ctx;
+use lib_core::model::user::UserRole;
 
+use ahash::AHashMap;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::value::RawValue;
 use serde_json::Value;
-use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::Arc;
 
 // region:    --- RpcRouter
 
@@ -16,70 +21,294 @@ use std::pin::Pin;
 /// method, which calls the appropriate handler matching the method_name.
 ///
 /// RpcRouter can be extended with other RpcRouters for composability.
+///
+/// A method can have aliases (e.g., its previous name after a rename), and can be
+/// deprecated, which still serves it, but with a warning (see `RpcDeprecation`).
+///
+/// A method is a query or a mutation (see `RpcMethodKind`).
 pub struct RpcRouter {
-	route_by_name: HashMap<&'static str, Box<dyn RpcHandlerWrapperTrait>>,
+	route_by_name: AHashMap<&'static str, RpcRoute>,
+}
+
+#[derive(Clone)]
+struct RpcRoute {
+	handler: Arc<dyn RpcHandlerWrapperTrait>,
+	deprecation: Option<RpcDeprecation>,
+	/// The role the ctx user must have (see `require_role`).
+	required_role: Option<UserRole>,
+	kind: RpcMethodKind,
+}
+
+/// The kind of a method, e.g., only the queries are served in the read-only mode
+/// (see `mw_read_only`).
+///
+/// NOTE: The methods added without kind are mutations (see `add`), so a query
+///       must be registered as such (see `rpc_router!`).
+///
+/// NOTE: Not used to route the queries to a db replica, nor to exempt them
+///       from a CSRF check, as there is no replica pool (one `ModelManager`
+///       db), and no CSRF layer (nothing to exempt). Those can match on the
+///       kind when added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcMethodKind {
+	/// Reads only (no db write, nor side effect).
+	Query,
+	Mutation,
+}
+
+/// The deprecation of a method name.
+#[derive(Debug, Clone)]
+pub struct RpcDeprecation {
+	/// The method to call instead, if any.
+	pub replacement: Option<&'static str>,
+}
+
+/// A method of the router, as listed by the `RPC_DISCOVER` method.
+#[derive(Debug, Serialize)]
+pub struct RpcMethodInfo {
+	pub name: &'static str,
+	pub kind: RpcMethodKind,
+	pub deprecated: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub replacement: Option<&'static str>,
+}
+
+impl RpcDeprecation {
+	/// The warning added to the responses of the deprecated method.
+	pub fn warning(&self, method: &str) -> String {
+		match self.replacement {
+			Some(replacement) => {
+				format!("'{method}' is deprecated, use '{replacement}' instead")
+			}
+			None => format!("'{method}' is deprecated"),
+		}
+	}
 }
 
 impl RpcRouter {
 	pub fn new() -> Self {
 		Self {
-			route_by_name: HashMap::new(),
+			route_by_name: AHashMap::new(),
 		}
 	}
 
+	/// Adds a mutation method (see `add_with_kind`).
 	pub fn add(
+		self,
+		name: &'static str,
+		erased_route: Box<dyn RpcHandlerWrapperTrait>,
+	) -> Self {
+		self.add_with_kind(name, RpcMethodKind::Mutation, erased_route)
+	}
+
+	pub fn add_with_kind(
 		mut self,
 		name: &'static str,
+		kind: RpcMethodKind,
 		erased_route: Box<dyn RpcHandlerWrapperTrait>,
 	) -> Self {
-		self.route_by_name.insert(name, erased_route);
+		let route = RpcRoute {
+			handler: Arc::from(erased_route),
+			deprecation: None,
+			required_role: None,
+			kind,
+		};
+		self.route_by_name.insert(name, route);
 		self
 	}
-	
+
+	/// Serves the `name` method as `alias` too.
+	///
+	/// Panics if `name` is not added yet (a router definition bug).
+	#[cfg(test)]
+	pub fn alias(mut self, alias: &'static str, name: &'static str) -> Self {
+		let route = RpcRoute {
+			deprecation: None,
+			..self.route_of(name)
+		};
+		self.route_by_name.insert(alias, route);
+		self
+	}
+
+	/// Serves the `name` method as the deprecated `alias`, typically its
+	/// previous name, e.g., `.deprecated_alias("list_tasks", "search_tasks")`.
+	///
+	/// Panics if `name` is not added yet (a router definition bug).
+	#[cfg(test)]
+	pub fn deprecated_alias(
+		mut self,
+		alias: &'static str,
+		name: &'static str,
+	) -> Self {
+		let route = RpcRoute {
+			deprecation: Some(RpcDeprecation {
+				replacement: Some(name),
+			}),
+			..self.route_of(name)
+		};
+		self.route_by_name.insert(alias, route);
+		self
+	}
+
+	/// Flags the `name` method as deprecated (a no-op if unknown).
+	#[cfg(test)]
+	pub fn deprecate(
+		mut self,
+		name: &'static str,
+		replacement: Option<&'static str>,
+	) -> Self {
+		if let Some(route) = self.route_by_name.get_mut(name) {
+			route.deprecation = Some(RpcDeprecation { replacement });
+		}
+		self
+	}
+
+	/// Restricts the methods added so far to the ctx users with the `role`
+	/// (the others get a `RoleRequired`), e.g., for the `admin_rpc` router.
+	pub fn require_role(mut self, role: UserRole) -> Self {
+		for route in self.route_by_name.values_mut() {
+			route.required_role = Some(role);
+		}
+		self
+	}
+
 	pub fn extend(mut self, other_router: RpcRouter) -> Self {
 		self.route_by_name.extend(other_router.route_by_name);
 		self
 	}
 
+	/// The kind of the `method`, none when unknown.
+	pub fn kind(&self, method: &str) -> Option<RpcMethodKind> {
+		self.route_by_name.get(method).map(|route| route.kind)
+	}
+
+	/// The deprecation of the `method`, if deprecated.
+	pub fn deprecation(&self, method: &str) -> Option<&RpcDeprecation> {
+		self.route_by_name
+			.get(method)
+			.and_then(|route| route.deprecation.as_ref())
+	}
+
+	/// The method names (with their kind and deprecation), sorted
+	/// (see `RPC_DISCOVER`).
+	pub fn methods(&self) -> Vec<RpcMethodInfo> {
+		let mut methods: Vec<RpcMethodInfo> = self
+			.route_by_name
+			.iter()
+			.map(|(name, route)| RpcMethodInfo {
+				name,
+				kind: route.kind,
+				deprecated: route.deprecation.is_some(),
+				replacement: route.deprecation.as_ref().and_then(|d| d.replacement),
+			})
+			.collect();
+		methods.sort_by_key(|method| method.name);
+
+		methods
+	}
+
 	pub async fn call(
 		&self,
 		method: &str,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		params: Option<Value>,
+		params: Option<Box<RawValue>>,
 	) -> Result<Value> {
-		if let Some(route) = self.route_by_name.get(method) {
-			route.call(ctx, rpc_state, params).await
-		} else {
-			Err(Error::RpcMethodUnknown(method.to_string()))
+		let Some(route) = self.route_by_name.get(method) else {
+			return Err(Error::RpcMethodUnknown(method.to_string()));
+		};
+		self.check_role(method, &ctx)?;
+		self.check_read_only(method, mw_read_only::is_read_only())?;
+
+		let role = field_policy::ctx_role(&ctx);
+		let mut result = route.handler.call(ctx, rpc_state, params).await?;
+		field_policy::redact_fields(role, &mut result);
+
+		Ok(result)
+	}
+
+	/// Fails with `RoleRequired` when the ctx user has not the method role
+	/// (see `require_role`).
+	pub fn check_role(&self, method: &str, ctx: &Ctx) -> Result<()> {
+		let required_role = self
+			.route_by_name
+			.get(method)
+			.and_then(|route| route.required_role);
+		match required_role {
+			Some(role) if ctx.extensions().get::<UserRole>() != Some(&role) => {
+				Err(Error::RoleRequired {
+					method: method.to_string(),
+					role: role.as_str(),
+				})
+			}
+			_ => Ok(()),
+		}
+	}
+
+	/// Fails with `ReadOnlyMode` when `read_only` and the method is a mutation.
+	pub fn check_read_only(&self, method: &str, read_only: bool) -> Result<()> {
+		if read_only && self.kind(method) == Some(RpcMethodKind::Mutation) {
+			return Err(Error::ReadOnlyMode {
+				mutation: method.to_string(),
+			});
+		}
+
+		Ok(())
+	}
+
+	#[cfg(test)]
+	fn route_of(&self, name: &str) -> RpcRoute {
+		match self.route_by_name.get(name) {
+			Some(route) => route.clone(),
+			None => panic!("rpc router - cannot alias the unknown method '{name}'"),
 		}
 	}
 }
 
 /// A simple macro to create a new RpcRouter
-/// and add each rpc handler-compatible function along with their corresponding names.
+/// and add each rpc handler-compatible function along with their corresponding names,
+/// as queries or mutations (see `RpcMethodKind`).
 ///
 /// e.g.,
 ///
 /// ```
 /// rpc_router!(
-///   create_project,
-///   list_projects,
-///   update_project,
-///   delete_project
+///   queries: [list_projects],
+///   mutations: [create_project, update_project, delete_project],
 /// );
 /// ```
 /// Is equivalent to:
 /// ```
 /// RpcRouter::new()
+///     .add_with_kind("list_projects", RpcMethodKind::Query, list_projects.into_box())
 ///     .add("create_project", create_project.into_box())
-///     .add("list_projects", list_projects.into_box())
 ///     .add("update_project", update_project.into_box())
 ///     .add("delete_project", delete_project.into_box())
 /// ```
 ///
+/// Without the `queries:` and `mutations:`, all the methods are mutations.
 #[macro_export]
 macro_rules! rpc_router {
+    (
+        queries: [$($query_name:ident),* $(,)?],
+        mutations: [$($mutation_name:ident),* $(,)?] $(,)?
+    ) => {
+        {
+            let mut router = RpcRouter::new();
+            $(
+                router = router.add_with_kind(
+                    stringify!($query_name),
+                    $crate::web::rpc::RpcMethodKind::Query,
+                    $query_name.into_box(),
+                );
+            )*
+            $(
+                router = router.add(stringify!($mutation_name), $mutation_name.into_box());
+            )*
+            router
+        }
+    };
     ($($fn_name:ident),+ $(,)?) => {
         {
             let mut router = RpcRouter::new();
@@ -114,7 +343,7 @@ pub trait RpcHandler<S, P, R>: Clone {
 		self,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		params: Option<Value>,
+		params: Option<Box<RawValue>>,
 	) -> Self::Future;
 
 	/// Convenient method that turns this handler into a Boxed RpcHandlerWrapper
@@ -125,31 +354,33 @@ pub trait RpcHandler<S, P, R>: Clone {
 	}
 }
 
-/// `IntoHandlerParams` allows for converting an `Option<Value>` into
-/// the necessary type for RPC handler parameters.
+/// `IntoHandlerParams` allows for converting the raw json `params` (not parsed
+/// into a `Value` first) into the necessary type for RPC handler parameters.
 /// The default implementation below will result in failure if the value is `None`.
 /// For customized behavior, users can implement their own `into_handler_params`
 /// method.
 pub trait IntoParams: DeserializeOwned + Send {
-	fn into_params(value: Option<Value>) -> Result<Self> {
+	fn into_params(value: Option<Box<RawValue>>) -> Result<Self> {
 		match value {
-			Some(value) => Ok(serde_json::from_value(value)?),
+			Some(value) => serde_json::from_str(value.get())
+				.map_err(Error::RpcIntoParamsInvalid),
 			None => Err(Error::RpcIntoParamsMissing),
 		}
 	}
 }
 
 /// Marker trait with a blanket implementation that return T::default
-/// if the `params: Option<Value>` is none.
+/// if the `params: Option<Box<RawValue>>` is none.
 pub trait IntoDefaultParams: DeserializeOwned + Send + Default {}
 
 impl<P> IntoParams for P
 where
 	P: IntoDefaultParams,
 {
-	fn into_params(value: Option<Value>) -> Result<Self> {
+	fn into_params(value: Option<Box<RawValue>>) -> Result<Self> {
 		match value {
-			Some(value) => Ok(serde_json::from_value(value)?),
+			Some(value) => serde_json::from_str(value.get())
+				.map_err(Error::RpcIntoParamsInvalid),
 			None => Ok(Self::default()),
 		}
 	}
@@ -171,7 +402,7 @@ where
 		self,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		_params: Option<Value>,
+		_params: Option<Box<RawValue>>,
 	) -> Self::Future {
 		Box::pin(async move {
 			let result = self(ctx, rpc_state.into()).await?;
@@ -197,7 +428,7 @@ where
 		self,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		params_value: Option<Value>,
+		params_value: Option<Box<RawValue>>,
 	) -> Self::Future {
 		Box::pin(async move {
 			let param = P::into_params(params_value)?;
@@ -239,7 +470,7 @@ where
 		&self,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		params: Option<Value>,
+		params: Option<Box<RawValue>>,
 	) -> H::Future {
 		// Note: Since handler is a FnOnce, we can use it only once, so we clone it.
 		//       This is likely optimized by the compiler.
@@ -255,13 +486,15 @@ pub trait RpcHandlerWrapperTrait: Send + Sync {
 		&self,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		params: Option<Value>,
+		params: Option<Box<RawValue>>,
 	) -> PinFutureValue;
 }
 
+/// Note: For the handlers with a boxed future (i.e., the `RpcHandler` impls above),
+///       so the future is not boxed again.
 impl<H, S, P, R> RpcHandlerWrapperTrait for RpcHandlerWrapper<H, S, P, R>
 where
-	H: RpcHandler<S, P, R> + Clone + Send + Sync + 'static,
+	H: RpcHandler<S, P, R, Future = PinFutureValue> + Clone + Send + Sync + 'static,
 	S: Send + Sync,
 	P: Send + Sync,
 	R: Send + Sync,
@@ -270,9 +503,9 @@ where
 		&self,
 		ctx: Ctx,
 		rpc_state: RpcState,
-		params: Option<Value>,
-	) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>> {
-		Box::pin(self.call(ctx, rpc_state, params))
+		params: Option<Box<RawValue>>,
+	) -> PinFutureValue {
+		self.call(ctx, rpc_state, params)
 	}
 }
 
@@ -0,0 +1,54 @@
+use crate::rpc_router;
+use crate::web::rpc::RpcMeta;
+use crate::web::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::search::{SearchBmc, SearchHit, SEARCH_MAX_LIMIT};
+use lib_core::model::{LimitClamp, ListPage, ModelManager};
+use serde::Deserialize;
+
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(queries: [search], mutations: [])
+}
+
+/// Params of `search` (see `lib_core::model::search`).
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsSearch {
+	pub query: String,
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub offset: Option<i64>,
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub limit: Option<i64>,
+}
+
+impl IntoParams for ParamsSearch {}
+
+/// The tasks and projects matching the query, by relevance.
+pub async fn search(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsSearch,
+) -> Result<ListPage<SearchHit>> {
+	let ParamsSearch {
+		query,
+		offset,
+		limit,
+	} = params;
+
+	// Note: Lowered by the SearchBmc, reported as for the list methods.
+	if let Some(requested) = limit.filter(|limit| *limit > SEARCH_MAX_LIMIT) {
+		let clamp = LimitClamp {
+			requested,
+			max: SEARCH_MAX_LIMIT,
+		};
+		RpcMeta::insert(&ctx, "limit_clamped", clamp);
+	}
+
+	let page = SearchBmc::search(&ctx, &mm, &query, offset, limit).await?;
+
+	Ok(page)
+}
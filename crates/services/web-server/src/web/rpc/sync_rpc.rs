@@ -0,0 +1,43 @@
+use crate::rpc_router;
+use crate::web::Result;
+use lib_base::time::Rfc3339;
+use lib_core::ctx::Ctx;
+use lib_core::model::sync::{SyncBmc, SyncChanges};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+use serde_with::serde_as;
+use time::OffsetDateTime;
+
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(queries: [sync_changes], mutations: [])
+}
+
+/// Params of `sync_changes`, where `since` is the `cursor` of the previous
+/// sync (none for the first one).
+#[serde_as]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsSyncChanges {
+	#[serde_as(as = "Option<Rfc3339>")]
+	#[serde(default)]
+	#[cfg_attr(feature = "ts", ts(type = "string", optional))]
+	pub since: Option<OffsetDateTime>,
+}
+
+impl IntoParams for ParamsSyncChanges {}
+
+pub async fn sync_changes(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsSyncChanges,
+) -> Result<SyncChanges> {
+	let ParamsSyncChanges { since } = params;
+
+	let changes = SyncBmc::changes(&ctx, &mm, since).await?;
+
+	Ok(changes)
+}
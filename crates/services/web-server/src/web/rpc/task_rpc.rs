@@ -1,19 +1,58 @@
 use lib_core::{
 	ctx::Ctx,
 	model::{
-		task::{Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate},
-		ModelManager,
+		task::{
+			Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate, TrashedTask,
+		},
+		ListPage, ModelManager,
 	},
 };
 
 use crate::web::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
 
 use super::{ParamsForCreate, ParamsForUpdate, ParamsIded, ParamsList};
 use crate::rpc_router;
-use crate::web::rpc::router::{RpcHandler, RpcRouter};
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+/// Params of `complete_tasks`.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsCompleteTasks {
+	pub ids: Vec<i64>,
+}
+
+impl IntoParams for ParamsCompleteTasks {}
+
+/// The `complete_tasks` result of one id (in the params order).
+#[derive(Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct TaskCompletion {
+	pub id: i64,
+	pub success: bool,
+	/// The client error code, when not a success (e.g., `entity.not_found`
+	/// for an unknown task, or one of a project not owned by the user).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub error: Option<&'static str>,
+}
 
 pub fn rpc_router() -> RpcRouter {
-	rpc_router!(create_task, update_task, list_tasks, delete_task)
+	rpc_router!(
+		queries: [list_tasks, list_tasks_paged, list_trashed_tasks],
+		mutations: [
+			create_task,
+			update_task,
+			complete_tasks,
+			delete_task,
+			restore_task,
+			purge_task
+		],
+	)
 }
 
 pub async fn create_task(
@@ -33,12 +72,23 @@ pub async fn list_tasks(
 	mm: ModelManager,
 	params: ParamsList<TaskFilter>,
 ) -> Result<Vec<Task>> {
-	let tasks =
-		TaskBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+	let (filters, list_options) = params.into_list_parts::<TaskBmc>(&ctx);
+	let tasks = TaskBmc::list(&ctx, &mm, filters, Some(list_options)).await?;
 
 	Ok(tasks)
 }
 
+pub async fn list_tasks_paged(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<TaskFilter>,
+) -> Result<ListPage<Task>> {
+	let (filters, list_options) = params.into_list_parts::<TaskBmc>(&ctx);
+	let page = TaskBmc::list_paged(&ctx, &mm, filters, Some(list_options)).await?;
+
+	Ok(page)
+}
+
 pub async fn update_task(
 	ctx: Ctx,
 	mm: ModelManager,
@@ -50,6 +100,35 @@ pub async fn update_task(
 	Ok(task)
 }
 
+/// Sets `done` on all the tasks in one statement (see `TaskBmc::complete_many`),
+/// with the result of each id.
+pub async fn complete_tasks(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsCompleteTasks,
+) -> Result<Vec<TaskCompletion>> {
+	let ParamsCompleteTasks { ids } = params;
+
+	let done_ids: HashSet<i64> = TaskBmc::complete_many(&ctx, &mm, &ids)
+		.await?
+		.into_iter()
+		.collect();
+
+	let completions = ids
+		.into_iter()
+		.map(|id| {
+			let success = done_ids.contains(&id);
+			TaskCompletion {
+				id,
+				success,
+				error: (!success).then_some("entity.not_found"),
+			}
+		})
+		.collect();
+
+	Ok(completions)
+}
+
 pub async fn delete_task(
 	ctx: Ctx,
 	mm: ModelManager,
@@ -61,3 +140,41 @@ pub async fn delete_task(
 	TaskBmc::delete(&ctx, &mm, id).await?;
 	Ok(task)
 }
+
+pub async fn list_trashed_tasks(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<TaskFilter>,
+) -> Result<Vec<TrashedTask>> {
+	let (filters, list_options) = params.into_list_parts::<TaskBmc>(&ctx);
+	let tasks =
+		TaskBmc::list_trashed(&ctx, &mm, filters, Some(list_options)).await?;
+
+	Ok(tasks)
+}
+
+pub async fn restore_task(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<Task> {
+	let ParamsIded { id } = params;
+
+	TaskBmc::restore(&ctx, &mm, id).await?;
+	let task = TaskBmc::get(&ctx, &mm, id).await?;
+
+	Ok(task)
+}
+
+/// Deletes a trashed task for good (it cannot be restored anymore).
+pub async fn purge_task(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<Value> {
+	let ParamsIded { id } = params;
+
+	TaskBmc::purge(&ctx, &mm, id).await?;
+
+	Ok(json!({ "success": true }))
+}
@@ -0,0 +1,134 @@
+use crate::rpc_router;
+use crate::web::{set_token_cookie, Error, Result};
+use lib_core::ctx::Ctx;
+use lib_core::model::quota::{QuotaBmc, Usage};
+use lib_core::model::user::{UserBmc, UserForAuth, UserForLogin};
+use lib_core::model::ModelManager;
+use lib_core::pwd::{self, ContentToHash};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+/// The only method of the sessions which must change the pwd
+/// (see `mw_auth::PwdChangeRequired`).
+pub const CHANGE_PWD: &str = "change_pwd";
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(
+		queries: [get_usage],
+		mutations: [change_pwd, rotate_token_salt, set_timezone, set_locale],
+	)
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsChangePwd {
+	pub pwd_old: String,
+	pub pwd_new: String,
+}
+
+impl IntoParams for ParamsChangePwd {}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsSetTimezone {
+	/// An IANA timezone, e.g., `Europe/Paris`.
+	pub timezone: String,
+}
+
+impl IntoParams for ParamsSetTimezone {}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsSetLocale {
+	/// A supported locale, e.g., `fr`, or none to follow the browser one.
+	pub locale: Option<String>,
+}
+
+impl IntoParams for ParamsSetLocale {}
+
+/// Changes the pwd of the ctx user, which clears its `must_change_pwd`.
+pub async fn change_pwd(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsChangePwd,
+) -> Result<Value> {
+	let ParamsChangePwd { pwd_old, pwd_new } = params;
+	if pwd_new.is_empty() {
+		return Err(Error::ChangePwdInvalid { reason: "empty" });
+	}
+	if pwd_new == pwd_old {
+		return Err(Error::ChangePwdInvalid {
+			reason: "same as the current pwd",
+		});
+	}
+
+	// -- Validate the current pwd (as for the login).
+	let user_id = ctx.user_id();
+	let user: UserForLogin = UserBmc::get(&ctx, &mm, user_id).await?;
+	let Some(pwd) = user.pwd else {
+		return Err(Error::ChangePwdUserHasNoPwd { user_id });
+	};
+	pwd::validate_pwd(
+		&ContentToHash {
+			salt: *user.pwd_salt.expose(),
+			content: pwd_old,
+		},
+		pwd.expose(),
+	)
+	.map_err(|cause| Error::ChangePwdFail { user_id, cause })?;
+
+	UserBmc::change_pwd(&ctx, &mm, user_id, &pwd_new).await?;
+
+	Ok(json!({ "success": true }))
+}
+
+/// The current usage of the user, with its quotas (see `model::quota`).
+pub async fn get_usage(ctx: Ctx, mm: ModelManager) -> Result<Usage> {
+	let usage = QuotaBmc::usage(&ctx, &mm).await?;
+
+	Ok(usage)
+}
+
+/// Signs out all the sessions of the ctx user (its issued tokens are
+/// invalidated), but this one, whose auth cookie is re-issued.
+pub async fn rotate_token_salt(ctx: Ctx, mm: ModelManager) -> Result<Value> {
+	let user_id = ctx.user_id();
+	UserBmc::rotate_token_salt(&ctx, &mm, user_id).await?;
+
+	// -- Re-issue the token of this session, with the new salt.
+	if let Some(cookies) = ctx.extensions().get::<Cookies>() {
+		let user: UserForAuth = UserBmc::get(&ctx, &mm, user_id).await?;
+		set_token_cookie(cookies, &user.username, *user.token_salt.expose())?;
+	}
+
+	Ok(json!({ "success": true }))
+}
+
+/// Sets the timezone the ctx user times are displayed in (e.g., the exports).
+pub async fn set_timezone(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsSetTimezone,
+) -> Result<Value> {
+	UserBmc::update_timezone(&ctx, &mm, ctx.user_id(), &params.timezone).await?;
+
+	Ok(json!({ "success": true }))
+}
+
+/// Sets the locale of the ctx user (the error messages, mails, and dates),
+/// rather than the `Accept-Language` one.
+pub async fn set_locale(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsSetLocale,
+) -> Result<Value> {
+	UserBmc::update_locale(&ctx, &mm, ctx.user_id(), params.locale.as_deref())
+		.await?;
+
+	Ok(json!({ "success": true }))
+}
@@ -0,0 +1,83 @@
+use crate::rpc_router;
+use crate::web::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::webhook::{
+	Webhook, WebhookBmc, WebhookDelivery, WebhookDeliveryBmc, WebhookForCreate,
+};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+
+use crate::web::rpc::params::{ParamsForCreate, ParamsIded};
+use crate::web::rpc::router::{IntoParams, RpcHandler, RpcRouter};
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+const DELIVERY_LIST_LIMIT_DEFAULT: i64 = 50;
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(
+		queries: [list_webhooks, list_webhook_deliveries],
+		mutations: [create_webhook, delete_webhook],
+	)
+}
+
+/// Params of `list_webhook_deliveries` (newest first, 50 by default).
+#[derive(Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export))]
+pub struct ParamsWebhookDeliveries {
+	pub webhook_id: i64,
+	#[cfg_attr(feature = "ts", ts(optional))]
+	pub limit: Option<i64>,
+}
+
+impl IntoParams for ParamsWebhookDeliveries {}
+
+pub async fn create_webhook(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<WebhookForCreate>,
+) -> Result<Webhook> {
+	let ParamsForCreate { data } = params;
+
+	let id = WebhookBmc::create(&ctx, &mm, data).await?;
+	let webhook = WebhookBmc::get(&ctx, &mm, id).await?;
+
+	Ok(webhook)
+}
+
+pub async fn list_webhooks(ctx: Ctx, mm: ModelManager) -> Result<Vec<Webhook>> {
+	let webhooks = WebhookBmc::list(&ctx, &mm, None).await?;
+
+	Ok(webhooks)
+}
+
+pub async fn delete_webhook(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<Webhook> {
+	let ParamsIded { id } = params;
+
+	let webhook = WebhookBmc::get(&ctx, &mm, id).await?;
+	WebhookBmc::delete(&ctx, &mm, id).await?;
+
+	Ok(webhook)
+}
+
+pub async fn list_webhook_deliveries(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsWebhookDeliveries,
+) -> Result<Vec<WebhookDelivery>> {
+	let ParamsWebhookDeliveries { webhook_id, limit } = params;
+
+	let deliveries = WebhookDeliveryBmc::list_for_webhook(
+		&ctx,
+		&mm,
+		webhook_id,
+		limit.unwrap_or(DELIVERY_LIST_LIMIT_DEFAULT),
+	)
+	.await?;
+
+	Ok(deliveries)
+}
@@ -0,0 +1,113 @@
+//! Server-side rendered html pages (minijinja), as an alternative to the
+//! static/SPA `WEB_FOLDER` for simple pages (see `routes_pages`).
+//!
+//! NOTE: The templates (`web-server/templates/`) are embedded at compile time,
+//!       and html auto-escaped (by the `.html` extension).
+
+use crate::web::{Error, Result};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use minijinja::{context, Environment};
+use serde::Serialize;
+use std::sync::OnceLock;
+
+const TEMPLATES: &[(&str, &str)] = &[
+	("base.html", include_str!("../../templates/base.html")),
+	("error.html", include_str!("../../templates/error.html")),
+	("login.html", include_str!("../../templates/login.html")),
+	(
+		"rpc_playground.html",
+		include_str!("../../templates/rpc_playground.html"),
+	),
+	("swagger.html", include_str!("../../templates/swagger.html")),
+	("tasks.html", include_str!("../../templates/tasks.html")),
+];
+
+fn env() -> &'static Environment<'static> {
+	static INSTANCE: OnceLock<Environment<'static>> = OnceLock::new();
+
+	INSTANCE.get_or_init(|| {
+		let mut env = Environment::new();
+		for (name, source) in TEMPLATES {
+			env.add_template(name, source).unwrap_or_else(|ex| {
+				panic!("FATAL - WHILE LOADING TEMPLATE {name} - Cause: {ex:?}")
+			});
+		}
+		env
+	})
+}
+
+pub fn render<S: Serialize>(name: &str, data: S) -> Result<Html<String>> {
+	env()
+		.get_template(name)
+		.and_then(|template| template.render(data))
+		.map(Html)
+		.map_err(|ex| Error::TemplateRender(ex.to_string()))
+}
+
+/// True when the request `Accept` header prefers an html page
+/// (i.e., a browser navigation, rather than an api call).
+pub fn accepts_html(headers: &HeaderMap) -> bool {
+	headers
+		.get(header::ACCEPT)
+		.and_then(|val| val.to_str().ok())
+		.is_some_and(|accept| accept.contains("text/html"))
+}
+
+/// Renders the error page, falling back to a plain text body if it fails.
+pub fn render_error_page(
+	status: StatusCode,
+	message: &str,
+	req_id: &str,
+) -> Response {
+	let data = context! {
+		status => status.as_u16(),
+		message => message,
+		req_id => req_id,
+	};
+
+	match render("error.html", data) {
+		Ok(html) => (status, html).into_response(),
+		Err(_) => (status, format!("{status} - {message}")).into_response(),
+	}
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn test_render_error_page_escaped_ok() -> Result<()> {
+		// -- Exec
+		let html = render(
+			"error.html",
+			context! { status => 403, message => "<NO_AUTH>", req_id => "r1" },
+		)?;
+
+		// -- Check
+		assert!(html.0.contains("403 - &lt;NO_AUTH&gt;"), "{}", html.0);
+		assert!(html.0.contains("Request id: r1"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_render_rpc_playground_ok() -> Result<()> {
+		// -- Exec
+		let html =
+			render("rpc_playground.html", context! { rpc_url => "/api/v1/rpc" })?;
+
+		// -- Check
+		// Note: The url is an attribute, html escaped (decoded by the browser).
+		assert!(
+			html.0.contains(r#"data-rpc-url="&#x2f;api&#x2f;v1&#x2f;rpc""#),
+			"{}",
+			html.0
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,248 @@
+//! The outgoing webhooks (see lib-core `model::webhook`).
+//!
+//! - The `WebhookEnqueuer` event subscriber queues a delivery per matching webhook.
+//! - The sender claims the due deliveries, POSTs their json payload, and records
+//!   the outcome, retried with an exponential backoff up to `MAX_ATTEMPTS`.
+//!
+//! The delivery requests have the headers:
+//! - `X-Webhook-Id` - the delivery id (the same across retries, for idempotency).
+//! - `X-Webhook-Event` - e.g., `task.created`
+//! - `X-Webhook-Timestamp` - the attempt unix time (seconds).
+//! - `X-Webhook-Signature` - `sha256=<hex>`, the HMAC-SHA256 of `{timestamp}.{body}`
+//!   with the webhook secret.
+
+use crate::{Error, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use lib_core::ctx::Ctx;
+use lib_core::model::event::{self, DomainEvent, EventSubscriber};
+use lib_core::model::webhook::{DeliveryJob, DeliveryOutcome, WebhookDeliveryBmc};
+use lib_core::model::ModelManager;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Then the delivery is `failed` (about 20 minutes after the first attempt).
+const MAX_ATTEMPTS: i32 = 8;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(10);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(3600);
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longer than the `SEND_TIMEOUT`, so a delivery is not sent twice at once.
+const CLAIM_LEASE: Duration = Duration::from_secs(60);
+const CLAIM_BATCH_SIZE: i64 = 20;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The finished deliveries are kept a week, for the delivery log.
+const FINISHED_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Spawns the webhooks enqueuer (event subscriber) and sender.
+pub fn spawn_webhooks(mm: ModelManager) -> Result<()> {
+	let client = reqwest::Client::builder()
+		.timeout(SEND_TIMEOUT)
+		.build()
+		.map_err(|ex| Error::WebhooksInit(ex.to_string()))?;
+	let ctx = Ctx::service("webhooks")
+		.map_err(|ex| Error::WebhooksInit(format!("{ex:?}")))?;
+	let notify = Arc::new(Notify::new());
+
+	event::spawn_subscriber(
+		&mm,
+		WebhookEnqueuer {
+			ctx: ctx.clone(),
+			mm: mm.clone(),
+			notify: notify.clone(),
+		},
+	);
+	tokio::spawn(send_loop(ctx, mm, client, notify));
+
+	Ok(())
+}
+
+// region:    --- Enqueuer
+
+struct WebhookEnqueuer {
+	ctx: Ctx,
+	mm: ModelManager,
+	/// Wakes up the sender.
+	notify: Arc<Notify>,
+}
+
+#[async_trait]
+impl EventSubscriber for WebhookEnqueuer {
+	fn name(&self) -> &'static str {
+		"webhooks"
+	}
+
+	async fn handle(&self, event: &DomainEvent) {
+		match WebhookDeliveryBmc::enqueue(&self.ctx, &self.mm, event).await {
+			Ok(0) => (),
+			Ok(_) => self.notify.notify_one(),
+			Err(ex) => warn!("webhooks - {} not queued - {ex:?}", event.name()),
+		}
+	}
+}
+
+// endregion: --- Enqueuer
+
+// region:    --- Sender
+
+async fn send_loop(
+	ctx: Ctx,
+	mm: ModelManager,
+	client: reqwest::Client,
+	notify: Arc<Notify>,
+) {
+	let mut poll = tokio::time::interval(POLL_INTERVAL);
+	let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+	loop {
+		tokio::select! {
+			_ = notify.notified() => {}
+			_ = poll.tick() => {}
+			_ = prune.tick() => {
+				match WebhookDeliveryBmc::prune_finished(&ctx, &mm, FINISHED_RETENTION).await {
+					Ok(count) => debug!("webhooks - {count} finished deliveries pruned"),
+					Err(ex) => warn!("webhooks - prune failed - {ex:?}"),
+				}
+				continue;
+			}
+		}
+
+		// Send until no more due (the batch deliveries concurrently).
+		loop {
+			let jobs = match WebhookDeliveryBmc::claim_due(
+				&ctx,
+				&mm,
+				CLAIM_BATCH_SIZE,
+				CLAIM_LEASE,
+			)
+			.await
+			{
+				Ok(jobs) if !jobs.is_empty() => jobs,
+				Ok(_) => break,
+				Err(ex) => {
+					warn!("webhooks - claim failed, retried on next poll - {ex:?}");
+					break;
+				}
+			};
+
+			join_all(jobs.into_iter().map(|job| async {
+				let id = job.id;
+				let outcome = deliver(&client, job).await;
+				// Note: If not recorded, sent again after the lease.
+				if let Err(ex) =
+					WebhookDeliveryBmc::record_attempt(&ctx, &mm, id, outcome).await
+				{
+					warn!("webhooks - delivery {id} outcome not recorded - {ex:?}");
+				}
+			}))
+			.await;
+		}
+	}
+}
+
+async fn deliver(client: &reqwest::Client, job: DeliveryJob) -> DeliveryOutcome {
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+	let Some(signature) = sign(&job.secret, timestamp, &job.payload) else {
+		return DeliveryOutcome::Failed {
+			status_code: None,
+			error: "cannot sign with the webhook secret".to_string(),
+		};
+	};
+
+	let res = client
+		.post(&job.url)
+		.header(reqwest::header::CONTENT_TYPE, "application/json")
+		.header("x-webhook-id", job.id.to_string())
+		.header("x-webhook-event", &job.event)
+		.header("x-webhook-timestamp", timestamp.to_string())
+		.header("x-webhook-signature", signature)
+		.body(job.payload)
+		.send()
+		.await;
+
+	let (status_code, error) = match res {
+		Ok(res) if res.status().is_success() => {
+			return DeliveryOutcome::Succeeded {
+				status_code: res.status().as_u16(),
+			};
+		}
+		Ok(res) => (
+			Some(res.status().as_u16()),
+			format!("http {}", res.status()),
+		),
+		Err(ex) => (None, ex.to_string()),
+	};
+
+	let attempts = job.attempts + 1;
+	if attempts >= MAX_ATTEMPTS {
+		DeliveryOutcome::Failed { status_code, error }
+	} else {
+		DeliveryOutcome::Retry {
+			status_code,
+			error,
+			retry_in: retry_delay(attempts),
+		}
+	}
+}
+
+/// `sha256=<hex>` of the HMAC-SHA256 of `{timestamp}.{payload}`.
+fn sign(secret: &str, timestamp: u64, payload: &str) -> Option<String> {
+	let mut hmac_sha256 = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+	hmac_sha256.update(format!("{timestamp}.").as_bytes());
+	hmac_sha256.update(payload.as_bytes());
+
+	let hex: String = hmac_sha256
+		.finalize()
+		.into_bytes()
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect();
+
+	Some(format!("sha256={hex}"))
+}
+
+/// The delay after the `attempts`-th failed attempt (10s, 20s, 40s, ...).
+fn retry_delay(attempts: i32) -> Duration {
+	let exp = attempts.saturating_sub(1).clamp(0, 16) as u32;
+	RETRY_BASE_DELAY
+		.saturating_mul(2u32.pow(exp))
+		.min(RETRY_MAX_DELAY)
+}
+
+// endregion: --- Sender
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_sign_ok() {
+		// -- Setup & Fixtures
+		// Note: `echo -n '1700000000.{}' | openssl dgst -sha256 -hmac 'test_webhook_secret_01'`
+		let fx_signature =
+			"sha256=458a97aa3143eac4438dd1691936f8bb3f1a34b5639667291c0ca7d623d6ea5e";
+
+		// -- Exec
+		let signature = sign("test_webhook_secret_01", 1_700_000_000, "{}");
+
+		// -- Check
+		assert_eq!(signature.as_deref(), Some(fx_signature));
+	}
+
+	#[test]
+	fn test_retry_delay_ok() {
+		assert_eq!(retry_delay(1), Duration::from_secs(10));
+		assert_eq!(retry_delay(3), Duration::from_secs(40));
+		assert_eq!(retry_delay(12), RETRY_MAX_DELAY);
+	}
+}
+// endregion: --- Tests
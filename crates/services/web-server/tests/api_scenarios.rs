@@ -0,0 +1,168 @@
+mod common;
+
+use anyhow::{anyhow, Result};
+use common::{TestServer, DEMO_PWD, DEMO_USERNAME};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+const RPC_PATH: &str = "/api/v1/rpc";
+
+#[tokio::test]
+async fn test_login_crud_logoff_ok() -> Result<()> {
+	// -- Setup & Fixtures
+	let server = TestServer::start().await?;
+	let hc = httpc_test::new_client(server.base_url.as_str())?;
+
+	// -- Exec & Check: login
+	let res = hc
+		.do_post(
+			"/api/login",
+			json!({ "username": DEMO_USERNAME, "pwd": DEMO_PWD }),
+		)
+		.await?;
+	assert_eq!(res.status(), 200);
+	assert!(json_value::<bool>(&res, "/result/success")?);
+
+	// -- Exec & Check: create project
+	let params = json!({ "data": { "name": "project AAA" } });
+	let res = hc.do_post(RPC_PATH, rpc("create_project", params)).await?;
+	assert_eq!(json_value::<String>(&res, "/result/name")?, "project AAA");
+	let project_id = json_value::<i64>(&res, "/result/id")?;
+
+	// -- Exec & Check: create tasks
+	let mut task_ids: Vec<i64> = Vec::new();
+	for i in 1..=3 {
+		let params = json!({
+			"data": { "project_id": project_id, "title": format!("task AAA {i}") }
+		});
+		let res = hc.do_post(RPC_PATH, rpc("create_task", params)).await?;
+		assert_eq!(json_value::<i64>(&res, "/result/project_id")?, project_id);
+		task_ids.push(json_value::<i64>(&res, "/result/id")?);
+	}
+
+	// -- Exec & Check: update task
+	let params = json!({ "id": task_ids[0], "data": { "title": "task BBB" } });
+	let res = hc.do_post(RPC_PATH, rpc("update_task", params)).await?;
+	assert_eq!(json_value::<String>(&res, "/result/title")?, "task BBB");
+
+	// -- Exec & Check: delete task
+	let params = json!({ "id": task_ids[1] });
+	let res = hc.do_post(RPC_PATH, rpc("delete_task", params)).await?;
+	assert_eq!(json_value::<i64>(&res, "/result/id")?, task_ids[1]);
+
+	// -- Exec & Check: list tasks
+	let params = json!({
+		"filters": { "project_id": project_id },
+		"list_options": { "order_bys": "!title" }
+	});
+	let res = hc.do_post(RPC_PATH, rpc("list_tasks", params)).await?;
+	let titles: Vec<String> = json_value::<Vec<Value>>(&res, "/result")?
+		.iter()
+		.filter_map(|task| task["title"].as_str().map(String::from))
+		.collect();
+	assert_eq!(titles, ["task BBB", "task AAA 3"]);
+
+	// -- Exec & Check: list tasks with unknown columns
+	let params = json!({ "list_options": { "order_bys": "title; x" } });
+	let res = hc.do_post(RPC_PATH, rpc("list_tasks", params)).await?;
+	assert_eq!(res.status(), 400);
+	assert_eq!(json_value::<String>(&res, "/error/code")?, "params.invalid");
+
+	let params = json!({ "filters": { "unknown_column": 1 } });
+	let res = hc.do_post(RPC_PATH, rpc("list_tasks", params)).await?;
+	assert_eq!(res.status(), 400);
+	assert_eq!(json_value::<String>(&res, "/error/code")?, "params.invalid");
+
+	// -- Exec & Check: logoff, then no more auth
+	let res = hc.do_post("/api/logoff", json!({ "logoff": true })).await?;
+	assert!(json_value::<bool>(&res, "/result/logged_off")?);
+
+	let res = hc.do_post(RPC_PATH, rpc("list_tasks", json!({}))).await?;
+	assert_eq!(res.status(), 403);
+	assert_eq!(json_value::<String>(&res, "/error/code")?, "auth.no_auth");
+
+	server.stop().await?;
+
+	Ok(())
+}
+
+#[tokio::test]
+async fn test_login_wrong_pwd_err() -> Result<()> {
+	// -- Setup & Fixtures
+	let server = TestServer::start().await?;
+	let hc = httpc_test::new_client(server.base_url.as_str())?;
+
+	// -- Exec
+	let res = hc
+		.do_post(
+			"/api/login",
+			json!({ "username": DEMO_USERNAME, "pwd": "not-the-pwd" }),
+		)
+		.await?;
+
+	// -- Check
+	assert_eq!(res.status(), 403);
+	assert_eq!(
+		json_value::<String>(&res, "/error/code")?,
+		"auth.login_fail"
+	);
+	assert!(hc.cookie_value("auth-token").is_none());
+
+	server.stop().await?;
+
+	Ok(())
+}
+
+#[tokio::test]
+async fn test_rotate_token_salt_ok() -> Result<()> {
+	// -- Setup & Fixtures
+	let server = TestServer::start().await?;
+	let hc = httpc_test::new_client(server.base_url.as_str())?;
+	let hc_other = httpc_test::new_client(server.base_url.as_str())?;
+	let login = json!({ "username": DEMO_USERNAME, "pwd": DEMO_PWD });
+	hc.do_post("/api/login", login.clone()).await?;
+	hc_other.do_post("/api/login", login).await?;
+
+	// -- Exec
+	let res = hc
+		.do_post(RPC_PATH, rpc("rotate_token_salt", json!({})))
+		.await?;
+
+	// -- Check: this session kept (re-issued cookie), the other signed out
+	assert!(json_value::<bool>(&res, "/result/success")?);
+	let res = hc.do_post(RPC_PATH, rpc("list_tasks", json!({}))).await?;
+	assert_eq!(res.status(), 200);
+
+	let res = hc_other
+		.do_post(RPC_PATH, rpc("list_tasks", json!({})))
+		.await?;
+	assert_eq!(res.status(), 403);
+	assert_eq!(json_value::<String>(&res, "/error/code")?, "auth.no_auth");
+
+	server.stop().await?;
+
+	Ok(())
+}
+
+// region:    --- Support
+
+/// Note: `httpc_test::Response::json_value` ignores its pointer (in 0.1.7).
+fn json_value<T: DeserializeOwned>(
+	res: &httpc_test::Response,
+	pointer: &str,
+) -> Result<T> {
+	let value = res
+		.json_body()?
+		.pointer(pointer)
+		.cloned()
+		.ok_or_else(|| anyhow!("no json value at {pointer}"))?;
+
+	Ok(serde_json::from_value(value)?)
+}
+
+/// The json-rpc request body.
+fn rpc(method: &str, params: Value) -> Value {
+	json!({ "id": 1, "method": method, "params": params })
+}
+
+// endregion: --- Support
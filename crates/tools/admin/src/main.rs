@@ -0,0 +1,216 @@
+//! The user management admin tool, directly against the db (with the service
+//! config, e.g., `SERVICE_DB_URL` and `SERVICE_PWD_KEY`), for bootstrapping and
+//! break-glass scenarios.
+//!
+//! ```sh
+//! admin create --username=NAME [--pwd=PWD] [--role=user|admin]
+//! admin set-pwd --username=NAME --pwd=PWD
+//! admin reset-pwd --username=NAME
+//! admin require-pwd-change --username=NAME
+//! admin set-role --username=NAME --role=user|admin
+//! admin deactivate --username=NAME
+//! admin activate --username=NAME
+//! admin seed [--set=baseline|demo] [--admin-username=NAME]
+//! ```
+//!
+//! Note: When not given, the pwd is generated and printed once.
+//!
+//! Note: The reset pwd must be changed by the user at the next login
+//!       (as `require-pwd-change`).
+//!
+//! Note: The `seed` is idempotent (see lib-core `model::seed`), and the `demo`
+//!       set is refused in prod (`SERVICE_ENV`).
+
+use anyhow::{anyhow, bail, Result};
+use lib_core::config::{self, config};
+use lib_core::ctx::Ctx;
+use lib_core::model::seed::{self, SeedSet};
+use lib_core::model::user::{User, UserBmc, UserForCreate, UserRole};
+use lib_core::model::ModelManager;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const DEFAULT_ADMIN_USERNAME: &str = "admin";
+
+const USAGE: &str = "\
+USAGE:
+    admin create --username=NAME [--pwd=PWD] [--role=user|admin]
+    admin set-pwd --username=NAME --pwd=PWD
+    admin reset-pwd --username=NAME
+    admin require-pwd-change --username=NAME
+    admin set-role --username=NAME --role=user|admin
+    admin deactivate --username=NAME
+    admin activate --username=NAME
+    admin seed [--set=baseline|demo] [--admin-username=NAME]";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+	let mut args = std::env::args().skip(1);
+	let Some(cmd) = args.next() else {
+		bail!("no command\n\n{USAGE}");
+	};
+	if matches!(cmd.as_str(), "help" | "--help" | "-h") {
+		println!("{USAGE}");
+		return Ok(());
+	}
+	let opts = parse_opts(args)?;
+
+	// -- External secret manager (must be before the first `config()`)
+	config::init_secrets().await?;
+
+	let mm = ModelManager::new().await?;
+	// Note: The changes are stamped (cid/mid) with the `admin_cli` service id.
+	let ctx = Ctx::service("admin_cli")?;
+	// Note: The only command not on a given user.
+	if cmd == "seed" {
+		return seed(&ctx, &mm, &opts).await;
+	}
+	let username = opts.required("username")?;
+
+	match cmd.as_str() {
+		"create" => {
+			let role = opts.role_or(UserRole::User)?;
+			let (pwd_clear, generated) = opts.pwd_or_generated();
+			let id = UserBmc::create(
+				&ctx,
+				&mm,
+				UserForCreate {
+					username: username.to_string(),
+					pwd_clear: pwd_clear.clone(),
+				},
+			)
+			.await?;
+			if role != UserRole::User {
+				UserBmc::update_role(&ctx, &mm, id, role).await?;
+			}
+			println!("Created user '{username}' (id: {id}, role: {role:?})");
+			if generated {
+				println!("Generated pwd (shown once): {pwd_clear}");
+			}
+		}
+		"set-pwd" => {
+			let user = user_by_username(&ctx, &mm, username).await?;
+			UserBmc::update_pwd(&ctx, &mm, user.id, opts.required("pwd")?).await?;
+			println!("Pwd set for user '{username}'");
+		}
+		"reset-pwd" => {
+			let user = user_by_username(&ctx, &mm, username).await?;
+			let pwd_clear = gen_pwd();
+			UserBmc::update_pwd(&ctx, &mm, user.id, &pwd_clear).await?;
+			UserBmc::update_must_change_pwd(&ctx, &mm, user.id, true).await?;
+			println!("Pwd reset for user '{username}' (shown once): {pwd_clear}");
+		}
+		"require-pwd-change" => {
+			let user = user_by_username(&ctx, &mm, username).await?;
+			UserBmc::update_must_change_pwd(&ctx, &mm, user.id, true).await?;
+			println!("User '{username}' must change the pwd at the next request");
+		}
+		"set-role" => {
+			let user = user_by_username(&ctx, &mm, username).await?;
+			let role = opts.role()?;
+			UserBmc::update_role(&ctx, &mm, user.id, role).await?;
+			println!("Role of user '{username}' set to {role:?}");
+		}
+		"deactivate" | "activate" => {
+			let user = user_by_username(&ctx, &mm, username).await?;
+			let active = cmd == "activate";
+			UserBmc::update_active(&ctx, &mm, user.id, active).await?;
+			println!("User '{username}' {cmd}d");
+		}
+		_ => bail!("unknown command '{cmd}'\n\n{USAGE}"),
+	}
+
+	Ok(())
+}
+
+async fn seed(ctx: &Ctx, mm: &ModelManager, opts: &Opts) -> Result<()> {
+	let set = match opts.0.get("set") {
+		Some(set) => set
+			.parse()
+			.map_err(|_| anyhow!("unknown seed set '{set}' (baseline or demo)"))?,
+		None => SeedSet::Baseline,
+	};
+	let admin_username = opts
+		.0
+		.get("admin-username")
+		.map(String::as_str)
+		.unwrap_or(DEFAULT_ADMIN_USERNAME);
+
+	let entries = seed::seed(ctx, mm, config().ENV, set, admin_username).await?;
+	for entry in entries {
+		let status = if entry.created { "created" } else { "exists" };
+		println!("{status:<8} {}", entry.name);
+		if let Some(pwd) = entry.pwd {
+			println!("         generated pwd (shown once): {pwd}");
+		}
+	}
+
+	Ok(())
+}
+
+async fn user_by_username(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	username: &str,
+) -> Result<User> {
+	UserBmc::first_by_username(ctx, mm, username)
+		.await?
+		.ok_or_else(|| anyhow!("no user '{username}'"))
+}
+
+/// A random pwd (122 random bits).
+fn gen_pwd() -> String {
+	Uuid::new_v4().simple().to_string()
+}
+
+// region:    --- Opts
+
+struct Opts(HashMap<String, String>);
+
+impl Opts {
+	fn required(&self, name: &str) -> Result<&str> {
+		self.0
+			.get(name)
+			.map(String::as_str)
+			.ok_or_else(|| anyhow!("--{name} is required\n\n{USAGE}"))
+	}
+
+	fn role(&self) -> Result<UserRole> {
+		let role = self.required("role")?;
+		role.parse()
+			.map_err(|_| anyhow!("unknown role '{role}' (user or admin)"))
+	}
+
+	fn role_or(&self, default: UserRole) -> Result<UserRole> {
+		if self.0.contains_key("role") {
+			self.role()
+		} else {
+			Ok(default)
+		}
+	}
+
+	/// Returns the pwd, and whether it was generated.
+	fn pwd_or_generated(&self) -> (String, bool) {
+		match self.0.get("pwd") {
+			Some(pwd) => (pwd.to_string(), false),
+			None => (gen_pwd(), true),
+		}
+	}
+}
+
+/// `--name=value` options.
+fn parse_opts(args: impl Iterator<Item = String>) -> Result<Opts> {
+	let mut opts = HashMap::new();
+	for arg in args {
+		let Some((name, value)) =
+			arg.strip_prefix("--").and_then(|arg| arg.split_once('='))
+		else {
+			bail!("invalid option '{arg}' (expected --name=value)\n\n{USAGE}");
+		};
+		opts.insert(name.to_string(), value.to_string());
+	}
+
+	Ok(Opts(opts))
+}
+
+// endregion: --- Opts
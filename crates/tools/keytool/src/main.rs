@@ -0,0 +1,260 @@
+//! The `PWD_KEY` and `TOKEN_KEY` management tool.
+//!
+//! ```sh
+//! keytool gen [--kind=pwd|token] [--format=env|file] [--out=PATH]
+//! keytool verify --key=B64U | --file=PATH
+//! keytool rotate --kind=pwd|token --current=B64U|--current-file=PATH [--version=N] [--out=PATH]
+//! ```
+//!
+//! - `env` format - `SERVICE_PWD_KEY="..."` (e.g., for `.cargo/config.toml` or a `.env`).
+//! - `file` format - only the key (e.g., for the `SERVICE_PWD_KEY_FILE` secret files).
+//! - `rotate` - a versioned key set, with the new key, its version, and the
+//!   previous key (e.g., `SERVICE_PWD_KEY_PREVIOUS`).
+//!
+//! Note: The services only read the `SERVICE_PWD_KEY`/`SERVICE_TOKEN_KEY` for now,
+//!       so the version and previous key are for the upcoming key rotation support.
+//!
+//! Note: `--out` files are written with the `600` mode (on unix).
+
+use anyhow::{anyhow, bail, Result};
+use lib_base::b64::{b64u_decode, b64u_encode};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::fs;
+
+/// 512 bits, as the lib-core `KEY_MIN_LEN` is 32 bytes.
+const KEY_LEN: usize = 64;
+/// Same as the lib-core config `KEY_MIN_LEN`.
+const KEY_MIN_LEN: usize = 32;
+
+const USAGE: &str = "\
+USAGE:
+    keytool gen [--kind=pwd|token] [--format=env|file] [--out=PATH]
+    keytool verify --key=B64U | --file=PATH
+    keytool rotate --kind=pwd|token --current=B64U|--current-file=PATH [--version=N] [--out=PATH]";
+
+fn main() -> Result<()> {
+	let mut args = std::env::args().skip(1);
+	let Some(cmd) = args.next() else {
+		bail!("no command\n\n{USAGE}");
+	};
+	let opts = parse_opts(args)?;
+
+	match cmd.as_str() {
+		"gen" => gen(&opts),
+		"verify" => verify(&opts),
+		"rotate" => rotate(&opts),
+		"help" | "--help" | "-h" => {
+			println!("{USAGE}");
+			Ok(())
+		}
+		_ => bail!("unknown command '{cmd}'\n\n{USAGE}"),
+	}
+}
+
+// region:    --- Commands
+
+fn gen(opts: &Opts) -> Result<()> {
+	let kind = opts.kind_or(KeyKind::Pwd)?;
+	let key = gen_key();
+
+	let content = match opts.get("format").unwrap_or("env") {
+		"env" => format!("{}={key:?}\n", kind.env_name()),
+		"file" => format!("{key}\n"),
+		format => bail!("unknown format '{format}' (env or file)"),
+	};
+
+	write_out(opts.get("out"), &content)
+}
+
+fn verify(opts: &Opts) -> Result<()> {
+	let key = match (opts.get("key"), opts.get("file")) {
+		(Some(key), None) => key.to_string(),
+		(None, Some(path)) => read_key_file(path)?,
+		_ => bail!("verify needs --key or --file\n\n{USAGE}"),
+	};
+
+	let len = verify_key(&key)?;
+	println!("OK - b64u encoded key of {len} bytes");
+	if len < KEY_LEN {
+		println!("WARNING - below the {KEY_LEN} bytes generated by `keytool gen`");
+	}
+
+	Ok(())
+}
+
+fn rotate(opts: &Opts) -> Result<()> {
+	let kind = opts.kind()?;
+	let current = match (opts.get("current"), opts.get("current-file")) {
+		(Some(key), None) => key.to_string(),
+		(None, Some(path)) => read_key_file(path)?,
+		_ => bail!("rotate needs --current or --current-file\n\n{USAGE}"),
+	};
+	verify_key(&current).map_err(|ex| anyhow!("current key - {ex}"))?;
+	let version: u32 = match opts.get("version") {
+		Some(version) => version
+			.parse()
+			.map_err(|_| anyhow!("--version must be an integer"))?,
+		None => 2,
+	};
+
+	let content = key_set(kind, version, &gen_key(), &current);
+
+	write_out(opts.get("out"), &content)
+}
+
+// endregion: --- Commands
+
+// region:    --- Keys
+
+#[derive(Debug, Clone, Copy)]
+enum KeyKind {
+	Pwd,
+	Token,
+}
+
+impl KeyKind {
+	fn env_name(&self) -> &'static str {
+		match self {
+			Self::Pwd => "SERVICE_PWD_KEY",
+			Self::Token => "SERVICE_TOKEN_KEY",
+		}
+	}
+}
+
+fn gen_key() -> String {
+	let mut key = [0u8; KEY_LEN];
+	rand::thread_rng().fill_bytes(&mut key);
+	b64u_encode(key)
+}
+
+/// Returns the decoded key length.
+fn verify_key(b64u: &str) -> Result<usize> {
+	let key = b64u_decode(b64u).map_err(|_| anyhow!("not b64u encoded"))?;
+	if key.len() < KEY_MIN_LEN {
+		bail!("{} bytes, min is {KEY_MIN_LEN}", key.len());
+	}
+
+	Ok(key.len())
+}
+
+/// The versioned key set (env format) of a rotation.
+fn key_set(
+	kind: KeyKind,
+	version: u32,
+	new_key: &str,
+	previous_key: &str,
+) -> String {
+	let name = kind.env_name();
+	format!(
+		"\
+# {kind:?} key set, version {version}
+{name}={new_key:?}
+{name}_VERSION=\"{version}\"
+{name}_PREVIOUS={previous_key:?}
+"
+	)
+}
+
+// endregion: --- Keys
+
+// region:    --- Support
+
+struct Opts(HashMap<String, String>);
+
+impl Opts {
+	fn get(&self, name: &str) -> Option<&str> {
+		self.0.get(name).map(String::as_str)
+	}
+
+	fn kind(&self) -> Result<KeyKind> {
+		match self.get("kind") {
+			Some("pwd") => Ok(KeyKind::Pwd),
+			Some("token") => Ok(KeyKind::Token),
+			Some(kind) => bail!("unknown kind '{kind}' (pwd or token)"),
+			None => bail!("--kind is required (pwd or token)"),
+		}
+	}
+
+	fn kind_or(&self, default: KeyKind) -> Result<KeyKind> {
+		if self.get("kind").is_some() {
+			self.kind()
+		} else {
+			Ok(default)
+		}
+	}
+}
+
+/// `--name=value` options.
+fn parse_opts(args: impl Iterator<Item = String>) -> Result<Opts> {
+	let mut opts = HashMap::new();
+	for arg in args {
+		let Some((name, value)) =
+			arg.strip_prefix("--").and_then(|arg| arg.split_once('='))
+		else {
+			bail!("invalid option '{arg}' (expected --name=value)\n\n{USAGE}");
+		};
+		opts.insert(name.to_string(), value.to_string());
+	}
+
+	Ok(Opts(opts))
+}
+
+/// Note: Like the lib-core `_FILE` secrets, the trailing new line is ignored.
+fn read_key_file(path: &str) -> Result<String> {
+	let content = fs::read_to_string(path)
+		.map_err(|ex| anyhow!("cannot read {path} - {ex}"))?;
+
+	Ok(content.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn write_out(out: Option<&str>, content: &str) -> Result<()> {
+	let Some(path) = out else {
+		print!("{content}");
+		return Ok(());
+	};
+
+	fs::write(path, content)?;
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+	}
+	eprintln!("Written to {path}");
+
+	Ok(())
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_verify_key_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_key = gen_key();
+		let fx_short_key = b64u_encode([0u8; 16]);
+
+		// -- Exec & Check
+		assert_eq!(verify_key(&fx_key)?, KEY_LEN);
+		assert!(verify_key(&fx_short_key).is_err());
+		assert!(verify_key("not b64u!").is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_key_set_ok() {
+		// -- Exec
+		let key_set = key_set(KeyKind::Token, 3, "new-key", "old-key");
+
+		// -- Check
+		assert!(key_set.contains("SERVICE_TOKEN_KEY=\"new-key\"\n"));
+		assert!(key_set.contains("SERVICE_TOKEN_KEY_VERSION=\"3\"\n"));
+		assert!(key_set.contains("SERVICE_TOKEN_KEY_PREVIOUS=\"old-key\"\n"));
+	}
+}
+// endregion: --- Tests
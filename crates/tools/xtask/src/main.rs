@@ -0,0 +1,54 @@
+//! The repo dev tasks, run with `cargo xtask <task>` (see `.cargo/config.toml`).
+//!
+//! ```sh
+//! cargo xtask scaffold <entity> <field:type>... [--plural=NAME]
+//! cargo xtask replay <file|dir> [--base-url=URL] [--username=NAME --pwd=PWD]
+//! cargo xtask ts-types [--out=DIR]
+//! ```
+
+mod replay;
+mod scaffold;
+mod ts_types;
+
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+USAGE:
+    cargo xtask scaffold <entity> <field:type>... [--plural=NAME]
+
+    <entity>      snake_case name, e.g., `note`
+    <field:type>  type is string, i64, f64, or bool (e.g., `title:string`)
+                  (a `project_id:i64` field makes it a project child, like `task`)
+
+    cargo xtask replay <file|dir> [--base-url=URL] [--username=NAME --pwd=PWD]
+
+    <file|dir>    a capture file, or a capture label dir (see `SERVICE_CAPTURE_DIR`)
+    --base-url    the running web-server (default http://127.0.0.1:8080)
+    --username    the user to log in as first (with --pwd)
+
+    cargo xtask ts-types [--out=DIR]
+
+    --out         the .d.ts files dir (default web-folder/types)";
+
+fn main() -> Result<()> {
+	let mut args = std::env::args().skip(1);
+
+	match args.next().as_deref() {
+		Some("scaffold") => scaffold::run(&workspace_dir(), args),
+		Some("replay") => replay::run(args),
+		Some("ts-types") => ts_types::run(&workspace_dir(), args),
+		Some("help" | "--help" | "-h") => {
+			println!("{USAGE}");
+			Ok(())
+		}
+		Some(task) => bail!("unknown task '{task}'\n\n{USAGE}"),
+		None => bail!("no task\n\n{USAGE}"),
+	}
+}
+
+/// The workspace root (from `crates/tools/xtask`).
+fn workspace_dir() -> PathBuf {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../..");
+	dir.canonicalize().unwrap_or(dir)
+}
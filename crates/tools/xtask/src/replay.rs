@@ -0,0 +1,252 @@
+//! Replays the captured requests (see web-server `mw_capture`) against a
+//! running web-server, in their capture order (i.e., the file names).
+//!
+//! The captures are redacted, so the session is a new one: the `--username`
+//! and `--pwd` log in first (the captured `/api/login` calls are skipped), and
+//! the redacted headers (e.g., `cookie`) are not sent.
+//!
+//! Prints the captured and replayed status of each request (with the replayed
+//! body when they differ).
+
+use crate::USAGE;
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::blocking::Client;
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BASE_URL_DEFAULT: &str = "http://127.0.0.1:8080";
+const LOGIN_PATH: &str = "/api/login";
+const REDACTED: &str = "[REDACTED]";
+
+/// The headers set by the client for the replayed request.
+const SKIPPED_HEADERS: &[&str] = &["host", "content-length", "cookie"];
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<()> {
+	let opts = ReplayOpts::from_args(args)?;
+	let files = capture_files(&opts.path)?;
+	if files.is_empty() {
+		bail!("no capture files in {}", opts.path.display());
+	}
+
+	let client = Client::builder().cookie_store(true).build()?;
+
+	// -- Login (when the credentials are given).
+	if let (Some(username), Some(pwd)) = (&opts.username, &opts.pwd) {
+		let res = client
+			.post(format!("{}{LOGIN_PATH}", opts.base_url))
+			.json(&json!({ "username": username, "pwd": pwd }))
+			.send()?;
+		if !res.status().is_success() {
+			bail!("login failed ({})", res.status());
+		}
+	}
+
+	// -- Replay the captures.
+	let mut mismatches = 0;
+	for file in &files {
+		let capture: Value = serde_json::from_slice(&fs::read(file)?)
+			.with_context(|| format!("invalid capture {}", file.display()))?;
+		let Some(req) = ReplayRequest::from_capture(&capture)? else {
+			println!("-- SKIP {} (login)", file.display());
+			continue;
+		};
+
+		let mut builder =
+			client.request(req.method, format!("{}{}", opts.base_url, req.uri));
+		for (name, value) in &req.headers {
+			builder = builder.header(name, value);
+		}
+		if let Some(body) = req.body {
+			builder = builder.body(body);
+		}
+		let res = builder.send()?;
+
+		let status = res.status().as_u16();
+		if status == req.captured_status {
+			println!("-- OK   {} {status}", file.display());
+		} else {
+			mismatches += 1;
+			println!(
+				"-- DIFF {} captured {} replayed {status}\n{}",
+				file.display(),
+				req.captured_status,
+				res.text()?
+			);
+		}
+	}
+
+	println!("\n{} replayed, {mismatches} status mismatches", files.len());
+
+	Ok(())
+}
+
+// region:    --- ReplayOpts
+
+struct ReplayOpts {
+	path: PathBuf,
+	base_url: String,
+	username: Option<String>,
+	pwd: Option<String>,
+}
+
+impl ReplayOpts {
+	fn from_args(args: impl Iterator<Item = String>) -> Result<Self> {
+		let mut path = None;
+		let mut base_url = BASE_URL_DEFAULT.to_string();
+		let mut username = None;
+		let mut pwd = None;
+		for arg in args {
+			if let Some(val) = arg.strip_prefix("--base-url=") {
+				base_url = val.trim_end_matches('/').to_string();
+			} else if let Some(val) = arg.strip_prefix("--username=") {
+				username = Some(val.to_string());
+			} else if let Some(val) = arg.strip_prefix("--pwd=") {
+				pwd = Some(val.to_string());
+			} else if arg.starts_with("--") {
+				bail!("unknown option '{arg}'\n\n{USAGE}");
+			} else {
+				path = Some(PathBuf::from(arg));
+			}
+		}
+		if username.is_some() != pwd.is_some() {
+			bail!("--username and --pwd go together\n\n{USAGE}");
+		}
+
+		Ok(Self {
+			path: path
+				.ok_or_else(|| anyhow!("no capture file or dir\n\n{USAGE}"))?,
+			base_url,
+			username,
+			pwd,
+		})
+	}
+}
+
+// endregion: --- ReplayOpts
+
+// region:    --- ReplayRequest
+
+struct ReplayRequest {
+	method: Method,
+	uri: String,
+	headers: Vec<(String, String)>,
+	body: Option<String>,
+	captured_status: u16,
+}
+
+impl ReplayRequest {
+	/// None for the captured login (the session is logged in with the options).
+	fn from_capture(capture: &Value) -> Result<Option<Self>> {
+		let req = &capture["request"];
+		let uri = req["uri"]
+			.as_str()
+			.ok_or_else(|| anyhow!("capture without request.uri"))?;
+		if uri.split('?').next() == Some(LOGIN_PATH) {
+			return Ok(None);
+		}
+		let method = req["method"]
+			.as_str()
+			.ok_or_else(|| anyhow!("capture without request.method"))?
+			.parse::<Method>()?;
+
+		let headers = req["headers"]
+			.as_object()
+			.into_iter()
+			.flatten()
+			.filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+			.filter_map(|(name, value)| {
+				Some((name.clone(), value.as_str()?.to_string()))
+			})
+			.filter(|(_, value)| value != REDACTED)
+			.collect();
+
+		// Note: The json bodies are captured as json, the others as strings.
+		let body = match &req["body"] {
+			Value::Null => None,
+			Value::String(text) => Some(text.clone()),
+			json => Some(json.to_string()),
+		};
+
+		let captured_status = capture["response"]["status"]
+			.as_u64()
+			.and_then(|status| u16::try_from(status).ok())
+			.ok_or_else(|| anyhow!("capture without response.status"))?;
+
+		Ok(Some(Self {
+			method,
+			uri: uri.to_string(),
+			headers,
+			body,
+			captured_status,
+		}))
+	}
+}
+
+// endregion: --- ReplayRequest
+
+/// The capture file, or the `.json` files of the capture dir (sorted by name,
+/// which starts with the capture time).
+fn capture_files(path: &Path) -> Result<Vec<PathBuf>> {
+	if path.is_file() {
+		return Ok(vec![path.to_path_buf()]);
+	}
+
+	let mut files: Vec<PathBuf> = fs::read_dir(path)
+		.with_context(|| format!("cannot read {}", path.display()))?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+		.collect();
+	files.sort();
+
+	Ok(files)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_replay_request_from_capture_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_capture = json!({
+			"request": {
+				"method": "POST",
+				"uri": "/api/v1/rpc",
+				"headers": {
+					"host": "localhost:8080",
+					"cookie": "[REDACTED]",
+					"content-type": "application/json",
+				},
+				"body": {"id": 1, "method": "list_tasks"},
+			},
+			"response": {"status": 200},
+		});
+		let fx_login = json!({
+			"request": {"method": "POST", "uri": "/api/login", "body": {"pwd": "[REDACTED]"}},
+			"response": {"status": 200},
+		});
+
+		// -- Exec
+		let req = ReplayRequest::from_capture(&fx_capture)?
+			.ok_or_else(|| anyhow!("should not be skipped"))?;
+
+		// -- Check
+		assert_eq!(req.method, Method::POST);
+		assert_eq!(
+			req.headers,
+			[("content-type".to_string(), "application/json".to_string())]
+		);
+		assert_eq!(
+			serde_json::from_str::<Value>(req.body.as_deref().unwrap_or_default())?,
+			json!({"id": 1, "method": "list_tasks"})
+		);
+		assert_eq!(req.captured_status, 200);
+		assert!(ReplayRequest::from_capture(&fx_login)?.is_none());
+
+		Ok(())
+	}
+}
+// endregion: --- Tests
@@ -0,0 +1,629 @@
+//! Scaffolds a new entity, following the `task`/`project` patterns:
+//!
+//! - `crates/libs/lib-core/src/model/{entity}.rs` - the types, the Bmc, and the tests.
+//! - `sql/migrations/{NNNN}_create_{entity}.sql` - the table (as a new migration).
+//! - `crates/services/web-server/src/web/rpc/{entity}_rpc.rs` - the json-rpc methods.
+//!
+//! And registers the modules and the rpc router (in `model/mod.rs` and `rpc/mod.rs`).
+//!
+//! Note: The templates use `@name@` placeholders, and the generated files are
+//!       then formatted with `rustfmt` (not the `mod.rs` files, as rustfmt would
+//!       also format their child modules).
+
+use crate::USAGE;
+use anyhow::{anyhow, bail, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const MODEL_DIR: &str = "crates/libs/lib-core/src/model";
+const RPC_DIR: &str = "crates/services/web-server/src/web/rpc";
+const SQL_DIR: &str = "sql/migrations";
+
+/// The columns managed by the model layer (not allowed as fields).
+const RESERVED_FIELDS: &[&str] = &["id", "cid", "ctime", "mid", "mtime"];
+
+pub fn run(workspace_dir: &Path, args: impl Iterator<Item = String>) -> Result<()> {
+	let entity = Entity::from_args(args)?;
+
+	// -- Check nothing is overwritten.
+	let model_file = workspace_dir
+		.join(MODEL_DIR)
+		.join(format!("{}.rs", entity.name));
+	let rpc_file = workspace_dir
+		.join(RPC_DIR)
+		.join(format!("{}_rpc.rs", entity.name));
+	for file in [&model_file, &rpc_file] {
+		if file.exists() {
+			bail!("{} already exists", file.display());
+		}
+	}
+	let sql_file = workspace_dir.join(SQL_DIR).join(format!(
+		"{:04}_create_{}.sql",
+		next_migration_version(&workspace_dir.join(SQL_DIR))?,
+		entity.name
+	));
+
+	// -- Write the files.
+	fs::write(&model_file, entity.render_model())?;
+	fs::write(&sql_file, entity.render_sql())?;
+	fs::write(&rpc_file, entity.render_rpc())?;
+
+	// -- Register the modules.
+	let model_mod = workspace_dir.join(MODEL_DIR).join("mod.rs");
+	let model_mod_line = format!("pub mod {};", entity.name);
+	insert_line(&model_mod, "pub mod ", &model_mod_line, true)?;
+	let rpc_mod = workspace_dir.join(RPC_DIR).join("mod.rs");
+	insert_line(&rpc_mod, "mod ", &format!("mod {}_rpc;", entity.name), true)?;
+	let rpc_router_line = format!("\t\t.extend({}_rpc::rpc_router())", entity.name);
+	insert_line(&rpc_mod, "\t\t.extend(", &rpc_router_line, false)?;
+
+	// Note: Best effort, the files are valid without it.
+	let _ = Command::new("rustfmt")
+		.args(["--edition", "2021"])
+		.args([&model_file, &rpc_file])
+		.status();
+
+	for file in [&model_file, &sql_file, &rpc_file, &model_mod, &rpc_mod] {
+		println!("-> {}", file.display());
+	}
+	println!(
+		"\nNext: `cargo test -p lib-core {name}`, and the eventual REST routes \
+		 and OpenAPI entries (see `routes_rest.rs`).",
+		name = entity.name
+	);
+
+	Ok(())
+}
+
+// region:    --- Entity
+
+#[derive(Debug)]
+struct Entity {
+	/// snake_case, e.g., `note`
+	name: String,
+	/// e.g., `notes`
+	plural: String,
+	fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+struct Field {
+	name: String,
+	ty: FieldType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldType {
+	String,
+	I64,
+	F64,
+	Bool,
+}
+
+impl FieldType {
+	fn rust_type(&self) -> &'static str {
+		match self {
+			Self::String => "String",
+			Self::I64 => "i64",
+			Self::F64 => "f64",
+			Self::Bool => "bool",
+		}
+	}
+
+	fn sql_type(&self) -> &'static str {
+		match self {
+			Self::String => "varchar(256)",
+			Self::I64 => "BIGINT",
+			Self::F64 => "double precision",
+			Self::Bool => "bool",
+		}
+	}
+
+	fn op_vals(&self) -> &'static str {
+		match self {
+			Self::String => "OpValsString",
+			Self::I64 => "OpValsInt64",
+			Self::F64 => "OpValsFloat64",
+			Self::Bool => "OpValsBool",
+		}
+	}
+}
+
+impl Entity {
+	fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self> {
+		let name = args
+			.next()
+			.ok_or_else(|| anyhow!("no entity name\n\n{USAGE}"))?;
+		check_snake_case(&name)?;
+
+		let mut plural = format!("{name}s");
+		let mut fields = Vec::new();
+		for arg in args {
+			if let Some(val) = arg.strip_prefix("--plural=") {
+				check_snake_case(val)?;
+				plural = val.to_string();
+				continue;
+			}
+
+			let Some((field_name, ty)) = arg.split_once(':') else {
+				bail!("invalid field '{arg}' (expected name:type)\n\n{USAGE}");
+			};
+			check_snake_case(field_name)?;
+			if RESERVED_FIELDS.contains(&field_name) {
+				bail!("'{field_name}' is managed by the model layer");
+			}
+			let ty = match ty {
+				"string" => FieldType::String,
+				"i64" => FieldType::I64,
+				"f64" => FieldType::F64,
+				"bool" => FieldType::Bool,
+				_ => bail!("unknown type '{ty}' (string, i64, f64, or bool)"),
+			};
+			fields.push(Field {
+				name: field_name.to_string(),
+				ty,
+			});
+		}
+		if fields.is_empty() {
+			bail!("no fields\n\n{USAGE}");
+		}
+
+		Ok(Entity {
+			name,
+			plural,
+			fields,
+		})
+	}
+
+	/// e.g., `task_label` to `TaskLabel`
+	fn pascal_name(&self) -> String {
+		self.name
+			.split('_')
+			.map(|part| {
+				let mut chars = part.chars();
+				chars
+					.next()
+					.map(|first| first.to_uppercase().chain(chars).collect())
+					.unwrap_or_default()
+			})
+			.collect::<Vec<String>>()
+			.concat()
+	}
+
+	/// A `project_id` field makes the entity a project child (like `task`).
+	fn is_project_child(&self) -> bool {
+		self.fields
+			.iter()
+			.any(|f| f.name == "project_id" && f.ty == FieldType::I64)
+	}
+
+	fn render(&self, template: &str) -> String {
+		template
+			.replace("@Entity@", &self.pascal_name())
+			.replace("@entity@", &self.name)
+			.replace("@entities@", &self.plural)
+	}
+
+	fn render_model(&self) -> String {
+		let mut op_vals = vec!["OpValsInt64", "OpValsValue"];
+		op_vals.extend(self.fields.iter().map(|f| f.ty.op_vals()));
+		op_vals.sort();
+		op_vals.dedup();
+
+		let mut fields = String::new();
+		let mut update_fields = String::new();
+		let mut filter_fields = String::new();
+		let mut fx_fields = String::new();
+		for Field { name, ty } in &self.fields {
+			let rust_type = ty.rust_type();
+			fields.push_str(&format!("\tpub {name}: {rust_type},\n"));
+			filter_fields
+				.push_str(&format!("\t{name}: Option<{}>,\n", ty.op_vals()));
+			// Note: As for `task`, the parent project cannot be changed.
+			if name != "project_id" {
+				update_fields
+					.push_str(&format!("\tpub {name}: Option<{rust_type}>,\n"));
+			}
+			let fx_value = match (name.as_str(), ty) {
+				("project_id", FieldType::I64) => "fx_project_id".to_string(),
+				(_, FieldType::String) => {
+					format!("\"test_create_ok {name}\".to_string()")
+				}
+				(_, FieldType::I64) => "1".to_string(),
+				(_, FieldType::F64) => "1.0".to_string(),
+				(_, FieldType::Bool) => "true".to_string(),
+			};
+			fx_fields.push_str(&format!("\t\t\t{name}: {fx_value},\n"));
+		}
+
+		let (event_project_id_column, fx_project) = if self.is_project_child() {
+			(
+				"\tconst EVENT_PROJECT_ID_COLUMN: Option<&'static str> = Some(\"project_id\");\n",
+				"\t\tlet fx_project_id = _dev_utils::seed_project(&ctx, &mm, \
+				 \"test_create_ok project for @entity@\").await?;\n",
+			)
+		} else {
+			("", "")
+		};
+
+		self.render(
+			&MODEL_TEMPLATE
+				.replace("@op_vals@", &op_vals.join(", "))
+				.replace("@fields@", &fields)
+				.replace("@update_fields@", &update_fields)
+				.replace("@filter_fields@", &filter_fields)
+				.replace("@event_project_id_column@", event_project_id_column)
+				.replace("@fx_project@", fx_project)
+				.replace("@fx_fields@", &fx_fields),
+		)
+	}
+
+	fn render_sql(&self) -> String {
+		let mut columns = String::new();
+		for Field { name, ty } in &self.fields {
+			columns.push_str(&format!("    {name} {} NOT NULL,\n", ty.sql_type()));
+		}
+
+		let mut sql = SQL_TEMPLATE.replace("@columns@", &columns);
+		if self.is_project_child() {
+			sql.push_str(SQL_PROJECT_FK_TEMPLATE);
+		}
+
+		self.render(&sql)
+	}
+
+	fn render_rpc(&self) -> String {
+		self.render(RPC_TEMPLATE)
+	}
+}
+
+fn check_snake_case(name: &str) -> Result<()> {
+	let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+		&& name
+			.chars()
+			.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+	if !valid {
+		bail!("'{name}' is not snake_case");
+	}
+
+	Ok(())
+}
+
+// endregion: --- Entity
+
+// region:    --- Files
+
+/// The next `NNNN_` version of the migration files.
+fn next_migration_version(sql_dir: &Path) -> Result<u32> {
+	let max = fs::read_dir(sql_dir)?
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| {
+			let name = entry.file_name().to_string_lossy().to_string();
+			name.split_once('_')?.0.parse::<u32>().ok()
+		})
+		.max()
+		.unwrap_or(0);
+
+	Ok(max + 1)
+}
+
+/// Inserts the `line` among the lines starting with `prefix` and ending like it
+/// (e.g., `mod ` and `;`, so not a `mod tests {`), at its sorted position when
+/// `sorted`, otherwise after the last one.
+fn insert_line(file: &Path, prefix: &str, line: &str, sorted: bool) -> Result<()> {
+	let content = fs::read_to_string(file)?;
+	let mut lines: Vec<&str> = content.lines().collect();
+	let suffix = line.chars().last().unwrap_or_default();
+	let is_match = |l: &&str| l.starts_with(prefix) && l.ends_with(suffix);
+
+	let Some(last_idx) = lines.iter().rposition(is_match) else {
+		bail!("no '{prefix}' line in {}, add: {line}", file.display());
+	};
+	let idx = lines
+		.iter()
+		.position(|l| sorted && is_match(l) && *l > line)
+		.unwrap_or(last_idx + 1);
+	lines.insert(idx, line);
+
+	fs::write(file, format!("{}\n", lines.join("\n")))?;
+
+	Ok(())
+}
+
+// endregion: --- Files
+
+// region:    --- Templates
+
+const MODEL_TEMPLATE: &str = r#"use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::ModelManager;
+use crate::model::Result;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, @op_vals@};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+// region:    --- @Entity@ Types
+
+#[derive(Debug, Clone, Fields, FromRow, Serialize, ToSchema)]
+pub struct @Entity@ {
+	pub id: i64,
+
+@fields@}
+
+#[derive(Deserialize, Fields, ToSchema)]
+pub struct @Entity@ForCreate {
+@fields@}
+
+#[derive(Deserialize, Fields, Default, ToSchema)]
+pub struct @Entity@ForUpdate {
+@update_fields@}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct @Entity@Filter {
+	id: Option<OpValsInt64>,
+@filter_fields@
+	cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	ctime: Option<OpValsValue>,
+	mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	mtime: Option<OpValsValue>,
+}
+
+// endregion: --- @Entity@ Types
+
+// region:    --- @Entity@Bmc
+pub struct @Entity@Bmc;
+
+impl DbBmc for @Entity@Bmc {
+	const TABLE: &'static str = "@entity@";
+@event_project_id_column@}
+
+impl @Entity@Bmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		@entity@_c: @Entity@ForCreate,
+	) -> Result<i64> {
+		base::create::<Self, _>(ctx, mm, @entity@_c).await
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<@Entity@> {
+		base::get::<Self, _>(ctx, mm, id).await
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<@Entity@Filter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<@Entity@>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		@entity@_u: @Entity@ForUpdate,
+	) -> Result<()> {
+		base::update::<Self, _>(ctx, mm, id, @entity@_u).await
+	}
+
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		base::delete::<Self>(ctx, mm, id).await
+	}
+}
+// endregion: --- @Entity@Bmc
+
+// region:    --- TestBmc
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{_dev_utils, model::Error};
+	use anyhow::Result;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+@fx_project@
+		// -- Exec
+		let @entity@_c = @Entity@ForCreate {
+@fx_fields@		};
+		let id = @Entity@Bmc::create(&ctx, &mm, @entity@_c).await?;
+
+		// -- Check
+		let @entity@ = @Entity@Bmc::get(&ctx, &mm, id).await?;
+		assert_eq!(@entity@.id, id);
+
+		// -- Clean
+		@Entity@Bmc::delete(&ctx, &mm, id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_get_err_not_found() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_id = 100;
+
+		// -- Exec
+		let res = @Entity@Bmc::get(&ctx, &mm, fx_id).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(Error::EntityNotFound {
+					entity: "@entity@",
+					id: 100
+				})
+			),
+			"EntityNotFound not matching"
+		);
+
+		Ok(())
+	}
+}
+// endregion: --- TestBmc
+"#;
+
+const SQL_TEMPLATE: &str = r#"-- @Entity@
+CREATE TABLE @entity@ (
+    -- PK
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    -- Properties
+@columns@    -- Timestamps
+    cid bigint NOT NULL,
+    ctime timestamp with time zone NOT NULL,
+    mid bigint NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+"#;
+
+const SQL_PROJECT_FK_TEMPLATE: &str = r#"
+ALTER TABLE
+    @entity@
+ADD
+    CONSTRAINT fk_@entity@_project FOREIGN KEY (project_id) REFERENCES project(id) ON DELETE CASCADE;
+"#;
+
+const RPC_TEMPLATE: &str = r#"use crate::rpc_router;
+use crate::web::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::@entity@::{
+	@Entity@, @Entity@Bmc, @Entity@Filter, @Entity@ForCreate, @Entity@ForUpdate,
+};
+use lib_core::model::ModelManager;
+
+use crate::web::rpc::params::{
+	ParamsForCreate, ParamsForUpdate, ParamsIded, ParamsList,
+};
+use crate::web::rpc::router::{RpcHandler, RpcRouter};
+
+pub fn rpc_router() -> RpcRouter {
+	rpc_router!(
+		create_@entity@,
+		list_@entities@,
+		update_@entity@,
+		delete_@entity@
+	)
+}
+
+pub async fn create_@entity@(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<@Entity@ForCreate>,
+) -> Result<@Entity@> {
+	let ParamsForCreate { data } = params;
+
+	let id = @Entity@Bmc::create(&ctx, &mm, data).await?;
+	let @entity@ = @Entity@Bmc::get(&ctx, &mm, id).await?;
+
+	Ok(@entity@)
+}
+
+pub async fn list_@entities@(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<@Entity@Filter>,
+) -> Result<Vec<@Entity@>> {
+	let @entities@ =
+		@Entity@Bmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+	Ok(@entities@)
+}
+
+pub async fn update_@entity@(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForUpdate<@Entity@ForUpdate>,
+) -> Result<@Entity@> {
+	let ParamsForUpdate { id, data } = params;
+
+	@Entity@Bmc::update(&ctx, &mm, id, data).await?;
+
+	let @entity@ = @Entity@Bmc::get(&ctx, &mm, id).await?;
+
+	Ok(@entity@)
+}
+
+pub async fn delete_@entity@(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<@Entity@> {
+	let ParamsIded { id } = params;
+
+	let @entity@ = @Entity@Bmc::get(&ctx, &mm, id).await?;
+	@Entity@Bmc::delete(&ctx, &mm, id).await?;
+
+	Ok(@entity@)
+}
+"#;
+
+// endregion: --- Templates
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_entity_render_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_args = [
+			"task_label",
+			"project_id:i64",
+			"label:string",
+			"--plural=task_labels",
+		];
+
+		// -- Exec
+		let entity = Entity::from_args(fx_args.iter().map(|s| s.to_string()))?;
+		let model = entity.render_model();
+		let sql = entity.render_sql();
+		let rpc = entity.render_rpc();
+
+		// -- Check
+		assert_eq!(entity.pascal_name(), "TaskLabel");
+		assert!(!model.contains('@'), "unreplaced placeholder");
+		assert!(model.contains(
+			"pub struct TaskLabelForUpdate {\n\tpub label: Option<String>,\n}"
+		));
+		assert!(model.contains("Some(\"project_id\")"));
+		assert!(sql.contains("CREATE TABLE task_label ("));
+		assert!(sql.contains("    label varchar(256) NOT NULL,\n"));
+		assert!(sql.contains("fk_task_label_project"));
+		assert!(rpc.contains("pub async fn list_task_labels("));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_entity_from_args_err() {
+		// -- Exec & Check
+		for fx_args in [
+			vec!["Note", "title:string"],
+			vec!["note"],
+			vec!["note", "id:i64"],
+			vec!["note", "title:text"],
+		] {
+			let res = Entity::from_args(fx_args.iter().map(|s| s.to_string()));
+			assert!(res.is_err(), "{fx_args:?}");
+		}
+	}
+}
+// endregion: --- Tests
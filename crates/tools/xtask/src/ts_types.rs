@@ -0,0 +1,127 @@
+//! Generates the TypeScript types of the api payloads (e.g., `Task`,
+//! `TaskForCreate`, `ParamsForCreate`) as `.d.ts` files, for the frontend.
+//!
+//! The types are the ones with the ts-rs derives of the `ts` feature (in
+//! lib-core and web-server), which export tests write the `.ts` files in a
+//! temp dir. They are then moved as `.d.ts` files to the out dir (replacing
+//! its previous ones), with an `index.d.ts` re-exporting them all.
+//!
+//! NOTE: The ts-rs `i64` is a `bigint`, but serde_json writes it as a json
+//!       number (and the ids are below 2^53), so it is written as `number`.
+
+use crate::USAGE;
+use anyhow::{bail, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const OUT_DIR_DEFAULT: &str = "web-folder/types";
+const EXPORT_DIR: &str = "target/ts-rs";
+
+pub fn run(workspace_dir: &Path, args: impl Iterator<Item = String>) -> Result<()> {
+	let mut out_dir = workspace_dir.join(OUT_DIR_DEFAULT);
+	for arg in args {
+		match arg.strip_prefix("--out=") {
+			Some(val) => out_dir = PathBuf::from(val),
+			None => bail!("unknown option '{arg}'\n\n{USAGE}"),
+		}
+	}
+
+	// -- Run the ts-rs export tests.
+	let export_dir = workspace_dir.join(EXPORT_DIR);
+	if export_dir.exists() {
+		fs::remove_dir_all(&export_dir)?;
+	}
+	let status = Command::new(std::env::var("CARGO").unwrap_or("cargo".into()))
+		.current_dir(workspace_dir)
+		.env("TS_RS_EXPORT_DIR", &export_dir)
+		.args([
+			"test",
+			"--lib",
+			"--bins",
+			"-p",
+			"lib-core",
+			"-p",
+			"web-server",
+		])
+		.args(["--features", "lib-core/ts,web-server/ts", "export_bindings"])
+		.status()?;
+	if !status.success() {
+		bail!("ts-rs export failed ({status})");
+	}
+
+	// -- Replace the out dir `.d.ts` files.
+	fs::create_dir_all(&out_dir)?;
+	for entry in fs::read_dir(&out_dir)? {
+		let file = entry?.path();
+		if file.to_string_lossy().ends_with(".d.ts") {
+			fs::remove_file(file)?;
+		}
+	}
+	let mut names = Vec::new();
+	for entry in fs::read_dir(&export_dir)? {
+		let file = entry?.path();
+		let Some(name) = file
+			.file_name()
+			.and_then(|name| name.to_str())
+			.and_then(|name| name.strip_suffix(".ts"))
+		else {
+			continue;
+		};
+		let content = fs::read_to_string(&file)?;
+		fs::write(
+			out_dir.join(format!("{name}.d.ts")),
+			bigint_as_number(&content),
+		)?;
+		names.push(name.to_string());
+	}
+	names.sort();
+	fs::write(out_dir.join("index.d.ts"), index_content(&names))?;
+
+	println!("{} types written to {}", names.len(), out_dir.display());
+
+	Ok(())
+}
+
+fn bigint_as_number(content: &str) -> String {
+	content
+		.replace(": bigint", ": number")
+		.replace("<bigint>", "<number>")
+}
+
+fn index_content(names: &[String]) -> String {
+	let mut content =
+		String::from("// Generated by `cargo xtask ts-types`, do not edit.\n");
+	for name in names {
+		content.push_str(&format!("export type * from \"./{name}\";\n"));
+	}
+	content
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_bigint_as_number() {
+		let fx_content = "export type Task = { id: bigint, ids: Array<bigint>, };";
+
+		assert_eq!(
+			bigint_as_number(fx_content),
+			"export type Task = { id: number, ids: Array<number>, };"
+		);
+	}
+
+	#[test]
+	fn test_index_content() {
+		let fx_names = ["Task".to_string(), "TaskForCreate".to_string()];
+
+		let content = index_content(&fx_names);
+
+		assert!(content.ends_with(
+			"export type * from \"./Task\";\nexport type * from \"./TaskForCreate\";\n"
+		));
+	}
+}
+// endregion: --- Tests
@@ -1,8 +1,8 @@
 -- root user (at id = 0)
 INSERT INTO
-    "user" (id, username, cid, ctime, mid, mtime)
+    "user" (id, username, role, cid, ctime, mid, mtime)
 VALUES
-    (0, 'root', 0, now(), 0, now());
+    (0, 'root', 'admin', 0, now(), 0, now());
 
 -- User demo1
 INSERT INTO
@@ -0,0 +1,151 @@
+---- Base app schema
+-- User
+CREATE TABLE "user" (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    username varchar(128) NOT NULL UNIQUE,
+    -- Auth
+    pwd varchar(256),
+    pwd_salt uuid NOT NULL DEFAULT gen_random_uuid(),
+    token_salt uuid NOT NULL DEFAULT gen_random_uuid(),
+    -- Access
+    role varchar(32) NOT NULL DEFAULT 'user',
+    active bool NOT NULL DEFAULT true,
+    -- Set by the admins, the user sessions can only change the pwd until cleared.
+    must_change_pwd bool NOT NULL DEFAULT false,
+    -- Timestamps
+    cid bigint NOT NULL,
+    ctime timestamp with time zone NOT NULL,
+    mid bigint NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+
+-- Project
+CREATE TABLE project (
+    -- PK
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    -- Properties
+    owner_id BIGINT NOT NULL,
+    name varchar(256) NOT NULL,
+    -- Timestamps
+    cid bigint NOT NULL,
+    ctime timestamp with time zone NOT NULL,
+    mid bigint NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+
+-- Task
+CREATE TABLE task (
+    -- PK
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    -- FK
+    project_id BIGINT NOT NULL,
+    -- Properties
+    title varchar(256) NOT NULL,
+    done bool NOT NULL DEFAULT false,
+    -- Timestamps
+    cid bigint NOT NULL,
+    ctime timestamp with time zone NOT NULL,
+    mid bigint NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+
+ALTER TABLE
+    task
+ADD
+    CONSTRAINT fk_project FOREIGN KEY (project_id) REFERENCES project(id) ON DELETE CASCADE;
+-- Event Outbox
+-- The model change events, written in the mutation transaction,
+-- and published by the relay (see lib-core `model::outbox`).
+CREATE TABLE event_outbox (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    -- Event
+    entity varchar(128) NOT NULL,
+    entity_id BIGINT NOT NULL,
+    project_id BIGINT NOT NULL,
+    kind varchar(16) NOT NULL,
+    user_id BIGINT NOT NULL,
+    -- Relay
+    ctime timestamp with time zone NOT NULL,
+    published_time timestamp with time zone
+);
+
+CREATE INDEX event_outbox_pending_idx ON event_outbox (id) WHERE published_time IS NULL;
+
+-- Tombstone (the deleted entities, for the delta sync)
+CREATE TABLE tombstone (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    entity varchar(128) NOT NULL,
+    entity_id BIGINT NOT NULL,
+    dtime timestamp with time zone NOT NULL
+);
+
+CREATE INDEX tombstone_entity_dtime_idx ON tombstone (entity, dtime);
+
+-- Webhook
+CREATE TABLE webhook (
+    -- PK
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    -- Properties
+    owner_id BIGINT NOT NULL,
+    url varchar(1024) NOT NULL,
+    secret varchar(256) NOT NULL,
+    -- Comma separated event names (e.g., `task.created,task.deleted`), or `*`.
+    events varchar(1024) NOT NULL,
+    -- When set, only the events of this project.
+    project_id BIGINT,
+    -- Timestamps
+    cid bigint NOT NULL,
+    ctime timestamp with time zone NOT NULL,
+    mid bigint NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+
+-- Webhook Delivery
+-- The delivery queue (retried with backoff) and the delivery log.
+CREATE TABLE webhook_delivery (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    -- FK
+    webhook_id BIGINT NOT NULL,
+    -- Properties
+    event varchar(64) NOT NULL,
+    payload text NOT NULL,
+    -- `pending`, `succeeded`, or `failed` (after the max attempts).
+    status varchar(16) NOT NULL,
+    attempts int NOT NULL DEFAULT 0,
+    last_status_code int,
+    last_error text,
+    next_attempt_time timestamp with time zone NOT NULL,
+    -- Timestamps
+    ctime timestamp with time zone NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+
+ALTER TABLE
+    webhook_delivery
+ADD
+    CONSTRAINT fk_webhook FOREIGN KEY (webhook_id) REFERENCES webhook(id) ON DELETE CASCADE;
+
+CREATE INDEX webhook_delivery_pending_idx ON webhook_delivery (next_attempt_time) WHERE status = 'pending';
+
+-- Request Log
+-- The served requests (written in batches by the web-server request log writer).
+CREATE TABLE request_log (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    req_id varchar(128) NOT NULL,
+    time_in timestamp with time zone NOT NULL,
+    duration_ms double precision NOT NULL,
+    user_id BIGINT,
+    -- The resolved client ip (see CLIENT_IP_HEADER and SERVICE_PROXY_PROTOCOL).
+    client_ip varchar(64),
+    http_method varchar(16) NOT NULL,
+    http_path text NOT NULL,
+    http_status int NOT NULL,
+    rpc_id text,
+    rpc_method varchar(128),
+    client_error_type varchar(128),
+    error_type varchar(128),
+    -- The redacted error data json.
+    error_data text
+);
+
+CREATE INDEX request_log_time_in_idx ON request_log (time_in);
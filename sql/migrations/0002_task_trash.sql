@@ -0,0 +1,11 @@
+-- Task trash
+-- The deleted tasks are kept (soft delete) until restored, or purged after
+-- the trash retention (see lib-core `model::trash`).
+ALTER TABLE
+    task
+ADD
+    COLUMN deleted_at timestamp with time zone;
+
+CREATE INDEX idx_task_deleted_at ON task (deleted_at)
+WHERE
+    deleted_at IS NOT NULL;
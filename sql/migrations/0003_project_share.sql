@@ -0,0 +1,25 @@
+-- Project Share
+-- The public (read-only) links of a project. A link token is signed with the
+-- row `token_salt`, so deleting the row revokes it (see lib-core
+-- `model::project_share`).
+CREATE TABLE project_share (
+    -- PK
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+    -- FK
+    project_id BIGINT NOT NULL,
+    -- Properties
+    token_salt uuid NOT NULL DEFAULT gen_random_uuid(),
+    expires_at timestamp with time zone NOT NULL,
+    -- Timestamps
+    cid bigint NOT NULL,
+    ctime timestamp with time zone NOT NULL,
+    mid bigint NOT NULL,
+    mtime timestamp with time zone NOT NULL
+);
+
+ALTER TABLE
+    project_share
+ADD
+    CONSTRAINT fk_project FOREIGN KEY (project_id) REFERENCES project(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_project_share_project_id ON project_share (project_id);
@@ -0,0 +1,8 @@
+-- User timezone
+-- The IANA timezone (e.g., `Europe/Paris`) the user times are formatted in
+-- for display (see lib-base `time::format_time_in_tz`), the times being stored
+-- in UTC.
+ALTER TABLE
+    "user"
+ADD
+    COLUMN timezone varchar(64) NOT NULL DEFAULT 'UTC';
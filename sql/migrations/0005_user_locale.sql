@@ -0,0 +1,8 @@
+-- User locale
+-- The preferred locale (a supported BCP 47 tag, e.g., `fr`) of the user, taking
+-- precedence over the `Accept-Language` one (see lib-core `ctx::locale`), NULL
+-- to follow the browser.
+ALTER TABLE
+    "user"
+ADD
+    COLUMN locale varchar(35);
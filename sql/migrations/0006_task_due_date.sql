@@ -0,0 +1,7 @@
+-- Task due date
+-- Optional, and cleared with a `null` in the task updates (see lib-core
+-- `modql_utils::Nullable`).
+ALTER TABLE
+    task
+ADD
+    COLUMN due_date timestamp with time zone;
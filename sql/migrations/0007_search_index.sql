@@ -0,0 +1,7 @@
+-- Search
+-- The full-text indexes of the task titles and project names (see lib-core
+-- `model::search`), with the `simple` config (no stemming, as the titles are
+-- in any language).
+CREATE INDEX idx_task_title_search ON task USING GIN (to_tsvector('simple', title));
+
+CREATE INDEX idx_project_name_search ON project USING GIN (to_tsvector('simple', name));
@@ -0,0 +1,8 @@
+-- Tombstone owner
+-- The owner of the project of the deleted entity, so the delta sync only
+-- returns the deleted ids of the user projects (see lib-core `model::sync`).
+-- The previous tombstones have none, so are only synced to root.
+ALTER TABLE
+    tombstone
+ADD
+    COLUMN owner_id BIGINT;